@@ -1,4 +1,4 @@
 #!/bin/sh
 
-./usr/local/bin/api &
+./usr/local/bin/api --migrate &
 exit 0
\ No newline at end of file
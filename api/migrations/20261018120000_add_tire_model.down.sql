@@ -0,0 +1,13 @@
+-- Add down migration script here
+
+ALTER TABLE stints
+    DROP COLUMN tire_set_stint;
+
+ALTER TABLE stints
+    DROP COLUMN tire_age;
+
+ALTER TABLE fuel_stint_configs
+    DROP COLUMN max_stints_per_tire_set;
+
+ALTER TABLE fuel_stint_configs
+    DROP COLUMN tire_degradation_per_lap;
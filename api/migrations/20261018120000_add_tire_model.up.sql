@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+ALTER TABLE fuel_stint_configs
+    ADD tire_degradation_per_lap INTERVAL NOT NULL DEFAULT '0';
+
+ALTER TABLE fuel_stint_configs
+    ADD max_stints_per_tire_set INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE stints
+    ADD tire_age INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE stints
+    ADD tire_set_stint INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+CREATE TABLE track_conditions(
+    id SERIAL PRIMARY KEY,
+    plan_id UUID NOT NULL,
+
+    start_offset INTERVAL NOT NULL,
+    air_temperature REAL NOT NULL,
+    track_temperature REAL NOT NULL,
+    weather SMALLINT NOT NULL,
+    lap_time_factor DOUBLE PRECISION NOT NULL,
+    fuel_per_lap_factor DOUBLE PRECISION NOT NULL,
+
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id)
+);
+
+CREATE INDEX idx_track_conditions_plan_id ON track_conditions(plan_id);
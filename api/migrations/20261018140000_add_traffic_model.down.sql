@@ -0,0 +1,10 @@
+-- Add down migration script here
+
+ALTER TABLE stints
+    DROP COLUMN traffic_loss;
+
+DROP TABLE traffic_intensities;
+
+DROP TABLE traffic_classes;
+
+DROP TABLE traffic_models;
@@ -0,0 +1,41 @@
+-- Add up migration script here
+
+CREATE TABLE traffic_models(
+    plan_id UUID PRIMARY KEY,
+
+    time_loss_per_encounter INTERVAL NOT NULL,
+
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id)
+);
+
+CREATE TABLE traffic_classes(
+    id SERIAL PRIMARY KEY,
+    plan_id UUID NOT NULL,
+
+    name TEXT NOT NULL,
+    relative_pace DOUBLE PRECISION NOT NULL,
+    field_size INTEGER NOT NULL,
+
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES traffic_models(plan_id)
+        ON DELETE CASCADE
+);
+
+CREATE TABLE traffic_intensities(
+    id SERIAL PRIMARY KEY,
+    plan_id UUID NOT NULL,
+
+    tod_start TIME NOT NULL,
+    intensity DOUBLE PRECISION NOT NULL,
+
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES traffic_models(plan_id)
+        ON DELETE CASCADE
+);
+
+ALTER TABLE stints
+    ADD traffic_loss INTERVAL NOT NULL DEFAULT '0';
@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+DELETE FROM stints s
+    USING scenarios sc
+    WHERE sc.id = s.scenario_id
+    AND NOT sc.is_active;
+
+ALTER TABLE stints
+    DROP COLUMN scenario_id;
+
+DROP TABLE scenarios;
@@ -0,0 +1,35 @@
+-- Add up migration script here
+
+CREATE TABLE scenarios(
+    id UUID NOT NULL PRIMARY KEY,
+    plan_id UUID NOT NULL,
+
+    name TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_date TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id)
+);
+
+CREATE UNIQUE INDEX ix_scenarios_active_plan_id
+    ON scenarios(plan_id)
+    WHERE is_active;
+
+INSERT INTO scenarios (id, plan_id, name, is_active)
+    SELECT gen_random_uuid(), id, 'Main', TRUE FROM plans;
+
+ALTER TABLE stints
+    ADD scenario_id UUID NULL;
+
+UPDATE stints s
+    SET scenario_id = sc.id
+    FROM scenarios sc
+    WHERE sc.plan_id = s.plan_id;
+
+ALTER TABLE stints
+    ALTER COLUMN scenario_id SET NOT NULL,
+    ADD CONSTRAINT fk_scenario_id
+        FOREIGN KEY(scenario_id)
+        REFERENCES scenarios(id);
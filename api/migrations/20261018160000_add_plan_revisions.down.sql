@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP TRIGGER tr_plan_revisions_append_only ON plan_revisions;
+
+DROP FUNCTION plan_revisions_append_only;
+
+DROP TABLE plan_revisions;
@@ -0,0 +1,32 @@
+-- Add up migration script here
+
+CREATE TABLE plan_revisions(
+    id BIGSERIAL NOT NULL PRIMARY KEY,
+    plan_id UUID NOT NULL,
+    user_id INTEGER NOT NULL,
+
+    created_date TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    section SMALLINT NOT NULL,
+    before JSONB NULL,
+    after JSONB NOT NULL,
+
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id),
+    CONSTRAINT fk_user_id
+        FOREIGN KEY(user_id)
+        REFERENCES users(id)
+);
+
+CREATE INDEX ix_plan_revisions_plan_id
+    ON plan_revisions(plan_id, id);
+
+CREATE FUNCTION plan_revisions_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'plan_revisions is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER tr_plan_revisions_append_only
+    BEFORE UPDATE OR DELETE ON plan_revisions
+    FOR EACH ROW EXECUTE FUNCTION plan_revisions_append_only();
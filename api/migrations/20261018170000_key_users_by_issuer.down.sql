@@ -0,0 +1,13 @@
+-- Add down migration script here
+
+ALTER TABLE users
+    DROP CONSTRAINT users_issuer_subject_key;
+
+ALTER TABLE users
+    RENAME COLUMN subject TO oauth_id;
+
+ALTER TABLE users
+    ADD CONSTRAINT users_oauth_id_key UNIQUE (oauth_id);
+
+ALTER TABLE users
+    DROP COLUMN issuer;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- every user so far signed in with Google
+ALTER TABLE users
+    ADD COLUMN issuer TEXT NOT NULL DEFAULT 'https://accounts.google.com';
+
+ALTER TABLE users
+    ALTER COLUMN issuer DROP DEFAULT;
+
+ALTER TABLE users
+    RENAME COLUMN oauth_id TO subject;
+
+ALTER TABLE users
+    DROP CONSTRAINT users_oauth_id_key;
+
+ALTER TABLE users
+    ADD CONSTRAINT users_issuer_subject_key UNIQUE (issuer, subject);
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+ALTER TABLE users
+    ADD COLUMN iracing_customer_id INTEGER NULL;
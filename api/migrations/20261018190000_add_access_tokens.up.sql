@@ -0,0 +1,27 @@
+-- Add up migration script here
+
+CREATE TABLE access_tokens(
+    id UUID NOT NULL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    plan_id UUID NULL,
+
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    read_only BOOLEAN NOT NULL,
+    created_date TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_date TIMESTAMPTZ NULL,
+
+    CONSTRAINT uq_access_tokens_token_hash
+        UNIQUE(token_hash),
+    CONSTRAINT fk_user_id
+        FOREIGN KEY(user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX ix_access_tokens_user_id
+    ON access_tokens(user_id);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE plan_revisions
+    DROP COLUMN scenario_id;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- the scenario a schedule revision changed, revisions recorded before scenarios were tracked
+-- have none and are restored into the active scenario. There is no foreign key because the
+-- history is append-only and a deleted scenario can't null out its revisions.
+ALTER TABLE plan_revisions
+    ADD COLUMN scenario_id UUID NULL;
@@ -0,0 +1,13 @@
+-- Add down migration script here
+
+CREATE OR REPLACE FUNCTION plan_revisions_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'plan_revisions is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE plan_revisions
+    DROP CONSTRAINT fk_plan_id,
+    ADD CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id);
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+-- the history of a plan is deleted with the plan, the trigger still rejects deleting the
+-- revisions of a plan that exists
+ALTER TABLE plan_revisions
+    DROP CONSTRAINT fk_plan_id,
+    ADD CONSTRAINT fk_plan_id
+        FOREIGN KEY(plan_id)
+        REFERENCES plans(id)
+        ON DELETE CASCADE;
+
+CREATE OR REPLACE FUNCTION plan_revisions_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND NOT EXISTS (SELECT 1 FROM plans WHERE id = OLD.plan_id) THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'plan_revisions is append-only';
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,257 @@
+use axum::{
+    extract::State,
+    http::{header, Method, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use endurance_racing_planner_common::{
+    access_token::{AccessTokenDto, CreatedAccessTokenDto, NewAccessTokenDto, ACCESS_TOKEN_PREFIX},
+    User,
+};
+use sha2::{Digest, Sha256};
+use sqlx::{types::Uuid, PgPool};
+
+use crate::{
+    data_access::{
+        access_tokens::{
+            create_access_token, delete_access_token as delete_user_access_token,
+            get_access_token_by_hash, get_access_tokens_by_user_id, touch_access_token,
+        },
+        entities::access_token::AccessToken,
+        plans::get_plan_by_id,
+        user::Users,
+    },
+    error::{ApiError, ApiPath, ValidJson},
+    AuthenticatedUser,
+};
+
+/// The routes that manage access tokens, only a signed in user can reach them
+const ACCESS_TOKENS_ROUTE: &str = "/users/me/tokens";
+
+/// A new token, the prefix and 32 random bytes as hex
+fn generate_token() -> String {
+    format!(
+        "{}{}",
+        ACCESS_TOKEN_PREFIX,
+        hex::encode(rand::random::<[u8; 32]>())
+    )
+}
+
+/// Tokens are random enough that a fast unsalted hash is all a leaked table needs
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Finds the user a personal access token belongs to and checks the token can make the request
+pub(crate) async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+    method: &Method,
+    path: &str,
+) -> Result<User, ApiError> {
+    let access_token = get_access_token_by_hash(pool, &hash_token(token))
+        .await
+        .map_err(|e| ApiError::internal("there was a problem locating the access token", e))?
+        .ok_or_else(|| ApiError::Unauthorized("unknown access token".to_string()))?;
+    check_access(&access_token, method, path)?;
+
+    touch_access_token(pool, access_token.id)
+        .await
+        .map_err(|e| ApiError::internal("there was a problem locating the access token", e))?;
+    Users::get_user_by_id(pool, access_token.user_id)
+        .await
+        .map_err(|e| ApiError::internal("there was a problem locating the user", e))?
+        .ok_or_else(|| ApiError::Unauthorized("user not found".to_string()))
+}
+
+/// A read only token can only read, a token scoped to a plan can only reach the plan's routes and
+/// no token can manage tokens
+fn check_access(access_token: &AccessToken, method: &Method, path: &str) -> Result<(), ApiError> {
+    if path.starts_with(ACCESS_TOKENS_ROUTE) {
+        return Err(ApiError::Forbidden(
+            "access tokens can't manage access tokens".to_string(),
+        ));
+    }
+    if access_token.read_only && !matches!(*method, Method::GET | Method::HEAD) {
+        return Err(ApiError::Forbidden(
+            "the access token is read only".to_string(),
+        ));
+    }
+    if let Some(plan_id) = access_token.plan_id {
+        let path_plan_id = path
+            .strip_prefix("/plans/")
+            .and_then(|rest| rest.split('/').next())
+            .and_then(|id| Uuid::parse_str(id).ok());
+        if path_plan_id != Some(plan_id) {
+            return Err(ApiError::Forbidden(
+                "the access token can only reach its plan".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_access_tokens(
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let access_tokens = get_access_tokens_by_user_id(&pool, user.id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the access tokens", e))?;
+
+    Ok(Json(
+        access_tokens
+            .iter()
+            .map(|access_token| access_token.into())
+            .collect::<Vec<AccessTokenDto>>(),
+    ))
+}
+
+/// Creates a token for the user, the response is the only time the token is shown
+pub(crate) async fn add_access_token(
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    ValidJson(new_access_token): ValidJson<NewAccessTokenDto>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(plan_id) = new_access_token.plan_id {
+        get_plan_by_id(&pool, plan_id, user.id)
+            .await
+            .map_err(|e| ApiError::internal("failed to get the plan", e))?
+            .ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+    }
+
+    let token = generate_token();
+    let access_token = create_access_token(&pool, user.id, &new_access_token, &hash_token(&token))
+        .await
+        .map_err(|e| ApiError::internal("failed to save the access token", e))?;
+
+    Ok((
+        StatusCode::CREATED,
+        [(
+            header::CONTENT_LOCATION,
+            format!("{}/{}", ACCESS_TOKENS_ROUTE, access_token.id),
+        )],
+        Json(CreatedAccessTokenDto {
+            access_token: (&access_token).into(),
+            token,
+        }),
+    ))
+}
+
+pub(crate) async fn delete_access_token(
+    ApiPath(id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let deleted = delete_user_access_token(&pool, user.id, id)
+        .await
+        .map_err(|e| ApiError::internal("failed to revoke the access token", e))?;
+    if !deleted {
+        return Err(ApiError::not_found("the access token does not exist"));
+    }
+
+    Ok((StatusCode::OK, Json(id.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use chrono::Utc;
+    use endurance_racing_planner_common::access_token::ACCESS_TOKEN_PREFIX;
+    use sqlx::types::Uuid;
+
+    use super::{check_access, generate_token, hash_token};
+    use crate::data_access::entities::access_token::AccessToken;
+
+    fn access_token(plan_id: Option<Uuid>, read_only: bool) -> AccessToken {
+        AccessToken {
+            id: Uuid::new_v4(),
+            user_id: 1,
+            plan_id,
+            name: "telemetry relay".to_string(),
+            token_hash: String::new(),
+            read_only,
+            created_date: Utc::now(),
+            last_used_date: None,
+        }
+    }
+
+    fn status(access_token: &AccessToken, method: Method, path: &str) -> Option<StatusCode> {
+        check_access(access_token, &method, path)
+            .err()
+            .map(|e| e.status())
+    }
+
+    #[test]
+    fn tokens_are_prefixed_and_only_their_hash_is_kept() {
+        let token = generate_token();
+
+        assert!(token.starts_with(ACCESS_TOKEN_PREFIX));
+        assert_ne!(token, generate_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert!(!hash_token(&token).contains(&token));
+    }
+
+    #[test]
+    fn read_only_tokens_can_only_read() {
+        let read_only = access_token(None, true);
+        let read_write = access_token(None, false);
+
+        assert_eq!(None, status(&read_only, Method::GET, "/plans"));
+        assert_eq!(
+            Some(StatusCode::FORBIDDEN),
+            status(&read_only, Method::PATCH, "/users/me")
+        );
+        assert_eq!(None, status(&read_write, Method::PATCH, "/users/me"));
+    }
+
+    #[test]
+    fn plan_tokens_only_reach_their_plan() {
+        let plan_id = Uuid::new_v4();
+        let plan_token = access_token(Some(plan_id), false);
+
+        assert_eq!(
+            None,
+            status(
+                &plan_token,
+                Method::PUT,
+                &format!("/plans/{}/schedule.csv", plan_id)
+            )
+        );
+        assert_eq!(
+            None,
+            status(&plan_token, Method::GET, &format!("/plans/{}", plan_id))
+        );
+        assert_eq!(
+            Some(StatusCode::FORBIDDEN),
+            status(
+                &plan_token,
+                Method::GET,
+                &format!("/plans/{}", Uuid::new_v4())
+            )
+        );
+        assert_eq!(
+            Some(StatusCode::FORBIDDEN),
+            status(&plan_token, Method::GET, "/plans")
+        );
+        assert_eq!(
+            Some(StatusCode::FORBIDDEN),
+            status(&plan_token, Method::GET, "/users/me")
+        );
+    }
+
+    #[test]
+    fn tokens_cant_manage_tokens() {
+        let read_write = access_token(None, false);
+
+        assert_eq!(
+            Some(StatusCode::FORBIDDEN),
+            status(&read_write, Method::GET, "/users/me/tokens")
+        );
+        assert_eq!(
+            Some(StatusCode::FORBIDDEN),
+            status(&read_write, Method::POST, "/users/me/tokens")
+        );
+    }
+}
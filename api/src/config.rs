@@ -0,0 +1,497 @@
+use std::{
+    env,
+    fmt::{Display, Formatter},
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use axum::http::HeaderValue;
+use reqwest::Url;
+use serde::Deserialize;
+
+/// Names the TOML file to load the settings from, settings from the environment override the file
+pub const CONFIG_FILE_ENV: &str = "API_CONFIG_FILE";
+/// `OAUTH_CLIENT_ID` predates the provider list and sets the client id of the Google provider
+const GOOGLE_PROVIDER_NAME: &str = "Google";
+
+/// The api's settings. Every setting has a default except the database url, the defaults are
+/// overridden by the optional TOML file and the file is overridden by the environment.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind_address: SocketAddr,
+    pub database: DatabaseConfig,
+    pub cors: CorsConfig,
+    pub log: LogConfig,
+    pub oauth: OAuthConfig,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    /// Connections idle for longer than this are closed, `None` keeps them open
+    pub idle_timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Cross-origin requests are refused when there are no allowed origins
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Compact,
+    Json,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OAuthConfig {
+    /// The OpenID Connect providers users can sign in with, their tokens are the only ones accepted
+    pub providers: Vec<IdentityProviderConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct IdentityProviderConfig {
+    /// Shown on the provider's sign in button
+    pub name: String,
+    /// The provider's signing keys and endpoints are found through the issuer's discovery document
+    pub issuer: String,
+    /// The OAuth client the web signs in with
+    #[serde(default)]
+    pub client_id: String,
+    /// The scopes the web asks for, they differ between providers
+    #[serde(default = "IdentityProviderConfig::default_scopes")]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    ReadFile(PathBuf, std::io::Error),
+    ParseFile(PathBuf, toml::de::Error),
+    InvalidValue { key: String, reason: String },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::ReadFile(path, e) => {
+                write!(f, "failed to read {}: {}", path.display(), e)
+            }
+            ConfigError::ParseFile(path, e) => {
+                write!(f, "failed to parse {}: {}", path.display(), e)
+            }
+            ConfigError::InvalidValue { key, reason } => write!(f, "invalid {}: {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 3000)),
+            database: DatabaseConfig::default(),
+            cors: CorsConfig::default(),
+            log: LogConfig::default(),
+            oauth: OAuthConfig::default(),
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: 5,
+            min_connections: 0,
+            acquire_timeout_secs: 30,
+            idle_timeout_secs: Some(600),
+        }
+    }
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            providers: vec![IdentityProviderConfig {
+                name: GOOGLE_PROVIDER_NAME.to_string(),
+                issuer: "https://accounts.google.com".to_string(),
+                client_id: String::new(),
+                scopes: IdentityProviderConfig::default_scopes(),
+            }],
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "compact" => Ok(LogFormat::Compact),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(
+                "expected one of pretty, compact or json, found {}",
+                value
+            )),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_secs)
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_secs.map(Duration::from_secs)
+    }
+}
+
+impl IdentityProviderConfig {
+    fn default_scopes() -> Vec<String> {
+        vec![
+            "openid".to_string(),
+            "email".to_string(),
+            "profile".to_string(),
+        ]
+    }
+}
+
+impl OAuthConfig {
+    pub fn provider(&self, issuer: &str) -> Option<&IdentityProviderConfig> {
+        self.providers
+            .iter()
+            .find(|provider| provider.issuer == issuer)
+    }
+
+    /// Where the issuer publishes its OpenID Connect discovery document
+    pub fn discovery_url(issuer: &str) -> String {
+        format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        )
+    }
+}
+
+impl Config {
+    /// Loads the settings from the file named by `API_CONFIG_FILE`, when it's set, and the
+    /// process environment
+    pub fn load() -> Result<Self, ConfigError> {
+        let file = match env::var(CONFIG_FILE_ENV) {
+            Ok(path) => {
+                let path = PathBuf::from(path);
+                let contents = fs::read_to_string(&path)
+                    .map_err(|e| ConfigError::ReadFile(path.clone(), e))?;
+                Some((path, contents))
+            }
+            Err(_) => None,
+        };
+
+        Self::from_sources(
+            file.as_ref()
+                .map(|(path, contents)| (path.clone(), contents.as_str())),
+            |key| env::var(key).ok(),
+        )
+    }
+
+    /// Builds the settings from the contents of a TOML file and an environment lookup, then
+    /// validates them
+    pub fn from_sources(
+        file: Option<(PathBuf, &str)>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = match file {
+            Some((path, contents)) => {
+                toml::from_str::<Config>(contents).map_err(|e| ConfigError::ParseFile(path, e))?
+            }
+            None => Config::default(),
+        };
+
+        if let Some(bind_address) = parse_env(&env, "BIND_ADDRESS")? {
+            config.bind_address = bind_address;
+        }
+        if let Some(url) = env("DATABASE_URL") {
+            config.database.url = url;
+        }
+        if let Some(max_connections) = parse_env(&env, "DATABASE_MAX_CONNECTIONS")? {
+            config.database.max_connections = max_connections;
+        }
+        if let Some(min_connections) = parse_env(&env, "DATABASE_MIN_CONNECTIONS")? {
+            config.database.min_connections = min_connections;
+        }
+        if let Some(acquire_timeout_secs) = parse_env(&env, "DATABASE_ACQUIRE_TIMEOUT_SECS")? {
+            config.database.acquire_timeout_secs = acquire_timeout_secs;
+        }
+        if let Some(idle_timeout_secs) = parse_env::<u64>(&env, "DATABASE_IDLE_TIMEOUT_SECS")? {
+            // zero turns the idle timeout off
+            config.database.idle_timeout_secs = Some(idle_timeout_secs).filter(|secs| *secs > 0);
+        }
+        // UI_ORIGIN predates CORS_ALLOWED_ORIGINS and names a single origin
+        if let Some(origins) = env("CORS_ALLOWED_ORIGINS").or_else(|| env("UI_ORIGIN")) {
+            config.cors.allowed_origins = split_list(&origins);
+        }
+        if let Some(format) = parse_env(&env, "LOG_FORMAT")? {
+            config.log.format = format;
+        }
+        if let Some(client_id) = env("OAUTH_CLIENT_ID") {
+            config
+                .oauth
+                .providers
+                .iter_mut()
+                .filter(|provider| provider.name == GOOGLE_PROVIDER_NAME)
+                .for_each(|provider| provider.client_id = client_id.clone());
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.database.url.is_empty() {
+            return Err(invalid(
+                "database.url",
+                "set DATABASE_URL or database.url in the config file",
+            ));
+        }
+        if self.database.max_connections == 0 {
+            return Err(invalid("database.max_connections", "must be at least 1"));
+        }
+        if self.database.min_connections > self.database.max_connections {
+            return Err(invalid(
+                "database.min_connections",
+                "can't be more than database.max_connections",
+            ));
+        }
+        if self.database.acquire_timeout_secs == 0 {
+            return Err(invalid(
+                "database.acquire_timeout_secs",
+                "must be at least 1 second",
+            ));
+        }
+
+        for origin in self.cors.allowed_origins.iter() {
+            let is_origin = Url::parse(origin)
+                .map(|url| {
+                    ["http", "https"].contains(&url.scheme())
+                        && url.host().is_some()
+                        && url.path() == "/"
+                        && !origin.ends_with('/')
+                })
+                .unwrap_or(false);
+            if !is_origin || HeaderValue::from_str(origin).is_err() {
+                return Err(invalid(
+                    "cors.allowed_origins",
+                    format!("{} is not an origin, expected scheme://host[:port]", origin),
+                ));
+            }
+        }
+
+        if self.oauth.providers.is_empty() {
+            return Err(invalid(
+                "oauth.providers",
+                "at least one provider is required",
+            ));
+        }
+        for (index, provider) in self.oauth.providers.iter().enumerate() {
+            if provider.name.trim().is_empty() {
+                return Err(invalid("oauth.providers", "every provider needs a name"));
+            }
+            match Url::parse(&provider.issuer) {
+                Ok(url) if url.scheme() == "https" => {}
+                _ => {
+                    return Err(invalid(
+                        "oauth.providers",
+                        format!("{} is not an https url", provider.issuer),
+                    ))
+                }
+            }
+            if self.oauth.providers[..index]
+                .iter()
+                .any(|other| other.issuer == provider.issuer)
+            {
+                return Err(invalid(
+                    "oauth.providers",
+                    format!("{} is listed more than once", provider.issuer),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid(key: &str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::InvalidValue {
+        key: key.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn parse_env<T>(env: &impl Fn(&str) -> Option<String>, key: &str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    env(key)
+        .map(|value| {
+            value
+                .parse::<T>()
+                .map_err(|e| invalid(key, format!("{}: {}", value, e)))
+        })
+        .transpose()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use super::{Config, ConfigError, LogFormat};
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn environment_overrides_the_config_file() {
+        let file = r#"
+            bind_address = "0.0.0.0:8080"
+
+            [database]
+            url = "postgres://file"
+            max_connections = 20
+
+            [log]
+            format = "json"
+        "#;
+
+        let config = Config::from_sources(
+            Some((PathBuf::from("api.toml"), file)),
+            env(&[
+                ("DATABASE_MAX_CONNECTIONS", "8"),
+                ("UI_ORIGIN", "http://localhost:9000"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!("0.0.0.0:8080", config.bind_address.to_string());
+        assert_eq!("postgres://file", config.database.url);
+        assert_eq!(8, config.database.max_connections);
+        assert_eq!(LogFormat::Json, config.log.format);
+        assert_eq!(vec!["http://localhost:9000"], config.cors.allowed_origins);
+        assert_eq!(1, config.oauth.providers.len());
+        assert_eq!(
+            "https://accounts.google.com",
+            config.oauth.providers[0].issuer
+        );
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let database_url = ("DATABASE_URL", "postgres://localhost");
+
+        assert!(matches!(
+            Config::from_sources(None, env(&[])),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "database.url"
+        ));
+        assert!(matches!(
+            Config::from_sources(None, env(&[database_url, ("BIND_ADDRESS", "localhost")])),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "BIND_ADDRESS"
+        ));
+        assert!(matches!(
+            Config::from_sources(
+                None,
+                env(&[database_url, ("CORS_ALLOWED_ORIGINS", "http://localhost:9000/app")])
+            ),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "cors.allowed_origins"
+        ));
+        assert!(matches!(
+            Config::from_sources(
+                Some((PathBuf::from("api.toml"), "[oauth]\nproviders = []\n")),
+                env(&[database_url])
+            ),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "oauth.providers"
+        ));
+    }
+
+    #[test]
+    fn unknown_settings_in_the_file_are_rejected() {
+        let file = "[database]\nmax_conections = 10\n";
+
+        assert!(matches!(
+            Config::from_sources(Some((PathBuf::from("api.toml"), file)), env(&[])),
+            Err(ConfigError::ParseFile(_, _))
+        ));
+    }
+
+    #[test]
+    fn identity_providers_are_read_from_the_file() {
+        let file = r#"
+            [[oauth.providers]]
+            name = "Google"
+            issuer = "https://accounts.google.com"
+
+            [[oauth.providers]]
+            name = "Discord"
+            issuer = "https://discord.com"
+            client_id = "discord-client"
+            scopes = ["openid", "identify", "email"]
+        "#;
+
+        let config = Config::from_sources(
+            Some((PathBuf::from("api.toml"), file)),
+            env(&[
+                ("DATABASE_URL", "postgres://localhost"),
+                ("OAUTH_CLIENT_ID", "google-client"),
+            ]),
+        )
+        .unwrap();
+
+        let google = config
+            .oauth
+            .provider("https://accounts.google.com")
+            .unwrap();
+        let discord = config.oauth.provider("https://discord.com").unwrap();
+        assert_eq!("google-client", google.client_id);
+        assert_eq!("Discord", discord.name);
+        assert_eq!("discord-client", discord.client_id);
+        assert_eq!(vec!["openid", "email", "profile"], google.scopes);
+        assert_eq!(vec!["openid", "identify", "email"], discord.scopes);
+        assert!(config.oauth.provider("https://discord.com/").is_none());
+    }
+}
@@ -0,0 +1,86 @@
+use endurance_racing_planner_common::access_token::NewAccessTokenDto;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::data_access::entities::access_token::AccessToken;
+
+/// The user's tokens, newest first
+pub async fn get_access_tokens_by_user_id(
+    pool: &PgPool,
+    user_id: i32,
+) -> Result<Vec<AccessToken>, sqlx::Error> {
+    sqlx::query_as!(
+        AccessToken,
+        "SELECT * FROM access_tokens WHERE user_id = $1 ORDER BY created_date DESC",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_access_token_by_hash(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<AccessToken>, sqlx::Error> {
+    sqlx::query_as!(
+        AccessToken,
+        "SELECT * FROM access_tokens WHERE token_hash = $1",
+        token_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn create_access_token(
+    pool: &PgPool,
+    user_id: i32,
+    access_token: &NewAccessTokenDto,
+    token_hash: &str,
+) -> Result<AccessToken, sqlx::Error> {
+    sqlx::query_as!(
+        AccessToken,
+        r#"INSERT INTO access_tokens (id, user_id, plan_id, name, token_hash, read_only)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *"#,
+        Uuid::new_v4(),
+        user_id,
+        access_token.plan_id,
+        access_token.name.trim(),
+        token_hash,
+        access_token.read_only
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Records that the token was used, at most once a minute so a busy script doesn't write on every
+/// request
+pub async fn touch_access_token(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE access_tokens SET last_used_date = NOW()
+            WHERE id = $1
+                AND (last_used_date IS NULL OR last_used_date < NOW() - INTERVAL '1 minute')"#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Revokes the token, returns false when the user has no such token
+pub async fn delete_access_token(
+    pool: &PgPool,
+    user_id: i32,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM access_tokens WHERE user_id = $1 AND id = $2",
+        user_id,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
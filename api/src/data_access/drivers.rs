@@ -1,4 +1,4 @@
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 
 use crate::data_access::entities::driver::Driver;
@@ -18,7 +18,10 @@ pub async fn get_drivers_by_plan_id(
     Ok(drivers)
 }
 
-pub async fn create_driver(pool: &PgPool, driver: Driver) -> Result<Driver, sqlx::Error> {
+pub async fn create_driver<'e, E: PgExecutor<'e>>(
+    executor: E,
+    driver: Driver,
+) -> Result<Driver, sqlx::Error> {
     let driver: Driver = sqlx::query_as!(
             Driver,
             r#"INSERT INTO drivers (plan_id, name, color, utc_offset, irating, stint_preference) VALUES ($1, $2, $3, $4, $5, $6)
@@ -30,7 +33,7 @@ pub async fn create_driver(pool: &PgPool, driver: Driver) -> Result<Driver, sqlx
             driver.irating,
             driver.stint_preference
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
     Ok(driver)
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use endurance_racing_planner_common::access_token::AccessTokenDto;
+use uuid::Uuid;
+
+pub struct AccessToken {
+    pub id: Uuid,
+    pub user_id: i32,
+    pub plan_id: Option<Uuid>,
+    pub name: String,
+    pub token_hash: String,
+    pub read_only: bool,
+    pub created_date: DateTime<Utc>,
+    pub last_used_date: Option<DateTime<Utc>>,
+}
+
+impl From<&AccessToken> for AccessTokenDto {
+    fn from(val: &AccessToken) -> Self {
+        AccessTokenDto {
+            id: val.id,
+            name: val.name.clone(),
+            plan_id: val.plan_id,
+            read_only: val.read_only,
+            created_date: val.created_date,
+            last_used_date: val.last_used_date,
+        }
+    }
+}
@@ -1,4 +1,9 @@
-﻿pub mod driver;
+﻿pub mod access_token;
+pub mod driver;
 pub mod plan;
+pub mod revision;
+pub mod scenario;
 pub mod schedule;
+pub mod track_condition;
+pub mod traffic;
 pub use plan::Plan;
@@ -1,6 +1,7 @@
 ﻿use chrono::{DateTime, NaiveDateTime, Utc};
 use endurance_racing_planner_common::{
-    EventConfigDto, OverallFuelStintConfigData, RacePlannerDto, StintDataDto,
+    conditions::TrackConditionDto, traffic::TrafficModelDto, EventConfigDto,
+    OverallFuelStintConfigData, RacePlannerDto, StintDataDto,
 };
 use sqlx::{
     postgres::{types::PgInterval, PgValueRef},
@@ -42,6 +43,8 @@ pub struct PlanWithOverview {
     pub fuel_tank_size: Option<i32>,
     pub tire_change_time: Option<PgInterval>,
     pub add_tire_time: Option<bool>,
+    pub tire_degradation_per_lap: Option<PgInterval>,
+    pub max_stints_per_tire_set: Option<i32>,
 }
 
 pub struct PatchPlan {
@@ -67,6 +70,8 @@ pub enum PatchPlanType {
     EventConfig(EventConfigDto),
     FuelStintConfig(OverallFuelStintConfigData),
     FuelStintAverageTime(StintDataDto, StintType),
+    TrackConditions(Vec<TrackConditionDto>),
+    TrafficModel(TrafficModelDto),
 }
 
 impl From<RacePlannerDto> for Plan {
@@ -97,6 +102,8 @@ impl From<&Plan> for RacePlannerDto {
             overall_fuel_stint_config: None,
             fuel_stint_average_times: None,
             time_of_day_lap_factors: vec![],
+            track_conditions: vec![],
+            traffic_model: None,
             per_driver_lap_factors: vec![],
             driver_roster: vec![],
             schedule_rows: None,
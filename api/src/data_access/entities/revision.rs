@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use endurance_racing_planner_common::revision::{PlanRevisionDto, PlanSection};
+use serde_json::Value;
+use sqlx::{postgres::PgValueRef, Decode, Postgres};
+use uuid::Uuid;
+
+#[repr(i16)]
+#[derive(Clone, Copy)]
+pub enum RevisionSection {
+    Title,
+    EventConfig,
+    FuelStintConfig,
+    FuelStintAverageTimes,
+    TrackConditions,
+    TrafficModel,
+    Schedule,
+}
+
+impl TryFrom<i16> for RevisionSection {
+    type Error = Box<dyn std::error::Error + 'static + Sync + Send>;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RevisionSection::Title),
+            1 => Ok(RevisionSection::EventConfig),
+            2 => Ok(RevisionSection::FuelStintConfig),
+            3 => Ok(RevisionSection::FuelStintAverageTimes),
+            4 => Ok(RevisionSection::TrackConditions),
+            5 => Ok(RevisionSection::TrafficModel),
+            6 => Ok(RevisionSection::Schedule),
+            _ => Err(format!("value: {} couldn't be converted to RevisionSection", value).into()),
+        }
+    }
+}
+
+impl Decode<'_, Postgres> for RevisionSection {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <i16 as Decode<Postgres>>::decode(value)?;
+
+        value.try_into()
+    }
+}
+
+impl From<PlanSection> for RevisionSection {
+    fn from(section: PlanSection) -> Self {
+        match section {
+            PlanSection::Title => Self::Title,
+            PlanSection::EventConfig => Self::EventConfig,
+            PlanSection::FuelStintConfig => Self::FuelStintConfig,
+            PlanSection::FuelStintAverageTimes => Self::FuelStintAverageTimes,
+            PlanSection::TrackConditions => Self::TrackConditions,
+            PlanSection::TrafficModel => Self::TrafficModel,
+            PlanSection::Schedule => Self::Schedule,
+        }
+    }
+}
+
+impl From<RevisionSection> for PlanSection {
+    fn from(val: RevisionSection) -> Self {
+        match val {
+            RevisionSection::Title => PlanSection::Title,
+            RevisionSection::EventConfig => PlanSection::EventConfig,
+            RevisionSection::FuelStintConfig => PlanSection::FuelStintConfig,
+            RevisionSection::FuelStintAverageTimes => PlanSection::FuelStintAverageTimes,
+            RevisionSection::TrackConditions => PlanSection::TrackConditions,
+            RevisionSection::TrafficModel => PlanSection::TrafficModel,
+            RevisionSection::Schedule => PlanSection::Schedule,
+        }
+    }
+}
+
+pub struct PlanRevision {
+    pub id: i64,
+    pub plan_id: Uuid,
+    pub scenario_id: Option<Uuid>,
+    pub user_name: String,
+    pub created_date: DateTime<Utc>,
+    pub section: RevisionSection,
+    pub before: Option<Value>,
+    pub after: Value,
+}
+
+impl From<PlanRevision> for PlanRevisionDto {
+    fn from(val: PlanRevision) -> Self {
+        PlanRevisionDto {
+            id: val.id,
+            user_name: val.user_name,
+            created_date: val.created_date,
+            section: val.section.into(),
+            before: val.before,
+            after: val.after,
+        }
+    }
+}
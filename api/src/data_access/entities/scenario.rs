@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use endurance_racing_planner_common::scenario::ScenarioDto;
+use uuid::Uuid;
+
+pub struct Scenario {
+    pub id: Uuid,
+    pub plan_id: Uuid,
+    pub name: String,
+    pub is_active: bool,
+    pub created_date: DateTime<Utc>,
+}
+
+impl From<&Scenario> for ScenarioDto {
+    fn from(val: &Scenario) -> Self {
+        ScenarioDto {
+            id: val.id,
+            name: val.name.clone(),
+            is_active: val.is_active,
+        }
+    }
+}
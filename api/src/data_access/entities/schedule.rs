@@ -6,6 +6,8 @@ use sqlx::{
 };
 use uuid::Uuid;
 
+use crate::data_access::to_interval;
+
 #[repr(i16)]
 #[derive(Clone)]
 pub enum StintType {
@@ -90,6 +92,9 @@ pub struct Stint {
     pub actual_laps: i32,
     pub driver_stint_count: i32,
     pub driver_id: Option<i32>,
+    pub tire_age: i32,
+    pub tire_set_stint: i32,
+    pub traffic_loss: PgInterval,
 }
 
 impl From<&ScheduleStintDto> for Stint {
@@ -103,8 +108,8 @@ impl From<&ScheduleStintDto> for Stint {
             tod_start: dto.tod_start,
             tod_end: dto.tod_end,
             actual_end: dto.actual_end,
-            duration_delta: dto.duration_delta.try_into().unwrap(),
-            damage_modifier: dto.damage_modifier.try_into().unwrap(),
+            duration_delta: to_interval(dto.duration_delta),
+            damage_modifier: to_interval(dto.damage_modifier),
             calculated_laps: dto.calculated_laps,
             actual_laps: dto.actual_laps,
             driver_stint_count: dto.stint_number,
@@ -113,6 +118,9 @@ impl From<&ScheduleStintDto> for Stint {
             } else {
                 Some(dto.driver_id)
             },
+            tire_age: dto.tire_age,
+            tire_set_stint: dto.tire_set_stint,
+            traffic_loss: to_interval(dto.traffic_loss),
         }
     }
 }
@@ -136,6 +144,9 @@ impl From<&Stint> for ScheduleStintDto {
             availability: "".into(),
             stint_number: val.driver_stint_count,
             factor: 1_f32,
+            tire_age: val.tire_age,
+            tire_set_stint: val.tire_set_stint,
+            traffic_loss: Duration::microseconds(val.traffic_loss.microseconds),
         }
     }
 }
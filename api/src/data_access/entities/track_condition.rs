@@ -0,0 +1,92 @@
+use chrono::Duration;
+use endurance_racing_planner_common::conditions::TrackConditionDto;
+use sqlx::{
+    postgres::{types::PgInterval, PgValueRef},
+    Decode, Postgres,
+};
+
+use crate::data_access::to_interval;
+
+#[repr(i16)]
+#[derive(Clone, Copy)]
+pub enum Weather {
+    Dry,
+    Damp,
+    Wet,
+}
+
+impl TryFrom<i16> for Weather {
+    type Error = Box<dyn std::error::Error + 'static + Sync + Send>;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Weather::Dry),
+            1 => Ok(Weather::Damp),
+            2 => Ok(Weather::Wet),
+            _ => Err(format!("value: {} couldn't be converted to Weather", value).into()),
+        }
+    }
+}
+
+impl Decode<'_, Postgres> for Weather {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <i16 as Decode<Postgres>>::decode(value)?;
+
+        value.try_into()
+    }
+}
+
+impl From<endurance_racing_planner_common::conditions::Weather> for Weather {
+    fn from(dto_weather: endurance_racing_planner_common::conditions::Weather) -> Self {
+        match dto_weather {
+            endurance_racing_planner_common::conditions::Weather::Dry => Self::Dry,
+            endurance_racing_planner_common::conditions::Weather::Damp => Self::Damp,
+            endurance_racing_planner_common::conditions::Weather::Wet => Self::Wet,
+        }
+    }
+}
+
+impl From<Weather> for endurance_racing_planner_common::conditions::Weather {
+    fn from(val: Weather) -> Self {
+        match val {
+            Weather::Dry => endurance_racing_planner_common::conditions::Weather::Dry,
+            Weather::Damp => endurance_racing_planner_common::conditions::Weather::Damp,
+            Weather::Wet => endurance_racing_planner_common::conditions::Weather::Wet,
+        }
+    }
+}
+
+pub struct TrackCondition {
+    pub start_offset: PgInterval,
+    pub air_temperature: f32,
+    pub track_temperature: f32,
+    pub weather: Weather,
+    pub lap_time_factor: f64,
+    pub fuel_per_lap_factor: f64,
+}
+
+impl From<&TrackConditionDto> for TrackCondition {
+    fn from(dto: &TrackConditionDto) -> Self {
+        Self {
+            start_offset: to_interval(dto.start_offset),
+            air_temperature: dto.air_temperature,
+            track_temperature: dto.track_temperature,
+            weather: dto.weather.into(),
+            lap_time_factor: dto.lap_time_factor,
+            fuel_per_lap_factor: dto.fuel_per_lap_factor,
+        }
+    }
+}
+
+impl From<&TrackCondition> for TrackConditionDto {
+    fn from(val: &TrackCondition) -> Self {
+        TrackConditionDto {
+            start_offset: Duration::microseconds(val.start_offset.microseconds),
+            air_temperature: val.air_temperature,
+            track_temperature: val.track_temperature,
+            weather: val.weather.into(),
+            lap_time_factor: val.lap_time_factor,
+            fuel_per_lap_factor: val.fuel_per_lap_factor,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{Duration, NaiveTime};
+use endurance_racing_planner_common::traffic::{
+    TrafficClassDto, TrafficIntensityDto, TrafficModelDto,
+};
+use sqlx::postgres::types::PgInterval;
+
+pub struct TrafficModel {
+    pub time_loss_per_encounter: PgInterval,
+}
+
+pub struct TrafficClass {
+    pub name: String,
+    pub relative_pace: f64,
+    pub field_size: i32,
+}
+
+pub struct TrafficIntensity {
+    pub tod_start: NaiveTime,
+    pub intensity: f64,
+}
+
+impl From<&TrafficClass> for TrafficClassDto {
+    fn from(val: &TrafficClass) -> Self {
+        TrafficClassDto {
+            name: val.name.clone(),
+            relative_pace: val.relative_pace,
+            field_size: val.field_size,
+        }
+    }
+}
+
+impl From<&TrafficIntensity> for TrafficIntensityDto {
+    fn from(val: &TrafficIntensity) -> Self {
+        TrafficIntensityDto {
+            tod_start: val.tod_start,
+            intensity: val.intensity,
+        }
+    }
+}
+
+pub fn to_traffic_model_dto(
+    model: &TrafficModel,
+    classes: &[TrafficClass],
+    intensities: &[TrafficIntensity],
+) -> TrafficModelDto {
+    TrafficModelDto {
+        time_loss_per_encounter: Duration::microseconds(model.time_loss_per_encounter.microseconds),
+        classes: classes.iter().map(|c| c.into()).collect(),
+        intensities: intensities.iter().map(|i| i.into()).collect(),
+    }
+}
@@ -0,0 +1,43 @@
+use sqlx::{
+    migrate::{MigrateError, Migrator},
+    PgPool,
+};
+
+/// The migrations in `api/migrations`, embedded in the binary at compile time
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Applies the embedded migrations that haven't been applied yet
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await
+}
+
+/// Fails with `MigrateError::VersionMissing` when the database has a migration applied that this
+/// binary doesn't know about, the schema is ahead of the code and the queries can't be trusted.
+/// Returns the versions of the embedded migrations that haven't been applied yet.
+pub async fn check_schema_version(pool: &PgPool) -> Result<Vec<i64>, MigrateError> {
+    let has_migrations_table =
+        sqlx::query_scalar::<_, bool>("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    let applied_versions = if has_migrations_table {
+        sqlx::query_scalar::<_, i64>("SELECT version FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await?
+    } else {
+        vec![]
+    };
+
+    if let Some(version) = applied_versions
+        .iter()
+        .find(|version| !MIGRATOR.iter().any(|m| m.version == **version))
+    {
+        return Err(MigrateError::VersionMissing(*version));
+    }
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .filter(|version| !applied_versions.contains(version))
+        .collect())
+}
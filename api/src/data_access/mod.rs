@@ -1,20 +1,66 @@
+pub mod access_tokens;
 pub mod drivers;
 pub mod entities;
+pub mod migrations;
 pub mod plans;
+pub mod revisions;
+pub mod scenarios;
 pub mod schedules;
+pub mod traffic;
 pub mod user;
 
-use std::env;
-
-use sqlx::postgres::PgPoolOptions;
+use chrono::Duration;
+use sqlx::postgres::{types::PgInterval, PgPoolOptions};
 use sqlx::PgPool;
 
-pub async fn initialize() -> Result<PgPool, sqlx::Error> {
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL environment variable to be set");
+use crate::config::DatabaseConfig;
+
+pub async fn initialize(config: &DatabaseConfig) -> Result<PgPool, sqlx::Error> {
     let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect_lazy(db_url.as_str())
-        .expect("database to exist");
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.acquire_timeout())
+        .idle_timeout(config.idle_timeout())
+        .connect_lazy(config.url.as_str())?;
 
     Ok(pool)
 }
+
+/// Postgres intervals only have microsecond precision and sqlx refuses to convert a duration with
+/// nanoseconds or one that overflows, so the duration is truncated to microseconds and saturated
+/// instead of failing the request
+pub fn to_interval(duration: Duration) -> PgInterval {
+    let microseconds = duration
+        .num_microseconds()
+        .unwrap_or(if duration < Duration::zero() {
+            i64::MIN
+        } else {
+            i64::MAX
+        });
+
+    PgInterval {
+        months: 0,
+        days: 0,
+        microseconds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::to_interval;
+
+    #[test]
+    fn intervals_are_truncated_to_microseconds() {
+        let interval = to_interval(Duration::nanoseconds(1_500_999));
+
+        assert_eq!(1_500, interval.microseconds);
+    }
+
+    #[test]
+    fn intervals_saturate_instead_of_overflowing() {
+        assert_eq!(i64::MAX, to_interval(Duration::MAX).microseconds);
+        assert_eq!(i64::MIN, to_interval(Duration::MIN).microseconds);
+    }
+}
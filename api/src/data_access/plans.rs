@@ -1,17 +1,22 @@
 ﻿use chrono::{DateTime, Duration, Utc};
 use endurance_racing_planner_common::{
-    EventConfigDto, OverallFuelStintConfigData, RacePlannerDto, StintDataDto,
+    conditions::TrackConditionDto, EventConfigDto, OverallFuelStintConfigData, RacePlannerDto,
+    StintDataDto,
 };
 use sqlx::{
     postgres::{types::PgInterval, PgArguments, PgQueryResult},
     query::Query,
     types::Uuid,
-    PgPool, Postgres, QueryBuilder,
+    Connection, PgConnection, PgExecutor, PgPool, Postgres, QueryBuilder,
 };
 use tokio::try_join;
 
+use crate::data_access::scenarios::{create_scenario, DEFAULT_SCENARIO_NAME};
+use crate::data_access::to_interval;
+use crate::data_access::traffic::{get_traffic_model_by_plan_id, replace_traffic_model};
 use crate::data_access::entities::{
     plan::{PatchPlan, PatchPlanType, PlanWithOverview, PlanWithOwner, StintType, FuelStintAverageTimes},
+    track_condition::{TrackCondition, Weather},
     Plan,
 };
 
@@ -34,7 +39,9 @@ pub async fn get_plan_by_id(
                 fsc.pit_duration as "pit_duration: Option<_>", 
                 fsc.fuel_tank_size as "fuel_tank_size: Option<_>", 
                 fsc.tire_change_time as "tire_change_time: Option<_>", 
-                fsc.add_tire_time as "add_tire_time: Option<_>" 
+                fsc.add_tire_time as "add_tire_time: Option<_>", 
+                fsc.tire_degradation_per_lap as "tire_degradation_per_lap: Option<_>", 
+                fsc.max_stints_per_tire_set as "max_stints_per_tire_set: Option<_>" 
             FROM plans p 
                 INNER JOIN user_plans up ON up.plan_id = p.id AND up.user_id = $2
                 LEFT OUTER JOIN event_configs ec ON ec.plan_id = p.id
@@ -64,46 +71,55 @@ pub async fn get_plan_by_id(
     )
     .fetch_all(pool);
 
-    let (plan, fuel_stint_average_times) = try_join!(plan, fuel_stint_average_times)?;
+    let track_conditions = sqlx::query_as!(
+        TrackCondition,
+        r#"SELECT
+            start_offset,
+            air_temperature,
+            track_temperature,
+            weather as "weather: Weather",
+            lap_time_factor,
+            fuel_per_lap_factor
+        FROM track_conditions
+        WHERE plan_id = $1
+        ORDER BY start_offset"#,
+        id
+    )
+    .fetch_all(pool);
+
+    let traffic_model = get_traffic_model_by_plan_id(pool, id);
 
-    let fuel_stint_average_times = if !fuel_stint_average_times.is_empty() {
-        let mut stints = fuel_stint_average_times.into_iter();
-        
-        Some(endurance_racing_planner_common::FuelStintAverageTimes {
-            standard_fuel_stint: stints
-                .find(|s| match s.stint_type {
-                    StintType::Standard => true,
-                    StintType::FuelSaving => false,
-                })
-                .map(|f| StintDataDto {
-                    lap_time: Duration::microseconds(f.lap_time.microseconds),
-                    fuel_per_lap: f.fuel_per_lap,
-                    lap_count: f.lap_count,
-                    lap_time_with_pit: Duration::microseconds(f.lap_time_with_pit.microseconds),
-                    track_time: Duration::microseconds(f.track_time.microseconds),
-                    track_time_with_pit: Duration::microseconds(f.track_time_with_pit.microseconds),
-                    fuel_per_stint: f.fuel_per_stint,
-                })
-                .expect("standard fuel stint average times"),
-            fuel_saving_stint: stints
-                .find(|s| match s.stint_type {
-                    StintType::Standard => false,
-                    StintType::FuelSaving => true,
-                })
-                .map(|f| StintDataDto {
-                    lap_time: Duration::microseconds(f.lap_time.microseconds),
-                    fuel_per_lap: f.fuel_per_lap,
-                    lap_count: f.lap_count,
-                    lap_time_with_pit: Duration::microseconds(f.lap_time_with_pit.microseconds),
-                    track_time: Duration::microseconds(f.track_time.microseconds),
-                    track_time_with_pit: Duration::microseconds(f.track_time_with_pit.microseconds),
-                    fuel_per_stint: f.fuel_per_stint,
-                })
-                .expect("fuel saving fuel stint average times"),
-        })
-    } else {
-        None
+    let (plan, fuel_stint_average_times, track_conditions, traffic_model) = try_join!(
+        plan,
+        fuel_stint_average_times,
+        track_conditions,
+        traffic_model
+    )?;
+
+    // the web saves the standard and fuel saving times one at a time, the plan has no stint
+    // times until both are saved
+    let stint_data = |stint_type: StintType| {
+        fuel_stint_average_times
+            .iter()
+            .find(|s| s.stint_type == stint_type)
+            .map(|f| StintDataDto {
+                lap_time: Duration::microseconds(f.lap_time.microseconds),
+                fuel_per_lap: f.fuel_per_lap,
+                lap_count: f.lap_count,
+                lap_time_with_pit: Duration::microseconds(f.lap_time_with_pit.microseconds),
+                track_time: Duration::microseconds(f.track_time.microseconds),
+                track_time_with_pit: Duration::microseconds(f.track_time_with_pit.microseconds),
+                fuel_per_stint: f.fuel_per_stint,
+            })
     };
+    let fuel_stint_average_times = stint_data(StintType::Standard)
+        .zip(stint_data(StintType::FuelSaving))
+        .map(|(standard_fuel_stint, fuel_saving_stint)| {
+            endurance_racing_planner_common::FuelStintAverageTimes {
+                standard_fuel_stint,
+                fuel_saving_stint,
+            }
+        });
     let dto = plan.map(|p| RacePlannerDto {
         id: p.id,
         title: p.title,
@@ -124,9 +140,18 @@ pub async fn get_plan_by_id(
                 fuel_tank_size: p.fuel_tank_size.unwrap(),
                 tire_change_time: Duration::microseconds(p.tire_change_time.unwrap().microseconds),
                 add_tire_time: p.add_tire_time.unwrap(),
+                tire_degradation_per_lap: Duration::microseconds(
+                    p.tire_degradation_per_lap.unwrap().microseconds,
+                ),
+                max_stints_per_tire_set: p.max_stints_per_tire_set.unwrap(),
             }),
         fuel_stint_average_times,
         time_of_day_lap_factors: vec![],
+        track_conditions: track_conditions
+            .iter()
+            .map(|c| c.into())
+            .collect::<Vec<TrackConditionDto>>(),
+        traffic_model,
         per_driver_lap_factors: vec![],
         driver_roster: vec![],
         schedule_rows: None,
@@ -135,7 +160,14 @@ pub async fn get_plan_by_id(
     Ok(dto)
 }
 
-pub async fn create_plan(pool: &PgPool, plan: Plan) -> Result<Plan, sqlx::Error> {
+/// Creates the plan with its owner and its default scenario. Runs in its own transaction, or in a
+/// savepoint when `connection` is already in a transaction.
+pub async fn create_plan(
+    connection: &mut PgConnection,
+    plan: Plan,
+) -> Result<Plan, sqlx::Error> {
+    let mut transaction = connection.begin().await?;
+
     let plan = sqlx::query_as!(
         Plan,
         r#"INSERT INTO plans (id, title, created_by, created_date) VALUES ($1, $2, $3, $4)
@@ -145,14 +177,24 @@ pub async fn create_plan(pool: &PgPool, plan: Plan) -> Result<Plan, sqlx::Error>
         plan.created_by,
         plan.created_date
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *transaction)
     .await?;
 
-    add_users_to_plan(pool, plan.id, &[plan.created_by]).await?;
+    add_users_to_plan(&mut *transaction, plan.id, &[plan.created_by]).await?;
+    create_scenario(&mut transaction, plan.id, DEFAULT_SCENARIO_NAME, true, vec![]).await?;
 
+    transaction.commit().await?;
     Ok(plan)
 }
 
+pub async fn get_plan_title(pool: &PgPool, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    let title = sqlx::query_scalar!("SELECT title FROM plans WHERE id = $1", id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(title)
+}
+
 pub async fn get_plans_by_user_id(
     pool: &PgPool,
     user_id: i32,
@@ -172,7 +214,7 @@ pub async fn get_plans_by_user_id(
     Ok(plans)
 }
 
-pub async fn add_users_to_plan(pool: &PgPool, plan_id: Uuid, user_ids: &[i32]) -> Result<PgQueryResult, sqlx::Error> {
+pub async fn add_users_to_plan<'e, E: PgExecutor<'e>>(executor: E, plan_id: Uuid, user_ids: &[i32]) -> Result<PgQueryResult, sqlx::Error> {
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
         "INSERT INTO user_plans (user_id, plan_id) "
     );
@@ -180,31 +222,56 @@ pub async fn add_users_to_plan(pool: &PgPool, plan_id: Uuid, user_ids: &[i32]) -
         builder.push_bind(user_id).push_bind(plan_id);
     });
     query_builder.build()
-    .execute(pool)
+    .execute(executor)
     .await
 }
 
-pub async fn patch_plan(pool: &PgPool, plan: PatchPlan) -> Result<bool, sqlx::Error> {
+/// Applies the patch in its own transaction, or in a savepoint when `connection` is already in a
+/// transaction. Returns false when the plan doesn't exist, nothing is saved then or on an error.
+pub async fn patch_plan(
+    connection: &mut PgConnection,
+    plan: PatchPlan,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = connection.begin().await?;
+
+    match apply_patch(&mut transaction, plan).await {
+        Ok(true) => {
+            transaction.commit().await?;
+            Ok(true)
+        }
+        Ok(false) => {
+            transaction.rollback().await?;
+            Ok(false)
+        }
+        Err(e) => {
+            transaction.rollback().await?;
+            Err(e)
+        }
+    }
+}
+
+async fn apply_patch(connection: &mut PgConnection, plan: PatchPlan) -> Result<bool, sqlx::Error> {
     match plan.patch_type {
         PatchPlanType::Title(title) => {
             let result = sqlx::query!(
                 r#"UPDATE plans SET title = $1, modified_by = $2, modified_date = $3 WHERE id = $4"#, 
                     title, plan.modified_by, plan.modified_date, plan.id)
-                .execute(pool)
-                .await;
+                .execute(&mut *connection)
+                .await?;
 
-            Ok(match result {
-                Ok(query_result) => query_result.rows_affected() == 1,
-                Err(_) => false,
-            })
+            Ok(result.rows_affected() == 1)
         }
         PatchPlanType::EventConfig(data) => {
-            let transaction = pool.begin().await?;
-
-            let race_duration: PgInterval = data.race_duration.try_into().unwrap();
-            let green_flag_offset: PgInterval = data.green_flag_offset.try_into().unwrap();
-            let tod_offset: PgInterval = data.tod_offset.try_into().unwrap();
-            let upsert_event_config = sqlx::query!(
+            let race_duration: PgInterval = to_interval(data.race_duration);
+            let green_flag_offset: PgInterval = to_interval(data.green_flag_offset);
+            let tod_offset: PgInterval = to_interval(data.tod_offset);
+            let update_plan_result = update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
+                .execute(&mut *connection)
+                .await?;
+            if update_plan_result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            let upsert_result = sqlx::query!(
                 r#"
                 INSERT INTO event_configs AS ec (plan_id, race_duration, session_start_utc, race_start_utc, race_end_utc, race_start_tod, race_end_tod, green_flag_offset, tod_offset)
                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
@@ -229,74 +296,81 @@ pub async fn patch_plan(pool: &PgPool, plan: PatchPlan) -> Result<bool, sqlx::Er
                 green_flag_offset,
                 tod_offset
             )
-            .execute(pool);
-
-            let result = try_join!(
-                upsert_event_config,
-                update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
-                    .execute(pool)
-            );
+            .execute(&mut *connection)
+            .await?;
 
-            Ok(match result {
-                Ok((upsert_result, update_plan_result)) => {
-                    transaction.commit().await?;
-                    upsert_result.rows_affected() == 1 && update_plan_result.rows_affected() == 1
-                }
-                Err(_) => {
-                    transaction.rollback().await?;
-                    false
-                }
-            })
+            Ok(upsert_result.rows_affected() == 1)
         }
         PatchPlanType::FuelStintConfig(config) => {
-            let transaction = pool.begin().await?;
-
-            let pit_duration: PgInterval = config.pit_duration.try_into().unwrap();
-            let tire_change_time: PgInterval = config.tire_change_time.try_into().unwrap();
-            let upsert_fuel_stint_config = sqlx::query!(
+            let pit_duration: PgInterval = to_interval(config.pit_duration);
+            let tire_change_time: PgInterval = to_interval(config.tire_change_time);
+            let tire_degradation_per_lap: PgInterval = to_interval(config.tire_degradation_per_lap);
+            let update_plan_result = update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
+                .execute(&mut *connection)
+                .await?;
+            if update_plan_result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            let upsert_result = sqlx::query!(
                 r#"
-                INSERT INTO fuel_stint_configs AS fsc (plan_id, pit_duration, fuel_tank_size, tire_change_time, add_tire_time)
-                VALUES ($5, $1, $2, $3, $4)
+                INSERT INTO fuel_stint_configs AS fsc (plan_id, pit_duration, fuel_tank_size, tire_change_time, add_tire_time, tire_degradation_per_lap, max_stints_per_tire_set)
+                VALUES ($5, $1, $2, $3, $4, $6, $7)
                 ON CONFLICT (plan_id) DO UPDATE 
                 SET 
                     pit_duration = $1, 
                     fuel_tank_size = $2, 
                     tire_change_time = $3, 
-                    add_tire_time = $4
+                    add_tire_time = $4,
+                    tire_degradation_per_lap = $6,
+                    max_stints_per_tire_set = $7
                 WHERE fsc.plan_id = $5"#,
                 pit_duration,
                 config.fuel_tank_size,
                 tire_change_time,
                 config.add_tire_time,
-                plan.id
+                plan.id,
+                tire_degradation_per_lap,
+                config.max_stints_per_tire_set
             ) 
-            .execute(pool);
+            .execute(&mut *connection)
+            .await?;
 
-            let result = try_join!(
-                upsert_fuel_stint_config,
-                update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
-                    .execute(pool)
-            );
+            Ok(upsert_result.rows_affected() == 1)
+        }
+        PatchPlanType::TrackConditions(ref track_conditions) => {
+            let update_plan_result = update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
+                .execute(&mut *connection)
+                .await?;
+            if update_plan_result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            replace_track_conditions(&mut *connection, plan.id, track_conditions).await?;
 
-            Ok(match result {
-                Ok((upsert_result, update_plan_result)) => {
-                    transaction.commit().await?;
-                    upsert_result.rows_affected() == 1 && update_plan_result.rows_affected() == 1
-                }
-                Err(_) => {
-                    transaction.rollback().await?;
-                    false
-                }
-            })
+            Ok(true)
         }
-        PatchPlanType::FuelStintAverageTime(data, stint_type) => {
-            let transaction = pool.begin().await?;
+        PatchPlanType::TrafficModel(ref traffic_model) => {
+            let update_plan_result = update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
+                .execute(&mut *connection)
+                .await?;
+            if update_plan_result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            replace_traffic_model(&mut *connection, plan.id, traffic_model).await?;
 
-            let lap_time: PgInterval = data.lap_time.try_into().unwrap();
-            let lap_time_with_pit: PgInterval = data.lap_time_with_pit.try_into().unwrap();
-            let track_time: PgInterval = data.track_time.try_into().unwrap();
-            let track_time_with_pit: PgInterval = data.track_time_with_pit.try_into().unwrap();
-            let upsert_stint_data = sqlx::query!(
+            Ok(true)
+        }
+        PatchPlanType::FuelStintAverageTime(data, stint_type) => {
+            let lap_time: PgInterval = to_interval(data.lap_time);
+            let lap_time_with_pit: PgInterval = to_interval(data.lap_time_with_pit);
+            let track_time: PgInterval = to_interval(data.track_time);
+            let track_time_with_pit: PgInterval = to_interval(data.track_time_with_pit);
+            let update_plan_result = update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
+                .execute(&mut *connection)
+                .await?;
+            if update_plan_result.rows_affected() != 1 {
+                return Ok(false);
+            }
+            sqlx::query!(
                 r#"
                 INSERT INTO fuel_stint_average_times AS fs
                     (plan_id,
@@ -328,28 +402,45 @@ pub async fn patch_plan(pool: &PgPool, plan: PatchPlan) -> Result<bool, sqlx::Er
                 track_time_with_pit,
                 data.fuel_per_stint,
                 stint_type as i16,
-            );
-
-            let result = try_join!(
-                upsert_stint_data.execute(pool),
-                update_plan_modified_by(plan.id, plan.modified_by, plan.modified_date)
-                    .execute(pool)
-            );
+            )
+            .execute(&mut *connection)
+            .await?;
 
-            match result {
-                Ok(_) => {
-                    transaction.commit().await?;
-                    Ok(true)
-                }
-                Err(_) => {
-                    transaction.rollback().await?;
-                    Ok(false)
-                }
-            }
+            Ok(true)
         }
     }
 }
 
+async fn replace_track_conditions(
+    connection: &mut PgConnection,
+    plan_id: Uuid,
+    track_conditions: &[TrackConditionDto],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM track_conditions WHERE plan_id = $1", plan_id)
+        .execute(&mut *connection)
+        .await?;
+
+    if !track_conditions.is_empty() {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO track_conditions (plan_id, start_offset, air_temperature, track_temperature, weather, lap_time_factor, fuel_per_lap_factor) "
+        );
+        query_builder.push_values(track_conditions.iter(), |mut builder, condition| {
+            let condition: TrackCondition = condition.into();
+            builder
+                .push_bind(plan_id)
+                .push_bind(condition.start_offset)
+                .push_bind(condition.air_temperature)
+                .push_bind(condition.track_temperature)
+                .push_bind(condition.weather as i16)
+                .push_bind(condition.lap_time_factor)
+                .push_bind(condition.fuel_per_lap_factor);
+        });
+        query_builder.build().execute(&mut *connection).await?;
+    }
+
+    Ok(())
+}
+
 fn update_plan_modified_by(
     id: Uuid,
     modified_by: i32,
@@ -0,0 +1,70 @@
+use endurance_racing_planner_common::revision::PlanSection;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::data_access::entities::revision::{PlanRevision, RevisionSection};
+
+/// Appends a revision to the plan's history, revisions are never updated or deleted. Schedule
+/// revisions carry the scenario whose schedule changed.
+pub async fn add_revision(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Option<Uuid>,
+    user_id: i32,
+    section: PlanSection,
+    before: Option<Value>,
+    after: Value,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"INSERT INTO plan_revisions (plan_id, scenario_id, user_id, section, before, after)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id"#,
+        plan_id,
+        scenario_id,
+        user_id,
+        RevisionSection::from(section) as i16,
+        before,
+        after
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// The plan's history, newest revision first
+pub async fn get_revisions_by_plan_id(
+    pool: &PgPool,
+    plan_id: Uuid,
+) -> Result<Vec<PlanRevision>, sqlx::Error> {
+    sqlx::query_as!(
+        PlanRevision,
+        r#"SELECT r.id, r.plan_id, r.scenario_id, u.name AS user_name, r.created_date,
+                r.section as "section: RevisionSection", r.before, r.after
+            FROM plan_revisions r
+            INNER JOIN users u ON u.id = r.user_id
+            WHERE r.plan_id = $1
+            ORDER BY r.id DESC"#,
+        plan_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_revision_by_id(
+    pool: &PgPool,
+    plan_id: Uuid,
+    revision_id: i64,
+) -> Result<Option<PlanRevision>, sqlx::Error> {
+    sqlx::query_as!(
+        PlanRevision,
+        r#"SELECT r.id, r.plan_id, r.scenario_id, u.name AS user_name, r.created_date,
+                r.section as "section: RevisionSection", r.before, r.after
+            FROM plan_revisions r
+            INNER JOIN users u ON u.id = r.user_id
+            WHERE r.plan_id = $1 AND r.id = $2"#,
+        plan_id,
+        revision_id
+    )
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,164 @@
+use sqlx::{Connection, PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::data_access::{
+    entities::{scenario::Scenario, schedule::Stint},
+    schedules::insert_stints_query,
+};
+
+/// The name of the scenario every plan starts with
+pub const DEFAULT_SCENARIO_NAME: &str = "Main";
+
+pub async fn get_scenarios_by_plan_id(
+    pool: &PgPool,
+    plan_id: Uuid,
+) -> Result<Vec<Scenario>, sqlx::Error> {
+    sqlx::query_as!(
+        Scenario,
+        "SELECT * FROM scenarios WHERE plan_id = $1 ORDER BY created_date",
+        plan_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_scenario_by_id(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+) -> Result<Option<Scenario>, sqlx::Error> {
+    sqlx::query_as!(
+        Scenario,
+        "SELECT * FROM scenarios WHERE plan_id = $1 AND id = $2",
+        plan_id,
+        scenario_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// The scenario holding the plan's schedule, `None` when the plan doesn't exist
+pub async fn get_active_scenario_id<'e, E: PgExecutor<'e>>(
+    executor: E,
+    plan_id: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT id FROM scenarios WHERE plan_id = $1 AND is_active",
+        plan_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Creates the scenario with its stints in its own transaction, or in a savepoint when
+/// `connection` is already in a transaction
+pub async fn create_scenario(
+    connection: &mut PgConnection,
+    plan_id: Uuid,
+    name: &str,
+    is_active: bool,
+    schedule: Vec<Stint>,
+) -> Result<Scenario, sqlx::Error> {
+    let mut transaction = connection.begin().await?;
+
+    let scenario = sqlx::query_as!(
+        Scenario,
+        r#"INSERT INTO scenarios (id, plan_id, name, is_active) VALUES ($1, $2, $3, $4)
+            RETURNING *"#,
+        Uuid::new_v4(),
+        plan_id,
+        name,
+        is_active
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    if !schedule.is_empty() {
+        insert_stints_query(plan_id, scenario.id, &schedule)
+            .build()
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+    Ok(scenario)
+}
+
+pub async fn rename_scenario<'e, E: PgExecutor<'e>>(
+    executor: E,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+    name: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE scenarios SET name = $3 WHERE plan_id = $1 AND id = $2",
+        plan_id,
+        scenario_id,
+        name
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Makes `scenario_id` the plan's active scenario, returns false when the plan has no such scenario
+pub async fn activate_scenario(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    // deactivate first, only one scenario per plan may be active at a time
+    sqlx::query!(
+        "UPDATE scenarios SET is_active = FALSE WHERE plan_id = $1 AND is_active",
+        plan_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let result = sqlx::query!(
+        "UPDATE scenarios SET is_active = TRUE WHERE plan_id = $1 AND id = $2",
+        plan_id,
+        scenario_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    if result.rows_affected() == 1 {
+        transaction.commit().await?;
+        Ok(true)
+    } else {
+        transaction.rollback().await?;
+        Ok(false)
+    }
+}
+
+/// Deletes an inactive scenario and its stints, returns false when there is no such inactive
+/// scenario
+pub async fn delete_scenario(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        r#"DELETE FROM stints s
+            USING scenarios sc
+            WHERE sc.id = s.scenario_id AND sc.plan_id = $1 AND sc.id = $2 AND NOT sc.is_active"#,
+        plan_id,
+        scenario_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let result = sqlx::query!(
+        "DELETE FROM scenarios WHERE plan_id = $1 AND id = $2 AND NOT is_active",
+        plan_id,
+        scenario_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+    Ok(result.rows_affected() == 1)
+}
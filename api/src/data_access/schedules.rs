@@ -1,12 +1,26 @@
 use endurance_racing_planner_common::schedule::ScheduleStintDto;
-use sqlx::{PgPool, Postgres, QueryBuilder};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::data_access::entities::schedule::{Stint, StintType};
+use crate::data_access::{
+    entities::schedule::{Stint, StintType},
+    scenarios::get_active_scenario_id,
+};
 
+/// The schedule of the plan's active scenario
 pub async fn get_schedule_by_plan_id(
     pool: &PgPool,
     plan_id: Uuid,
+) -> Result<Vec<ScheduleStintDto>, sqlx::Error> {
+    match get_active_scenario_id(pool, plan_id).await? {
+        Some(scenario_id) => get_schedule_by_scenario_id(pool, scenario_id).await,
+        None => Ok(vec![]),
+    }
+}
+
+pub async fn get_schedule_by_scenario_id(
+    pool: &PgPool,
+    scenario_id: Uuid,
 ) -> Result<Vec<ScheduleStintDto>, sqlx::Error> {
     let stints = sqlx::query_as!(
         Stint,
@@ -25,10 +39,13 @@ pub async fn get_schedule_by_plan_id(
             ,actual_laps
             ,driver_stint_count
             ,driver_id
+            ,tire_age
+            ,tire_set_stint
+            ,traffic_loss
             from public.stints s
-            WHERE s.plan_id = $1
+            WHERE s.scenario_id = $1
             ORDER BY s.number"#,
-        plan_id
+        scenario_id
     )
     .fetch_all(pool)
     .await?;
@@ -39,19 +56,101 @@ pub async fn get_schedule_by_plan_id(
         .collect::<Vec<ScheduleStintDto>>())
 }
 
+/// Adds the stints to the plan's active scenario and returns the scenario's id, `None` when the
+/// plan has no active scenario
 pub async fn create_schedule(
+    connection: &mut PgConnection,
+    plan_id: Uuid,
+    schedule: Vec<Stint>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let scenario_id = match get_active_scenario_id(&mut *connection, plan_id).await? {
+        Some(scenario_id) => scenario_id,
+        None => return Ok(None),
+    };
+    let mut query_builder = insert_stints_query(plan_id, scenario_id, &schedule);
+    let query = query_builder.build();
+    query.execute(&mut *connection).await?;
+
+    Ok(Some(scenario_id))
+}
+
+/// Swaps the schedule of the plan's active scenario and returns the scenario's id
+pub async fn replace_schedule(
     pool: &PgPool,
     plan_id: Uuid,
     schedule: Vec<Stint>,
+) -> Result<Uuid, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    let scenario_id = get_active_scenario_id(&mut *transaction, plan_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    replace_stints(&mut transaction, plan_id, scenario_id, &schedule).await?;
+
+    transaction.commit().await?;
+    Ok(scenario_id)
+}
+
+/// Swaps the schedule of one of the plan's scenarios, returns false when the plan has no scenario
+/// with `scenario_id`
+pub async fn replace_scenario_schedule(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+    schedule: Vec<Stint>,
 ) -> Result<bool, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM scenarios WHERE id = $1 AND plan_id = $2)",
+        scenario_id,
+        plan_id
+    )
+    .fetch_one(&mut *transaction)
+    .await?
+    .unwrap_or(false);
+    if !exists {
+        return Ok(false);
+    }
+    replace_stints(&mut transaction, plan_id, scenario_id, &schedule).await?;
+
+    transaction.commit().await?;
+    Ok(true)
+}
+
+async fn replace_stints(
+    connection: &mut PgConnection,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+    schedule: &[Stint],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM stints WHERE scenario_id = $1", scenario_id)
+        .execute(&mut *connection)
+        .await?;
+    if !schedule.is_empty() {
+        insert_stints_query(plan_id, scenario_id, schedule)
+            .build()
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn insert_stints_query(
+    plan_id: Uuid,
+    scenario_id: Uuid,
+    schedule: &[Stint],
+) -> QueryBuilder<'static, Postgres> {
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
-        "INSERT INTO stints (id, plan_id, stint_type, number, utc_start, utc_end, tod_start, tod_end, actual_end, duration_delta, damage_modifier, calculated_laps, actual_laps, driver_stint_count, driver_id) "
+        "INSERT INTO stints (id, plan_id, scenario_id, stint_type, number, utc_start, utc_end, tod_start, tod_end, actual_end, duration_delta, damage_modifier, calculated_laps, actual_laps, driver_stint_count, driver_id, tire_age, tire_set_stint, traffic_loss) "
     );
 
     query_builder.push_values(schedule.iter(), |mut builder, stint_data| {
         builder
             .push_bind(stint_data.id)
             .push_bind(plan_id)
+            .push_bind(scenario_id)
             .push_bind(stint_data.stint_type.clone() as i16)
             .push_bind(stint_data.number)
             .push_bind(stint_data.utc_start)
@@ -64,16 +163,22 @@ pub async fn create_schedule(
             .push_bind(stint_data.calculated_laps)
             .push_bind(stint_data.actual_laps)
             .push_bind(stint_data.driver_stint_count)
-            .push_bind(stint_data.driver_id);
+            .push_bind(stint_data.driver_id)
+            .push_bind(stint_data.tire_age)
+            .push_bind(stint_data.tire_set_stint)
+            .push_bind(stint_data.traffic_loss);
     });
 
-    let query = query_builder.build();
-    query.execute(pool).await?;
-
-    Ok(true)
+    query_builder
 }
 
-pub async fn update_schedule(pool: &PgPool, schedule: Vec<Stint>) -> Result<(), sqlx::Error> {
+/// Updates the stints of the scenario that are in `schedule`, stints of other scenarios are left
+/// alone
+pub async fn update_schedule(
+    pool: &PgPool,
+    scenario_id: Uuid,
+    schedule: Vec<Stint>,
+) -> Result<(), sqlx::Error> {
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
         "UPDATE stints as s SET
                 stint_type = u.stint_type,
@@ -88,7 +193,10 @@ pub async fn update_schedule(pool: &PgPool, schedule: Vec<Stint>) -> Result<(),
                 calculated_laps = u.calculated_laps,
                 actual_laps = u.actual_laps,
                 driver_stint_count = u.driver_stint_count,
-                driver_id = u.driver_id
+                driver_id = u.driver_id,
+                tire_age = u.tire_age,
+                tire_set_stint = u.tire_set_stint,
+                traffic_loss = u.traffic_loss
             FROM (",
     );
 
@@ -107,7 +215,10 @@ pub async fn update_schedule(pool: &PgPool, schedule: Vec<Stint>) -> Result<(),
             .push_bind(stint.calculated_laps)
             .push_bind(stint.actual_laps)
             .push_bind(stint.driver_stint_count)
-            .push_bind(stint.driver_id);
+            .push_bind(stint.driver_id)
+            .push_bind(stint.tire_age)
+            .push_bind(stint.tire_set_stint)
+            .push_bind(stint.traffic_loss);
     });
 
     query_builder.push(
@@ -124,10 +235,14 @@ pub async fn update_schedule(pool: &PgPool, schedule: Vec<Stint>) -> Result<(),
                 calculated_laps,
                 actual_laps,
                 driver_stint_count,
-                driver_id
+                driver_id,
+                tire_age,
+                tire_set_stint,
+                traffic_loss
             )
-            where s.id = u.id",
+            where s.id = u.id AND s.scenario_id = ",
     );
+    query_builder.push_bind(scenario_id);
 
     let query = query_builder.build();
     query.execute(pool).await?;
@@ -0,0 +1,100 @@
+use endurance_racing_planner_common::traffic::TrafficModelDto;
+use sqlx::{postgres::types::PgInterval, PgConnection, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::data_access::{
+    entities::traffic::{to_traffic_model_dto, TrafficClass, TrafficIntensity, TrafficModel},
+    to_interval,
+};
+
+pub async fn get_traffic_model_by_plan_id(
+    pool: &PgPool,
+    plan_id: Uuid,
+) -> Result<Option<TrafficModelDto>, sqlx::Error> {
+    let model = sqlx::query_as!(
+        TrafficModel,
+        "SELECT time_loss_per_encounter FROM traffic_models WHERE plan_id = $1",
+        plan_id
+    )
+    .fetch_optional(pool);
+
+    let classes = sqlx::query_as!(
+        TrafficClass,
+        "SELECT name, relative_pace, field_size FROM traffic_classes WHERE plan_id = $1 ORDER BY id",
+        plan_id
+    )
+    .fetch_all(pool);
+
+    let intensities = sqlx::query_as!(
+        TrafficIntensity,
+        "SELECT tod_start, intensity FROM traffic_intensities WHERE plan_id = $1 ORDER BY tod_start",
+        plan_id
+    )
+    .fetch_all(pool);
+
+    let (model, classes, intensities) = tokio::try_join!(model, classes, intensities)?;
+
+    Ok(model.map(|model| to_traffic_model_dto(&model, &classes, &intensities)))
+}
+
+/// Replaces the plan's traffic model, the caller runs it in a transaction with the rest of the
+/// patch
+pub async fn replace_traffic_model(
+    connection: &mut PgConnection,
+    plan_id: Uuid,
+    traffic_model: &TrafficModelDto,
+) -> Result<(), sqlx::Error> {
+    let time_loss_per_encounter: PgInterval = to_interval(traffic_model.time_loss_per_encounter);
+    sqlx::query!(
+        r#"
+        INSERT INTO traffic_models AS tm (plan_id, time_loss_per_encounter)
+        VALUES ($1, $2)
+        ON CONFLICT (plan_id) DO UPDATE
+        SET time_loss_per_encounter = $2
+        WHERE tm.plan_id = $1"#,
+        plan_id,
+        time_loss_per_encounter
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query!("DELETE FROM traffic_classes WHERE plan_id = $1", plan_id)
+        .execute(&mut *connection)
+        .await?;
+    if !traffic_model.classes.is_empty() {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO traffic_classes (plan_id, name, relative_pace, field_size) ",
+        );
+        query_builder.push_values(traffic_model.classes.iter(), |mut builder, class| {
+            builder
+                .push_bind(plan_id)
+                .push_bind(class.name.clone())
+                .push_bind(class.relative_pace)
+                .push_bind(class.field_size);
+        });
+        query_builder.build().execute(&mut *connection).await?;
+    }
+
+    sqlx::query!(
+        "DELETE FROM traffic_intensities WHERE plan_id = $1",
+        plan_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    if !traffic_model.intensities.is_empty() {
+        let mut query_builder: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO traffic_intensities (plan_id, tod_start, intensity) ");
+        query_builder.push_values(
+            traffic_model.intensities.iter(),
+            |mut builder, intensity| {
+                builder
+                    .push_bind(plan_id)
+                    .push_bind(intensity.tod_start)
+                    .push_bind(intensity.intensity);
+            },
+        );
+        query_builder.build().execute(&mut *connection).await?;
+    }
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
-use endurance_racing_planner_common::User;
-use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use endurance_racing_planner_common::{PatchUserDto, User};
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder, Row};
 use uuid::Uuid;
 
 pub struct Users;
@@ -13,14 +13,19 @@ impl Users {
         Ok(user)
     }
 
-    pub async fn get_user_by_oauth_id(
-        pool: &PgPool,
-        oauth_id: String,
+    pub async fn get_user_by_identity<'e, E: PgExecutor<'e>>(
+        executor: E,
+        issuer: &str,
+        subject: &str,
     ) -> Result<Option<User>, sqlx::Error> {
-        let user: Option<User> =
-            sqlx::query_as!(User, "SELECT * FROM users WHERE oauth_id = $1", oauth_id)
-                .fetch_optional(pool)
-                .await?;
+        let user: Option<User> = sqlx::query_as!(
+            User,
+            "SELECT * FROM users WHERE issuer = $1 AND subject = $2",
+            issuer,
+            subject
+        )
+        .fetch_optional(executor)
+        .await?;
 
         Ok(user)
     }
@@ -44,7 +49,9 @@ impl Users {
                     id: row.try_get("id")?,
                     name: row.try_get("name")?,
                     email: row.try_get("email")?,
-                    oauth_id: row.try_get("oauth_id")?,
+                    issuer: row.try_get("issuer")?,
+                    subject: row.try_get("subject")?,
+                    iracing_customer_id: row.try_get("iracing_customer_id")?,
                 })
             })
             .fetch_all(pool)
@@ -69,14 +76,65 @@ impl Users {
         Ok(users)
     }
 
-    pub async fn create_user(pool: &PgPool, user: User) -> Result<User, sqlx::Error> {
+    pub async fn create_user<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user: User,
+    ) -> Result<User, sqlx::Error> {
         let user: User = sqlx::query_as!(
             User,
-            r#"INSERT INTO users (name, email, oauth_id) VALUES ($1, $2, $3)
+            r#"INSERT INTO users (name, email, issuer, subject) VALUES ($1, $2, $3, $4)
+                RETURNING *"#,
+            user.name,
+            user.email,
+            user.issuer,
+            user.subject
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Creates the user unless the issuer and subject are already registered, the flag is true
+    /// when the user was created
+    pub async fn register_user(pool: &PgPool, user: User) -> Result<(User, bool), sqlx::Error> {
+        let created: Option<User> = sqlx::query_as!(
+            User,
+            r#"INSERT INTO users (name, email, issuer, subject) VALUES ($1, $2, $3, $4)
+                ON CONFLICT (issuer, subject) DO NOTHING
                 RETURNING *"#,
             user.name,
             user.email,
-            user.oauth_id
+            user.issuer,
+            user.subject
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        match created {
+            Some(created) => Ok((created, true)),
+            None => Users::get_user_by_identity(pool, &user.issuer, &user.subject)
+                .await?
+                .map(|existing| (existing, false))
+                .ok_or(sqlx::Error::RowNotFound),
+        }
+    }
+
+    pub async fn patch_user(
+        pool: &PgPool,
+        id: i32,
+        patch: PatchUserDto,
+    ) -> Result<User, sqlx::Error> {
+        let user: User = sqlx::query_as!(
+            User,
+            r#"UPDATE users
+                SET name = COALESCE($2, name),
+                    iracing_customer_id = COALESCE($3, iracing_customer_id)
+                WHERE id = $1
+                RETURNING *"#,
+            id,
+            patch.name,
+            patch.iracing_customer_id
         )
         .fetch_one(pool)
         .await?;
@@ -1,70 +1,86 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::State,
+    http::{header, StatusCode},
     response::IntoResponse,
     Json,
 };
-use endurance_racing_planner_common::Driver;
+use chrono::Utc;
+use endurance_racing_planner_common::{calendar::driver_calendar, Driver};
 use sqlx::{types::Uuid, PgPool};
+use tokio::try_join;
 
-use crate::data_access::{
-    self,
-    drivers::{create_driver, get_drivers_by_plan_id, update_driver},
+use crate::{
+    data_access::{
+        self,
+        drivers::{create_driver, get_drivers_by_plan_id, update_driver},
+        plans::get_plan_title,
+        schedules::get_schedule_by_plan_id,
+    },
+    error::{ApiError, ApiPath, ValidJson},
 };
 
 pub(crate) async fn add_driver(
     State(pool): State<PgPool>,
-    Path(plan_id): Path<Uuid>,
-    Json(driver): Json<Driver>,
-) -> impl IntoResponse {
+    ApiPath(plan_id): ApiPath<Uuid>,
+    ValidJson(driver): ValidJson<Driver>,
+) -> Result<impl IntoResponse, ApiError> {
     let driver = data_access::entities::driver::Driver::create(driver, plan_id);
-    let new_driver_result = create_driver(&pool, driver).await;
-    match new_driver_result {
-        Ok(new_driver) => (StatusCode::CREATED, Json::<Driver>(new_driver.into())).into_response(),
-        Err(e) => {
-            tracing::error!("{:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "there was a problem creating the driver",
-            )
-                .into_response()
-        }
-    }
+    let new_driver = create_driver(&pool, driver)
+        .await
+        .map_err(|e| ApiError::internal("there was a problem creating the driver", e))?;
+
+    Ok((StatusCode::CREATED, Json::<Driver>(new_driver.into())))
 }
 
 pub(crate) async fn get_plan_drivers(
-    Path(id): Path<Uuid>,
+    ApiPath(id): ApiPath<Uuid>,
     State(pool): State<PgPool>,
-) -> impl IntoResponse {
-    get_drivers_by_plan_id(&pool, id)
+) -> Result<impl IntoResponse, ApiError> {
+    let drivers = get_drivers_by_plan_id(&pool, id)
         .await
-        .map(|drivers| {
-            Json(
-                drivers
-                    .iter()
-                    .map(|d| -> Driver { d.into() })
-                    .collect::<Vec<_>>(),
-            )
-            .into_response()
-        })
-        .unwrap_or_else(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to get the plan drivers",
-            )
-                .into_response()
-        })
+        .map_err(|e| ApiError::internal("failed to get the plan drivers", e))?;
+
+    Ok(Json(
+        drivers
+            .iter()
+            .map(|d| -> Driver { d.into() })
+            .collect::<Vec<_>>(),
+    ))
 }
 
 pub(crate) async fn put_driver(
-    Path(driver_id): Path<i32>,
+    ApiPath(driver_id): ApiPath<i32>,
     State(pool): State<PgPool>,
-    Json(driver): Json<endurance_racing_planner_common::Driver>,
-) -> impl IntoResponse {
+    ValidJson(driver): ValidJson<Driver>,
+) -> Result<impl IntoResponse, ApiError> {
     let driver = data_access::entities::driver::Driver::create(driver, Uuid::nil());
-    let result = update_driver(&pool, driver_id, driver).await;
-    match result {
-        Ok(_) => (StatusCode::OK).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-    }
+    update_driver(&pool, driver_id, driver)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the driver", e))?;
+
+    Ok(StatusCode::OK)
+}
+
+pub(crate) async fn get_driver_calendar(
+    ApiPath((plan_id, driver_id)): ApiPath<(Uuid, i32)>,
+    State(pool): State<PgPool>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (plan_title, drivers, schedule) = try_join!(
+        get_plan_title(&pool, plan_id),
+        get_drivers_by_plan_id(&pool, plan_id),
+        get_schedule_by_plan_id(&pool, plan_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the driver calendar", e))?;
+    let plan_title = plan_title.ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+    let drivers = drivers.iter().map(|d| d.into()).collect::<Vec<Driver>>();
+    let driver = drivers
+        .iter()
+        .find(|d| d.id == driver_id)
+        .ok_or_else(|| ApiError::not_found("the driver does not exist"))?;
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        driver_calendar(&plan_title, driver, &drivers, &schedule, Utc::now()),
+    ))
 }
@@ -0,0 +1,210 @@
+use std::error::Error;
+
+use axum::{
+    async_trait,
+    extract::{
+        rejection::{JsonRejection, PathRejection},
+        FromRequest,
+    },
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_macros::FromRequestParts;
+use endurance_racing_planner_common::{
+    api_error::{ApiErrorCode, ApiErrorDto, FieldErrorDto},
+    validation::Validate,
+};
+use serde::de::DeserializeOwned;
+
+/// Every way a request can fail. The response body is an `ApiErrorDto`, the cause of an internal
+/// error is logged and never sent to the client.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Validation(String, Vec<FieldErrorDto>),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Internal {
+        message: &'static str,
+        cause: Box<dyn Error + Send + Sync>,
+    },
+}
+
+impl ApiError {
+    /// `message` is what the client sees, `cause` is only logged
+    pub fn internal(message: &'static str, cause: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        ApiError::Internal {
+            message,
+            cause: cause.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        ApiError::NotFound(message.into())
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError::BadRequest(message.into())
+    }
+
+    /// The fields of the request that failed their `Validate` rules
+    pub fn invalid(details: Vec<FieldErrorDto>) -> Self {
+        ApiError::Validation("some of the values are invalid".to_string(), details)
+    }
+
+    /// The message sent to the client
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::Validation(message, _)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::NotFound(message)
+            | ApiError::Conflict(message) => message,
+            ApiError::Internal { message, .. } => message,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Validation(_, _) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn into_dto(self) -> ApiErrorDto {
+        let (code, message, details) = match self {
+            ApiError::BadRequest(message) => (ApiErrorCode::BadRequest, message, vec![]),
+            ApiError::Validation(message, details) => {
+                (ApiErrorCode::ValidationFailed, message, details)
+            }
+            ApiError::Unauthorized(message) => (ApiErrorCode::Unauthorized, message, vec![]),
+            ApiError::Forbidden(message) => (ApiErrorCode::Forbidden, message, vec![]),
+            ApiError::NotFound(message) => (ApiErrorCode::NotFound, message, vec![]),
+            ApiError::Conflict(message) => (ApiErrorCode::Conflict, message, vec![]),
+            ApiError::Internal { message, cause } => {
+                tracing::error!("{}: {:?}", message, cause);
+                (ApiErrorCode::InternalError, message.to_string(), vec![])
+            }
+        };
+
+        ApiErrorDto {
+            code,
+            message,
+            details,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        (status, Json(self.into_dto())).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+/// `axum::Json` that rejects a bad body with an `ApiError`
+#[derive(axum_macros::FromRequest)]
+#[from_request(via(Json), rejection(ApiError))]
+pub struct ApiJson<T>(pub T);
+
+/// `ApiJson` that also rejects a body that fails its `Validate` rules
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    ApiJson<T>: FromRequest<S, B, Rejection = ApiError>,
+    S: Send + Sync,
+    B: Send + 'static,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let ApiJson(value) = ApiJson::<T>::from_request(request, state).await?;
+        value.validate().map_err(ApiError::invalid)?;
+
+        Ok(Self(value))
+    }
+}
+
+/// `axum::extract::Path` that rejects a bad path with an `ApiError`
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode, response::IntoResponse};
+    use endurance_racing_planner_common::api_error::FieldErrorDto;
+
+    use super::ApiError;
+
+    #[test]
+    fn error_statuses_match_their_codes() {
+        let errors = [
+            (ApiError::bad_request("bad"), StatusCode::BAD_REQUEST),
+            (
+                ApiError::Validation(
+                    "invalid".to_string(),
+                    vec![FieldErrorDto::new("title", "required")],
+                ),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ApiError::Unauthorized("no bearer token".to_string()),
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                ApiError::Forbidden("unverified email".to_string()),
+                StatusCode::FORBIDDEN,
+            ),
+            (ApiError::not_found("missing"), StatusCode::NOT_FOUND),
+            (
+                ApiError::Conflict("active".to_string()),
+                StatusCode::CONFLICT,
+            ),
+            (
+                ApiError::internal("failed to save the plan", "connection reset"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+
+        for (error, status) in errors {
+            assert_eq!(status, error.into_response().status());
+        }
+    }
+
+    #[test]
+    fn internal_errors_hide_their_cause() {
+        let dto = ApiError::internal(
+            "failed to save the plan",
+            "relation \"plans\" does not exist",
+        )
+        .into_dto();
+
+        assert_eq!("failed to save the plan", dto.message);
+        assert!(dto.details.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{data_access::migrations::check_schema_version, identity::IdentityProviders};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadinessDto {
+    pub(crate) ready: bool,
+    pub(crate) database: CheckResult,
+    pub(crate) migrations: CheckResult,
+    pub(crate) oauth_signing_keys: CheckResult,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CheckResult {
+    Ok,
+    Failed(String),
+}
+
+impl CheckResult {
+    fn is_ok(&self) -> bool {
+        matches!(self, CheckResult::Ok)
+    }
+}
+
+/// The process is up and serving requests
+pub(crate) async fn healthz() -> impl IntoResponse {
+    StatusCode::OK
+}
+
+/// The api can serve requests: the database is reachable, its schema matches this build and the
+/// OAuth signing keys can be fetched, or are cached, to authenticate users. The endpoint is
+/// anonymous, so the errors are logged and the response only says which check failed.
+pub(crate) async fn readyz(
+    State(pool): State<PgPool>,
+    State(identity): State<Arc<IdentityProviders>>,
+) -> impl IntoResponse {
+    let database = match sqlx::query("SELECT 1").execute(&pool).await {
+        Ok(_) => CheckResult::Ok,
+        Err(e) => {
+            tracing::error!("readiness check: database unavailable: {}", e);
+            CheckResult::Failed("database unavailable".to_string())
+        }
+    };
+    let migrations = match check_schema_version(&pool).await {
+        Ok(pending) if pending.is_empty() => CheckResult::Ok,
+        Ok(pending) => {
+            CheckResult::Failed(format!("{} migrations haven't been applied", pending.len()))
+        }
+        Err(e) => {
+            tracing::error!("readiness check: failed to check migrations: {}", e);
+            CheckResult::Failed("migrations couldn't be checked".to_string())
+        }
+    };
+    let oauth_signing_keys = match identity.check_signing_keys().await {
+        Ok(_) => CheckResult::Ok,
+        Err(e) => {
+            tracing::error!("readiness check: no oauth signing keys: {}", e);
+            CheckResult::Failed("oauth signing keys unavailable".to_string())
+        }
+    };
+
+    let ready = database.is_ok() && migrations.is_ok() && oauth_signing_keys.is_ok();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(ReadinessDto {
+            ready,
+            database,
+            migrations,
+            oauth_signing_keys,
+        }),
+    )
+}
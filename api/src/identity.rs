@@ -0,0 +1,689 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{async_trait, extract::State, response::IntoResponse, Json};
+use endurance_racing_planner_common::{IdentityProviderDto, OpenIdClaims};
+use jwt_compact::{
+    alg::{Rsa, RsaPublicKey},
+    jwk::JsonWebKey,
+    AlgorithmExt, TimeOptions, UntrustedToken,
+};
+use reqwest_middleware::ClientWithMiddleware;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use crate::{
+    config::{IdentityProviderConfig, OAuthConfig},
+    error::ApiError,
+};
+
+/// How long the signing keys of a provider are used before its discovery document and keys are
+/// fetched again
+const SIGNING_KEYS_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// A token signed with a key we don't know refetches the keys, in case the provider rotated them,
+/// but no more often than this
+const SIGNING_KEYS_MIN_REFRESH: Duration = Duration::from_secs(30);
+
+/// Verifies the bearer tokens that aren't personal access tokens. The api uses
+/// `IdentityProviders`, the integration tests swap in a verifier that never reaches a provider.
+#[async_trait]
+pub trait TokenVerifier: Send + Sync {
+    async fn verify(&self, token: &str) -> Result<Identity, IdentityError>;
+}
+
+/// Verifies ID tokens issued by the configured OpenID Connect providers. Every provider is found
+/// through its discovery document so adding one is only configuration.
+pub struct IdentityProviders {
+    oauth: OAuthConfig,
+    http_client: ClientWithMiddleware,
+    /// The signing keys of each provider by issuer
+    signing_keys: HashMap<String, RwLock<Option<CachedSigningKeys>>>,
+}
+
+/// Who a verified ID token was issued to. Subjects are only unique within their issuer.
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+    pub claims: OpenIdClaims,
+}
+
+#[derive(Debug)]
+pub enum IdentityError {
+    MalformedToken,
+    UnknownIssuer,
+    SigningKeys(Box<dyn Error + Send + Sync>),
+    MissingKeyId,
+    UnknownSigningKey,
+    InvalidSignature,
+    Expired,
+    NotYetValid,
+    WrongAudience,
+    MissingNonce,
+}
+
+impl Display for IdentityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityError::MalformedToken => write!(f, "invalid token"),
+            IdentityError::UnknownIssuer => write!(f, "the token's issuer isn't accepted"),
+            IdentityError::SigningKeys(e) => write!(f, "no oauth signing keys: {}", e),
+            IdentityError::MissingKeyId => write!(f, "the token doesn't name its signing key"),
+            IdentityError::UnknownSigningKey => write!(f, "bad signing key"),
+            IdentityError::InvalidSignature => write!(f, "invalid token signature"),
+            IdentityError::Expired => write!(f, "token expired"),
+            IdentityError::NotYetValid => write!(f, "token isn't valid yet"),
+            IdentityError::WrongAudience => write!(f, "the token wasn't issued to this app"),
+            IdentityError::MissingNonce => write!(f, "the token has no nonce"),
+        }
+    }
+}
+
+impl Error for IdentityError {}
+
+impl From<IdentityError> for ApiError {
+    fn from(error: IdentityError) -> Self {
+        match error {
+            IdentityError::SigningKeys(e) => ApiError::internal("no oauth signing keys", e),
+            e => ApiError::Unauthorized(e.to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DiscoveryDocument {
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct SigningKey<'a> {
+    kid: Option<String>,
+    #[serde(flatten)]
+    key: JsonWebKey<'a>,
+}
+
+#[derive(Deserialize)]
+struct SigningKeys<'a> {
+    keys: Vec<SigningKey<'a>>,
+}
+
+struct CachedSigningKeys {
+    jwks_uri: String,
+    keys: HashMap<String, RsaPublicKey>,
+    fetched_at: Instant,
+}
+
+impl CachedSigningKeys {
+    fn is_stale(&self) -> bool {
+        self.fetched_at.elapsed() >= SIGNING_KEYS_MAX_AGE
+    }
+
+    fn can_refresh(&self) -> bool {
+        self.fetched_at.elapsed() >= SIGNING_KEYS_MIN_REFRESH
+    }
+}
+
+impl IdentityProviders {
+    pub fn new(oauth: OAuthConfig, http_client: ClientWithMiddleware) -> Self {
+        let signing_keys = oauth
+            .providers
+            .iter()
+            .map(|provider| (provider.issuer.clone(), RwLock::new(None)))
+            .collect();
+
+        Self {
+            oauth,
+            http_client,
+            signing_keys,
+        }
+    }
+
+    pub fn providers(&self) -> &[IdentityProviderConfig] {
+        &self.oauth.providers
+    }
+
+    /// Checks the signature, lifetime, issuer, audience and nonce of the token with the keys of
+    /// the provider that issued it.
+    ///
+    /// The web asks for a nonce on every sign in and checks it against the one it stored, the
+    /// token is then reused as the bearer token for its lifetime so all the api can check is that
+    /// the token came from a sign in that asked for one.
+    pub async fn verify(&self, token: &str) -> Result<Identity, IdentityError> {
+        let token = UntrustedToken::new(token).map_err(|_| IdentityError::MalformedToken)?;
+        // the unverified issuer only picks the keys, a token signed by anyone else won't verify
+        let issuer = token
+            .deserialize_claims_unchecked::<OpenIdClaims>()
+            .map_err(|_| IdentityError::MalformedToken)?
+            .custom
+            .iss;
+        let provider = self
+            .oauth
+            .provider(&issuer)
+            .ok_or(IdentityError::UnknownIssuer)?;
+
+        let key_id = token
+            .header()
+            .key_id
+            .as_deref()
+            .ok_or(IdentityError::MissingKeyId)?;
+        let signing_key = self.signing_key(provider, key_id).await?;
+
+        let token = Rsa::rs256()
+            .validate_integrity::<OpenIdClaims>(&token, &signing_key)
+            .map_err(|_| IdentityError::InvalidSignature)?;
+        let time_options = TimeOptions::default();
+        token
+            .claims()
+            .validate_expiration(&time_options)
+            .map_err(|_| IdentityError::Expired)?;
+        if token.claims().not_before.is_some() {
+            token
+                .claims()
+                .validate_maturity(&time_options)
+                .map_err(|_| IdentityError::NotYetValid)?;
+        }
+
+        let (_, claims) = token.into_parts();
+        let claims = claims.custom;
+        if claims.iss != provider.issuer {
+            return Err(IdentityError::UnknownIssuer);
+        }
+        validate_audience(&claims, &provider.client_id)?;
+        if claims.nonce.as_deref().unwrap_or_default().is_empty() {
+            return Err(IdentityError::MissingNonce);
+        }
+
+        Ok(Identity {
+            issuer: provider.issuer.clone(),
+            subject: claims.sub.clone(),
+            claims,
+        })
+    }
+
+    /// Makes sure the signing keys of every provider are cached, readiness fails when any of them
+    /// can't be found
+    pub async fn check_signing_keys(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for provider in self.oauth.providers.iter() {
+            let fresh = self.signing_keys[&provider.issuer]
+                .read()
+                .await
+                .as_ref()
+                .is_some_and(|cached| !cached.is_stale());
+            if !fresh {
+                self.refresh_signing_keys(provider)
+                    .await
+                    .map_err(|e| format!("{}: {}", provider.name, e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The cached key named by the token, the keys are fetched again when they're stale or the
+    /// provider has rotated in a key we haven't seen
+    async fn signing_key(
+        &self,
+        provider: &IdentityProviderConfig,
+        key_id: &str,
+    ) -> Result<RsaPublicKey, IdentityError> {
+        if let Some(cached) = self.signing_keys[&provider.issuer].read().await.as_ref() {
+            if !cached.is_stale() {
+                match cached.keys.get(key_id) {
+                    Some(key) => return Ok(key.clone()),
+                    None if !cached.can_refresh() => return Err(IdentityError::UnknownSigningKey),
+                    None => {}
+                }
+            }
+        }
+
+        self.refresh_signing_keys(provider)
+            .await
+            .map_err(IdentityError::SigningKeys)?
+            .get(key_id)
+            .cloned()
+            .ok_or(IdentityError::UnknownSigningKey)
+    }
+
+    /// Fetches the provider's keys, the discovery document is only fetched again once the cached
+    /// keys are stale
+    async fn refresh_signing_keys(
+        &self,
+        provider: &IdentityProviderConfig,
+    ) -> Result<HashMap<String, RsaPublicKey>, Box<dyn Error + Send + Sync>> {
+        let mut cache = self.signing_keys[&provider.issuer].write().await;
+        let jwks_uri = match cache.as_ref() {
+            // another request refreshed the keys while this one waited for the lock
+            Some(cached) if !cached.can_refresh() => return Ok(cached.keys.clone()),
+            Some(cached) if !cached.is_stale() => cached.jwks_uri.clone(),
+            _ => {
+                self.http_client
+                    .get(OAuthConfig::discovery_url(&provider.issuer))
+                    .header("Accept", "application/json")
+                    .send()
+                    .await?
+                    .json::<DiscoveryDocument>()
+                    .await?
+                    .jwks_uri
+            }
+        };
+
+        let signing_keys = self
+            .http_client
+            .get(&jwks_uri)
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .json::<SigningKeys>()
+            .await?;
+        // keys without an id can't be picked by a token and only RSA keys can check RS256
+        let keys = signing_keys
+            .keys
+            .iter()
+            .filter_map(|key| {
+                let key_id = key.kid.clone()?;
+                let public_key = RsaPublicKey::try_from(&key.key).ok()?;
+                Some((key_id, public_key))
+            })
+            .collect::<HashMap<_, _>>();
+
+        *cache = Some(CachedSigningKeys {
+            jwks_uri,
+            keys: keys.clone(),
+            fetched_at: Instant::now(),
+        });
+
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl TokenVerifier for IdentityProviders {
+    async fn verify(&self, token: &str) -> Result<Identity, IdentityError> {
+        IdentityProviders::verify(self, token).await
+    }
+}
+
+/// The token must be issued to our client, a token with several audiences must also have been
+/// requested by our client
+fn validate_audience(claims: &OpenIdClaims, client_id: &str) -> Result<(), IdentityError> {
+    let issued_to_us = !client_id.is_empty() && claims.aud.iter().any(|aud| aud == client_id);
+    let requested_by_us = match claims.azp.as_deref() {
+        Some(azp) => azp == client_id,
+        None => claims.aud.len() == 1,
+    };
+
+    if issued_to_us && requested_by_us {
+        Ok(())
+    } else {
+        Err(IdentityError::WrongAudience)
+    }
+}
+
+/// The providers the web offers to sign in with, a provider without a client id can't be offered
+pub(crate) async fn get_providers(
+    State(identity): State<Arc<IdentityProviders>>,
+) -> impl IntoResponse {
+    let providers = identity
+        .providers()
+        .iter()
+        .filter(|provider| !provider.client_id.is_empty())
+        .map(|provider| IdentityProviderDto {
+            name: provider.name.clone(),
+            issuer: provider.issuer.clone(),
+            client_id: provider.client_id.clone(),
+            scopes: provider.scopes.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    Json(providers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use axum::{routing::get, Json, Router};
+    use chrono::{Duration, Utc};
+    use endurance_racing_planner_common::OpenIdClaims;
+    use jwt_compact::{
+        alg::{Rsa, RsaPrivateKey},
+        jwk::JsonWebKey,
+        AlgorithmExt, Claims, Header, TimeOptions,
+    };
+    use reqwest_middleware::ClientBuilder;
+    use serde_json::{json, Value};
+
+    use super::{IdentityError, IdentityProviders, SIGNING_KEYS_MAX_AGE, SIGNING_KEYS_MIN_REFRESH};
+    use crate::config::{IdentityProviderConfig, OAuthConfig};
+
+    const KEY_ID: &str = "mock-key";
+    const CLIENT_ID: &str = "planner-client";
+
+    /// A local OpenID Connect issuer that serves a discovery document and its JWKS, and counts
+    /// how often each is fetched
+    struct MockIssuer {
+        issuer: String,
+        signing_key: RsaPrivateKey,
+        jwks: Arc<Mutex<Vec<Value>>>,
+        discovery_fetches: Arc<AtomicUsize>,
+        jwks_fetches: Arc<AtomicUsize>,
+    }
+
+    impl MockIssuer {
+        fn start() -> Self {
+            let signing_key = new_signing_key();
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let jwks = Arc::new(Mutex::new(vec![public_jwk(&signing_key, KEY_ID)]));
+            let discovery_fetches = Arc::new(AtomicUsize::new(0));
+            let jwks_fetches = Arc::new(AtomicUsize::new(0));
+
+            let discovery_document = json!({
+                "issuer": issuer,
+                "jwks_uri": format!("{}/jwks", issuer),
+            });
+            let app = Router::new()
+                .route(
+                    "/.well-known/openid-configuration",
+                    get({
+                        let fetches = discovery_fetches.clone();
+                        move || async move {
+                            fetches.fetch_add(1, Ordering::SeqCst);
+                            Json(discovery_document)
+                        }
+                    }),
+                )
+                .route(
+                    "/jwks",
+                    get({
+                        let jwks = jwks.clone();
+                        let fetches = jwks_fetches.clone();
+                        move || async move {
+                            fetches.fetch_add(1, Ordering::SeqCst);
+                            let keys = jwks.lock().unwrap().clone();
+                            Json(json!({ "keys": keys }))
+                        }
+                    }),
+                );
+            let server = axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service());
+            tokio::spawn(server);
+
+            Self {
+                issuer,
+                signing_key,
+                jwks,
+                discovery_fetches,
+                jwks_fetches,
+            }
+        }
+
+        fn provider(&self, name: &str) -> IdentityProviderConfig {
+            IdentityProviderConfig {
+                name: name.to_string(),
+                issuer: self.issuer.clone(),
+                client_id: CLIENT_ID.to_string(),
+                scopes: vec!["openid".to_string()],
+            }
+        }
+
+        /// Publishes another key, as a provider does before it starts signing with it
+        fn rotate(&self, key_id: &str) -> RsaPrivateKey {
+            let signing_key = new_signing_key();
+            self.jwks
+                .lock()
+                .unwrap()
+                .push(public_jwk(&signing_key, key_id));
+
+            signing_key
+        }
+
+        fn token(&self, issuer: &str, subject: &str, valid_for: Duration) -> String {
+            let claims = Claims::new(claims(issuer, subject))
+                .set_duration_and_issuance(&TimeOptions::default(), valid_for);
+
+            sign(&claims, Some(KEY_ID), &self.signing_key)
+        }
+
+        fn token_with(&self, claims: OpenIdClaims) -> String {
+            let claims = Claims::new(claims)
+                .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(5));
+
+            sign(&claims, Some(KEY_ID), &self.signing_key)
+        }
+
+        fn fetches(&self) -> (usize, usize) {
+            (
+                self.discovery_fetches.load(Ordering::SeqCst),
+                self.jwks_fetches.load(Ordering::SeqCst),
+            )
+        }
+    }
+
+    fn new_signing_key() -> RsaPrivateKey {
+        RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap()
+    }
+
+    fn public_jwk(signing_key: &RsaPrivateKey, key_id: &str) -> Value {
+        let mut jwk = serde_json::to_value(JsonWebKey::from(&signing_key.to_public_key())).unwrap();
+        jwk["kid"] = json!(key_id);
+
+        jwk
+    }
+
+    fn sign(
+        claims: &Claims<OpenIdClaims>,
+        key_id: Option<&str>,
+        signing_key: &RsaPrivateKey,
+    ) -> String {
+        let header = match key_id {
+            Some(key_id) => Header::empty().with_key_id(key_id),
+            None => Header::empty(),
+        };
+
+        Rsa::rs256().token(&header, claims, signing_key).unwrap()
+    }
+
+    fn claims(issuer: &str, subject: &str) -> OpenIdClaims {
+        OpenIdClaims {
+            iss: issuer.to_string(),
+            sub: subject.to_string(),
+            aud: vec![CLIENT_ID.to_string()],
+            azp: None,
+            email: Some(format!("{}@example.com", subject)),
+            email_verified: true,
+            at_hash: None,
+            nonce: Some("nonce".to_string()),
+            name: None,
+            preferred_username: Some(subject.to_string()),
+            picture: None,
+            given_name: None,
+            family_name: None,
+            locale: None,
+            jti: None,
+        }
+    }
+
+    fn identity_providers(providers: Vec<IdentityProviderConfig>) -> IdentityProviders {
+        IdentityProviders::new(
+            OAuthConfig { providers },
+            ClientBuilder::new(reqwest::Client::new()).build(),
+        )
+    }
+
+    /// Pretends the issuer's keys were fetched `age` ago
+    async fn age_signing_keys(
+        identity: &IdentityProviders,
+        issuer: &str,
+        age: std::time::Duration,
+    ) {
+        let mut cache = identity.signing_keys[issuer].write().await;
+        let cached = cache.as_mut().unwrap();
+        cached.fetched_at = cached.fetched_at.checked_sub(age).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tokens_from_every_configured_issuer_are_verified() {
+        let google = MockIssuer::start();
+        let discord = MockIssuer::start();
+        let identity =
+            identity_providers(vec![google.provider("Google"), discord.provider("Discord")]);
+
+        let google_user = identity
+            .verify(&google.token(&google.issuer, "1234", Duration::minutes(5)))
+            .await
+            .unwrap();
+        let discord_user = identity
+            .verify(&discord.token(&discord.issuer, "1234", Duration::minutes(5)))
+            .await
+            .unwrap();
+
+        assert_eq!(google.issuer, google_user.issuer);
+        assert_eq!(discord.issuer, discord_user.issuer);
+        assert_eq!("1234", google_user.subject);
+        assert_eq!("1234", discord_user.subject);
+        assert_eq!("1234", discord_user.claims.display_name());
+        assert!(identity.check_signing_keys().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn tokens_not_signed_by_their_issuer_are_rejected() {
+        let google = MockIssuer::start();
+        let other = MockIssuer::start();
+        let identity = identity_providers(vec![google.provider("Google")]);
+
+        let unknown_issuer = other.token(&other.issuer, "1234", Duration::minutes(5));
+        let forged = other.token(&google.issuer, "1234", Duration::minutes(5));
+        let expired = google.token(&google.issuer, "1234", Duration::minutes(-5));
+        let not_yet_valid = sign(
+            &Claims::new(claims(&google.issuer, "1234"))
+                .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(30))
+                .set_not_before(Utc::now() + Duration::minutes(10)),
+            Some(KEY_ID),
+            &google.signing_key,
+        );
+        let without_key_id = sign(
+            &Claims::new(claims(&google.issuer, "1234"))
+                .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(5)),
+            None,
+            &google.signing_key,
+        );
+
+        assert!(matches!(
+            identity.verify(&unknown_issuer).await,
+            Err(IdentityError::UnknownIssuer)
+        ));
+        assert!(matches!(
+            identity.verify(&forged).await,
+            Err(IdentityError::InvalidSignature)
+        ));
+        assert!(matches!(
+            identity.verify(&expired).await,
+            Err(IdentityError::Expired)
+        ));
+        assert!(matches!(
+            identity.verify(&not_yet_valid).await,
+            Err(IdentityError::NotYetValid)
+        ));
+        assert!(matches!(
+            identity.verify(&without_key_id).await,
+            Err(IdentityError::MissingKeyId)
+        ));
+        assert!(matches!(
+            identity.verify("not a token").await,
+            Err(IdentityError::MalformedToken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn tokens_issued_to_another_client_are_rejected() {
+        let google = MockIssuer::start();
+        let identity = identity_providers(vec![google.provider("Google")]);
+
+        let other_client = OpenIdClaims {
+            aud: vec!["other-client".to_string()],
+            ..claims(&google.issuer, "1234")
+        };
+        let requested_by_other_client = OpenIdClaims {
+            aud: vec![CLIENT_ID.to_string(), "other-client".to_string()],
+            azp: Some("other-client".to_string()),
+            ..claims(&google.issuer, "1234")
+        };
+        let shared_with_other_client = OpenIdClaims {
+            aud: vec![CLIENT_ID.to_string(), "other-client".to_string()],
+            azp: Some(CLIENT_ID.to_string()),
+            ..claims(&google.issuer, "1234")
+        };
+        let without_nonce = OpenIdClaims {
+            nonce: None,
+            ..claims(&google.issuer, "1234")
+        };
+
+        assert!(matches!(
+            identity.verify(&google.token_with(other_client)).await,
+            Err(IdentityError::WrongAudience)
+        ));
+        assert!(matches!(
+            identity
+                .verify(&google.token_with(requested_by_other_client))
+                .await,
+            Err(IdentityError::WrongAudience)
+        ));
+        assert!(identity
+            .verify(&google.token_with(shared_with_other_client))
+            .await
+            .is_ok());
+        assert!(matches!(
+            identity.verify(&google.token_with(without_nonce)).await,
+            Err(IdentityError::MissingNonce)
+        ));
+    }
+
+    #[tokio::test]
+    async fn signing_keys_are_cached_until_the_issuer_rotates_them() {
+        let google = MockIssuer::start();
+        let identity = identity_providers(vec![google.provider("Google")]);
+
+        for _ in 0..3 {
+            identity
+                .verify(&google.token(&google.issuer, "1234", Duration::minutes(5)))
+                .await
+                .unwrap();
+        }
+        assert!(identity.check_signing_keys().await.is_ok());
+        assert_eq!((1, 1), google.fetches());
+
+        // a key we haven't seen is only looked for once the keys are old enough to refresh
+        let rotated_key = google.rotate("rotated-key");
+        let rotated = sign(
+            &Claims::new(claims(&google.issuer, "1234"))
+                .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(5)),
+            Some("rotated-key"),
+            &rotated_key,
+        );
+        assert!(matches!(
+            identity.verify(&rotated).await,
+            Err(IdentityError::UnknownSigningKey)
+        ));
+        assert_eq!((1, 1), google.fetches());
+
+        age_signing_keys(&identity, &google.issuer, SIGNING_KEYS_MIN_REFRESH).await;
+        assert!(identity.verify(&rotated).await.is_ok());
+        assert_eq!((1, 2), google.fetches());
+
+        age_signing_keys(&identity, &google.issuer, SIGNING_KEYS_MAX_AGE).await;
+        assert!(identity.verify(&rotated).await.is_ok());
+        assert_eq!((2, 3), google.fetches());
+    }
+}
@@ -0,0 +1,101 @@
+use axum::http::StatusCode;
+use endurance_racing_planner_common::{
+    api_error::{ApiErrorCode, ApiErrorDto},
+    Driver,
+};
+use sqlx::PgPool;
+
+use super::TestApp;
+
+fn driver(name: &str) -> Driver {
+    Driver {
+        id: 0,
+        name: name.to_string(),
+        total_stints: 0,
+        fair_share: false,
+        color: "#2196f3".to_string(),
+        utc_offset: -5,
+        irating: 2500,
+        stint_preference: 0,
+    }
+}
+
+#[sqlx::test]
+async fn drivers_are_added_listed_and_renamed(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let path = format!("/plans/{}/drivers", plan.id);
+
+    let response = app.post(&path, &max, &driver("Max")).await;
+    assert_eq!(StatusCode::CREATED, response.status);
+    let added = response.json::<Driver>();
+    assert_ne!(0, added.id);
+    assert_eq!("Max", added.name);
+
+    let renamed = Driver {
+        name: "Max V".to_string(),
+        ..added.clone()
+    };
+    let response = app
+        .put(&format!("/drivers/{}", added.id), &max, &renamed)
+        .await;
+    assert_eq!(StatusCode::OK, response.status);
+
+    let drivers = app.get(&path, Some(&max)).await.json::<Vec<Driver>>();
+    assert_eq!(
+        vec!["Max V"],
+        drivers.iter().map(|d| d.name.as_str()).collect::<Vec<_>>()
+    );
+}
+
+#[sqlx::test]
+async fn drivers_have_a_calendar(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let added = app
+        .post(&format!("/plans/{}/drivers", plan.id), &max, &driver("Max"))
+        .await
+        .json::<Driver>();
+
+    let response = app
+        .get(
+            &format!("/plans/{}/drivers/{}/calendar.ics", plan.id, added.id),
+            Some(&max),
+        )
+        .await;
+
+    assert_eq!(StatusCode::OK, response.status);
+    assert_eq!(
+        Some("text/calendar; charset=utf-8"),
+        response.content_type.as_deref()
+    );
+    assert!(response.text().starts_with("BEGIN:VCALENDAR"));
+}
+
+#[sqlx::test]
+async fn invalid_drivers_are_rejected(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let invalid = Driver {
+        color: "blue".to_string(),
+        ..driver("")
+    };
+
+    let response = app
+        .post(&format!("/plans/{}/drivers", plan.id), &max, &invalid)
+        .await;
+
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status);
+    let error = response.json::<ApiErrorDto>();
+    assert_eq!(ApiErrorCode::ValidationFailed, error.code);
+    let mut fields = error
+        .details
+        .iter()
+        .map(|detail| detail.field.as_str())
+        .collect::<Vec<_>>();
+    fields.sort_unstable();
+    assert_eq!(vec!["color", "name"], fields);
+}
@@ -0,0 +1,316 @@
+//! Requests sent to the api's router in-process. Every test gets a database of its own from
+//! `#[sqlx::test]`, created on the server in `DATABASE_URL` with the migrations applied and
+//! dropped when the test passes. Bearer tokens are checked by `FakeVerifier`, so no identity
+//! provider is ever reached.
+
+mod drivers;
+mod plans;
+mod schedules;
+mod sharing;
+
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, TimeZone, Utc};
+use endurance_racing_planner_common::{
+    EventConfigDto, FuelStintAverageTimes, OpenIdClaims, OverallFuelStintConfigData,
+    PatchFuelStintAverageTimes, PatchRacePlannerDto, RacePlannerDto, StintDataDto, User,
+};
+use reqwest_middleware::ClientBuilder;
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{types::Uuid, PgPool};
+use tower::ServiceExt;
+
+use crate::{
+    app,
+    config::OAuthConfig,
+    identity::{Identity, IdentityError, IdentityProviders, TokenVerifier},
+    metrics::Metrics,
+    AppState,
+};
+
+const TEST_ISSUER: &str = "https://issuer.test";
+const TEST_TOKEN_PREFIX: &str = "test-id-token:";
+
+/// Accepts `test-id-token:<subject>` as a verified ID token for `<subject>@example.com`
+struct FakeVerifier;
+
+#[async_trait]
+impl TokenVerifier for FakeVerifier {
+    async fn verify(&self, token: &str) -> Result<Identity, IdentityError> {
+        let subject = token
+            .strip_prefix(TEST_TOKEN_PREFIX)
+            .ok_or(IdentityError::MalformedToken)?;
+
+        Ok(Identity {
+            issuer: TEST_ISSUER.to_string(),
+            subject: subject.to_string(),
+            claims: OpenIdClaims {
+                iss: TEST_ISSUER.to_string(),
+                sub: subject.to_string(),
+                aud: vec!["planner-client".to_string()],
+                azp: None,
+                email: Some(format!("{}@example.com", subject)),
+                email_verified: true,
+                at_hash: None,
+                nonce: Some("nonce".to_string()),
+                name: Some(subject.to_string()),
+                preferred_username: None,
+                picture: None,
+                given_name: None,
+                family_name: None,
+                locale: None,
+                jti: None,
+            },
+        })
+    }
+}
+
+/// The api's router over a test database
+struct TestApp {
+    router: Router,
+}
+
+/// A registered user and the bearer token that signs in as them
+struct TestUser {
+    user: User,
+    token: String,
+}
+
+struct TestResponse {
+    status: StatusCode,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+impl TestApp {
+    fn new(pool: PgPool) -> Self {
+        let identity = Arc::new(IdentityProviders::new(
+            OAuthConfig { providers: vec![] },
+            ClientBuilder::new(reqwest::Client::new()).build(),
+        ));
+        let state = AppState {
+            pool,
+            identity,
+            verifier: Arc::new(FakeVerifier),
+            metrics: Arc::new(Metrics::new().expect("the metrics to register")),
+        };
+
+        Self {
+            router: app(state, &[]),
+        }
+    }
+
+    /// Registers `subject` the way the web does after their first sign in
+    async fn sign_up(&self, subject: &str) -> TestUser {
+        let token = format!("{}{}", TEST_TOKEN_PREFIX, subject);
+        let response = self.send(Method::POST, "/users", Some(&token), None).await;
+        assert_eq!(StatusCode::CREATED, response.status);
+
+        TestUser {
+            user: response.json(),
+            token,
+        }
+    }
+
+    /// Creates a plan the way the web does, a new plan only has a title
+    async fn create_plan(&self, user: &TestUser, title: &str) -> RacePlannerDto {
+        let plan = RacePlannerDto {
+            title: title.to_string(),
+            ..RacePlannerDto::new()
+        };
+        let response = self.post("/plans", user, &plan).await;
+        assert_eq!(StatusCode::CREATED, response.status);
+
+        response.json()
+    }
+
+    /// Creates a plan with every setting a schedule is calculated from, each setting is patched
+    /// on its own like the web's settings pages do
+    async fn create_schedulable_plan(&self, user: &TestUser) -> RacePlannerDto {
+        let plan = self.create_plan(user, "Daytona 24").await;
+        let path = format!("/plans/{}", plan.id);
+        let patches = [
+            PatchRacePlannerDto {
+                overall_event_config: Some(event_config()),
+                ..patch(plan.id)
+            },
+            PatchRacePlannerDto {
+                overall_fuel_stint_config: Some(OverallFuelStintConfigData {
+                    fuel_tank_size: 100,
+                    ..OverallFuelStintConfigData::new()
+                }),
+                ..patch(plan.id)
+            },
+            PatchRacePlannerDto {
+                fuel_stint_average_times: Some(PatchFuelStintAverageTimes {
+                    standard_fuel_stint: Some(stint_data()),
+                    fuel_saving_stint: None,
+                }),
+                ..patch(plan.id)
+            },
+            PatchRacePlannerDto {
+                fuel_stint_average_times: Some(PatchFuelStintAverageTimes {
+                    standard_fuel_stint: None,
+                    fuel_saving_stint: Some(stint_data()),
+                }),
+                ..patch(plan.id)
+            },
+        ];
+        for patch in patches.iter() {
+            assert_eq!(StatusCode::OK, self.patch(&path, user, patch).await.status);
+        }
+
+        let plan = self.get(&path, Some(user)).await.json::<RacePlannerDto>();
+        assert!(plan.overall_event_config.is_some());
+        assert_eq!(
+            Some(FuelStintAverageTimes {
+                standard_fuel_stint: stint_data(),
+                fuel_saving_stint: stint_data(),
+            }),
+            plan.fuel_stint_average_times
+        );
+
+        plan
+    }
+
+    async fn get(&self, path: &str, user: Option<&TestUser>) -> TestResponse {
+        self.send(Method::GET, path, token(user), None).await
+    }
+
+    async fn post(&self, path: &str, user: &TestUser, body: &impl Serialize) -> TestResponse {
+        self.send(Method::POST, path, Some(&user.token), Some(json(body)))
+            .await
+    }
+
+    async fn put(&self, path: &str, user: &TestUser, body: &impl Serialize) -> TestResponse {
+        self.send(Method::PUT, path, Some(&user.token), Some(json(body)))
+            .await
+    }
+
+    async fn patch(&self, path: &str, user: &TestUser, body: &impl Serialize) -> TestResponse {
+        self.send(Method::PATCH, path, Some(&user.token), Some(json(body)))
+            .await
+    }
+
+    async fn delete(&self, path: &str, user: &TestUser) -> TestResponse {
+        self.send(Method::DELETE, path, Some(&user.token), None)
+            .await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        json: Option<Vec<u8>>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match json {
+            Some(json) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json)),
+            None => request.body(Body::empty()),
+        }
+        .expect("the request to be valid");
+
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("the router to respond");
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(str::to_string);
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .expect("the body to be readable");
+
+        TestResponse {
+            status,
+            content_type,
+            body: body.to_vec(),
+        }
+    }
+}
+
+impl TestResponse {
+    fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| {
+            panic!(
+                "{} response isn't the expected json: {}\n{}",
+                self.status,
+                e,
+                String::from_utf8_lossy(&self.body)
+            )
+        })
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8(self.body.clone()).expect("the body to be utf-8")
+    }
+}
+
+/// A patch of the plan that doesn't change anything yet
+fn patch(id: Uuid) -> PatchRacePlannerDto {
+    PatchRacePlannerDto {
+        id,
+        title: None,
+        overall_event_config: None,
+        overall_fuel_stint_config: None,
+        fuel_stint_average_times: None,
+        time_of_day_lap_factors: None,
+        track_conditions: None,
+        traffic_model: None,
+        per_driver_lap_factors: None,
+        driver_roster: None,
+        schedule_rows: None,
+    }
+}
+
+/// A six hour race, on whole seconds so the times survive the database's precision
+fn event_config() -> EventConfigDto {
+    let session_start_utc = Utc.with_ymd_and_hms(2027, 1, 23, 18, 0, 0).unwrap();
+    let mut event_config = EventConfigDto {
+        race_duration: Duration::hours(6),
+        session_start_utc,
+        race_start_tod: session_start_utc.naive_utc() - Duration::hours(5),
+        green_flag_offset: Duration::minutes(40),
+        ..EventConfigDto::new()
+    };
+    event_config.update_race_times();
+
+    event_config
+}
+
+fn stint_data() -> StintDataDto {
+    StintDataDto {
+        lap_time: Duration::seconds(90),
+        fuel_per_lap: 3.25,
+        lap_count: 22,
+        lap_time_with_pit: Duration::seconds(120),
+        track_time: Duration::minutes(55),
+        track_time_with_pit: Duration::minutes(57),
+        fuel_per_stint: 100.0,
+    }
+}
+
+fn token(user: Option<&TestUser>) -> Option<&str> {
+    user.map(|user| user.token.as_str())
+}
+
+fn json(body: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec(body).expect("the body to be serializable")
+}
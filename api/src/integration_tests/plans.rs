@@ -0,0 +1,128 @@
+use axum::http::{Method, StatusCode};
+use endurance_racing_planner_common::{
+    api_error::{ApiErrorCode, ApiErrorDto},
+    PatchRacePlannerDto, PlanListDto, RacePlannerDto,
+};
+use sqlx::PgPool;
+
+use super::{patch, TestApp};
+
+#[sqlx::test]
+async fn plans_are_created_listed_and_renamed(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let plans = app
+        .get("/plans", Some(&max))
+        .await
+        .json::<Vec<PlanListDto>>();
+    assert_eq!(1, plans.len());
+    assert_eq!(plan.id, plans[0].id);
+    assert_eq!("Daytona 24", plans[0].title);
+
+    let path = format!("/plans/{}", plan.id);
+    let rename = PatchRacePlannerDto {
+        title: Some("Sebring 12".to_string()),
+        ..patch(plan.id)
+    };
+    assert_eq!(StatusCode::OK, app.patch(&path, &max, &rename).await.status);
+
+    let response = app.get(&path, Some(&max)).await;
+    assert_eq!(StatusCode::OK, response.status);
+    assert_eq!("Sebring 12", response.json::<RacePlannerDto>().title);
+}
+
+#[sqlx::test]
+async fn plans_need_a_registered_user(pool: PgPool) {
+    let app = TestApp::new(pool);
+
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        app.get("/plans", None).await.status
+    );
+
+    let response = app
+        .send(
+            Method::GET,
+            "/plans",
+            Some("test-id-token:unregistered"),
+            None,
+        )
+        .await;
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status);
+}
+
+#[sqlx::test]
+async fn plans_are_private_to_their_owner(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let bob = app.sign_up("bob").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+
+    let response = app.get(&format!("/plans/{}", plan.id), Some(&bob)).await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status);
+    assert_eq!(ApiErrorCode::NotFound, response.json::<ApiErrorDto>().code);
+    assert!(app
+        .get("/plans", Some(&bob))
+        .await
+        .json::<Vec<PlanListDto>>()
+        .is_empty());
+}
+
+#[sqlx::test]
+async fn plans_need_a_title(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let plan = RacePlannerDto {
+        title: " ".to_string(),
+        ..RacePlannerDto::new()
+    };
+
+    let response = app.post("/plans", &max, &plan).await;
+
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status);
+    let error = response.json::<ApiErrorDto>();
+    assert_eq!(ApiErrorCode::ValidationFailed, error.code);
+    assert!(error.details.iter().any(|detail| detail.field == "title"));
+}
+
+#[sqlx::test]
+async fn plans_with_revisions_can_be_deleted(pool: PgPool) {
+    let app = TestApp::new(pool.clone());
+    let max = app.sign_up("max").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let rename = PatchRacePlannerDto {
+        title: Some("Sebring 12".to_string()),
+        ..patch(plan.id)
+    };
+    let path = format!("/plans/{}", plan.id);
+    assert_eq!(StatusCode::OK, app.patch(&path, &max, &rename).await.status);
+
+    let delete_revisions = sqlx::query("DELETE FROM plan_revisions WHERE plan_id = $1")
+        .bind(plan.id)
+        .execute(&pool)
+        .await;
+    assert!(delete_revisions.is_err());
+
+    // the api can't delete plans yet, the rows of the plan without a cascade are deleted first
+    for statement in [
+        "DELETE FROM user_plans WHERE plan_id = $1",
+        "DELETE FROM stints WHERE plan_id = $1",
+        "DELETE FROM scenarios WHERE plan_id = $1",
+        "DELETE FROM plans WHERE id = $1",
+    ] {
+        sqlx::query(statement)
+            .bind(plan.id)
+            .execute(&pool)
+            .await
+            .expect("the plan's rows to be deleted");
+    }
+    let revisions: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM plan_revisions WHERE plan_id = $1")
+            .bind(plan.id)
+            .fetch_one(&pool)
+            .await
+            .expect("the revisions to be counted");
+    assert_eq!(0, revisions);
+}
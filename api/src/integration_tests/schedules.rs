@@ -0,0 +1,282 @@
+use axum::http::{Method, StatusCode};
+use chrono::Duration;
+use endurance_racing_planner_common::{
+    plan_export::PlanExportDto,
+    revision::PlanRevisionDto,
+    scenario::{NewScenarioDto, ScenarioDto},
+    schedule::{plan_schedule, ScheduleStintDto},
+    RacePlannerDto,
+};
+use sqlx::{types::Uuid, PgPool};
+
+use super::{TestApp, TestResponse, TestUser};
+
+async fn add_schedule(app: &TestApp, user: &TestUser) -> (RacePlannerDto, Vec<ScheduleStintDto>) {
+    let plan = app.create_schedulable_plan(user).await;
+    let schedule = plan_schedule(&plan).expect("the plan to be schedulable");
+    assert!(!schedule.is_empty());
+
+    let path = format!("/plans/{}/schedule", plan.id);
+    assert_eq!(
+        StatusCode::CREATED,
+        app.post(&path, user, &schedule).await.status
+    );
+
+    (plan, schedule)
+}
+
+async fn recalculate(app: &TestApp, user: &TestUser, plan: &RacePlannerDto) -> TestResponse {
+    let path = format!("/plans/{}/schedule/recalculate", plan.id);
+    app.send(Method::POST, &path, Some(&user.token), None).await
+}
+
+fn ids(schedule: &[ScheduleStintDto]) -> Vec<String> {
+    schedule.iter().map(|stint| stint.id.to_string()).collect()
+}
+
+/// Adds a scenario starting from the active scenario's stints
+async fn add_scenario(
+    app: &TestApp,
+    user: &TestUser,
+    plan: &RacePlannerDto,
+    name: &str,
+) -> ScenarioDto {
+    let new_scenario = NewScenarioDto {
+        name: name.to_string(),
+        copy_from: None,
+    };
+    let response = app
+        .post(
+            &format!("/plans/{}/scenarios", plan.id),
+            user,
+            &new_scenario,
+        )
+        .await;
+    assert_eq!(StatusCode::CREATED, response.status);
+
+    response.json()
+}
+
+async fn get_scenario_schedule(
+    app: &TestApp,
+    user: &TestUser,
+    plan_id: Uuid,
+    scenario: &ScenarioDto,
+) -> Vec<ScheduleStintDto> {
+    app.get(
+        &format!("/plans/{}/scenarios/{}/schedule", plan_id, scenario.id),
+        Some(user),
+    )
+    .await
+    .json()
+}
+
+#[sqlx::test]
+async fn schedules_are_saved_and_read_back(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let (plan, schedule) = add_schedule(&app, &max).await;
+
+    let saved = app
+        .get(&format!("/plans/{}/schedule", plan.id), Some(&max))
+        .await
+        .json::<Vec<ScheduleStintDto>>();
+
+    assert_eq!(ids(&schedule), ids(&saved));
+    assert_eq!(
+        schedule.last().unwrap().utc_end,
+        saved.last().unwrap().utc_end
+    );
+}
+
+#[sqlx::test]
+async fn schedules_are_only_changed_through_their_plan(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let bob = app.sign_up("bob").await;
+    let (plan, schedule) = add_schedule(&app, &max).await;
+    let (bob_plan, _) = add_schedule(&app, &bob).await;
+    let path = format!("/plans/{}/schedule", plan.id);
+    let mut edited = schedule.clone();
+    edited[0].actual_end = edited[0].utc_end + Duration::minutes(2);
+
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        app.post(&path, &bob, &edited).await.status
+    );
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        app.put(&path, &bob, &edited).await.status
+    );
+    // the stints of another plan aren't updated through a plan the user can change
+    let bob_path = format!("/plans/{}/schedule", bob_plan.id);
+    assert_eq!(
+        StatusCode::OK,
+        app.put(&bob_path, &bob, &edited).await.status
+    );
+
+    let saved = app
+        .get(&path, Some(&max))
+        .await
+        .json::<Vec<ScheduleStintDto>>();
+    assert_eq!(ids(&schedule), ids(&saved));
+    assert_eq!(schedule[0].actual_end, saved[0].actual_end);
+}
+
+#[sqlx::test]
+async fn schedule_csv_is_only_exported_to_users_the_plan_is_shared_with(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let bob = app.sign_up("bob").await;
+    let (plan, _) = add_schedule(&app, &max).await;
+    let path = format!("/plans/{}/schedule.csv", plan.id);
+
+    let response = app.get(&path, Some(&max)).await;
+    assert_eq!(StatusCode::OK, response.status);
+    assert_eq!(
+        Some("text/csv; charset=utf-8".to_string()),
+        response.content_type
+    );
+    assert_eq!(StatusCode::UNAUTHORIZED, app.get(&path, None).await.status);
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        app.get(&path, Some(&bob)).await.status
+    );
+}
+
+#[sqlx::test]
+async fn stints_are_updated(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let (plan, mut schedule) = add_schedule(&app, &max).await;
+    let path = format!("/plans/{}/schedule", plan.id);
+
+    let stint = &mut schedule[0];
+    stint.actual_end = stint.utc_end + Duration::minutes(2);
+    stint.actual_laps = stint.calculated_laps + 1;
+    let expected = stint.clone();
+    assert_eq!(
+        StatusCode::OK,
+        app.put(&path, &max, &vec![expected.clone()]).await.status
+    );
+
+    let saved = app
+        .get(&path, Some(&max))
+        .await
+        .json::<Vec<ScheduleStintDto>>();
+    assert_eq!(expected.actual_end, saved[0].actual_end);
+    assert_eq!(expected.actual_laps, saved[0].actual_laps);
+    assert_eq!(ids(&schedule), ids(&saved));
+}
+
+#[sqlx::test]
+async fn recalculated_schedules_cover_the_race(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let (plan, _) = add_schedule(&app, &max).await;
+    let race_end_utc = plan.overall_event_config.as_ref().unwrap().race_end_utc;
+
+    let response = recalculate(&app, &max, &plan).await;
+    assert_eq!(StatusCode::OK, response.status);
+    let recalculated = response.json::<Vec<ScheduleStintDto>>();
+    assert!(recalculated.last().unwrap().utc_end >= race_end_utc);
+
+    let saved = app
+        .get(&format!("/plans/{}/schedule", plan.id), Some(&max))
+        .await
+        .json::<Vec<ScheduleStintDto>>();
+    assert_eq!(ids(&recalculated), ids(&saved));
+}
+
+#[sqlx::test]
+async fn plans_without_settings_can_not_be_recalculated(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+
+    let response = recalculate(&app, &max, &plan).await;
+
+    assert_eq!(StatusCode::BAD_REQUEST, response.status);
+}
+
+#[sqlx::test]
+async fn exported_plans_keep_every_scenario(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let (plan, schedule) = add_schedule(&app, &max).await;
+    add_scenario(&app, &max, &plan, "Rain").await;
+
+    let export = app
+        .get(&format!("/plans/{}/export", plan.id), Some(&max))
+        .await
+        .json::<PlanExportDto>();
+    assert_eq!(2, export.scenarios.len());
+    let response = app.post("/plans/import", &max, &export).await;
+    assert_eq!(StatusCode::CREATED, response.status, "{}", response.text());
+    let imported = response.json::<RacePlannerDto>();
+
+    let scenarios = app
+        .get(&format!("/plans/{}/scenarios", imported.id), Some(&max))
+        .await
+        .json::<Vec<ScenarioDto>>();
+    assert_eq!(
+        vec![("Main", true), ("Rain", false)],
+        scenarios
+            .iter()
+            .map(|scenario| (scenario.name.as_str(), scenario.is_active))
+            .collect::<Vec<_>>()
+    );
+    for scenario in scenarios.iter() {
+        let imported_schedule = get_scenario_schedule(&app, &max, imported.id, scenario).await;
+        assert_eq!(schedule.len(), imported_schedule.len());
+        assert_ne!(ids(&schedule), ids(&imported_schedule));
+    }
+}
+
+#[sqlx::test]
+async fn schedule_revisions_are_restored_into_their_scenario(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let (plan, schedule) = add_schedule(&app, &max).await;
+    let main = app
+        .get(&format!("/plans/{}/scenarios", plan.id), Some(&max))
+        .await
+        .json::<Vec<ScenarioDto>>()
+        .remove(0);
+    let rain = add_scenario(&app, &max, &plan, "Rain").await;
+    let activate =
+        |scenario: &ScenarioDto| format!("/plans/{}/scenarios/{}/activate", plan.id, scenario.id);
+
+    // edit the rain scenario, then switch back to the main scenario before restoring the edit
+    assert_eq!(
+        StatusCode::OK,
+        app.post(&activate(&rain), &max, &()).await.status
+    );
+    let rain_schedule = get_scenario_schedule(&app, &max, plan.id, &rain).await;
+    let path = format!("/plans/{}/schedule/stints/{}", plan.id, rain_schedule[0].id);
+    let response = app.delete(&path, &max).await;
+    assert_eq!(StatusCode::OK, response.status, "{}", response.text());
+    let edited = response.json::<Vec<ScheduleStintDto>>();
+    assert_eq!(
+        StatusCode::OK,
+        app.post(&activate(&main), &max, &()).await.status
+    );
+
+    let revision = app
+        .get(&format!("/plans/{}/revisions", plan.id), Some(&max))
+        .await
+        .json::<Vec<PlanRevisionDto>>()
+        .remove(0);
+    let path = format!("/plans/{}/revisions/{}/restore", plan.id, revision.id);
+    let response = app.post(&path, &max, &()).await;
+    assert_eq!(StatusCode::OK, response.status, "{}", response.text());
+
+    assert_eq!(
+        ids(&schedule),
+        ids(&get_scenario_schedule(&app, &max, plan.id, &main).await)
+    );
+    assert_eq!(
+        ids(&edited),
+        ids(&get_scenario_schedule(&app, &max, plan.id, &rain).await)
+    );
+}
@@ -0,0 +1,110 @@
+use axum::http::StatusCode;
+use endurance_racing_planner_common::{
+    scenario::{NewScenarioDto, ScenarioDto},
+    PlanListDto, RacePlannerDto, User,
+};
+use sqlx::PgPool;
+
+use super::TestApp;
+
+#[sqlx::test]
+async fn shared_plans_reach_the_users_they_are_shared_with(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let bob = app.sign_up("bob").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let share = format!("/plans/{}/share", plan.id);
+
+    let response = app.post(&share, &max, &vec![bob.user.email.clone()]).await;
+    assert_eq!(StatusCode::OK, response.status);
+
+    let plans = app
+        .get("/plans", Some(&bob))
+        .await
+        .json::<Vec<PlanListDto>>();
+    assert_eq!(
+        vec![plan.id],
+        plans.iter().map(|p| p.id).collect::<Vec<_>>()
+    );
+    let response = app.get(&format!("/plans/{}", plan.id), Some(&bob)).await;
+    assert_eq!(StatusCode::OK, response.status);
+    assert_eq!(plan.title, response.json::<RacePlannerDto>().title);
+
+    let shared_with = app.get(&share, Some(&max)).await.json::<Vec<User>>();
+    assert_eq!(
+        vec![bob.user.id],
+        shared_with.iter().map(|user| user.id).collect::<Vec<_>>()
+    );
+}
+
+#[sqlx::test]
+async fn unknown_emails_are_ignored(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let share = format!("/plans/{}/share", plan.id);
+
+    let response = app
+        .post(&share, &max, &vec!["nobody@example.com".to_string()])
+        .await;
+
+    assert_eq!(StatusCode::OK, response.status);
+    assert!(app
+        .get(&share, Some(&max))
+        .await
+        .json::<Vec<User>>()
+        .is_empty());
+}
+
+#[sqlx::test]
+async fn scenarios_of_plans_that_are_not_shared_are_not_found(pool: PgPool) {
+    let app = TestApp::new(pool);
+    let max = app.sign_up("max").await;
+    let bob = app.sign_up("bob").await;
+    let plan = app.create_plan(&max, "Daytona 24").await;
+    let scenarios = format!("/plans/{}/scenarios", plan.id);
+    let scenario = app
+        .get(&scenarios, Some(&max))
+        .await
+        .json::<Vec<ScenarioDto>>()
+        .remove(0);
+    let new_scenario = NewScenarioDto {
+        name: "Rain".to_string(),
+        copy_from: None,
+    };
+
+    let responses = vec![
+        app.get(&scenarios, Some(&bob)).await,
+        app.post(&scenarios, &bob, &new_scenario).await,
+        app.get(&format!("{}/comparison", scenarios), Some(&bob))
+            .await,
+        app.get(
+            &format!("{}/{}/schedule", scenarios, scenario.id),
+            Some(&bob),
+        )
+        .await,
+        app.post(
+            &format!("{}/{}/activate", scenarios, scenario.id),
+            &bob,
+            &(),
+        )
+        .await,
+        app.delete(&format!("{}/{}", scenarios, scenario.id), &bob)
+            .await,
+    ];
+
+    for response in responses {
+        assert_eq!(
+            StatusCode::NOT_FOUND,
+            response.status,
+            "{}",
+            response.text()
+        );
+    }
+    assert_eq!(
+        vec![scenario],
+        app.get(&scenarios, Some(&max))
+            .await
+            .json::<Vec<ScenarioDto>>()
+    );
+}
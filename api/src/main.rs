@@ -2,39 +2,79 @@ use axum::{
     async_trait,
     extract::{FromRef, FromRequestParts},
     headers::{authorization::Bearer, Authorization},
-    http::{request::Parts, StatusCode},
-    routing::{get, post, put},
+    http::{header, request::Parts, Method},
+    middleware,
+    routing::{delete, get, post, put},
     Router, TypedHeader,
 };
 use dotenvy::dotenv;
-use endurance_racing_planner_common::{GoogleOpenIdClaims, User};
+use endurance_racing_planner_common::{access_token::ACCESS_TOKEN_PREFIX, User};
 use http_cache_reqwest::{Cache, CacheMode, HttpCache, MokaManager};
-use jwt_compact::{
-    alg::{Rsa, RsaPublicKey},
-    jwk::JsonWebKey,
-    AlgorithmExt, TimeOptions, UntrustedToken,
-};
 use reqwest::Client;
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use serde::Deserialize;
+use reqwest_middleware::ClientBuilder;
 use sqlx::PgPool;
-use std::net::SocketAddr;
+use std::{process, sync::Arc};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing_subscriber::EnvFilter;
 
-use crate::data_access::user::Users;
+use crate::{
+    config::{Config, LogFormat},
+    data_access::user::Users,
+    error::ApiError,
+    identity::{Identity, IdentityProviders, TokenVerifier},
+    metrics::Metrics,
+};
 
+mod access_tokens;
+mod config;
 mod data_access;
 mod drivers;
+mod error;
+mod health;
+mod identity;
+#[cfg(test)]
+mod integration_tests;
+mod metrics;
+mod openapi;
 mod plans;
+mod revisions;
+mod scenarios;
 mod schedules;
+mod seed;
 mod users;
 
 #[tokio::main]
 async fn main() {
-    // initialize tracing
-    tracing_subscriber::fmt::init();
     dotenv().ok();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            // tracing isn't initialized yet, the log format is part of the config
+            eprintln!("invalid configuration: {}", e);
+            process::exit(1);
+        }
+    };
+
+    // initialize tracing
+    let subscriber = tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env());
+    match config.log.format {
+        LogFormat::Pretty => subscriber.init(),
+        LogFormat::Compact => subscriber.compact().init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let run_migrations = args.iter().any(|arg| arg == "--migrate");
+    let seed_demo_data = args.iter().any(|arg| arg == "--seed");
 
-    let db_context = data_access::initialize().await.expect("database to exist");
+    let db_context = match data_access::initialize(&config.database).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!("failed to create the database pool: {}", e);
+            process::exit(1);
+        }
+    };
+    prepare_database(&db_context, run_migrations, seed_demo_data).await;
     let http_client = ClientBuilder::new(Client::new())
         .with(Cache(HttpCache {
             mode: CacheMode::Default,
@@ -42,45 +82,193 @@ async fn main() {
             options: None,
         }))
         .build();
+    let identity = Arc::new(IdentityProviders::new(config.oauth.clone(), http_client));
+
+    let metrics = match Metrics::new() {
+        Ok(metrics) => Arc::new(metrics),
+        Err(e) => {
+            tracing::error!("failed to register the metrics: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let app = app(
+        AppState {
+            pool: db_context,
+            verifier: identity.clone(),
+            identity,
+            metrics,
+        },
+        &config.cors.allowed_origins,
+    );
+
+    // run our app with hyper
+    // `axum::Server` is a re-export of `hyper::Server`
+    let addr = config.bind_address;
+    tracing::debug!("listening on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+        .unwrap();
+}
 
-    // build our application with a route
-    let app = Router::new()
-        .route("/users/me", get(users::me))
+/// Every route of the api, the integration tests send their requests to the same router
+fn app(state: AppState, allowed_origins: &[String]) -> Router {
+    Router::new()
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/metrics", get(metrics::get_metrics))
+        .route("/openapi.json", get(openapi::get_openapi))
+        .route("/docs", get(openapi::get_docs))
+        .route("/auth/providers", get(identity::get_providers))
+        .route("/users/me", get(users::me).patch(users::patch_me))
+        .route(
+            "/users/me/tokens",
+            get(access_tokens::get_access_tokens).post(access_tokens::add_access_token),
+        )
+        .route(
+            "/users/me/tokens/:id",
+            delete(access_tokens::delete_access_token),
+        )
         .route("/users", post(users::add_user))
         .route("/plans", get(plans::get_plans).post(plans::add_plan))
         .route("/plans/:id", get(plans::get_plan).patch(plans::patch_plan))
+        .route("/plans/:id/export", get(plans::export_plan))
+        .route("/plans/import", post(plans::import_plan))
         .route(
             "/plans/:id/schedule",
             get(schedules::get_schedule)
                 .post(schedules::add_schedule)
                 .put(schedules::put_schedule),
         )
+        .route(
+            "/plans/:id/schedule/recalculate",
+            post(schedules::recalculate_schedule),
+        )
+        .route(
+            "/plans/:id/schedule/stints/:stint_id",
+            delete(schedules::delete_stint),
+        )
+        .route(
+            "/plans/:id/schedule/stints/:stint_id/insert-after",
+            post(schedules::insert_stint),
+        )
+        .route(
+            "/plans/:id/schedule/stints/:stint_id/split",
+            post(schedules::split_stint),
+        )
+        .route(
+            "/plans/:id/schedule.csv",
+            get(schedules::get_schedule_csv).put(schedules::put_schedule_csv),
+        )
+        .route(
+            "/plans/:id/scenarios",
+            get(scenarios::get_scenarios).post(scenarios::add_scenario),
+        )
+        .route(
+            "/plans/:id/scenarios/comparison",
+            get(scenarios::compare_scenarios),
+        )
+        .route(
+            "/plans/:id/scenarios/:scenario_id",
+            delete(scenarios::delete_scenario),
+        )
+        .route(
+            "/plans/:id/scenarios/:scenario_id/schedule",
+            get(scenarios::get_scenario_schedule),
+        )
+        .route(
+            "/plans/:id/scenarios/:scenario_id/activate",
+            post(scenarios::activate_scenario),
+        )
         .route(
             "/plans/:id/drivers",
             get(drivers::get_plan_drivers).post(drivers::add_driver),
         )
+        .route(
+            "/plans/:id/drivers/:driver_id/calendar.ics",
+            get(drivers::get_driver_calendar),
+        )
+        .route("/plans/:id/revisions", get(revisions::get_revisions))
+        .route(
+            "/plans/:id/revisions/:revision_id/restore",
+            post(revisions::restore_revision),
+        )
+        .route(
+            "/plans/:id/revisions/:revision_id/undo",
+            post(revisions::undo_revision),
+        )
         .route("/plans/:id/share", post(plans::share_plan))
         .route("/plans/:id/share", get(plans::get_plan_shared_users))
         .route("/drivers/:id", put(drivers::put_driver))
-        .with_state(AppState {
-            pool: db_context,
-            http_client,
-        });
+        .route_layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            metrics::track_requests,
+        ))
+        .layer(cors_layer(allowed_origins))
+        .with_state(state)
+}
 
-    // run our app with hyper
-    // `axum::Server` is a re-export of `hyper::Server`
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    tracing::debug!("listening on {}", addr);
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await
-        .unwrap();
+/// Applies the embedded migrations when `run_migrations` is set and refuses to start when the
+/// database schema is ahead of this binary
+async fn prepare_database(pool: &PgPool, run_migrations: bool, seed_demo_data: bool) {
+    match data_access::migrations::check_schema_version(pool).await {
+        Ok(pending) if pending.is_empty() => {}
+        Ok(pending) if run_migrations => {
+            tracing::info!("applying {} database migrations", pending.len());
+            if let Err(e) = data_access::migrations::run_migrations(pool).await {
+                tracing::error!("failed to apply the database migrations: {}", e);
+                process::exit(1);
+            }
+        }
+        Ok(pending) => tracing::warn!(
+            "{} database migrations haven't been applied, start with --migrate to apply them",
+            pending.len()
+        ),
+        Err(e) => {
+            tracing::error!(
+                "the database schema is ahead of this build of the api: {}",
+                e
+            );
+            process::exit(1);
+        }
+    }
+
+    if seed_demo_data {
+        if let Err(e) = seed::seed_demo_data(pool).await {
+            tracing::error!("failed to seed the demo data: {:?}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Cross-origin requests are only allowed from the configured origins, the origins were validated
+/// when the config was loaded
+fn cors_layer(allowed_origins: &[String]) -> CorsLayer {
+    let origins = allowed_origins
+        .iter()
+        .filter_map(|origin| origin.parse().ok())
+        .collect::<Vec<_>>();
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+        .expose_headers([header::CONTENT_LOCATION, header::CONTENT_DISPOSITION])
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pool: PgPool,
-    http_client: ClientWithMiddleware,
+    identity: Arc<IdentityProviders>,
+    verifier: Arc<dyn TokenVerifier>,
+    metrics: Arc<Metrics>,
 }
 
 impl FromRef<AppState> for PgPool {
@@ -89,114 +277,111 @@ impl FromRef<AppState> for PgPool {
     }
 }
 
-impl FromRef<AppState> for ClientWithMiddleware {
-    fn from_ref(app_state: &AppState) -> ClientWithMiddleware {
-        app_state.http_client.clone()
+impl FromRef<AppState> for Arc<IdentityProviders> {
+    fn from_ref(app_state: &AppState) -> Arc<IdentityProviders> {
+        app_state.identity.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Metrics> {
+    fn from_ref(app_state: &AppState) -> Arc<Metrics> {
+        app_state.metrics.clone()
     }
 }
 
 pub struct AuthenticatedUser(pub User);
 
+/// The identity of a verified ID token, the user it was issued to might not be registered yet
+pub struct VerifiedIdentity(pub Identity);
+
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthenticatedUser
 where
     AppState: FromRef<S>,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let pool: PgPool = AppState::from_ref(state).pool;
-
-        let TypedHeader(Authorization(bearer)) =
-            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
-                .await
-                .map_err(|_| (StatusCode::UNAUTHORIZED, "no bearer token".to_string()))?;
-
-        let http_client = AppState::from_ref(state).http_client;
-        let oauth_signing_keys = get_google_signing_keys(http_client).await.map_err(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "no oauth signing keys".to_string(),
-            )
-        })?;
-        let oauth_id = UntrustedToken::new(bearer.token())
-            .map_err(|_| (StatusCode::UNAUTHORIZED, "invalid token".to_string()))
-            .and_then(|parsed_token| {
-                let mut signing_key = &oauth_signing_keys.keys[0].key;
-                let token_key_id = &parsed_token.header().key_id;
-                if let Some(key_id) = token_key_id {
-                    signing_key = oauth_signing_keys
-                        .keys
-                        .iter()
-                        .find(|key| &key.kid == key_id)
-                        .map(|k| &k.key)
-                        .ok_or((StatusCode::UNAUTHORIZED, "bad signing key".to_string()))?;
-                }
-                let rsa_public_key = RsaPublicKey::try_from(signing_key).unwrap();
-                let token_message = Rsa::rs256()
-                    .validate_integrity::<GoogleOpenIdClaims>(&parsed_token, &rsa_public_key);
-                token_message.map_err(|_| (StatusCode::UNAUTHORIZED, "invalid token".to_string()))
-            })
-            .and_then(|token| {
-                let claims = token
-                    .claims()
-                    .validate_expiration(&TimeOptions::default())
-                    .map_err(|_| (StatusCode::UNAUTHORIZED, "token expired".to_string()))?;
-                Ok(claims.custom.sub.clone())
-            })?;
-
-        Users::get_user_by_oauth_id(&pool, oauth_id)
-            .await
-            .map_err(|_| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "there was a problem locating the user".to_string(),
-                )
-            })
-            .and_then(|user| user.ok_or((StatusCode::UNAUTHORIZED, "user not found".to_string())))
-            .map(Self)
+        let result = authenticate(parts, state).await;
+        record_auth_failure(state, &result);
+
+        result.map(Self)
     }
 }
 
-#[derive(Deserialize)]
-struct GoogleDiscoveryResponse {
-    jwks_uri: String,
+#[async_trait]
+impl<S> FromRequestParts<S> for VerifiedIdentity
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let result = verify_bearer_token(parts, state).await;
+        record_auth_failure(state, &result);
+
+        result.map(Self)
+    }
 }
 
-#[derive(Deserialize)]
-struct GoogleSigningKey<'a> {
-    kid: String,
-    #[serde(flatten)]
-    key: JsonWebKey<'a>,
+fn record_auth_failure<S, T>(state: &S, result: &Result<T, ApiError>)
+where
+    AppState: FromRef<S>,
+{
+    if let Err(e) = result {
+        AppState::from_ref(state)
+            .metrics
+            .record_auth_failure(e.message());
+    }
 }
 
-#[derive(Deserialize)]
-struct GoogleSigningKeysResponse<'a> {
-    keys: Vec<GoogleSigningKey<'a>>,
+/// Finds the user the bearer token was issued to, the token is either an ID token or one of the
+/// user's personal access tokens
+async fn authenticate<S>(parts: &mut Parts, state: &S) -> Result<User, ApiError>
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    let app_state = AppState::from_ref(state);
+    let token = bearer_token(parts, state).await?;
+    if token.starts_with(ACCESS_TOKEN_PREFIX) {
+        return access_tokens::authenticate(
+            &app_state.pool,
+            &token,
+            &parts.method,
+            parts.uri.path(),
+        )
+        .await;
+    }
+
+    let identity = app_state.verifier.verify(&token).await?;
+    Users::get_user_by_identity(&app_state.pool, &identity.issuer, &identity.subject)
+        .await
+        .map_err(|e| ApiError::internal("there was a problem locating the user", e))
+        .and_then(|user| user.ok_or_else(|| ApiError::Unauthorized("user not found".to_string())))
 }
 
-async fn get_google_signing_keys(
-    client: ClientWithMiddleware,
-) -> Result<GoogleSigningKeysResponse<'static>, Box<dyn std::error::Error>> {
-    const GOOGLE_DISCOVERY_URL: &str =
-        "https://accounts.google.com/.well-known/openid-configuration";
-
-    let discovery_info = client
-        .get(GOOGLE_DISCOVERY_URL)
-        .header("Accept", "application/json")
-        .send()
-        .await?
-        .json::<GoogleDiscoveryResponse>()
-        .await?;
-
-    let signing_keys = client
-        .get(&discovery_info.jwks_uri)
-        .header("Accept", "application/json")
-        .send()
-        .await?
-        .json::<GoogleSigningKeysResponse>()
-        .await?;
-
-    Ok(signing_keys)
+async fn verify_bearer_token<S>(parts: &mut Parts, state: &S) -> Result<Identity, ApiError>
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    let token = bearer_token(parts, state).await?;
+    let identity = AppState::from_ref(state).verifier.verify(&token).await?;
+
+    Ok(identity)
+}
+
+async fn bearer_token<S>(parts: &mut Parts, state: &S) -> Result<String, ApiError>
+where
+    S: Send + Sync,
+{
+    let TypedHeader(Authorization(bearer)) =
+        TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::Unauthorized("no bearer token".to_string()))?;
+
+    Ok(bearer.token().to_string())
 }
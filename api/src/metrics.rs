@@ -0,0 +1,140 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sqlx::PgPool;
+
+/// The api's Prometheus metrics, rendered by `GET /metrics`
+pub struct Metrics {
+    registry: Registry,
+    http_requests_total: IntCounterVec,
+    http_request_duration_seconds: HistogramVec,
+    db_pool_connections: IntGaugeVec,
+    auth_failures_total: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new_custom(Some("planner_api".to_string()), None)?;
+        let http_requests_total = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "Requests handled by route and status",
+            ),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to handle a request by route",
+            ),
+            &["method", "route"],
+        )?;
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Database pool connections by state, updated when the metrics are scraped",
+            ),
+            &["state"],
+        )?;
+        let auth_failures_total = IntCounterVec::new(
+            Opts::new(
+                "auth_failures_total",
+                "Requests rejected by authentication by reason",
+            ),
+            &["reason"],
+        )?;
+
+        registry.register(Box::new(http_requests_total.clone()))?;
+        registry.register(Box::new(http_request_duration_seconds.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(auth_failures_total.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            db_pool_connections,
+            auth_failures_total,
+        })
+    }
+
+    pub fn record_auth_failure(&self, reason: &str) {
+        self.auth_failures_total.with_label_values(&[reason]).inc();
+    }
+
+    /// The metrics in the Prometheus text exposition format
+    pub fn render(&self, pool: &PgPool) -> Result<String, prometheus::Error> {
+        let size = pool.size() as i64;
+        let idle = pool.num_idle() as i64;
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle);
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set(size - idle);
+
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// Counts and times every request by the route it matched, so `/plans/:id` is a single series no
+/// matter how many plans there are
+pub async fn track_requests<B>(
+    State(metrics): State<Arc<Metrics>>,
+    matched_path: Option<MatchedPath>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let method = request.method().to_string();
+    let route = matched_path
+        .as_ref()
+        .map_or("unmatched", |path| path.as_str())
+        .to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    metrics
+        .http_request_duration_seconds
+        .with_label_values(&[&method, &route])
+        .observe(start.elapsed().as_secs_f64());
+    metrics
+        .http_requests_total
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+
+    response
+}
+
+pub(crate) async fn get_metrics(
+    State(metrics): State<Arc<Metrics>>,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match metrics.render(&pool) {
+        Ok(body) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("{:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to render the metrics",
+            )
+                .into_response()
+        }
+    }
+}
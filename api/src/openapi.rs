@@ -0,0 +1,1051 @@
+use std::sync::OnceLock;
+
+use axum::{
+    response::{Html, IntoResponse},
+    Json,
+};
+use chrono::{Duration, NaiveTime, TimeZone, Utc};
+use endurance_racing_planner_common::{
+    access_token::{AccessTokenDto, CreatedAccessTokenDto, NewAccessTokenDto},
+    api_error::{ApiErrorCode, ApiErrorDto, FieldErrorDto},
+    conditions::TrackConditionDto,
+    plan_export::{PlanExportDto, ScenarioExportDto},
+    revision::{PlanRevisionDto, PlanSection},
+    scenario::{DriverSeatTimeDto, NewScenarioDto, ScenarioComparisonDto, ScenarioDto},
+    schedule::{ScheduleStintDto, StintType},
+    traffic::{TrafficClassDto, TrafficIntensityDto, TrafficModelDto},
+    Driver, DriverLapFactor, EventConfigDto, FuelStintAverageTimes, IdentityProviderDto,
+    OverallFuelStintConfigData, PatchFuelStintAverageTimes, PatchRacePlannerDto, PatchUserDto,
+    PlanListDto, RacePlannerDto, StintDataDto, TimeOfDayLapFactor, User,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::health::{CheckResult, ReadinessDto};
+
+/// What a request or a response carries
+#[derive(Clone, Copy)]
+enum Body {
+    None,
+    /// A schema of the document's components
+    Json(&'static str),
+    /// A list of a schema of the document's components
+    JsonList(&'static str),
+    /// Anything that isn't described by a schema, e.g. `text/csv`
+    Text(&'static str),
+}
+
+/// A documented route. `path` is written the way axum routes it, its `:name` segments become path
+/// parameters.
+#[derive(Clone, Copy)]
+struct Operation {
+    method: &'static str,
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    authenticated: bool,
+    request: Body,
+    status: u16,
+    response: Body,
+}
+
+impl Operation {
+    const fn new(
+        method: &'static str,
+        path: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            tag,
+            summary,
+            authenticated: true,
+            request: Body::None,
+            status: 200,
+            response: Body::None,
+        }
+    }
+
+    const fn anonymous(self) -> Self {
+        Self {
+            authenticated: false,
+            ..self
+        }
+    }
+
+    const fn request(self, request: Body) -> Self {
+        Self { request, ..self }
+    }
+
+    const fn response(self, status: u16, response: Body) -> Self {
+        Self {
+            status,
+            response,
+            ..self
+        }
+    }
+}
+
+/// Every route of the api, a test fails when a route in `main.rs` is missing from this list
+const OPERATIONS: &[Operation] = &[
+    Operation::new("get", "/healthz", "health", "The api is up").anonymous(),
+    Operation::new(
+        "get",
+        "/readyz",
+        "health",
+        "The database, migrations and signing keys are ready",
+    )
+    .anonymous()
+    .response(200, Body::Json("Readiness")),
+    Operation::new("get", "/metrics", "health", "Prometheus metrics")
+        .anonymous()
+        .response(200, Body::Text("text/plain; version=0.0.4")),
+    Operation::new("get", "/openapi.json", "docs", "This document")
+        .anonymous()
+        .response(200, Body::Text("application/json")),
+    Operation::new(
+        "get",
+        "/docs",
+        "docs",
+        "Interactive documentation of this document",
+    )
+    .anonymous()
+    .response(200, Body::Text("text/html")),
+    Operation::new(
+        "get",
+        "/auth/providers",
+        "users",
+        "The identity providers users can sign in with",
+    )
+    .anonymous()
+    .response(200, Body::JsonList("IdentityProvider")),
+    Operation::new("get", "/users/me", "users", "The signed in user")
+        .response(200, Body::Json("User")),
+    Operation::new(
+        "patch",
+        "/users/me",
+        "users",
+        "Updates the signed in user's profile",
+    )
+    .request(Body::Json("PatchUser"))
+    .response(200, Body::Json("User")),
+    Operation::new(
+        "post",
+        "/users",
+        "users",
+        "Registers the user of the ID token, 200 when they're already registered",
+    )
+    .response(201, Body::Json("User")),
+    Operation::new(
+        "get",
+        "/users/me/tokens",
+        "access tokens",
+        "The user's personal access tokens",
+    )
+    .response(200, Body::JsonList("AccessToken")),
+    Operation::new(
+        "post",
+        "/users/me/tokens",
+        "access tokens",
+        "Creates a personal access token, the token is only returned once",
+    )
+    .request(Body::Json("NewAccessToken"))
+    .response(201, Body::Json("CreatedAccessToken")),
+    Operation::new(
+        "delete",
+        "/users/me/tokens/:id",
+        "access tokens",
+        "Revokes a personal access token",
+    )
+    .response(200, Body::Json("Id")),
+    Operation::new(
+        "get",
+        "/plans",
+        "plans",
+        "The plans the user owns or that were shared with them",
+    )
+    .response(200, Body::JsonList("PlanListItem")),
+    Operation::new("post", "/plans", "plans", "Creates a plan")
+        .request(Body::Json("RacePlanner"))
+        .response(201, Body::Json("RacePlanner")),
+    Operation::new(
+        "get",
+        "/plans/:id",
+        "plans",
+        "A plan with its roster and schedule",
+    )
+    .response(200, Body::Json("RacePlanner")),
+    Operation::new(
+        "patch",
+        "/plans/:id",
+        "plans",
+        "Updates the sections of the plan that are set",
+    )
+    .request(Body::Json("PatchRacePlanner"))
+    .response(200, Body::Json("Id")),
+    Operation::new(
+        "get",
+        "/plans/:id/export",
+        "plans",
+        "A self-contained copy of the plan",
+    )
+    .response(200, Body::Json("PlanExport")),
+    Operation::new(
+        "post",
+        "/plans/import",
+        "plans",
+        "Creates a plan from an exported plan",
+    )
+    .request(Body::Json("PlanExport"))
+    .response(201, Body::Json("RacePlanner")),
+    Operation::new(
+        "post",
+        "/plans/:id/share",
+        "plans",
+        "Shares the plan with the users with these emails",
+    )
+    .request(Body::JsonList("Email")),
+    Operation::new(
+        "get",
+        "/plans/:id/share",
+        "plans",
+        "The users the plan is shared with",
+    )
+    .response(200, Body::JsonList("User")),
+    Operation::new(
+        "get",
+        "/plans/:id/schedule",
+        "schedule",
+        "The stints of the active scenario",
+    )
+    .anonymous()
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "post",
+        "/plans/:id/schedule",
+        "schedule",
+        "Creates the plan's schedule",
+    )
+    .request(Body::JsonList("ScheduleStint"))
+    .response(201, Body::None),
+    Operation::new(
+        "put",
+        "/plans/:id/schedule",
+        "schedule",
+        "Updates the stints in the request",
+    )
+    .request(Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "post",
+        "/plans/:id/schedule/recalculate",
+        "schedule",
+        "Regenerates the schedule from the plan's settings",
+    )
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "delete",
+        "/plans/:id/schedule/stints/:stint_id",
+        "schedule",
+        "Removes a stint and re-flows the schedule",
+    )
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "post",
+        "/plans/:id/schedule/stints/:stint_id/insert-after",
+        "schedule",
+        "Inserts a stint after this one",
+    )
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "post",
+        "/plans/:id/schedule/stints/:stint_id/split",
+        "schedule",
+        "Splits a stint in two",
+    )
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "get",
+        "/plans/:id/schedule.csv",
+        "schedule",
+        "The schedule as CSV",
+    )
+    .response(200, Body::Text("text/csv")),
+    Operation::new(
+        "put",
+        "/plans/:id/schedule.csv",
+        "schedule",
+        "Replaces the schedule with an edited CSV export",
+    )
+    .request(Body::Text("text/csv"))
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "get",
+        "/plans/:id/scenarios",
+        "scenarios",
+        "The plan's scenarios",
+    )
+    .response(200, Body::JsonList("Scenario")),
+    Operation::new(
+        "post",
+        "/plans/:id/scenarios",
+        "scenarios",
+        "Creates a scenario from a copy of another scenario",
+    )
+    .request(Body::Json("NewScenario"))
+    .response(201, Body::Json("Scenario")),
+    Operation::new(
+        "get",
+        "/plans/:id/scenarios/comparison",
+        "scenarios",
+        "The headline numbers of every scenario",
+    )
+    .response(200, Body::JsonList("ScenarioComparison")),
+    Operation::new(
+        "delete",
+        "/plans/:id/scenarios/:scenario_id",
+        "scenarios",
+        "Deletes a scenario that isn't active",
+    )
+    .response(200, Body::Json("Id")),
+    Operation::new(
+        "get",
+        "/plans/:id/scenarios/:scenario_id/schedule",
+        "scenarios",
+        "The stints of a scenario",
+    )
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "post",
+        "/plans/:id/scenarios/:scenario_id/activate",
+        "scenarios",
+        "Makes the scenario the plan's schedule",
+    )
+    .response(200, Body::JsonList("ScheduleStint")),
+    Operation::new(
+        "get",
+        "/plans/:id/drivers",
+        "drivers",
+        "The plan's driver roster",
+    )
+    .anonymous()
+    .response(200, Body::JsonList("Driver")),
+    Operation::new(
+        "post",
+        "/plans/:id/drivers",
+        "drivers",
+        "Adds a driver to the roster",
+    )
+    .anonymous()
+    .request(Body::Json("Driver"))
+    .response(201, Body::Json("Driver")),
+    Operation::new(
+        "get",
+        "/plans/:id/drivers/:driver_id/calendar.ics",
+        "drivers",
+        "The driver's stints as a calendar",
+    )
+    .anonymous()
+    .response(200, Body::Text("text/calendar")),
+    Operation::new("put", "/drivers/:id", "drivers", "Updates a driver")
+        .anonymous()
+        .request(Body::Json("Driver")),
+    Operation::new(
+        "get",
+        "/plans/:id/revisions",
+        "revisions",
+        "The plan's history, newest first",
+    )
+    .response(200, Body::JsonList("PlanRevision")),
+    Operation::new(
+        "post",
+        "/plans/:id/revisions/:revision_id/restore",
+        "revisions",
+        "Puts the section back to its value after the revision",
+    )
+    .response(200, Body::Json("RacePlanner")),
+    Operation::new(
+        "post",
+        "/plans/:id/revisions/:revision_id/undo",
+        "revisions",
+        "Puts the section back to its value before the revision",
+    )
+    .response(200, Body::Json("RacePlanner")),
+];
+
+const DOCS_PAGE: &str = r##"<!DOCTYPE html>
+<html>
+  <head>
+    <title>Endurance Racing Planner API</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+    <script>
+      SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+    </script>
+  </body>
+</html>
+"##;
+
+pub(crate) async fn get_openapi() -> impl IntoResponse {
+    static DOCUMENT: OnceLock<Value> = OnceLock::new();
+
+    Json(DOCUMENT.get_or_init(document).clone())
+}
+
+pub(crate) async fn get_docs() -> impl IntoResponse {
+    Html(DOCS_PAGE)
+}
+
+/// The OpenAPI 3 document of every route in `OPERATIONS`
+fn document() -> Value {
+    let mut paths = Map::new();
+    for operation in OPERATIONS.iter() {
+        let path = paths
+            .entry(openapi_path(operation.path))
+            .or_insert_with(|| json!({}));
+        path[operation.method] = operation_object(operation);
+    }
+
+    let schemas = schemas()
+        .into_iter()
+        .map(|(name, full, minimal)| (name.to_string(), schema_of(&full, Some(&minimal))))
+        .collect::<Map<_, _>>();
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Endurance Racing Planner API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "An OpenID Connect ID token or a personal access token",
+                },
+            },
+        },
+    })
+}
+
+/// `/plans/:id` becomes `/plans/{id}`
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn operation_object(operation: &Operation) -> Value {
+    let parameters = operation
+        .path
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix(':'))
+        .map(|name| path_parameter(operation.path, name))
+        .collect::<Vec<_>>();
+    let response = match content(operation.response) {
+        Some(content) => json!({ "description": "Success", "content": content }),
+        None => json!({ "description": "Success" }),
+    };
+
+    let mut object = json!({
+        "tags": [operation.tag],
+        "summary": operation.summary,
+        "parameters": parameters,
+        "responses": {
+            operation.status.to_string(): response,
+            "default": {
+                "description": "The request failed",
+                "content": content(Body::Json("ApiError")),
+            },
+        },
+    });
+    if let Some(content) = content(operation.request) {
+        object["requestBody"] = json!({ "required": true, "content": content });
+    }
+    if operation.authenticated {
+        object["security"] = json!([{ "bearer": [] }]);
+    }
+
+    object
+}
+
+fn path_parameter(path: &str, name: &str) -> Value {
+    let schema = match name {
+        "driver_id" => json!({ "type": "integer", "format": "int32" }),
+        "revision_id" => json!({ "type": "integer", "format": "int64" }),
+        "id" if path.starts_with("/drivers/") => json!({ "type": "integer", "format": "int32" }),
+        _ => json!({ "type": "string", "format": "uuid" }),
+    };
+
+    json!({ "name": name, "in": "path", "required": true, "schema": schema })
+}
+
+fn content(body: Body) -> Option<Value> {
+    let (content_type, schema) = match body {
+        Body::None => return None,
+        Body::Json(name) => ("application/json", schema_ref(name)),
+        Body::JsonList(name) => (
+            "application/json",
+            json!({ "type": "array", "items": schema_ref(name) }),
+        ),
+        Body::Text(content_type) => (content_type, json!({ "type": "string" })),
+    };
+
+    Some(json!({ content_type: { "schema": schema } }))
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+/// The schema of a serialized dto. `minimal` is the same dto with every optional value left out,
+/// its nulls and missing fields mark the fields that are nullable or not required.
+fn schema_of(full: &Value, minimal: Option<&Value>) -> Value {
+    let mut schema = match full {
+        Value::Null => json!({}),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(number) if number.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(value) => string_schema(value),
+        Value::Array(items) => {
+            let minimal_item = minimal
+                .and_then(Value::as_array)
+                .and_then(|items| items.first());
+            let items = items
+                .first()
+                .map(|item| schema_of(item, minimal_item))
+                .unwrap_or_else(|| json!({}));
+            json!({ "type": "array", "items": items })
+        }
+        Value::Object(fields) => {
+            let properties = fields
+                .iter()
+                .map(|(name, value)| {
+                    let minimal_value = minimal.and_then(|minimal| minimal.get(name));
+                    (name.clone(), schema_of(value, minimal_value))
+                })
+                .collect::<Map<_, _>>();
+            let required = fields
+                .keys()
+                .filter(|name| {
+                    minimal
+                        .and_then(|minimal| minimal.get(name.as_str()))
+                        .is_some_and(|value| !value.is_null())
+                })
+                .collect::<Vec<_>>();
+            json!({ "type": "object", "properties": properties, "required": required })
+        }
+    };
+    if matches!(minimal, Some(Value::Null)) && !full.is_null() {
+        schema["nullable"] = json!(true);
+    }
+
+    schema
+}
+
+fn string_schema(value: &str) -> Value {
+    if value.len() == 36 && Uuid::parse_str(value).is_ok() {
+        json!({ "type": "string", "format": "uuid" })
+    } else if chrono::DateTime::parse_from_rfc3339(value).is_ok() {
+        json!({ "type": "string", "format": "date-time" })
+    } else {
+        json!({ "type": "string" })
+    }
+}
+
+/// The name, an example with every optional value set and an example without them of each schema
+fn schemas() -> Vec<(&'static str, Value, Value)> {
+    vec![
+        same("Id", Uuid::new_v4().to_string()),
+        same("Email", "driver@example.com"),
+        same("Readiness", example_readiness()),
+        pair(
+            "ApiError",
+            example_api_error(),
+            ApiErrorDto {
+                details: vec![],
+                ..example_api_error()
+            },
+        ),
+        same("IdentityProvider", example_identity_provider()),
+        pair(
+            "User",
+            example_user(),
+            User {
+                iracing_customer_id: None,
+                ..example_user()
+            },
+        ),
+        pair(
+            "PatchUser",
+            PatchUserDto {
+                name: Some("Max".to_string()),
+                iracing_customer_id: Some(123456),
+            },
+            PatchUserDto::default(),
+        ),
+        pair(
+            "AccessToken",
+            example_access_token(),
+            AccessTokenDto {
+                plan_id: None,
+                last_used_date: None,
+                ..example_access_token()
+            },
+        ),
+        pair(
+            "NewAccessToken",
+            NewAccessTokenDto {
+                name: "telemetry relay".to_string(),
+                plan_id: Some(Uuid::new_v4()),
+                read_only: true,
+            },
+            NewAccessTokenDto {
+                name: "telemetry relay".to_string(),
+                ..NewAccessTokenDto::default()
+            },
+        ),
+        pair(
+            "CreatedAccessToken",
+            CreatedAccessTokenDto {
+                access_token: example_access_token(),
+                token: "erp_0123456789abcdef".to_string(),
+            },
+            CreatedAccessTokenDto {
+                access_token: AccessTokenDto {
+                    plan_id: None,
+                    last_used_date: None,
+                    ..example_access_token()
+                },
+                token: "erp_0123456789abcdef".to_string(),
+            },
+        ),
+        same(
+            "PlanListItem",
+            PlanListDto {
+                id: Uuid::new_v4(),
+                title: "Daytona 24".to_string(),
+                owner: "Max".to_string(),
+                last_modified: Utc::now(),
+            },
+        ),
+        pair("RacePlanner", example_plan(), RacePlannerDto::new()),
+        pair(
+            "PatchRacePlanner",
+            example_patch_plan(),
+            PatchRacePlannerDto {
+                id: Uuid::new_v4(),
+                title: None,
+                overall_event_config: None,
+                overall_fuel_stint_config: None,
+                fuel_stint_average_times: None,
+                time_of_day_lap_factors: None,
+                track_conditions: None,
+                traffic_model: None,
+                per_driver_lap_factors: None,
+                driver_roster: None,
+                schedule_rows: None,
+            },
+        ),
+        pair(
+            "PlanExport",
+            PlanExportDto::new(
+                example_plan(),
+                vec![ScenarioExportDto {
+                    name: "Main".to_string(),
+                    is_active: true,
+                    schedule: vec![example_stint()],
+                }],
+            ),
+            PlanExportDto::new(RacePlannerDto::new(), vec![]),
+        ),
+        same("ScheduleStint", example_stint()),
+        same("Scenario", example_scenario()),
+        pair(
+            "NewScenario",
+            NewScenarioDto {
+                name: "Wet race".to_string(),
+                copy_from: Some(Uuid::new_v4()),
+            },
+            NewScenarioDto {
+                name: "Wet race".to_string(),
+                copy_from: None,
+            },
+        ),
+        pair(
+            "ScenarioComparison",
+            example_comparison(),
+            ScenarioComparisonDto {
+                finish_time: None,
+                ..example_comparison()
+            },
+        ),
+        same("Driver", example_driver()),
+        pair(
+            "PlanRevision",
+            example_revision(),
+            PlanRevisionDto {
+                before: None,
+                ..example_revision()
+            },
+        ),
+    ]
+}
+
+fn same(name: &'static str, example: impl Serialize) -> (&'static str, Value, Value) {
+    let example = serde_json::to_value(example).expect("the example is serializable");
+    (name, example.clone(), example)
+}
+
+fn pair(
+    name: &'static str,
+    full: impl Serialize,
+    minimal: impl Serialize,
+) -> (&'static str, Value, Value) {
+    (
+        name,
+        serde_json::to_value(full).expect("the example is serializable"),
+        serde_json::to_value(minimal).expect("the example is serializable"),
+    )
+}
+
+fn example_readiness() -> ReadinessDto {
+    ReadinessDto {
+        ready: true,
+        database: CheckResult::Ok,
+        migrations: CheckResult::Ok,
+        oauth_signing_keys: CheckResult::Ok,
+    }
+}
+
+fn example_api_error() -> ApiErrorDto {
+    ApiErrorDto {
+        code: ApiErrorCode::ValidationFailed,
+        message: "some of the values are invalid".to_string(),
+        details: vec![FieldErrorDto::new("name", "is required")],
+    }
+}
+
+fn example_identity_provider() -> IdentityProviderDto {
+    IdentityProviderDto {
+        name: "Google".to_string(),
+        issuer: "https://accounts.google.com".to_string(),
+        client_id: "planner.apps.googleusercontent.com".to_string(),
+        scopes: vec!["openid".to_string(), "email".to_string()],
+    }
+}
+
+fn example_user() -> User {
+    User {
+        id: 1,
+        name: "Max".to_string(),
+        email: "driver@example.com".to_string(),
+        issuer: "https://accounts.google.com".to_string(),
+        subject: "1234".to_string(),
+        iracing_customer_id: Some(123456),
+    }
+}
+
+fn example_access_token() -> AccessTokenDto {
+    AccessTokenDto {
+        id: Uuid::new_v4(),
+        name: "telemetry relay".to_string(),
+        plan_id: Some(Uuid::new_v4()),
+        read_only: true,
+        created_date: Utc::now(),
+        last_used_date: Some(Utc::now()),
+    }
+}
+
+fn example_driver() -> Driver {
+    Driver {
+        id: 1,
+        name: "Max".to_string(),
+        total_stints: 4,
+        fair_share: true,
+        color: "#1E88E5".to_string(),
+        utc_offset: -5,
+        irating: 2500,
+        stint_preference: 2,
+    }
+}
+
+fn example_stint_data() -> StintDataDto {
+    StintDataDto {
+        lap_time: Duration::seconds(105),
+        fuel_per_lap: 2.5,
+        lap_count: 40,
+        lap_time_with_pit: Duration::seconds(106),
+        track_time: Duration::minutes(70),
+        track_time_with_pit: Duration::minutes(71),
+        fuel_per_stint: 100.0,
+    }
+}
+
+fn example_stint() -> ScheduleStintDto {
+    let start = Utc.with_ymd_and_hms(2026, 1, 24, 18, 40, 0).unwrap();
+    let end = start + Duration::minutes(70);
+
+    ScheduleStintDto {
+        id: Uuid::new_v4(),
+        stint_type: StintType::StandardWithTires,
+        fuel_stint_number: 1,
+        utc_start: start,
+        utc_end: end,
+        tod_start: start.naive_utc(),
+        tod_end: end.naive_utc(),
+        actual_end: end,
+        duration_delta: Duration::zero(),
+        damage_modifier: Duration::zero(),
+        calculated_laps: 40,
+        actual_laps: 0,
+        driver_id: 1,
+        availability: "Available".to_string(),
+        stint_number: 1,
+        factor: 1.5,
+        tire_age: 0,
+        tire_set_stint: 1,
+        traffic_loss: Duration::seconds(12),
+    }
+}
+
+fn example_traffic_model() -> TrafficModelDto {
+    TrafficModelDto {
+        time_loss_per_encounter: Duration::seconds(1),
+        classes: vec![TrafficClassDto {
+            name: "GT3".to_string(),
+            relative_pace: 1.1,
+            field_size: 20,
+        }],
+        intensities: vec![TrafficIntensityDto {
+            tod_start: NaiveTime::from_hms_opt(14, 0, 0).unwrap(),
+            intensity: 1.5,
+        }],
+    }
+}
+
+fn example_plan() -> RacePlannerDto {
+    let mut event_config = EventConfigDto::new();
+    event_config.race_duration = Duration::hours(24);
+    event_config.update_race_times();
+
+    RacePlannerDto {
+        title: "Daytona 24".to_string(),
+        overall_event_config: Some(event_config),
+        overall_fuel_stint_config: Some(OverallFuelStintConfigData::new()),
+        fuel_stint_average_times: Some(FuelStintAverageTimes {
+            standard_fuel_stint: example_stint_data(),
+            fuel_saving_stint: example_stint_data(),
+        }),
+        time_of_day_lap_factors: vec![TimeOfDayLapFactor {
+            time_of_day: "Night".to_string(),
+            lap_time: Duration::seconds(106),
+            tod_start: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+            delta: Duration::seconds(1),
+            factor: 1.5,
+            has_edited_lap_time: false,
+        }],
+        track_conditions: vec![TrackConditionDto::new(Duration::zero())],
+        traffic_model: Some(example_traffic_model()),
+        per_driver_lap_factors: vec![DriverLapFactor {
+            driver_name: "Max".to_string(),
+            driver_color: "#1E88E5".to_string(),
+            lap_time: Duration::seconds(105),
+            factor: 1.5,
+        }],
+        driver_roster: vec![example_driver()],
+        schedule_rows: Some(vec![example_stint()]),
+        ..RacePlannerDto::new()
+    }
+}
+
+fn example_patch_plan() -> PatchRacePlannerDto {
+    let plan = example_plan();
+
+    PatchRacePlannerDto {
+        id: plan.id,
+        title: Some(plan.title),
+        overall_event_config: plan.overall_event_config,
+        overall_fuel_stint_config: plan.overall_fuel_stint_config,
+        fuel_stint_average_times: Some(PatchFuelStintAverageTimes {
+            standard_fuel_stint: Some(example_stint_data()),
+            fuel_saving_stint: Some(example_stint_data()),
+        }),
+        time_of_day_lap_factors: Some(plan.time_of_day_lap_factors),
+        track_conditions: Some(plan.track_conditions),
+        traffic_model: plan.traffic_model,
+        per_driver_lap_factors: Some(plan.per_driver_lap_factors),
+        driver_roster: Some(plan.driver_roster),
+        schedule_rows: plan.schedule_rows,
+    }
+}
+
+fn example_scenario() -> ScenarioDto {
+    ScenarioDto {
+        id: Uuid::new_v4(),
+        name: "Main".to_string(),
+        is_active: true,
+    }
+}
+
+fn example_comparison() -> ScenarioComparisonDto {
+    ScenarioComparisonDto {
+        scenario: example_scenario(),
+        total_laps: 820,
+        stop_count: 20,
+        finish_time: Some(Utc::now()),
+        driver_seat_times: vec![DriverSeatTimeDto {
+            driver_id: 1,
+            name: "Max".to_string(),
+            seat_time: Duration::hours(6),
+        }],
+    }
+}
+
+fn example_revision() -> PlanRevisionDto {
+    PlanRevisionDto {
+        id: 1,
+        user_name: "Max".to_string(),
+        created_date: Utc::now(),
+        section: PlanSection::Title,
+        before: Some(json!("Daytona")),
+        after: json!("Daytona 24"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use serde_json::Value;
+
+    use super::{document, openapi_path, schema_of, schemas, OPERATIONS};
+
+    /// The method and path of every route registered in `main.rs`
+    fn routed_operations() -> BTreeSet<(String, String)> {
+        let source = include_str!("main.rs");
+        let mut operations = BTreeSet::new();
+        let mut rest = source;
+        while let Some(start) = rest.find(".route(") {
+            rest = &rest[start + ".route(".len()..];
+            // the route's arguments end at the parenthesis that closes `.route(`
+            let mut depth = 1;
+            let end = rest
+                .char_indices()
+                .find(|(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map(|(index, _)| index)
+                .expect("the route call is closed");
+            let arguments = &rest[..end];
+            let path = arguments.split('"').nth(1).expect("the route has a path");
+
+            for method in ["get", "post", "put", "patch", "delete"] {
+                let routed = arguments
+                    .match_indices(&format!("{}(", method))
+                    .any(|(index, _)| {
+                        !arguments[..index]
+                            .ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':')
+                    });
+                if routed {
+                    operations.insert((method.to_string(), path.to_string()));
+                }
+            }
+        }
+
+        operations
+    }
+
+    #[test]
+    fn every_route_is_documented() {
+        let routed = routed_operations();
+        let documented = OPERATIONS
+            .iter()
+            .map(|operation| (operation.method.to_string(), operation.path.to_string()))
+            .collect::<BTreeSet<_>>();
+
+        assert!(routed.len() > 40);
+        assert_eq!(
+            Vec::<&(String, String)>::new(),
+            routed.difference(&documented).collect::<Vec<_>>(),
+            "these routes aren't in openapi::OPERATIONS"
+        );
+        assert_eq!(
+            Vec::<&(String, String)>::new(),
+            documented.difference(&routed).collect::<Vec<_>>(),
+            "these documented routes aren't routed"
+        );
+    }
+
+    #[test]
+    fn every_referenced_schema_is_defined() {
+        let document = document();
+        let text = document.to_string();
+        let names = schemas()
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect::<Vec<_>>();
+
+        for reference in text.split("#/components/schemas/").skip(1) {
+            let name = reference.split('"').next().unwrap();
+            assert!(names.contains(&name), "{} isn't defined", name);
+        }
+        assert_eq!(Value::String("3.0.3".to_string()), document["openapi"]);
+        assert!(
+            document["paths"][openapi_path("/plans/:id/scenarios/:scenario_id")]["delete"]
+                .is_object()
+        );
+    }
+
+    fn schema(name: &str) -> Value {
+        schemas()
+            .into_iter()
+            .find(|(schema_name, _, _)| *schema_name == name)
+            .map(|(_, full, minimal)| schema_of(&full, Some(&minimal)))
+            .unwrap()
+    }
+
+    #[test]
+    fn optional_fields_are_nullable_and_not_required() {
+        let user = schema("User");
+
+        assert_eq!(
+            Value::Bool(true),
+            user["properties"]["iracingCustomerId"]["nullable"]
+        );
+        assert_eq!("integer", user["properties"]["iracingCustomerId"]["type"]);
+        assert_eq!("integer", user["properties"]["id"]["type"]);
+        let required = user["required"].as_array().unwrap();
+        assert!(required.contains(&Value::String("email".to_string())));
+        assert!(!required.contains(&Value::String("iracingCustomerId".to_string())));
+    }
+
+    #[test]
+    fn ids_and_dates_have_formats() {
+        let access_token = schema("AccessToken");
+
+        assert_eq!("uuid", access_token["properties"]["id"]["format"]);
+        assert_eq!(
+            "date-time",
+            access_token["properties"]["createdDate"]["format"]
+        );
+        assert_eq!("boolean", access_token["properties"]["readOnly"]["type"]);
+    }
+}
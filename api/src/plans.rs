@@ -1,50 +1,73 @@
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::{header, StatusCode},
     response::IntoResponse,
     Json,
 };
-use endurance_racing_planner_common::{PatchRacePlannerDto, PlanListDto, RacePlannerDto};
+use endurance_racing_planner_common::{
+    plan_export::{
+        PlanExportDto, PlanExportVersionDto, ScenarioExportDto, OLDEST_PLAN_EXPORT_SCHEMA_VERSION,
+        PLAN_EXPORT_SCHEMA_VERSION,
+    },
+    validation::Validate,
+    Driver, PatchRacePlannerDto, PlanListDto, RacePlannerDto,
+};
 use sqlx::{types::Uuid, PgPool};
+use std::collections::HashMap;
+use tokio::try_join;
 
 use crate::{
     data_access::{
         self,
+        drivers::{create_driver, get_drivers_by_plan_id},
+        entities::driver,
         entities::{
             plan::{PatchPlan, PatchPlanType, StintType},
             Plan,
         },
         plans::{create_plan, get_plan_by_id, get_plans_by_user_id},
+        scenarios::{
+            create_scenario, get_active_scenario_id, get_scenarios_by_plan_id, rename_scenario,
+        },
+        schedules::{create_schedule, get_schedule_by_plan_id, get_schedule_by_scenario_id},
     },
+    error::{ApiError, ApiJson, ApiPath, ValidJson},
+    revisions::{patch_plan_with_revision, patch_section},
     AuthenticatedUser,
 };
 
 pub(crate) async fn add_plan(
     AuthenticatedUser(user): AuthenticatedUser,
     State(pool): State<PgPool>,
-    Json(plan): Json<RacePlannerDto>,
-) -> impl IntoResponse {
+    ValidJson(plan): ValidJson<RacePlannerDto>,
+) -> Result<impl IntoResponse, ApiError> {
     let mut new_plan: Plan = plan.into();
     new_plan.created_by = user.id;
 
-    let new_plan_result = create_plan(&pool, new_plan).await;
-    if let Ok(new_plan) = new_plan_result {
-        (
-            StatusCode::CREATED,
-            [(header::CONTENT_LOCATION, format!("/plans/{}", new_plan.id))],
-            Json::<RacePlannerDto>(new_plan.into()),
-        )
-            .into_response()
-    } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, "failed to save the plan").into_response()
-    }
+    let mut connection = pool
+        .acquire()
+        .await
+        .map_err(|e| ApiError::internal("failed to save the plan", e))?;
+    let new_plan = create_plan(&mut connection, new_plan)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the plan", e))?;
+
+    Ok((
+        StatusCode::CREATED,
+        [(header::CONTENT_LOCATION, format!("/plans/{}", new_plan.id))],
+        Json::<RacePlannerDto>(new_plan.into()),
+    ))
 }
 
 pub(crate) async fn get_plans(
     AuthenticatedUser(user): AuthenticatedUser,
     State(pool): State<PgPool>,
-) -> impl IntoResponse {
-    let plans = get_plans_by_user_id(&pool, user.id).await.map(|plans| {
+) -> Result<impl IntoResponse, ApiError> {
+    let plans = get_plans_by_user_id(&pool, user.id)
+        .await
+        .map_err(|e| ApiError::internal("failed to load the plans", e))?;
+
+    Ok(Json(
         plans
             .iter()
             .map(|p| PlanListDto {
@@ -53,41 +76,28 @@ pub(crate) async fn get_plans(
                 owner: p.owner.clone(),
                 last_modified: p.modified_date.unwrap_or(p.created_date),
             })
-            .collect::<Vec<PlanListDto>>()
-    });
-
-    match plans {
-        Ok(plans) => Json(plans).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to load the plans",
-        )
-            .into_response(),
-    }
+            .collect::<Vec<PlanListDto>>(),
+    ))
 }
 
 pub(crate) async fn get_plan(
-    Path(id): Path<Uuid>,
+    ApiPath(id): ApiPath<Uuid>,
     State(pool): State<PgPool>,
     AuthenticatedUser(user): AuthenticatedUser,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
     get_plan_by_id(&pool, id, user.id)
         .await
-        .map(|plan| match plan {
-            Some(plan) => Json(plan).into_response(),
-            None => (StatusCode::NOT_FOUND).into_response(),
-        })
-        .unwrap_or_else(|_| {
-            (StatusCode::INTERNAL_SERVER_ERROR, "failed to get the plan").into_response()
-        })
+        .map_err(|e| ApiError::internal("failed to get the plan", e))?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))
 }
 
 pub(crate) async fn patch_plan(
     AuthenticatedUser(user): AuthenticatedUser,
     State(pool): State<PgPool>,
-    Path(id): Path<Uuid>,
-    Json(plan): Json<PatchRacePlannerDto>,
-) -> impl IntoResponse {
+    ApiPath(id): ApiPath<Uuid>,
+    ValidJson(plan): ValidJson<PatchRacePlannerDto>,
+) -> Result<impl IntoResponse, ApiError> {
     let patch = if plan.overall_event_config.is_some() {
         PatchPlanType::EventConfig(plan.overall_event_config.unwrap())
     } else if plan.overall_fuel_stint_config.is_some() {
@@ -106,57 +116,243 @@ pub(crate) async fn patch_plan(
                 .unwrap(),
             stint_type,
         )
-    } else if plan.title.is_some() {
-        PatchPlanType::Title(plan.title.unwrap())
+    } else if let Some(track_conditions) = plan.track_conditions {
+        PatchPlanType::TrackConditions(track_conditions)
+    } else if let Some(traffic_model) = plan.traffic_model {
+        PatchPlanType::TrafficModel(traffic_model)
+    } else if let Some(title) = plan.title {
+        PatchPlanType::Title(title)
     } else {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json("failed to supply any values to patch".to_string()),
-        );
+        return Err(ApiError::bad_request(
+            "failed to supply any values to patch",
+        ));
     };
 
-    let result = data_access::plans::patch_plan(&pool, PatchPlan::new(id, user.id, patch)).await;
-    match result {
-        Ok(_) => (StatusCode::OK, Json(id.to_string())),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json("something went wrong saving the plan".to_string()),
-        ),
+    let section = patch_section(&patch);
+    let patched = patch_plan_with_revision(&pool, id, user.id, section, vec![patch])
+        .await
+        .map_err(|e| ApiError::internal("something went wrong saving the plan", e))?;
+    if !patched {
+        return Err(ApiError::not_found("the plan does not exist"));
     }
+
+    Ok((StatusCode::OK, Json(id.to_string())))
 }
 
 pub(crate) async fn share_plan(
     AuthenticatedUser(_): AuthenticatedUser,
     State(pool): State<PgPool>,
-    Path(id): Path<Uuid>,
-    Json(emails): Json<Vec<String>>,
-) -> impl IntoResponse {
-    match data_access::user::Users::get_users_by_emails(&pool, &emails).await {
-        Ok(users) => {
-            let user_ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
-            if user_ids.is_empty() {
-                return StatusCode::OK;
-            }
-            match data_access::plans::add_users_to_plan(&pool, id, &user_ids).await {
-                Ok(_) => StatusCode::OK,
-                Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            }
-        }
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(emails): ApiJson<Vec<String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let users = data_access::user::Users::get_users_by_emails(&pool, &emails)
+        .await
+        .map_err(|e| ApiError::internal("failed to find the users to share with", e))?;
+    let user_ids = users.iter().map(|user| user.id).collect::<Vec<_>>();
+    if !user_ids.is_empty() {
+        data_access::plans::add_users_to_plan(&pool, id, &user_ids)
+            .await
+            .map_err(|e| ApiError::internal("failed to share the plan", e))?;
     }
+
+    Ok(StatusCode::OK)
 }
 
 pub(crate) async fn get_plan_shared_users(
     AuthenticatedUser(_): AuthenticatedUser,
     State(pool): State<PgPool>,
-    Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match data_access::user::Users::get_shared_users_by_plan_id(&pool, id).await {
-        Ok(users) => (StatusCode::OK, Json(users)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json("Something went wrong getting shared users. Please try again later.".to_string()),
-        )
-            .into_response(),
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let users = data_access::user::Users::get_shared_users_by_plan_id(&pool, id)
+        .await
+        .map_err(|e| {
+            ApiError::internal(
+                "Something went wrong getting shared users. Please try again later.",
+                e,
+            )
+        })?;
+
+    Ok((StatusCode::OK, Json(users)))
+}
+
+pub(crate) async fn export_plan(
+    ApiPath(id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let plan = get_full_plan(&pool, id, user.id)
+        .await
+        .map_err(|e| ApiError::internal("failed to export the plan", e))?
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+    let scenarios = get_scenario_exports(&pool, id)
+        .await
+        .map_err(|e| ApiError::internal("failed to export the plan", e))?;
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"plan-{}.json\"", id),
+        )],
+        Json(PlanExportDto::new(plan, scenarios)),
+    ))
+}
+
+/// Every scenario of the plan with its schedule
+async fn get_scenario_exports(
+    pool: &PgPool,
+    plan_id: Uuid,
+) -> Result<Vec<ScenarioExportDto>, sqlx::Error> {
+    let mut scenarios = vec![];
+    for scenario in get_scenarios_by_plan_id(pool, plan_id).await? {
+        scenarios.push(ScenarioExportDto {
+            schedule: get_schedule_by_scenario_id(pool, scenario.id).await?,
+            name: scenario.name,
+            is_active: scenario.is_active,
+        });
+    }
+
+    Ok(scenarios)
+}
+
+/// Creates a copy of an exported plan owned by the current user. The plan, its drivers, its
+/// scenarios and their stints all get new ids so the same document can be imported more than once.
+pub(crate) async fn import_plan(
+    AuthenticatedUser(user): AuthenticatedUser,
+    State(pool): State<PgPool>,
+    ApiJson(document): ApiJson<serde_json::Value>,
+) -> Result<impl IntoResponse, ApiError> {
+    let version = serde_json::from_value::<PlanExportVersionDto>(document.clone())
+        .map_err(|e| ApiError::bad_request(format!("the document is not a plan export: {}", e)))?;
+    if !version.is_supported() {
+        return Err(ApiError::bad_request(format!(
+            "unsupported plan export schema version {}, expected a version from {} to {}",
+            version.schema_version, OLDEST_PLAN_EXPORT_SCHEMA_VERSION, PLAN_EXPORT_SCHEMA_VERSION
+        )));
+    }
+
+    let mut export = serde_json::from_value::<PlanExportDto>(document)
+        .map_err(|e| ApiError::bad_request(format!("the plan export is invalid: {}", e)))?;
+    export.validate().map_err(ApiError::invalid)?;
+    export.plan.id = Uuid::new_v4();
+
+    save_imported_plan(&pool, &mut export, user.id)
+        .await
+        .map_err(|e| ApiError::internal("failed to import the plan", e))?;
+
+    Ok((
+        StatusCode::CREATED,
+        [(
+            header::CONTENT_LOCATION,
+            format!("/plans/{}", export.plan.id),
+        )],
+        Json(export.plan),
+    ))
+}
+
+/// The plan with its driver roster and the schedule of its active scenario
+pub(crate) async fn get_full_plan(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: i32,
+) -> Result<Option<RacePlannerDto>, sqlx::Error> {
+    let (plan, drivers, schedule) = try_join!(
+        get_plan_by_id(pool, id, user_id),
+        get_drivers_by_plan_id(pool, id),
+        get_schedule_by_plan_id(pool, id)
+    )?;
+
+    Ok(plan.map(|mut plan| {
+        plan.driver_roster = drivers.iter().map(|d| d.into()).collect::<Vec<Driver>>();
+        plan.schedule_rows = if schedule.is_empty() {
+            None
+        } else {
+            Some(schedule)
+        };
+        plan
+    }))
+}
+
+/// Saves the plan, its drivers and its scenarios in one transaction so a failed import doesn't
+/// leave a partial plan behind. The export's active scenario fills the scenario every new plan
+/// starts with, a version 1 export only has the plan's schedule for it.
+async fn save_imported_plan(
+    pool: &PgPool,
+    export: &mut PlanExportDto,
+    user_id: i32,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    let plan = &mut export.plan;
+    let mut new_plan: Plan = plan.clone().into();
+    new_plan.created_by = user_id;
+    create_plan(&mut transaction, new_plan).await?;
+
+    let mut patches = vec![];
+    if let Some(event_config) = plan.overall_event_config.clone() {
+        patches.push(PatchPlanType::EventConfig(event_config));
     }
+    if let Some(fuel_stint_config) = plan.overall_fuel_stint_config.clone() {
+        patches.push(PatchPlanType::FuelStintConfig(fuel_stint_config));
+    }
+    if let Some(fuel_stint_times) = plan.fuel_stint_average_times.clone() {
+        patches.push(PatchPlanType::FuelStintAverageTime(
+            fuel_stint_times.standard_fuel_stint,
+            StintType::Standard,
+        ));
+        patches.push(PatchPlanType::FuelStintAverageTime(
+            fuel_stint_times.fuel_saving_stint,
+            StintType::FuelSaving,
+        ));
+    }
+    if !plan.track_conditions.is_empty() {
+        patches.push(PatchPlanType::TrackConditions(
+            plan.track_conditions.clone(),
+        ));
+    }
+    if let Some(traffic_model) = plan.traffic_model.clone() {
+        patches.push(PatchPlanType::TrafficModel(traffic_model));
+    }
+    for patch in patches {
+        let patch = PatchPlan::new(plan.id, user_id, patch);
+        if !data_access::plans::patch_plan(&mut transaction, patch).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+    }
+
+    let mut driver_ids = HashMap::new();
+    for d in plan.driver_roster.iter_mut() {
+        let new_driver = driver::Driver::create(d.clone(), plan.id);
+        let new_driver = create_driver(&mut *transaction, new_driver).await?;
+        driver_ids.insert(d.id, new_driver.id);
+        d.id = new_driver.id;
+    }
+
+    export.remap_schedules(&driver_ids);
+    let plan_id = export.plan.id;
+    let scenario_id = match export.plan.schedule_rows.as_ref().filter(|s| !s.is_empty()) {
+        Some(schedule) => {
+            let stints = schedule
+                .iter()
+                .map(|stint| stint.into())
+                .collect::<Vec<_>>();
+            create_schedule(&mut transaction, plan_id, stints).await?
+        }
+        None => get_active_scenario_id(&mut *transaction, plan_id).await?,
+    }
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    for scenario in export.scenarios.iter() {
+        if scenario.is_active {
+            rename_scenario(&mut *transaction, plan_id, scenario_id, &scenario.name).await?;
+        } else {
+            let stints = scenario
+                .schedule
+                .iter()
+                .map(|stint| stint.into())
+                .collect::<Vec<_>>();
+            create_scenario(&mut transaction, plan_id, &scenario.name, false, stints).await?;
+        }
+    }
+
+    transaction.commit().await
 }
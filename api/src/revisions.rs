@@ -0,0 +1,306 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use endurance_racing_planner_common::{
+    revision::{PlanRevisionDto, PlanSection},
+    schedule::ScheduleStintDto,
+    EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData, RacePlannerDto,
+};
+use serde_json::Value;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::{
+    data_access::{
+        self,
+        drivers::get_drivers_by_plan_id,
+        entities::{
+            plan::{PatchPlan, PatchPlanType, StintType},
+            schedule::Stint,
+        },
+        plans::get_plan_by_id,
+        revisions::{add_revision, get_revision_by_id, get_revisions_by_plan_id},
+        scenarios::get_active_scenario_id,
+        schedules::{get_schedule_by_scenario_id, replace_scenario_schedule},
+    },
+    error::{ApiError, ApiPath},
+    plans::get_full_plan,
+    AuthenticatedUser,
+};
+
+pub(crate) async fn get_revisions(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    get_plan_by_id(&pool, plan_id, user.id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the plan", e))?
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+
+    let revisions = get_revisions_by_plan_id(&pool, plan_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the plan history", e))?;
+
+    Ok(Json(
+        revisions
+            .into_iter()
+            .map(|revision| revision.into())
+            .collect::<Vec<PlanRevisionDto>>(),
+    ))
+}
+
+/// Puts the section the revision changed back to the value it had right after the revision
+pub(crate) async fn restore_revision(
+    ApiPath((plan_id, revision_id)): ApiPath<(Uuid, i64)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    apply_revision(&pool, plan_id, revision_id, user.id, false).await
+}
+
+/// Puts the section the revision changed back to the value it had right before the revision
+pub(crate) async fn undo_revision(
+    ApiPath((plan_id, revision_id)): ApiPath<(Uuid, i64)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    apply_revision(&pool, plan_id, revision_id, user.id, true).await
+}
+
+/// Applies the revision's `after` value, or its `before` value when `undo` is set, as a new
+/// revision and returns the whole plan
+async fn apply_revision(
+    pool: &PgPool,
+    plan_id: Uuid,
+    revision_id: i64,
+    user_id: i32,
+    undo: bool,
+) -> Result<Json<RacePlannerDto>, ApiError> {
+    get_plan_by_id(pool, plan_id, user_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the plan", e))?
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+    let revision = get_revision_by_id(pool, plan_id, revision_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the revision", e))?
+        .ok_or_else(|| ApiError::not_found("the revision does not exist"))?;
+
+    let section: PlanSection = revision.section.into();
+    let value = match (undo, revision.before) {
+        (false, _) => revision.after,
+        (true, Some(before)) => before,
+        (true, None) => {
+            return Err(ApiError::bad_request(
+                "the revision has no earlier value to undo to",
+            ))
+        }
+    };
+
+    if section == PlanSection::Schedule {
+        let schedule =
+            serde_json::from_value::<Vec<ScheduleStintDto>>(value).map_err(invalid_revision)?;
+        let restored = restore_schedule(pool, plan_id, revision.scenario_id, user_id, schedule)
+            .await
+            .map_err(|e| ApiError::internal("failed to restore the revision", e))?;
+        if !restored {
+            return Err(ApiError::not_found(
+                "the scenario the revision changed no longer exists",
+            ));
+        }
+    } else {
+        let patches = section_patches(section, value).map_err(invalid_revision)?;
+        let patched = patch_plan_with_revision(pool, plan_id, user_id, section, patches)
+            .await
+            .map_err(|e| ApiError::internal("failed to restore the revision", e))?;
+        if !patched {
+            return Err(ApiError::not_found("the plan does not exist"));
+        }
+    }
+
+    get_full_plan(pool, plan_id, user_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the plan", e))?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))
+}
+
+/// A revision recorded by an older build can fail to deserialize into the current dtos
+fn invalid_revision(e: serde_json::Error) -> ApiError {
+    tracing::error!("{:?}", e);
+    ApiError::Validation(format!("the revision can't be restored: {}", e), vec![])
+}
+
+/// The patches that set a plan section to `value`, `section` can't be the schedule
+fn section_patches(
+    section: PlanSection,
+    value: Value,
+) -> Result<Vec<PatchPlanType>, serde_json::Error> {
+    let patches = match section {
+        PlanSection::Title => vec![PatchPlanType::Title(serde_json::from_value(value)?)],
+        PlanSection::EventConfig => vec![PatchPlanType::EventConfig(serde_json::from_value::<
+            EventConfigDto,
+        >(value)?)],
+        PlanSection::FuelStintConfig => {
+            vec![PatchPlanType::FuelStintConfig(serde_json::from_value::<
+                OverallFuelStintConfigData,
+            >(value)?)]
+        }
+        PlanSection::FuelStintAverageTimes => {
+            let fuel_stint_times = serde_json::from_value::<FuelStintAverageTimes>(value)?;
+            vec![
+                PatchPlanType::FuelStintAverageTime(
+                    fuel_stint_times.standard_fuel_stint,
+                    StintType::Standard,
+                ),
+                PatchPlanType::FuelStintAverageTime(
+                    fuel_stint_times.fuel_saving_stint,
+                    StintType::FuelSaving,
+                ),
+            ]
+        }
+        PlanSection::TrackConditions => {
+            vec![PatchPlanType::TrackConditions(serde_json::from_value(
+                value,
+            )?)]
+        }
+        PlanSection::TrafficModel => {
+            vec![PatchPlanType::TrafficModel(serde_json::from_value(value)?)]
+        }
+        PlanSection::Schedule => vec![],
+    };
+
+    Ok(patches)
+}
+
+/// The history section a patch changes
+pub(crate) fn patch_section(patch: &PatchPlanType) -> PlanSection {
+    match patch {
+        PatchPlanType::Title(_) => PlanSection::Title,
+        PatchPlanType::EventConfig(_) => PlanSection::EventConfig,
+        PatchPlanType::FuelStintConfig(_) => PlanSection::FuelStintConfig,
+        PatchPlanType::FuelStintAverageTime(_, _) => PlanSection::FuelStintAverageTimes,
+        PatchPlanType::TrackConditions(_) => PlanSection::TrackConditions,
+        PatchPlanType::TrafficModel(_) => PlanSection::TrafficModel,
+    }
+}
+
+/// Applies `patches` to the plan in one transaction and records the change to `section` in the
+/// plan's history. Returns false when the plan doesn't exist or isn't shared with the user.
+pub(crate) async fn patch_plan_with_revision(
+    pool: &PgPool,
+    plan_id: Uuid,
+    user_id: i32,
+    section: PlanSection,
+    patches: Vec<PatchPlanType>,
+) -> Result<bool, sqlx::Error> {
+    let before = match get_plan_by_id(pool, plan_id, user_id).await? {
+        Some(plan) => plan.section_value(section),
+        None => return Ok(false),
+    };
+
+    let mut transaction = pool.begin().await?;
+    for patch in patches {
+        let patch = PatchPlan::new(plan_id, user_id, patch);
+        if !data_access::plans::patch_plan(&mut transaction, patch).await? {
+            transaction.rollback().await?;
+            return Ok(false);
+        }
+    }
+    transaction.commit().await?;
+
+    if let Some(after) = get_plan_by_id(pool, plan_id, user_id)
+        .await?
+        .and_then(|plan| plan.section_value(section))
+    {
+        record_revision(pool, plan_id, None, user_id, section, before, after).await;
+    }
+
+    Ok(true)
+}
+
+/// Records a change to the schedule of one of the plan's scenarios, an empty schedule is recorded
+/// as not having a value
+pub(crate) async fn record_schedule_revision(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Uuid,
+    user_id: i32,
+    before: &[ScheduleStintDto],
+    after: &[ScheduleStintDto],
+) {
+    let mut plan = RacePlannerDto::new();
+    plan.schedule_rows = (!before.is_empty()).then(|| before.to_vec());
+    let before = plan.section_value(PlanSection::Schedule);
+    plan.schedule_rows = Some(after.to_vec());
+
+    if let Some(after) = plan.section_value(PlanSection::Schedule) {
+        record_revision(
+            pool,
+            plan_id,
+            Some(scenario_id),
+            user_id,
+            PlanSection::Schedule,
+            before,
+            after,
+        )
+        .await;
+    }
+}
+
+/// The history is an audit log of changes that have already been saved, failing to record a
+/// revision is logged instead of failing the change
+async fn record_revision(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Option<Uuid>,
+    user_id: i32,
+    section: PlanSection,
+    before: Option<Value>,
+    after: Value,
+) {
+    if before.as_ref() == Some(&after) {
+        return;
+    }
+
+    let added = add_revision(pool, plan_id, scenario_id, user_id, section, before, after).await;
+    if let Err(e) = added {
+        tracing::error!("failed to record a revision of plan {}: {:?}", plan_id, e);
+    }
+}
+
+/// Replaces the schedule of the scenario the revision changed, or of the active scenario for
+/// revisions recorded without one. Stints for drivers that have since left the roster become
+/// unassigned. Returns false when the scenario has since been deleted.
+async fn restore_schedule(
+    pool: &PgPool,
+    plan_id: Uuid,
+    scenario_id: Option<Uuid>,
+    user_id: i32,
+    mut schedule: Vec<ScheduleStintDto>,
+) -> Result<bool, sqlx::Error> {
+    let scenario_id = match scenario_id {
+        Some(scenario_id) => Some(scenario_id),
+        None => get_active_scenario_id(pool, plan_id).await?,
+    };
+    let scenario_id = match scenario_id {
+        Some(scenario_id) => scenario_id,
+        None => return Ok(false),
+    };
+
+    let before = get_schedule_by_scenario_id(pool, scenario_id).await?;
+    let drivers = get_drivers_by_plan_id(pool, plan_id).await?;
+    for stint in schedule.iter_mut() {
+        if !drivers.iter().any(|driver| driver.id == stint.driver_id) {
+            stint.driver_id = 0;
+        }
+    }
+
+    let stints = schedule
+        .iter()
+        .map(|stint| stint.into())
+        .collect::<Vec<Stint>>();
+    if !replace_scenario_schedule(pool, plan_id, scenario_id, stints).await? {
+        return Ok(false);
+    }
+    record_schedule_revision(pool, plan_id, scenario_id, user_id, &before, &schedule).await;
+
+    Ok(true)
+}
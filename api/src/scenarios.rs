@@ -0,0 +1,200 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use endurance_racing_planner_common::{
+    scenario::{NewScenarioDto, ScenarioComparisonDto, ScenarioDto},
+    uuid_gen::uuid_time_nextval,
+    Driver,
+};
+use sqlx::{types::Uuid, PgPool};
+use tokio::try_join;
+
+use crate::{
+    data_access::{
+        drivers::get_drivers_by_plan_id,
+        entities::schedule::Stint,
+        plans::get_plan_by_id,
+        scenarios::{
+            activate_scenario as activate_plan_scenario, create_scenario,
+            delete_scenario as delete_plan_scenario, get_active_scenario_id, get_scenario_by_id,
+            get_scenarios_by_plan_id,
+        },
+        schedules::get_schedule_by_scenario_id,
+    },
+    error::{ApiError, ApiPath, ValidJson},
+    AuthenticatedUser,
+};
+
+pub(crate) async fn get_scenarios(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let scenarios = get_scenarios_by_plan_id(&pool, plan_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the scenarios", e))?;
+
+    Ok(Json(
+        scenarios
+            .iter()
+            .map(|scenario| scenario.into())
+            .collect::<Vec<ScenarioDto>>(),
+    ))
+}
+
+/// Creates a scenario starting from a copy of the stints of another scenario
+pub(crate) async fn add_scenario(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    ValidJson(new_scenario): ValidJson<NewScenarioDto>,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let name = new_scenario.name.trim();
+
+    let source_scenario_id = match new_scenario.copy_from {
+        Some(scenario_id) => get_scenario_by_id(&pool, plan_id, scenario_id)
+            .await
+            .map(|scenario| scenario.map(|scenario| scenario.id)),
+        None => get_active_scenario_id(&pool, plan_id).await,
+    }
+    .map_err(|e| ApiError::internal("failed to get the scenario to copy", e))?
+    .ok_or_else(|| ApiError::not_found("the scenario to copy does not exist"))?;
+
+    let schedule = get_schedule_by_scenario_id(&pool, source_scenario_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the scenario to copy", e))?;
+    let stints = schedule
+        .into_iter()
+        .map(|mut stint| {
+            stint.id = uuid_time_nextval();
+            (&stint).into()
+        })
+        .collect::<Vec<Stint>>();
+    let mut connection = pool
+        .acquire()
+        .await
+        .map_err(|e| ApiError::internal("failed to save the scenario", e))?;
+    let scenario = create_scenario(&mut connection, plan_id, name, false, stints)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the scenario", e))?;
+
+    Ok((
+        StatusCode::CREATED,
+        [(
+            header::CONTENT_LOCATION,
+            format!("/plans/{}/scenarios/{}", plan_id, scenario.id),
+        )],
+        Json::<ScenarioDto>((&scenario).into()),
+    ))
+}
+
+pub(crate) async fn get_scenario_schedule(
+    ApiPath((plan_id, scenario_id)): ApiPath<(Uuid, Uuid)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let (scenario, schedule) = try_join!(
+        get_scenario_by_id(&pool, plan_id, scenario_id),
+        get_schedule_by_scenario_id(&pool, scenario_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the schedule", e))?;
+
+    match scenario {
+        Some(_) => Ok(Json(schedule)),
+        None => Err(ApiError::not_found("the scenario does not exist")),
+    }
+}
+
+/// Makes the scenario the plan's schedule and returns its stints
+pub(crate) async fn activate_scenario(
+    ApiPath((plan_id, scenario_id)): ApiPath<(Uuid, Uuid)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let activated = activate_plan_scenario(&pool, plan_id, scenario_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to activate the scenario", e))?;
+    if !activated {
+        return Err(ApiError::not_found("the scenario does not exist"));
+    }
+
+    get_schedule_by_scenario_id(&pool, scenario_id)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::internal("failed to get the schedule", e))
+}
+
+pub(crate) async fn delete_scenario(
+    ApiPath((plan_id, scenario_id)): ApiPath<(Uuid, Uuid)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let scenario = get_scenario_by_id(&pool, plan_id, scenario_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the scenario", e))?
+        .ok_or_else(|| ApiError::not_found("the scenario does not exist"))?;
+    if scenario.is_active {
+        return Err(ApiError::Conflict(
+            "the active scenario can't be deleted".to_string(),
+        ));
+    }
+
+    let deleted = delete_plan_scenario(&pool, plan_id, scenario_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to delete the scenario", e))?;
+    if !deleted {
+        return Err(ApiError::not_found("the scenario does not exist"));
+    }
+
+    Ok((StatusCode::OK, Json(scenario_id.to_string())))
+}
+
+/// Total laps, stops, finish time and seat time per driver of every scenario of the plan
+pub(crate) async fn compare_scenarios(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let (scenarios, drivers) = try_join!(
+        get_scenarios_by_plan_id(&pool, plan_id),
+        get_drivers_by_plan_id(&pool, plan_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the scenarios", e))?;
+    let drivers = drivers.iter().map(|d| d.into()).collect::<Vec<Driver>>();
+
+    let mut comparisons = Vec::with_capacity(scenarios.len());
+    for scenario in scenarios.iter() {
+        let schedule = get_schedule_by_scenario_id(&pool, scenario.id)
+            .await
+            .map_err(|e| ApiError::internal("failed to get the scenario schedules", e))?;
+        comparisons.push(ScenarioComparisonDto::new(
+            scenario.into(),
+            &schedule,
+            &drivers,
+        ));
+    }
+
+    Ok(Json(comparisons))
+}
+
+/// A plan's scenarios and schedule are only visible to the users the plan is visible to
+pub(crate) async fn check_plan_access(
+    pool: &PgPool,
+    plan_id: Uuid,
+    user_id: i32,
+) -> Result<(), ApiError> {
+    get_plan_by_id(pool, plan_id, user_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the plan", e))?
+        .map(|_| ())
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))
+}
@@ -1,76 +1,277 @@
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::{header, StatusCode},
     response::IntoResponse,
     Json,
 };
-use endurance_racing_planner_common::schedule::ScheduleStintDto;
+use endurance_racing_planner_common::{
+    api_error::FieldErrorDto,
+    schedule::{self, ScheduleConfig, ScheduleStintDto},
+    schedule_csv::{import_schedule_csv, schedule_to_csv},
+    Driver, EventConfigDto,
+};
 use sqlx::{types::Uuid, PgPool};
+use tokio::try_join;
 
-use crate::data_access::{
-    entities::schedule::Stint,
-    schedules::{create_schedule, get_schedule_by_plan_id, update_schedule},
+use crate::{
+    data_access::{
+        drivers::get_drivers_by_plan_id,
+        entities::schedule::Stint,
+        plans::get_plan_by_id,
+        scenarios::get_active_scenario_id,
+        schedules::{
+            create_schedule, get_schedule_by_plan_id, get_schedule_by_scenario_id,
+            replace_schedule, update_schedule,
+        },
+    },
+    error::{ApiError, ApiPath, ValidJson},
+    revisions::record_schedule_revision,
+    scenarios::check_plan_access,
+    AuthenticatedUser,
 };
 
 pub(crate) async fn add_schedule(
-    Path(plan_id): Path<Uuid>,
+    ApiPath(plan_id): ApiPath<Uuid>,
     State(pool): State<PgPool>,
-    Json(schedule): Json<Vec<ScheduleStintDto>>,
-) -> impl IntoResponse {
+    AuthenticatedUser(user): AuthenticatedUser,
+    ValidJson(schedule): ValidJson<Vec<ScheduleStintDto>>,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
     let new_schedule = schedule
         .iter()
         .map(|stint| stint.into())
         .collect::<Vec<_>>();
 
-    let new_schedule_result = create_schedule(&pool, plan_id, new_schedule).await;
-    match new_schedule_result {
-        Ok(success) => {
-            if success {
-                (
-                    StatusCode::CREATED,
-                    [(
-                        header::CONTENT_LOCATION,
-                        format!("/plans/{}/schedule", &plan_id),
-                    )],
-                )
-                    .into_response()
-            } else {
-                (StatusCode::BAD_REQUEST, "failed to save the schedule").into_response()
-            }
-        }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-    }
+    let mut connection = pool
+        .acquire()
+        .await
+        .map_err(|e| ApiError::internal("failed to save the schedule", e))?;
+    let scenario_id = create_schedule(&mut connection, plan_id, new_schedule)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the schedule", e))?
+        .ok_or_else(|| ApiError::bad_request("failed to save the schedule"))?;
+
+    record_schedule_revision(&pool, plan_id, scenario_id, user.id, &[], &schedule).await;
+    Ok((
+        StatusCode::CREATED,
+        [(
+            header::CONTENT_LOCATION,
+            format!("/plans/{}/schedule", &plan_id),
+        )],
+    ))
 }
 
 pub(crate) async fn get_schedule(
-    Path(id): Path<Uuid>,
+    ApiPath(id): ApiPath<Uuid>,
     State(pool): State<PgPool>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
     get_schedule_by_plan_id(&pool, id)
         .await
-        .map(|schedule| Json(schedule).into_response())
-        .unwrap_or_else(|_| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to get the schedule",
-            )
-                .into_response()
-        })
+        .map(Json)
+        .map_err(|e| ApiError::internal("failed to get the schedule", e))
 }
 
 pub(crate) async fn put_schedule(
-    Path(_plan_id): Path<Uuid>,
+    ApiPath(plan_id): ApiPath<Uuid>,
     State(pool): State<PgPool>,
-    Json(schedule): Json<Vec<ScheduleStintDto>>,
-) -> impl IntoResponse {
+    AuthenticatedUser(user): AuthenticatedUser,
+    ValidJson(schedule): ValidJson<Vec<ScheduleStintDto>>,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let scenario_id = get_active_scenario_id(&pool, plan_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the schedule", e))?
+        .ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+    let before = get_schedule_by_scenario_id(&pool, scenario_id)
+        .await
+        .map_err(|e| ApiError::internal("failed to get the schedule", e))?;
     let schedule = schedule
         .iter()
         .map(|stint| stint.into())
         .collect::<Vec<Stint>>();
 
-    let result = update_schedule(&pool, schedule).await;
-    match result {
-        Ok(_) => (StatusCode::OK).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    update_schedule(&pool, scenario_id, schedule)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the schedule", e))?;
+
+    // only the stints in the request are updated, re-read the schedule to record all of it
+    match get_schedule_by_scenario_id(&pool, scenario_id).await {
+        Ok(after) => {
+            record_schedule_revision(&pool, plan_id, scenario_id, user.id, &before, &after).await
+        }
+        Err(e) => tracing::error!("{:?}", e),
     }
+
+    Ok(StatusCode::OK)
+}
+
+pub(crate) async fn get_schedule_csv(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    check_plan_access(&pool, plan_id, user.id).await?;
+    let (schedule, drivers) = try_join!(
+        get_schedule_by_plan_id(&pool, plan_id),
+        get_drivers_by_plan_id(&pool, plan_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the schedule", e))?;
+    let drivers = drivers.iter().map(|d| d.into()).collect::<Vec<Driver>>();
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"schedule.csv\"",
+            ),
+        ],
+        schedule_to_csv(&schedule, &drivers),
+    ))
+}
+
+pub(crate) async fn put_schedule_csv(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    csv: String,
+) -> Result<impl IntoResponse, ApiError> {
+    let (plan, mut schedule, drivers) = try_join!(
+        get_plan_by_id(&pool, plan_id, user.id),
+        get_schedule_by_plan_id(&pool, plan_id),
+        get_drivers_by_plan_id(&pool, plan_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the schedule", e))?;
+    let plan = plan.ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+
+    let (event_config, config) = ScheduleConfig::from_plan(&plan).ok_or_else(|| {
+        ApiError::bad_request(
+            "the plan needs an event config and fuel stint times to import a schedule",
+        )
+    })?;
+    let drivers = drivers.iter().map(|d| d.into()).collect::<Vec<Driver>>();
+    let before = schedule.clone();
+
+    if let Err(errors) = import_schedule_csv(&csv, &mut schedule, &drivers, event_config, &config) {
+        return Err(ApiError::Validation(
+            "the schedule csv is invalid".to_string(),
+            errors
+                .into_iter()
+                .map(|error| FieldErrorDto::new(format!("row {}", error.row), error.message))
+                .collect(),
+        ));
+    }
+
+    let stints = schedule
+        .iter()
+        .map(|stint| stint.into())
+        .collect::<Vec<Stint>>();
+    let scenario_id = replace_schedule(&pool, plan_id, stints)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the schedule", e))?;
+    record_schedule_revision(&pool, plan_id, scenario_id, user.id, &before, &schedule).await;
+
+    Ok(Json(schedule))
+}
+
+/// Regenerates the schedule from the plan's stored settings, the schedule is created when the plan
+/// doesn't have one yet and re-flowed from the race start otherwise
+pub(crate) async fn recalculate_schedule(
+    ApiPath(plan_id): ApiPath<Uuid>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let (plan, mut schedule) = try_join!(
+        get_plan_by_id(&pool, plan_id, user.id),
+        get_schedule_by_plan_id(&pool, plan_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the schedule", e))?;
+    let plan = plan.ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+
+    let (event_config, config) = ScheduleConfig::from_plan(&plan)
+        .filter(|(_, config)| config.is_schedulable())
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "the plan needs a race duration, fuel stint times and a fuel stint config to calculate a schedule",
+            )
+        })?;
+    let before = schedule.clone();
+    schedule::recalculate_schedule(&mut schedule, event_config, &config);
+
+    let stints = schedule
+        .iter()
+        .map(|stint| stint.into())
+        .collect::<Vec<Stint>>();
+    let scenario_id = replace_schedule(&pool, plan_id, stints)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the schedule", e))?;
+    record_schedule_revision(&pool, plan_id, scenario_id, user.id, &before, &schedule).await;
+
+    Ok(Json(schedule))
+}
+
+pub(crate) async fn insert_stint(
+    ApiPath((plan_id, stint_id)): ApiPath<(Uuid, Uuid)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    edit_stint(&pool, plan_id, stint_id, user.id, schedule::insert_stint).await
+}
+
+pub(crate) async fn split_stint(
+    ApiPath((plan_id, stint_id)): ApiPath<(Uuid, Uuid)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    edit_stint(&pool, plan_id, stint_id, user.id, schedule::split_stint).await
+}
+
+pub(crate) async fn delete_stint(
+    ApiPath((plan_id, stint_id)): ApiPath<(Uuid, Uuid)>,
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    edit_stint(&pool, plan_id, stint_id, user.id, schedule::delete_stint).await
+}
+
+/// Applies `edit` to the stint with `stint_id`, then persists and returns the re-flowed schedule
+async fn edit_stint(
+    pool: &PgPool,
+    plan_id: Uuid,
+    stint_id: Uuid,
+    user_id: i32,
+    edit: fn(&mut Vec<ScheduleStintDto>, usize, &EventConfigDto, &ScheduleConfig),
+) -> Result<Json<Vec<ScheduleStintDto>>, ApiError> {
+    let (plan, mut schedule) = try_join!(
+        get_plan_by_id(pool, plan_id, user_id),
+        get_schedule_by_plan_id(pool, plan_id)
+    )
+    .map_err(|e| ApiError::internal("failed to get the schedule", e))?;
+    let plan = plan.ok_or_else(|| ApiError::not_found("the plan does not exist"))?;
+
+    let index = schedule
+        .iter()
+        .position(|stint| stint.id == stint_id)
+        .ok_or_else(|| ApiError::not_found("the stint does not exist"))?;
+    let (event_config, config) = ScheduleConfig::from_plan(&plan)
+        .filter(|(_, config)| config.is_schedulable())
+        .ok_or_else(|| {
+            ApiError::bad_request(
+                "the plan needs a race duration, fuel stint times and a fuel stint config to edit the schedule",
+            )
+        })?;
+    let before = schedule.clone();
+    edit(&mut schedule, index, event_config, &config);
+
+    let stints = schedule
+        .iter()
+        .map(|stint| stint.into())
+        .collect::<Vec<Stint>>();
+    let scenario_id = replace_schedule(pool, plan_id, stints)
+        .await
+        .map_err(|e| ApiError::internal("failed to save the schedule", e))?;
+    record_schedule_revision(pool, plan_id, scenario_id, user_id, &before, &schedule).await;
+
+    Ok(Json(schedule))
 }
@@ -0,0 +1,189 @@
+use chrono::{Duration, TimeZone, Utc};
+use endurance_racing_planner_common::{
+    schedule::{create_schedule, ScheduleConfig},
+    Driver, EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData, StintDataDto, User,
+};
+use sqlx::{types::Uuid, PgPool};
+
+use crate::data_access::{
+    self,
+    drivers::create_driver,
+    entities::{
+        driver,
+        plan::{PatchPlan, PatchPlanType, StintType},
+        Plan,
+    },
+    plans::{create_plan, get_plan_title},
+    schedules::create_schedule as save_schedule,
+    user::Users,
+};
+
+/// The seeded plan always has the same id so it's easy to find in local development and tests
+pub const DEMO_PLAN_ID: Uuid = Uuid::from_u128(0x5eed_0000_0000_0000_0000_0000_0000_0001);
+const DEMO_USER_ISSUER: &str = "urn:endurance-racing-planner:demo";
+const DEMO_USER_SUBJECT: &str = "demo-user";
+const DEMO_FUEL_TANK_SIZE: i32 = 104;
+
+/// Loads a demo plan with an event config, fuel stint times, a roster and a schedule, owned by a
+/// demo user. Does nothing when the demo plan already exists so it is safe to seed on every start,
+/// the user and plan are saved in one transaction so a failed seed leaves nothing behind.
+pub async fn seed_demo_data(pool: &PgPool) -> Result<(), sqlx::Error> {
+    if get_plan_title(pool, DEMO_PLAN_ID).await?.is_some() {
+        tracing::info!("demo plan {} already exists, skipping seed", DEMO_PLAN_ID);
+        return Ok(());
+    }
+
+    let mut transaction = pool.begin().await?;
+    let demo_user =
+        Users::get_user_by_identity(&mut *transaction, DEMO_USER_ISSUER, DEMO_USER_SUBJECT).await?;
+    let user = match demo_user {
+        Some(user) => user,
+        None => {
+            Users::create_user(
+                &mut *transaction,
+                User {
+                    id: 0,
+                    name: "Demo Driver".to_string(),
+                    email: "demo@example.com".to_string(),
+                    issuer: DEMO_USER_ISSUER.to_string(),
+                    subject: DEMO_USER_SUBJECT.to_string(),
+                    iracing_customer_id: None,
+                },
+            )
+            .await?
+        }
+    };
+
+    create_plan(
+        &mut transaction,
+        Plan {
+            id: DEMO_PLAN_ID,
+            title: "Demo - Daytona 24".to_string(),
+            created_by: user.id,
+            created_date: Utc::now(),
+            modified_by: None,
+            modified_date: None,
+        },
+    )
+    .await?;
+
+    let mut event_config = EventConfigDto::new();
+    event_config.race_duration = Duration::hours(24);
+    event_config.session_start_utc = Utc.with_ymd_and_hms(2027, 1, 23, 18, 0, 0).unwrap();
+    event_config.race_start_tod = Utc
+        .with_ymd_and_hms(2027, 1, 23, 13, 40, 0)
+        .unwrap()
+        .naive_utc();
+    event_config.green_flag_offset = Duration::minutes(40);
+    event_config.update_race_times();
+
+    let fuel_stint_config = OverallFuelStintConfigData {
+        pit_duration: Duration::seconds(45),
+        fuel_tank_size: DEMO_FUEL_TANK_SIZE,
+        tire_change_time: Duration::seconds(20),
+        add_tire_time: true,
+        tire_degradation_per_lap: Duration::milliseconds(20),
+        max_stints_per_tire_set: 2,
+    };
+    let fuel_stint_times = FuelStintAverageTimes {
+        standard_fuel_stint: stint_data(
+            Duration::milliseconds(107_500),
+            3.4,
+            fuel_stint_config.pit_duration,
+        ),
+        fuel_saving_stint: stint_data(
+            Duration::milliseconds(108_200),
+            3.2,
+            fuel_stint_config.pit_duration,
+        ),
+    };
+
+    let patches = vec![
+        PatchPlanType::EventConfig(event_config.clone()),
+        PatchPlanType::FuelStintConfig(fuel_stint_config.clone()),
+        PatchPlanType::FuelStintAverageTime(
+            fuel_stint_times.standard_fuel_stint.clone(),
+            StintType::Standard,
+        ),
+        PatchPlanType::FuelStintAverageTime(
+            fuel_stint_times.fuel_saving_stint.clone(),
+            StintType::FuelSaving,
+        ),
+    ];
+    for patch in patches {
+        let patch = PatchPlan::new(DEMO_PLAN_ID, user.id, patch);
+        if !data_access::plans::patch_plan(&mut transaction, patch).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+    }
+
+    let roster = [
+        ("Alice Demo", "#e53935", -5),
+        ("Bruno Demo", "#1e88e5", 1),
+        ("Chen Demo", "#43a047", 8),
+        ("Dana Demo", "#fdd835", 0),
+    ];
+    let mut driver_ids = vec![];
+    for (name, color, utc_offset) in roster {
+        let new_driver = create_driver(
+            &mut *transaction,
+            driver::Driver::create(
+                Driver {
+                    id: 0,
+                    name: name.to_string(),
+                    total_stints: 0,
+                    fair_share: false,
+                    color: color.to_string(),
+                    utc_offset,
+                    irating: 2500,
+                    stint_preference: 0,
+                },
+                DEMO_PLAN_ID,
+            ),
+        )
+        .await?;
+        driver_ids.push(new_driver.id);
+    }
+
+    let config = ScheduleConfig::new(
+        &event_config,
+        &fuel_stint_times,
+        &fuel_stint_config,
+        &[],
+        None,
+    );
+    // every driver does a double stint before handing over
+    let mut schedule = create_schedule(&event_config, &config);
+    for (index, stint) in schedule.iter_mut().enumerate() {
+        stint.driver_id = driver_ids[(index / 2) % driver_ids.len()];
+    }
+    save_schedule(
+        &mut transaction,
+        DEMO_PLAN_ID,
+        schedule.iter().map(|stint| stint.into()).collect(),
+    )
+    .await?;
+    transaction.commit().await?;
+
+    tracing::info!(
+        "seeded demo plan {} with {} stints",
+        DEMO_PLAN_ID,
+        schedule.len()
+    );
+    Ok(())
+}
+
+fn stint_data(lap_time: Duration, fuel_per_lap: f32, pit_duration: Duration) -> StintDataDto {
+    let lap_count = (DEMO_FUEL_TANK_SIZE as f32 / fuel_per_lap).floor() as i32;
+    let track_time = lap_time * lap_count;
+
+    StintDataDto {
+        lap_time,
+        fuel_per_lap,
+        lap_count,
+        lap_time_with_pit: lap_time + pit_duration / lap_count,
+        track_time,
+        track_time_with_pit: track_time + pit_duration,
+        fuel_per_stint: fuel_per_lap * lap_count as f32,
+    }
+}
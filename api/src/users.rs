@@ -4,31 +4,69 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use endurance_racing_planner_common::User;
+use endurance_racing_planner_common::{PatchUserDto, User};
 use sqlx::PgPool;
 
-use crate::{data_access::user::Users, AuthenticatedUser};
+use crate::{
+    data_access::user::Users,
+    error::{ApiError, ValidJson},
+    AuthenticatedUser, VerifiedIdentity,
+};
 
 pub(crate) async fn me(user: AuthenticatedUser) -> impl IntoResponse {
     (StatusCode::OK, Json(user.0))
 }
 
+/// Registers the user the ID token was issued to. Everything about the user comes from the verified
+/// token, registering again returns the existing user.
 pub(crate) async fn add_user(
     State(pool): State<PgPool>,
-    Json(user): Json<User>,
-) -> impl IntoResponse {
-    let new_user_result = Users::create_user(&pool, user).await;
-    match new_user_result {
-        Ok(new_user) => (
-            StatusCode::CREATED,
-            [(header::CONTENT_LOCATION, format!("/users/{}", new_user.id))],
-            Json(new_user),
-        )
-            .into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "there was a problem creating the user",
-        )
-            .into_response(),
-    }
+    VerifiedIdentity(identity): VerifiedIdentity,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = identity.claims.display_name().to_string();
+    let email = match identity.claims.email {
+        Some(email) if identity.claims.email_verified => email,
+        _ => {
+            return Err(ApiError::Forbidden(
+                "the provider hasn't verified the email address".to_string(),
+            ))
+        }
+    };
+
+    let (user, created) = Users::register_user(
+        &pool,
+        User {
+            id: 0,
+            name,
+            email,
+            issuer: identity.issuer,
+            subject: identity.subject,
+            iracing_customer_id: None,
+        },
+    )
+    .await
+    .map_err(|e| ApiError::internal("there was a problem creating the user", e))?;
+
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((
+        status,
+        [(header::CONTENT_LOCATION, "/users/me".to_string())],
+        Json(user),
+    ))
+}
+
+pub(crate) async fn patch_me(
+    State(pool): State<PgPool>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    ValidJson(patch): ValidJson<PatchUserDto>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = Users::patch_user(&pool, user.id, patch)
+        .await
+        .map_err(|e| ApiError::internal("there was a problem updating the user", e))?;
+
+    Ok((StatusCode::OK, Json(user)))
 }
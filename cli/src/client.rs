@@ -0,0 +1,114 @@
+use std::{rc::Rc, thread, time::Duration};
+
+use endurance_racing_planner_common::{
+    client::{
+        self, BearerToken, ClientError, HttpMethod, HttpRequest, HttpResponse, LocalFuture,
+        Transport,
+    },
+    schedule::ScheduleStintDto,
+    PlanListDto, RacePlannerDto,
+};
+use futures_executor::block_on;
+use reqwest::{blocking::Client, header::CONTENT_TYPE, Method};
+use uuid::Uuid;
+
+use crate::error::CliError;
+
+/// The api calls the cli makes, every request is authenticated with a personal access token
+pub struct ApiClient {
+    client: client::ApiClient,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str, token: String) -> Self {
+        let transport = BlockingTransport {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        };
+
+        Self {
+            client: client::ApiClient::new(Rc::new(transport), Rc::new(BearerToken(token))),
+        }
+    }
+
+    pub fn get_plans(&self) -> Result<Vec<PlanListDto>, CliError> {
+        Ok(block_on(self.client.get_plans())?)
+    }
+
+    pub fn get_plan(&self, plan_id: Uuid) -> Result<RacePlannerDto, CliError> {
+        Ok(block_on(self.client.get_plan(plan_id))?)
+    }
+
+    /// The stints of the plan's active scenario, a plan is returned without its schedule
+    pub fn get_schedule(&self, plan_id: Uuid) -> Result<Vec<ScheduleStintDto>, CliError> {
+        Ok(block_on(self.client.get_schedule(plan_id))?)
+    }
+
+    /// Saves the stints, the api only updates the stints that are already in the schedule
+    pub fn update_stints(
+        &self,
+        plan_id: Uuid,
+        stints: &[ScheduleStintDto],
+    ) -> Result<(), CliError> {
+        Ok(block_on(self.client.update_schedule(plan_id, stints))?)
+    }
+
+    /// Re-flows the schedule from the race start on the api, stints with an actual end keep it
+    pub fn recalculate_schedule(&self, plan_id: Uuid) -> Result<Vec<ScheduleStintDto>, CliError> {
+        Ok(block_on(self.client.recalculate_schedule(plan_id))?)
+    }
+}
+
+/// Sends each request before the future it returns is polled, the cli only waits on one request
+/// at a time
+struct BlockingTransport {
+    client: Client,
+    base_url: String,
+}
+
+impl BlockingTransport {
+    fn send_now(&self, request: HttpRequest) -> Result<HttpResponse, ClientError> {
+        let method = match request.method {
+            HttpMethod::Get => Method::GET,
+            HttpMethod::Post => Method::POST,
+            HttpMethod::Put => Method::PUT,
+            HttpMethod::Patch => Method::PATCH,
+            HttpMethod::Delete => Method::DELETE,
+        };
+        let mut builder = self
+            .client
+            .request(method, format!("{}/{}", self.base_url, request.route));
+        if let Some(token) = request.token {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(body) = request.body {
+            builder = builder.header(CONTENT_TYPE, "application/json").body(body);
+        }
+
+        let response = builder.send().map_err(|e| {
+            if e.is_builder() {
+                ClientError::Request(e.to_string())
+            } else {
+                ClientError::Offline(e.to_string())
+            }
+        })?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .map_err(|e| ClientError::Offline(e.to_string()))?;
+
+        Ok(HttpResponse { status, body })
+    }
+}
+
+impl Transport for BlockingTransport {
+    fn send(&self, request: HttpRequest) -> LocalFuture<Result<HttpResponse, ClientError>> {
+        let response = self.send_now(request);
+        Box::pin(async move { response })
+    }
+
+    fn sleep(&self, duration: Duration) -> LocalFuture<()> {
+        thread::sleep(duration);
+        Box::pin(async {})
+    }
+}
@@ -0,0 +1,57 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+use endurance_racing_planner_common::client::ClientError;
+
+/// Every way a command can fail, printed to stderr before the cli exits with a failure
+#[derive(Debug)]
+pub enum CliError {
+    MissingToken,
+    /// The api rejected the request, or couldn't be reached after the retries
+    Client(ClientError),
+    Io(PathBuf, std::io::Error),
+    Json(serde_json::Error),
+    /// The plan can't be used for the command, e.g. it can't be scheduled
+    Plan(String),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::MissingToken => write!(
+                f,
+                "an access token is required, set PLANNER_TOKEN or pass --token"
+            ),
+            CliError::Client(ClientError::SignedOut | ClientError::Unauthorized(_)) => {
+                write!(
+                    f,
+                    "the access token was rejected, create a new one in the planner"
+                )
+            }
+            CliError::Client(ClientError::Offline(e)) => {
+                write!(f, "the api couldn't be reached: {}", e)
+            }
+            CliError::Client(e) => write!(f, "the request to the api failed: {}", e),
+            CliError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            CliError::Json(e) => write!(f, "the json is invalid: {}", e),
+            CliError::Plan(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+impl From<ClientError> for CliError {
+    fn from(e: ClientError) -> Self {
+        CliError::Client(e)
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(e: serde_json::Error) -> Self {
+        CliError::Json(e)
+    }
+}
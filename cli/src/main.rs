@@ -0,0 +1,173 @@
+mod client;
+mod error;
+mod offline;
+mod output;
+
+use std::{path::PathBuf, process::ExitCode};
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use clap::{Parser, Subcommand};
+use uuid::Uuid;
+
+use crate::{
+    client::ApiClient,
+    error::CliError,
+    output::{Format, PlanFormat},
+};
+
+/// Plans and schedules of the endurance racing planner from a terminal
+#[derive(Parser)]
+#[command(name = "planner-cli", version)]
+struct Args {
+    /// The address of the api
+    #[arg(
+        long,
+        env = "PLANNER_API_URL",
+        default_value = "http://localhost:3000",
+        global = true
+    )]
+    api_url: String,
+    /// A personal access token created on the Access Tokens page
+    #[arg(long, env = "PLANNER_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists the plans the token can reach
+    Plans {
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Prints a plan's settings and roster
+    Plan {
+        plan_id: Uuid,
+        #[arg(long, value_enum, default_value_t = PlanFormat::Table)]
+        format: PlanFormat,
+    },
+    /// Prints a plan's schedule, the CSV can be edited and put back with the api
+    Schedule {
+        plan_id: Uuid,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Records when a stint actually ended and re-flows the rest of the schedule
+    SetActuals {
+        plan_id: Uuid,
+        stint_id: Uuid,
+        /// UTC, as `2026-01-24 19:45:00` or RFC 3339
+        #[arg(long, value_parser = parse_utc)]
+        actual_end: DateTime<Utc>,
+        #[arg(long)]
+        actual_laps: Option<i32>,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+    /// Calculates the schedule of a plan file without the api, the file is a plan or an exported
+    /// plan
+    Calculate {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(output) => {
+            print!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<String, CliError> {
+    // only the commands that talk to the api need a token
+    let token = args.token;
+    let api_url = args.api_url;
+    let client = || {
+        token
+            .clone()
+            .map(|token| ApiClient::new(&api_url, token))
+            .ok_or(CliError::MissingToken)
+    };
+
+    match args.command {
+        Command::Plans { format } => output::plans(&client()?.get_plans()?, format),
+        Command::Plan { plan_id, format } => output::plan(&client()?.get_plan(plan_id)?, format),
+        Command::Schedule { plan_id, format } => {
+            let client = client()?;
+            let plan = client.get_plan(plan_id)?;
+            output::schedule(&client.get_schedule(plan_id)?, &plan.driver_roster, format)
+        }
+        Command::SetActuals {
+            plan_id,
+            stint_id,
+            actual_end,
+            actual_laps,
+            format,
+        } => {
+            let client = client()?;
+            let plan = client.get_plan(plan_id)?;
+            let mut stint = client
+                .get_schedule(plan_id)?
+                .into_iter()
+                .find(|stint| stint.id == stint_id)
+                .ok_or_else(|| {
+                    CliError::Plan(format!("stint {} is not in the schedule", stint_id))
+                })?;
+            stint.actual_end = actual_end;
+            stint.duration_delta = stint.actual_end - stint.utc_end;
+            if let Some(actual_laps) = actual_laps {
+                stint.actual_laps = actual_laps;
+            }
+
+            // the api only updates existing stints, its recalculate adds and removes the stints
+            // the new actual end moves past the race end
+            client.update_stints(plan_id, &[stint])?;
+            let schedule = client.recalculate_schedule(plan_id)?;
+            output::schedule(&schedule, &plan.driver_roster, format)
+        }
+        Command::Calculate { file, format } => {
+            let plan = offline::read_plan(&file)?;
+            let schedule = offline::calculate_schedule(&plan)?;
+            output::schedule(&schedule, &plan.driver_roster, format)
+        }
+    }
+}
+
+/// Reads a UTC date and time in the schedule CSV's format or as RFC 3339
+fn parse_utc(value: &str) -> Result<DateTime<Utc>, String> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .map(|value| Utc.from_utc_datetime(&value))
+        .or_else(|_| DateTime::parse_from_rfc3339(value).map(|value| value.with_timezone(&Utc)))
+        .map_err(|_| format!("{} is not a UTC date and time", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use clap::CommandFactory;
+
+    use super::{parse_utc, Args};
+
+    #[test]
+    fn the_arguments_are_valid() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn actual_ends_are_utc() {
+        let expected = Utc.with_ymd_and_hms(2026, 1, 24, 19, 45, 0).unwrap();
+
+        assert_eq!(Ok(expected), parse_utc("2026-01-24 19:45:00"));
+        assert_eq!(Ok(expected), parse_utc("2026-01-24T14:45:00-05:00"));
+        assert!(parse_utc("19:45").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use std::{fs, path::Path};
+
+use endurance_racing_planner_common::{
+    plan_export::{
+        PlanExportDto, PlanExportVersionDto, OLDEST_PLAN_EXPORT_SCHEMA_VERSION,
+        PLAN_EXPORT_SCHEMA_VERSION,
+    },
+    schedule::{self, ScheduleStintDto},
+    RacePlannerDto,
+};
+use serde_json::Value;
+
+use crate::error::CliError;
+
+/// Reads a plan file, either a plan as the api returns it or a plan exported from the api
+pub fn read_plan(path: &Path) -> Result<RacePlannerDto, CliError> {
+    let text = fs::read_to_string(path).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+    let value = serde_json::from_str::<Value>(&text)?;
+    if value.get("schemaVersion").is_none() {
+        return Ok(serde_json::from_value(value)?);
+    }
+
+    let version = serde_json::from_value::<PlanExportVersionDto>(value.clone())?;
+    if !version.is_supported() {
+        return Err(CliError::Plan(format!(
+            "the plan was exported with schema version {}, only versions {} to {} can be read",
+            version.schema_version, OLDEST_PLAN_EXPORT_SCHEMA_VERSION, PLAN_EXPORT_SCHEMA_VERSION
+        )));
+    }
+
+    Ok(serde_json::from_value::<PlanExportDto>(value)?.plan)
+}
+
+/// Calculates the plan's schedule the same way the api's recalculate does, stints already in the
+/// plan keep their driver, stint type and actual end
+pub fn calculate_schedule(plan: &RacePlannerDto) -> Result<Vec<ScheduleStintDto>, CliError> {
+    schedule::plan_schedule(plan).ok_or_else(|| {
+        CliError::Plan(
+            "the plan needs a race duration, fuel stint times and a fuel stint config to calculate a schedule"
+                .to_string(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use endurance_racing_planner_common::{
+        EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData, RacePlannerDto,
+        StintDataDto,
+    };
+
+    use super::calculate_schedule;
+
+    fn stint_data() -> StintDataDto {
+        StintDataDto {
+            lap_time: Duration::seconds(90),
+            fuel_per_lap: 3.25,
+            lap_count: 22,
+            lap_time_with_pit: Duration::seconds(120),
+            track_time: Duration::minutes(55),
+            track_time_with_pit: Duration::minutes(57),
+            fuel_per_stint: 100.0,
+        }
+    }
+
+    #[test]
+    fn a_plan_without_settings_cant_be_calculated() {
+        assert!(calculate_schedule(&RacePlannerDto::new()).is_err());
+    }
+
+    #[test]
+    fn the_schedule_covers_the_race() {
+        let mut event_config = EventConfigDto::new();
+        event_config.race_duration = Duration::hours(6);
+        event_config.update_race_times();
+        let plan = RacePlannerDto {
+            overall_event_config: Some(event_config.clone()),
+            overall_fuel_stint_config: Some(OverallFuelStintConfigData::new()),
+            fuel_stint_average_times: Some(FuelStintAverageTimes {
+                standard_fuel_stint: stint_data(),
+                fuel_saving_stint: stint_data(),
+            }),
+            ..RacePlannerDto::new()
+        };
+
+        let schedule = calculate_schedule(&plan).unwrap();
+
+        assert_eq!(event_config.race_start_utc, schedule[0].utc_start);
+        assert!(schedule.last().unwrap().utc_end >= event_config.race_end_utc);
+        assert!(schedule[schedule.len() - 2].utc_end < event_config.race_end_utc);
+    }
+}
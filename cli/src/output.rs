@@ -0,0 +1,266 @@
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+use endurance_racing_planner_common::{
+    schedule::ScheduleStintDto, schedule_csv::schedule_to_csv, Driver, PlanListDto, RacePlannerDto,
+};
+use serde::Serialize;
+
+use crate::error::CliError;
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+/// A plan has no single table of rows, so it can't be written as CSV
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PlanFormat {
+    Table,
+    Json,
+}
+
+/// Rows of text written as aligned columns or as CSV
+struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn to_text(&self) -> String {
+        let mut widths = self
+            .headers
+            .iter()
+            .map(|header| header.chars().count())
+            .collect::<Vec<_>>();
+        for row in self.rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let headers = self.headers.iter().map(|header| header.to_string());
+        std::iter::once(headers.collect::<Vec<_>>())
+            .chain(self.rows.iter().cloned())
+            .map(|row| {
+                row.iter()
+                    .zip(widths.iter())
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer
+            .write_record(&self.headers)
+            .expect("writing to memory to succeed");
+        for row in self.rows.iter() {
+            writer
+                .write_record(row)
+                .expect("writing to memory to succeed");
+        }
+
+        String::from_utf8(writer.into_inner().expect("writing to memory to succeed"))
+            .expect("the csv to be utf-8")
+    }
+}
+
+pub fn plans(plans: &[PlanListDto], format: Format) -> Result<String, CliError> {
+    let table = Table {
+        headers: vec!["Id", "Title", "Owner", "Last Modified (UTC)"],
+        rows: plans
+            .iter()
+            .map(|plan| {
+                vec![
+                    plan.id.to_string(),
+                    plan.title.clone(),
+                    plan.owner.clone(),
+                    format_date_time(plan.last_modified),
+                ]
+            })
+            .collect(),
+    };
+
+    match format {
+        Format::Table => Ok(table.to_text()),
+        Format::Json => json(&plans),
+        Format::Csv => Ok(table.to_csv()),
+    }
+}
+
+/// The plan's event settings and roster, the schedule has its own command
+pub fn plan(plan: &RacePlannerDto, format: PlanFormat) -> Result<String, CliError> {
+    if format == PlanFormat::Json {
+        return json(plan);
+    }
+
+    let mut settings = vec![
+        vec!["Title".to_string(), plan.title.clone()],
+        vec!["Id".to_string(), plan.id.to_string()],
+    ];
+    if let Some(event_config) = plan.overall_event_config.as_ref() {
+        settings.push(vec![
+            "Race Start (UTC)".to_string(),
+            format_date_time(event_config.race_start_utc),
+        ]);
+        settings.push(vec![
+            "Race End (UTC)".to_string(),
+            format_date_time(event_config.race_end_utc),
+        ]);
+        settings.push(vec![
+            "Race Duration".to_string(),
+            format_duration(event_config.race_duration),
+        ]);
+    }
+    if let Some(fuel_stint_config) = plan.overall_fuel_stint_config.as_ref() {
+        settings.push(vec![
+            "Fuel Tank".to_string(),
+            fuel_stint_config.fuel_tank_size.to_string(),
+        ]);
+    }
+
+    let roster = Table {
+        headers: vec!["Driver", "iRating", "UTC Offset", "Stints"],
+        rows: plan
+            .driver_roster
+            .iter()
+            .map(|driver| {
+                vec![
+                    driver.name.clone(),
+                    driver.irating.to_string(),
+                    driver.utc_offset.to_string(),
+                    driver.total_stints.to_string(),
+                ]
+            })
+            .collect(),
+    };
+    let settings = Table {
+        headers: vec!["Setting", "Value"],
+        rows: settings,
+    };
+
+    Ok(format!("{}\n{}", settings.to_text(), roster.to_text()))
+}
+
+/// The CSV is the same as the api's export, so it can be edited and put back with the api
+pub fn schedule(
+    schedule: &[ScheduleStintDto],
+    drivers: &[Driver],
+    format: Format,
+) -> Result<String, CliError> {
+    match format {
+        Format::Table => Ok(schedule_table(schedule, drivers).to_text()),
+        Format::Json => json(&schedule),
+        Format::Csv => Ok(schedule_to_csv(schedule, drivers)),
+    }
+}
+
+fn schedule_table(schedule: &[ScheduleStintDto], drivers: &[Driver]) -> Table {
+    Table {
+        headers: vec![
+            "Stint",
+            "Type",
+            "Driver",
+            "Start (UTC)",
+            "End (UTC)",
+            "Actual End (UTC)",
+            "Laps",
+            "Actual Laps",
+            "Tire Age",
+            "Id",
+        ],
+        rows: schedule
+            .iter()
+            .map(|stint| {
+                let driver = drivers
+                    .iter()
+                    .find(|driver| driver.id == stint.driver_id)
+                    .map_or("", |driver| driver.name.as_str());
+                vec![
+                    stint.fuel_stint_number.to_string(),
+                    stint.stint_type.to_string(),
+                    driver.to_string(),
+                    format_date_time(stint.utc_start),
+                    format_date_time(stint.utc_end),
+                    format_date_time(stint.actual_end),
+                    stint.calculated_laps.to_string(),
+                    stint.actual_laps.to_string(),
+                    stint.tire_age.to_string(),
+                    stint.id.to_string(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn json(value: &impl Serialize) -> Result<String, CliError> {
+    Ok(serde_json::to_string_pretty(value)? + "\n")
+}
+
+fn format_date_time(date_time: DateTime<Utc>) -> String {
+    date_time.format(DATE_TIME_FORMAT).to_string()
+}
+
+/// Formats as `HH:MM:SS`
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use endurance_racing_planner_common::PlanListDto;
+    use uuid::Uuid;
+
+    use super::{plans, Format, Table};
+
+    #[test]
+    fn tables_are_aligned_to_the_widest_cell() {
+        let table = Table {
+            headers: vec!["Driver", "Stints"],
+            rows: vec![
+                vec!["Max".to_string(), "4".to_string()],
+                vec!["Nürburg Ace".to_string(), "12".to_string()],
+            ],
+        };
+
+        assert_eq!(
+            "Driver       Stints\nMax          4\nNürburg Ace  12\n",
+            table.to_text()
+        );
+    }
+
+    #[test]
+    fn plans_are_written_as_csv_with_a_header() {
+        let id = Uuid::new_v4();
+        let plan = PlanListDto {
+            id,
+            title: "Daytona 24, 2027".to_string(),
+            owner: "Max".to_string(),
+            last_modified: Utc.with_ymd_and_hms(2026, 10, 1, 12, 30, 0).unwrap(),
+        };
+
+        assert_eq!(
+            format!(
+                "Id,Title,Owner,Last Modified (UTC)\n{},\"Daytona 24, 2027\",Max,2026-10-01 12:30:00\n",
+                id
+            ),
+            plans(&[plan], Format::Csv).unwrap()
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Every personal access token starts with this, the api uses it to tell them from ID tokens
+pub const ACCESS_TOKEN_PREFIX: &str = "erp_";
+
+/// A personal access token lets a script or bot call the api as the user who created it
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessTokenDto {
+    pub id: Uuid,
+    pub name: String,
+    /// The only plan the token can reach, every plan of the user when `None`
+    pub plan_id: Option<Uuid>,
+    pub read_only: bool,
+    pub created_date: DateTime<Utc>,
+    pub last_used_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NewAccessTokenDto {
+    pub name: String,
+    pub plan_id: Option<Uuid>,
+    pub read_only: bool,
+}
+
+/// The token is only returned when it's created, the api only keeps its hash
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedAccessTokenDto {
+    #[serde(flatten)]
+    pub access_token: AccessTokenDto,
+    pub token: String,
+}
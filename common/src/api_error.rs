@@ -0,0 +1,87 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// Machine readable reason an api request failed
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorCode {
+    BadRequest,
+    ValidationFailed,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    InternalError,
+}
+
+/// A field of the request that failed validation, `field` is the camelCase path of the field
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldErrorDto {
+    pub field: String,
+    pub message: String,
+}
+
+/// The body of every api error response
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorDto {
+    pub code: ApiErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldErrorDto>,
+}
+
+impl FieldErrorDto {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ApiErrorDto {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        for detail in self.details.iter() {
+            write!(f, "\n{}: {}", detail.field, detail.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiErrorCode, ApiErrorDto, FieldErrorDto};
+
+    #[test]
+    fn details_are_left_out_when_there_are_none() {
+        let error = ApiErrorDto {
+            code: ApiErrorCode::NotFound,
+            message: "the plan does not exist".to_string(),
+            details: vec![],
+        };
+
+        assert_eq!(
+            r#"{"code":"not_found","message":"the plan does not exist"}"#,
+            serde_json::to_string(&error).unwrap()
+        );
+    }
+
+    #[test]
+    fn display_lists_the_field_errors() {
+        let error = ApiErrorDto {
+            code: ApiErrorCode::ValidationFailed,
+            message: "the schedule csv is invalid".to_string(),
+            details: vec![FieldErrorDto::new("row 3", "unknown driver Bob")],
+        };
+
+        assert_eq!(
+            "the schedule csv is invalid\nrow 3: unknown driver Bob",
+            error.to_string()
+        );
+    }
+}
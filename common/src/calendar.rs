@@ -0,0 +1,230 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    schedule::{effective_stint_times, ScheduleStintDto},
+    Driver,
+};
+
+const PRODUCT_ID: &str = "-//Endurance Racing Planner//Driver Schedule//EN";
+const UID_DOMAIN: &str = "endurance-racing-planner";
+const UTC_DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const LOCAL_DATE_TIME_FORMAT: &str = "%a %b %e %I:%M %p";
+/// Lines longer than this many octets have to be folded (RFC 5545 section 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders an RFC 5545 calendar with one event for every stint in `schedule` assigned to `driver`.
+///
+/// Event uids come from the stint ids so a calendar app subscribed to the feed updates the
+/// existing events when the schedule is recalculated instead of duplicating them.
+pub fn driver_calendar(
+    plan_title: &str,
+    driver: &Driver,
+    drivers: &[Driver],
+    schedule: &[ScheduleStintDto],
+    generated_at: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!(
+            "X-WR-CALNAME:{}",
+            escape_text(format!("{} - {}", plan_title, driver.name).as_str())
+        ),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT1H".to_string(),
+        "X-PUBLISHED-TTL:PT1H".to_string(),
+    ];
+
+    let stint_times = effective_stint_times(schedule);
+    for (index, stint) in schedule.iter().enumerate() {
+        if stint.driver_id != driver.id {
+            continue;
+        }
+
+        let taking_over_from = match index.checked_sub(1).map(|previous| &schedule[previous]) {
+            None => "Race start".to_string(),
+            Some(previous) if previous.driver_id == driver.id => "Yourself".to_string(),
+            Some(previous) => drivers
+                .iter()
+                .find(|d| d.id == previous.driver_id)
+                .map_or_else(|| "Unassigned".to_string(), |d| d.name.clone()),
+        };
+        let (utc_start, utc_end) = stint_times[index];
+        let local_start = utc_start + Duration::hours(driver.utc_offset as i64);
+        let description = format!(
+            "Fuel stint {}, your stint {}\nExpected laps: {}\nTaking over from: {}\nLocal start: {} (UTC{:+})",
+            stint.fuel_stint_number,
+            stint.stint_number,
+            stint.calculated_laps,
+            taking_over_from,
+            local_start.format(LOCAL_DATE_TIME_FORMAT),
+            driver.utc_offset
+        );
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@{}", stint.id, UID_DOMAIN));
+        lines.push(format!(
+            "DTSTAMP:{}",
+            generated_at.format(UTC_DATE_TIME_FORMAT)
+        ));
+        lines.push(format!(
+            "DTSTART:{}",
+            utc_start.format(UTC_DATE_TIME_FORMAT)
+        ));
+        lines.push(format!("DTEND:{}", utc_end.format(UTC_DATE_TIME_FORMAT)));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(format!("{} - Stint {}", plan_title, stint.fuel_stint_number).as_str())
+        ));
+        lines.push(format!("DESCRIPTION:{}", escape_text(description.as_str())));
+        lines.push("TRANSP:OPAQUE".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line.as_str()))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits `line` into 75 octet chunks, continuation lines start with a single space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_octets = 0;
+
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDateTime, TimeZone, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        schedule::{ScheduleStintDto, StintType},
+        Driver,
+    };
+
+    use super::{driver_calendar, escape_text, fold_line};
+
+    fn driver(id: i32, name: &str, utc_offset: i16) -> Driver {
+        Driver {
+            id,
+            name: name.to_string(),
+            total_stints: 0,
+            fair_share: false,
+            color: "#000000".to_string(),
+            utc_offset,
+            irating: 0,
+            stint_preference: 0,
+        }
+    }
+
+    fn stint(fuel_stint_number: i32, driver_id: i32) -> ScheduleStintDto {
+        let utc_start = Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap()
+            + Duration::hours((fuel_stint_number - 1) as i64);
+        ScheduleStintDto {
+            id: Uuid::from_u128(fuel_stint_number as u128),
+            stint_type: StintType::StandardNoTires,
+            fuel_stint_number,
+            utc_start,
+            utc_end: utc_start + Duration::hours(1),
+            tod_start: NaiveDateTime::default(),
+            tod_end: NaiveDateTime::default(),
+            actual_end: utc_start + Duration::hours(1),
+            duration_delta: Duration::zero(),
+            damage_modifier: Duration::zero(),
+            calculated_laps: 30,
+            actual_laps: 0,
+            driver_id,
+            availability: "".to_string(),
+            stint_number: 1,
+            factor: 1.0,
+            tire_age: 0,
+            tire_set_stint: 1,
+            traffic_loss: Duration::zero(),
+        }
+    }
+
+    #[test]
+    fn driver_calendar_only_includes_the_drivers_stints() {
+        let drivers = vec![driver(1, "Alice", -5), driver(2, "Bob", 1)];
+        let schedule = vec![stint(1, 1), stint(2, 2), stint(3, 1), stint(4, 1)];
+        let generated_at = Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap();
+
+        let calendar =
+            driver_calendar("Daytona 24", &drivers[0], &drivers, &schedule, generated_at);
+        let unfolded = calendar.replace("\r\n ", "");
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(3, unfolded.matches("BEGIN:VEVENT").count());
+        assert!(
+            unfolded.contains("UID:00000000-0000-0000-0000-000000000003@endurance-racing-planner")
+        );
+        assert!(!unfolded.contains("SUMMARY:Daytona 24 - Stint 2\r\n"));
+        assert!(unfolded.contains("DTSTART:20231222T140000Z\r\nDTEND:20231222T150000Z"));
+        assert!(unfolded.contains("Taking over from: Race start"));
+        assert!(unfolded.contains("Taking over from: Bob"));
+        assert!(unfolded.contains("Taking over from: Yourself"));
+        assert!(unfolded.contains("Local start: Fri Dec 22 09:00 AM (UTC-5)"));
+    }
+
+    #[test]
+    fn driver_calendar_uses_actual_ends_and_shifts_later_stints() {
+        let drivers = vec![driver(1, "Alice", 0), driver(2, "Bob", 0)];
+        let mut schedule = vec![stint(1, 1), stint(2, 2), stint(3, 1)];
+        schedule[0].actual_end = schedule[0].utc_end + Duration::minutes(10);
+        let generated_at = Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap();
+
+        let alice = driver_calendar("Daytona 24", &drivers[0], &drivers, &schedule, generated_at)
+            .replace("\r\n ", "");
+        let bob = driver_calendar("Daytona 24", &drivers[1], &drivers, &schedule, generated_at)
+            .replace("\r\n ", "");
+
+        assert!(alice.contains("DTSTART:20231222T120000Z\r\nDTEND:20231222T131000Z"));
+        assert!(bob.contains("DTSTART:20231222T131000Z\r\nDTEND:20231222T141000Z"));
+        assert!(alice.contains("DTSTART:20231222T141000Z\r\nDTEND:20231222T151000Z"));
+    }
+
+    #[test]
+    fn escape_text_escapes_special_characters() {
+        assert_eq!(
+            "a\\, b\\; c\\\\d\\ne",
+            escape_text("a, b; c\\d\ne").as_str()
+        );
+    }
+
+    #[test]
+    fn fold_line_keeps_lines_within_75_octets() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(60));
+
+        let folded = fold_line(line.as_str());
+
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(line, folded.replace("\r\n ", ""));
+    }
+}
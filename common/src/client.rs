@@ -0,0 +1,833 @@
+//! A typed client of the api for the web and the cli. The client builds the requests and reads
+//! the responses, each front end provides the `Transport` that sends them and the `Credentials`
+//! that sign them.
+
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    access_token::{AccessTokenDto, CreatedAccessTokenDto, NewAccessTokenDto},
+    api_error::{ApiErrorCode, ApiErrorDto},
+    revision::PlanRevisionDto,
+    scenario::{NewScenarioDto, ScenarioComparisonDto, ScenarioDto},
+    schedule::ScheduleStintDto,
+    Driver, IdentityProviderDto, PatchRacePlannerDto, PlanListDto, RacePlannerDto, User,
+};
+
+/// A future that doesn't have to be `Send`, the browser's futures aren't
+pub type LocalFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl HttpMethod {
+    /// Whether sending the request twice leaves the api as sending it once does
+    fn is_idempotent(self) -> bool {
+        self != HttpMethod::Post
+    }
+}
+
+impl Display for HttpMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpMethod::Get => write!(f, "GET"),
+            HttpMethod::Post => write!(f, "POST"),
+            HttpMethod::Put => write!(f, "PUT"),
+            HttpMethod::Patch => write!(f, "PATCH"),
+            HttpMethod::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+/// A request to the api, the route is relative to the api's address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub route: String,
+    /// The JSON body
+    pub body: Option<String>,
+    /// Sent as the bearer token, routes that don't need a signed in user have none
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Sends the client's requests, a request that got no response fails with
+/// `ClientError::Offline`
+pub trait Transport {
+    fn send(&self, request: HttpRequest) -> LocalFuture<Result<HttpResponse, ClientError>>;
+
+    /// Waits before a failed request is retried
+    fn sleep(&self, duration: Duration) -> LocalFuture<()>;
+}
+
+/// The tokens the client signs requests with
+pub trait Credentials {
+    /// `None` when nobody is signed in
+    fn token(&self) -> Option<String>;
+
+    /// A new token after the api rejected `rejected`, `None` when the user has to sign in again
+    fn refresh(&self, rejected: &str) -> LocalFuture<Option<String>>;
+}
+
+/// A token that can't be refreshed, e.g. a personal access token
+pub struct BearerToken(pub String);
+
+impl Credentials for BearerToken {
+    fn token(&self) -> Option<String> {
+        Some(self.0.clone())
+    }
+
+    fn refresh(&self, _rejected: &str) -> LocalFuture<Option<String>> {
+        Box::pin(async { None })
+    }
+}
+
+/// How often a request that failed for a reason that may pass is sent, the wait doubles after
+/// every attempt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Every request is only sent once
+    pub fn none() -> Self {
+        Self {
+            attempts: 1,
+            backoff: Duration::from_millis(0),
+        }
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        self.backoff * 2u32.pow(retry)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Every way a request can fail. The api's error body is kept for every status, a response
+/// without one gets a body made from its status.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientError {
+    /// There's no token, or the api rejected it and it couldn't be refreshed
+    SignedOut,
+    /// 400 and 422, `details` has the fields that failed validation
+    Invalid(ApiErrorDto),
+    /// 401 with a refreshed token
+    Unauthorized(ApiErrorDto),
+    Forbidden(ApiErrorDto),
+    NotFound(ApiErrorDto),
+    Conflict(ApiErrorDto),
+    /// 429
+    RateLimited(ApiErrorDto),
+    /// 5xx
+    Server(u16, ApiErrorDto),
+    /// Any other status that isn't a success
+    Status(u16, ApiErrorDto),
+    /// The request got no response, e.g. the network is down
+    Offline(String),
+    /// The request couldn't be made, e.g. the api's address is invalid
+    Request(String),
+    /// The response isn't what the route returns
+    Decode(String),
+}
+
+impl ClientError {
+    pub fn from_response(response: &HttpResponse) -> Self {
+        let status = response.status;
+        let error =
+            serde_json::from_str::<ApiErrorDto>(&response.body).unwrap_or_else(|_| ApiErrorDto {
+                code: code_of_status(status),
+                message: if response.body.trim().is_empty() {
+                    format!("the api returned {}", status)
+                } else {
+                    response.body.clone()
+                },
+                details: vec![],
+            });
+
+        match status {
+            400 | 422 => ClientError::Invalid(error),
+            401 => ClientError::Unauthorized(error),
+            403 => ClientError::Forbidden(error),
+            404 => ClientError::NotFound(error),
+            409 => ClientError::Conflict(error),
+            429 => ClientError::RateLimited(error),
+            500..=599 => ClientError::Server(status, error),
+            _ => ClientError::Status(status, error),
+        }
+    }
+
+    /// The api's error body, requests that got no response don't have one
+    pub fn api_error(&self) -> Option<&ApiErrorDto> {
+        match self {
+            ClientError::Invalid(error)
+            | ClientError::Unauthorized(error)
+            | ClientError::Forbidden(error)
+            | ClientError::NotFound(error)
+            | ClientError::Conflict(error)
+            | ClientError::RateLimited(error)
+            | ClientError::Server(_, error)
+            | ClientError::Status(_, error) => Some(error),
+            ClientError::SignedOut
+            | ClientError::Offline(_)
+            | ClientError::Request(_)
+            | ClientError::Decode(_) => None,
+        }
+    }
+
+    /// The user has to sign in before the request can succeed
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, ClientError::SignedOut | ClientError::Unauthorized(_))
+    }
+
+    pub fn is_offline(&self) -> bool {
+        matches!(self, ClientError::Offline(_))
+    }
+
+    /// Whether the same request may succeed later
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ClientError::Offline(_)
+                | ClientError::RateLimited(_)
+                | ClientError::Server(502, _)
+                | ClientError::Server(503, _)
+                | ClientError::Server(504, _)
+        )
+    }
+
+    /// A retried request might be handled twice, so a request that isn't idempotent is only
+    /// retried when the api turned it away before handling it
+    fn can_retry(&self, method: HttpMethod) -> bool {
+        if method.is_idempotent() {
+            self.is_transient()
+        } else {
+            matches!(
+                self,
+                ClientError::RateLimited(_) | ClientError::Server(503, _)
+            )
+        }
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::SignedOut => write!(f, "you need to sign in again"),
+            ClientError::Offline(_) => write!(f, "the planner couldn't reach the server"),
+            ClientError::Request(e) => write!(f, "the request couldn't be made: {}", e),
+            ClientError::Decode(e) => write!(f, "the api's response couldn't be read: {}", e),
+            e => match e.api_error() {
+                Some(error) => write!(f, "{}", error),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+impl Error for ClientError {}
+
+fn code_of_status(status: u16) -> ApiErrorCode {
+    match status {
+        400 => ApiErrorCode::BadRequest,
+        422 => ApiErrorCode::ValidationFailed,
+        401 => ApiErrorCode::Unauthorized,
+        403 => ApiErrorCode::Forbidden,
+        404 => ApiErrorCode::NotFound,
+        409 => ApiErrorCode::Conflict,
+        _ => ApiErrorCode::InternalError,
+    }
+}
+
+/// The api's routes, every method returns the route's response body
+#[derive(Clone)]
+pub struct ApiClient {
+    transport: Rc<dyn Transport>,
+    credentials: Rc<dyn Credentials>,
+    retry: RetryPolicy,
+}
+
+impl ApiClient {
+    pub fn new(transport: Rc<dyn Transport>, credentials: Rc<dyn Credentials>) -> Self {
+        Self {
+            transport,
+            credentials,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    pub async fn get_providers(&self) -> Result<Vec<IdentityProviderDto>, ClientError> {
+        self.get_anonymous("auth/providers").await
+    }
+
+    /// Registers the signed in user from their ID token, registering again returns the existing
+    /// user
+    pub async fn register_user(&self) -> Result<User, ClientError> {
+        self.post_empty("users").await
+    }
+
+    pub async fn get_me(&self) -> Result<User, ClientError> {
+        self.get("users/me").await
+    }
+
+    pub async fn get_access_tokens(&self) -> Result<Vec<AccessTokenDto>, ClientError> {
+        self.get("users/me/tokens").await
+    }
+
+    /// The token is only returned once, it can't be fetched again
+    pub async fn create_access_token(
+        &self,
+        access_token: &NewAccessTokenDto,
+    ) -> Result<CreatedAccessTokenDto, ClientError> {
+        self.post("users/me/tokens", access_token).await
+    }
+
+    /// Returns the id of the revoked token
+    pub async fn revoke_access_token(&self, id: Uuid) -> Result<String, ClientError> {
+        self.delete(&format!("users/me/tokens/{}", id)).await
+    }
+
+    pub async fn get_plans(&self) -> Result<Vec<PlanListDto>, ClientError> {
+        self.get("plans").await
+    }
+
+    /// The plan without its schedule or roster, they have routes of their own
+    pub async fn get_plan(&self, plan_id: Uuid) -> Result<RacePlannerDto, ClientError> {
+        self.get(&format!("plans/{}", plan_id)).await
+    }
+
+    pub async fn create_plan(&self, plan: &RacePlannerDto) -> Result<RacePlannerDto, ClientError> {
+        self.post("plans", plan).await
+    }
+
+    pub async fn patch_plan(
+        &self,
+        plan_id: Uuid,
+        patch: &PatchRacePlannerDto,
+    ) -> Result<(), ClientError> {
+        self.patch(&format!("plans/{}", plan_id), patch).await
+    }
+
+    pub async fn share_plan(&self, plan_id: Uuid, emails: &[String]) -> Result<(), ClientError> {
+        self.post_ignoring_response(&format!("plans/{}/share", plan_id), emails)
+            .await
+    }
+
+    pub async fn get_shared_users(&self, plan_id: Uuid) -> Result<Vec<User>, ClientError> {
+        self.get(&format!("plans/{}/share", plan_id)).await
+    }
+
+    /// The stints of the plan's active scenario
+    pub async fn get_schedule(&self, plan_id: Uuid) -> Result<Vec<ScheduleStintDto>, ClientError> {
+        self.get(&format!("plans/{}/schedule", plan_id)).await
+    }
+
+    /// The stints of the plan's active scenario as CSV, with the names of their drivers
+    pub async fn get_schedule_csv(&self, plan_id: Uuid) -> Result<String, ClientError> {
+        self.send(
+            HttpMethod::Get,
+            &format!("plans/{}/schedule.csv", plan_id),
+            None,
+            true,
+        )
+        .await
+        .map(|response| response.body)
+    }
+
+    pub async fn create_schedule(
+        &self,
+        plan_id: Uuid,
+        schedule: &[ScheduleStintDto],
+    ) -> Result<(), ClientError> {
+        self.post_ignoring_response(&format!("plans/{}/schedule", plan_id), schedule)
+            .await
+    }
+
+    /// Saves the stints, the api only updates the stints that are already in the schedule
+    pub async fn update_schedule(
+        &self,
+        plan_id: Uuid,
+        schedule: &[ScheduleStintDto],
+    ) -> Result<(), ClientError> {
+        self.put(&format!("plans/{}/schedule", plan_id), schedule)
+            .await
+    }
+
+    /// Re-flows the schedule from the race start, stints with an actual end keep it
+    pub async fn recalculate_schedule(
+        &self,
+        plan_id: Uuid,
+    ) -> Result<Vec<ScheduleStintDto>, ClientError> {
+        self.post_empty(&format!("plans/{}/schedule/recalculate", plan_id))
+            .await
+    }
+
+    pub async fn insert_stint(
+        &self,
+        plan_id: Uuid,
+        stint_id: Uuid,
+    ) -> Result<Vec<ScheduleStintDto>, ClientError> {
+        self.post_empty(&format!(
+            "plans/{}/schedule/stints/{}/insert-after",
+            plan_id, stint_id
+        ))
+        .await
+    }
+
+    pub async fn split_stint(
+        &self,
+        plan_id: Uuid,
+        stint_id: Uuid,
+    ) -> Result<Vec<ScheduleStintDto>, ClientError> {
+        self.post_empty(&format!(
+            "plans/{}/schedule/stints/{}/split",
+            plan_id, stint_id
+        ))
+        .await
+    }
+
+    pub async fn delete_stint(
+        &self,
+        plan_id: Uuid,
+        stint_id: Uuid,
+    ) -> Result<Vec<ScheduleStintDto>, ClientError> {
+        self.delete(&format!("plans/{}/schedule/stints/{}", plan_id, stint_id))
+            .await
+    }
+
+    pub async fn get_plan_drivers(&self, plan_id: Uuid) -> Result<Vec<Driver>, ClientError> {
+        self.get(&format!("plans/{}/drivers", plan_id)).await
+    }
+
+    pub async fn create_plan_driver(
+        &self,
+        plan_id: Uuid,
+        driver: &Driver,
+    ) -> Result<Driver, ClientError> {
+        self.post(&format!("plans/{}/drivers", plan_id), driver)
+            .await
+    }
+
+    pub async fn update_driver(&self, driver: &Driver) -> Result<(), ClientError> {
+        self.put(&format!("drivers/{}", driver.id), driver).await
+    }
+
+    pub async fn get_plan_revisions(
+        &self,
+        plan_id: Uuid,
+    ) -> Result<Vec<PlanRevisionDto>, ClientError> {
+        self.get(&format!("plans/{}/revisions", plan_id)).await
+    }
+
+    /// Returns the whole plan after the revision was restored
+    pub async fn restore_revision(
+        &self,
+        plan_id: Uuid,
+        revision_id: i64,
+    ) -> Result<RacePlannerDto, ClientError> {
+        self.post_empty(&format!(
+            "plans/{}/revisions/{}/restore",
+            plan_id, revision_id
+        ))
+        .await
+    }
+
+    /// Returns the whole plan after the revision was undone
+    pub async fn undo_revision(
+        &self,
+        plan_id: Uuid,
+        revision_id: i64,
+    ) -> Result<RacePlannerDto, ClientError> {
+        self.post_empty(&format!("plans/{}/revisions/{}/undo", plan_id, revision_id))
+            .await
+    }
+
+    pub async fn get_scenario_comparison(
+        &self,
+        plan_id: Uuid,
+    ) -> Result<Vec<ScenarioComparisonDto>, ClientError> {
+        self.get(&format!("plans/{}/scenarios/comparison", plan_id))
+            .await
+    }
+
+    pub async fn create_scenario(
+        &self,
+        plan_id: Uuid,
+        scenario: &NewScenarioDto,
+    ) -> Result<ScenarioDto, ClientError> {
+        self.post(&format!("plans/{}/scenarios", plan_id), scenario)
+            .await
+    }
+
+    /// Returns the schedule of the newly active scenario
+    pub async fn activate_scenario(
+        &self,
+        plan_id: Uuid,
+        scenario_id: Uuid,
+    ) -> Result<Vec<ScheduleStintDto>, ClientError> {
+        self.post_empty(&format!(
+            "plans/{}/scenarios/{}/activate",
+            plan_id, scenario_id
+        ))
+        .await
+    }
+
+    /// Returns the id of the deleted scenario
+    pub async fn delete_scenario(
+        &self,
+        plan_id: Uuid,
+        scenario_id: Uuid,
+    ) -> Result<String, ClientError> {
+        self.delete(&format!("plans/{}/scenarios/{}", plan_id, scenario_id))
+            .await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, route: &str) -> Result<T, ClientError> {
+        read_json(self.send(HttpMethod::Get, route, None, true).await?)
+    }
+
+    async fn get_anonymous<T: DeserializeOwned>(&self, route: &str) -> Result<T, ClientError> {
+        read_json(self.send(HttpMethod::Get, route, None, false).await?)
+    }
+
+    async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        route: &str,
+        body: &B,
+    ) -> Result<T, ClientError> {
+        read_json(
+            self.send(HttpMethod::Post, route, Some(to_json(body)?), true)
+                .await?,
+        )
+    }
+
+    async fn post_ignoring_response<B: Serialize + ?Sized>(
+        &self,
+        route: &str,
+        body: &B,
+    ) -> Result<(), ClientError> {
+        self.send(HttpMethod::Post, route, Some(to_json(body)?), true)
+            .await
+            .map(|_| ())
+    }
+
+    /// Posts to a route that doesn't take a body
+    async fn post_empty<T: DeserializeOwned>(&self, route: &str) -> Result<T, ClientError> {
+        read_json(self.send(HttpMethod::Post, route, None, true).await?)
+    }
+
+    async fn put<B: Serialize + ?Sized>(&self, route: &str, body: &B) -> Result<(), ClientError> {
+        self.send(HttpMethod::Put, route, Some(to_json(body)?), true)
+            .await
+            .map(|_| ())
+    }
+
+    async fn patch<B: Serialize + ?Sized>(&self, route: &str, body: &B) -> Result<(), ClientError> {
+        self.send(HttpMethod::Patch, route, Some(to_json(body)?), true)
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete<T: DeserializeOwned>(&self, route: &str) -> Result<T, ClientError> {
+        read_json(self.send(HttpMethod::Delete, route, None, true).await?)
+    }
+
+    /// Sends the request until it succeeds or fails for a reason that won't pass. A rejected
+    /// token is refreshed once.
+    async fn send(
+        &self,
+        method: HttpMethod,
+        route: &str,
+        body: Option<String>,
+        signed_in: bool,
+    ) -> Result<HttpResponse, ClientError> {
+        let mut token = if signed_in {
+            Some(self.credentials.token().ok_or(ClientError::SignedOut)?)
+        } else {
+            None
+        };
+        let mut refreshed = false;
+        let mut retries = 0;
+
+        loop {
+            let request = HttpRequest {
+                method,
+                route: route.to_string(),
+                body: body.clone(),
+                token: token.clone(),
+            };
+            let error = match self.transport.send(request).await {
+                Ok(response) if response.is_success() => return Ok(response),
+                Ok(response) => ClientError::from_response(&response),
+                Err(e) => e,
+            };
+
+            match (error, token.as_deref()) {
+                (ClientError::Unauthorized(_), Some(rejected)) if !refreshed => {
+                    refreshed = true;
+                    token = Some(
+                        self.credentials
+                            .refresh(rejected)
+                            .await
+                            .ok_or(ClientError::SignedOut)?,
+                    );
+                }
+                (error, _) if retries + 1 < self.retry.attempts && error.can_retry(method) => {
+                    self.transport.sleep(self.retry.delay(retries)).await;
+                    retries += 1;
+                }
+                (error, _) => return Err(error),
+            }
+        }
+    }
+}
+
+fn to_json<B: Serialize + ?Sized>(body: &B) -> Result<String, ClientError> {
+    serde_json::to_string(body).map_err(|e| ClientError::Request(e.to_string()))
+}
+
+fn read_json<T: DeserializeOwned>(response: HttpResponse) -> Result<T, ClientError> {
+    serde_json::from_str(&response.body).map_err(|e| ClientError::Decode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration};
+
+    use futures_executor::block_on;
+
+    use super::{
+        ApiClient, BearerToken, ClientError, Credentials, HttpMethod, HttpRequest, HttpResponse,
+        LocalFuture, RetryPolicy, Transport,
+    };
+    use crate::api_error::{ApiErrorCode, ApiErrorDto};
+
+    /// Answers each request with the next of its responses and records what it was sent
+    #[derive(Default)]
+    struct FakeTransport {
+        responses: RefCell<VecDeque<Result<HttpResponse, ClientError>>>,
+        requests: RefCell<Vec<HttpRequest>>,
+        sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl FakeTransport {
+        fn new(responses: Vec<Result<HttpResponse, ClientError>>) -> Rc<Self> {
+            Rc::new(Self {
+                responses: RefCell::new(responses.into()),
+                ..Self::default()
+            })
+        }
+    }
+
+    impl Transport for FakeTransport {
+        fn send(&self, request: HttpRequest) -> LocalFuture<Result<HttpResponse, ClientError>> {
+            self.requests.borrow_mut().push(request);
+            let response = self
+                .responses
+                .borrow_mut()
+                .pop_front()
+                .expect("a response for every request");
+            Box::pin(async move { response })
+        }
+
+        fn sleep(&self, duration: Duration) -> LocalFuture<()> {
+            self.sleeps.borrow_mut().push(duration);
+            Box::pin(async {})
+        }
+    }
+
+    /// Gives out `refreshed` once the first token is rejected
+    struct RefreshableToken {
+        refreshed: Option<String>,
+    }
+
+    impl Credentials for RefreshableToken {
+        fn token(&self) -> Option<String> {
+            Some("expired".to_string())
+        }
+
+        fn refresh(&self, rejected: &str) -> LocalFuture<Option<String>> {
+            assert_eq!("expired", rejected);
+            let refreshed = self.refreshed.clone();
+            Box::pin(async move { refreshed })
+        }
+    }
+
+    fn response(status: u16, body: &str) -> Result<HttpResponse, ClientError> {
+        Ok(HttpResponse {
+            status,
+            body: body.to_string(),
+        })
+    }
+
+    fn client(transport: &Rc<FakeTransport>) -> ApiClient {
+        ApiClient::new(transport.clone(), Rc::new(BearerToken("token".to_string())))
+    }
+
+    #[test]
+    fn statuses_are_mapped_to_errors_with_the_apis_body() {
+        let not_found = r#"{"code":"not_found","message":"the plan does not exist"}"#;
+        let transport = FakeTransport::new(vec![response(404, not_found)]);
+
+        let result = block_on(client(&transport).get_plans()).map(|plans| plans.len());
+
+        assert_eq!(
+            Err(ClientError::NotFound(ApiErrorDto {
+                code: ApiErrorCode::NotFound,
+                message: "the plan does not exist".to_string(),
+                details: vec![],
+            })),
+            result
+        );
+        let requests = transport.requests.borrow();
+        assert_eq!(HttpMethod::Get, requests[0].method);
+        assert_eq!("plans", requests[0].route);
+        assert_eq!(Some("token".to_string()), requests[0].token);
+    }
+
+    #[test]
+    fn a_response_without_the_apis_body_gets_one_from_its_status() {
+        let error = ClientError::from_response(&HttpResponse {
+            status: 403,
+            body: "".to_string(),
+        });
+
+        assert_eq!(
+            Some(ApiErrorCode::Forbidden),
+            error.api_error().map(|e| e.code)
+        );
+        assert_eq!("the api returned 403", error.to_string());
+    }
+
+    #[test]
+    fn transient_failures_are_retried_with_a_growing_wait() {
+        let transport = FakeTransport::new(vec![
+            Err(ClientError::Offline("connection refused".to_string())),
+            response(503, ""),
+            response(200, "[]"),
+        ]);
+
+        let result = block_on(client(&transport).get_plans()).map(|plans| plans.len());
+
+        assert_eq!(Ok(0), result);
+        assert_eq!(3, transport.requests.borrow().len());
+        assert_eq!(
+            vec![Duration::from_millis(500), Duration::from_millis(1000)],
+            *transport.sleeps.borrow()
+        );
+    }
+
+    #[test]
+    fn retries_stop_after_the_last_attempt() {
+        let transport = FakeTransport::new(vec![
+            Err(ClientError::Offline("timed out".to_string())),
+            Err(ClientError::Offline("timed out".to_string())),
+        ]);
+        let client = client(&transport).with_retry(RetryPolicy {
+            attempts: 2,
+            backoff: Duration::from_millis(10),
+        });
+
+        let result = block_on(client.get_plans()).map(|plans| plans.len());
+
+        assert!(result.unwrap_err().is_offline());
+        assert_eq!(2, transport.requests.borrow().len());
+    }
+
+    #[test]
+    fn a_post_is_not_retried_when_the_api_may_have_handled_it() {
+        let transport = FakeTransport::new(vec![response(502, "bad gateway")]);
+
+        let result = block_on(client(&transport).register_user());
+
+        assert!(matches!(result, Err(ClientError::Server(502, _))));
+        assert_eq!(1, transport.requests.borrow().len());
+    }
+
+    #[test]
+    fn a_rejected_token_is_refreshed_once() {
+        let unauthorized = r#"{"code":"unauthorized","message":"the token expired"}"#;
+        let transport = FakeTransport::new(vec![response(401, unauthorized), response(200, "[]")]);
+        let client = ApiClient::new(
+            transport.clone(),
+            Rc::new(RefreshableToken {
+                refreshed: Some("fresh".to_string()),
+            }),
+        );
+
+        assert!(block_on(client.get_plans()).is_ok());
+        let tokens = transport
+            .requests
+            .borrow()
+            .iter()
+            .map(|request| request.token.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![Some("expired".to_string()), Some("fresh".to_string())],
+            tokens
+        );
+    }
+
+    #[test]
+    fn the_user_is_signed_out_when_the_token_can_not_be_refreshed() {
+        let unauthorized = r#"{"code":"unauthorized","message":"the token expired"}"#;
+        let transport = FakeTransport::new(vec![response(401, unauthorized)]);
+        let client = ApiClient::new(
+            transport.clone(),
+            Rc::new(RefreshableToken { refreshed: None }),
+        );
+
+        let result = block_on(client.get_plans()).map(|plans| plans.len());
+
+        assert_eq!(Err(ClientError::SignedOut), result);
+        assert!(result.unwrap_err().is_unauthorized());
+    }
+
+    #[test]
+    fn a_response_that_is_not_the_routes_body_fails_to_decode() {
+        let transport = FakeTransport::new(vec![response(200, "<html>")]);
+
+        let result = block_on(client(&transport).get_plans());
+
+        assert!(matches!(result, Err(ClientError::Decode(_))));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::StintDataDto;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub enum Weather {
+    Dry,
+    Damp,
+    Wet,
+}
+
+impl Display for Weather {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Weather::Dry => write!(f, "dry"),
+            Weather::Damp => write!(f, "damp"),
+            Weather::Wet => write!(f, "wet"),
+        }
+    }
+}
+
+impl FromStr for Weather {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dry" => Ok(Weather::Dry),
+            "damp" => Ok(Weather::Damp),
+            "wet" => Ok(Weather::Wet),
+            _ => Err(format!("{} cannot be mapped to a valid Weather", s)),
+        }
+    }
+}
+
+/// The track conditions from `start_offset` into the race until the next condition on the
+/// timeline starts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackConditionDto {
+    /// Time since the green flag when the condition starts
+    #[serde(with = "crate::duration_serde")]
+    pub start_offset: Duration,
+    pub air_temperature: f32,
+    pub track_temperature: f32,
+    pub weather: Weather,
+    /// Lap time in these conditions relative to the reference lap time
+    pub lap_time_factor: f64,
+    /// Fuel per lap in these conditions relative to the reference fuel per lap
+    pub fuel_per_lap_factor: f64,
+}
+
+impl Eq for TrackConditionDto {}
+
+impl TrackConditionDto {
+    pub fn new(start_offset: Duration) -> Self {
+        Self {
+            start_offset,
+            air_temperature: 0.0,
+            track_temperature: 0.0,
+            weather: Weather::Dry,
+            lap_time_factor: 1.0,
+            fuel_per_lap_factor: 1.0,
+        }
+    }
+
+    /// The stint data for running `stint_data` in these conditions. The lap count is scaled by the
+    /// fuel per lap factor because the same tank lasts more laps when less fuel is used per lap.
+    pub fn apply(&self, stint_data: &StintDataDto) -> StintDataDto {
+        if self.lap_time_factor == 1.0 && self.fuel_per_lap_factor == 1.0 {
+            return stint_data.clone();
+        }
+
+        let pit_duration = stint_data.track_time_with_pit - stint_data.track_time;
+        let lap_time = Duration::milliseconds(
+            (stint_data.lap_time.num_milliseconds() as f64 * self.lap_time_factor).round() as i64,
+        );
+        let fuel_per_lap = (stint_data.fuel_per_lap as f64 * self.fuel_per_lap_factor) as f32;
+        let lap_count = if self.fuel_per_lap_factor > 0.0 {
+            (stint_data.lap_count as f64 / self.fuel_per_lap_factor).floor() as i32
+        } else {
+            stint_data.lap_count
+        };
+        let track_time = lap_time * lap_count;
+
+        StintDataDto {
+            lap_time,
+            fuel_per_lap,
+            lap_count,
+            lap_time_with_pit: if lap_count == 0 {
+                Duration::zero()
+            } else {
+                lap_time + pit_duration / lap_count
+            },
+            track_time,
+            track_time_with_pit: track_time + pit_duration,
+            fuel_per_stint: fuel_per_lap * lap_count as f32,
+        }
+    }
+}
+
+/// The condition on the timeline in effect `race_time` after the green flag. The timeline doesn't
+/// need to be sorted, and there are no conditions in effect before the first one starts.
+pub fn condition_at(
+    track_conditions: &[TrackConditionDto],
+    race_time: Duration,
+) -> Option<&TrackConditionDto> {
+    track_conditions
+        .iter()
+        .filter(|condition| condition.start_offset <= race_time)
+        .max_by_key(|condition| condition.start_offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::StintDataDto;
+
+    use super::{condition_at, TrackConditionDto, Weather};
+
+    fn stint_data() -> StintDataDto {
+        StintDataDto {
+            lap_time: Duration::seconds(100),
+            fuel_per_lap: 4.0,
+            lap_count: 25,
+            lap_time_with_pit: Duration::seconds(102),
+            track_time: Duration::seconds(2500),
+            track_time_with_pit: Duration::seconds(2550),
+            fuel_per_stint: 100.0,
+        }
+    }
+
+    #[test]
+    fn condition_at_returns_the_latest_condition_started() {
+        let track_conditions = vec![
+            TrackConditionDto::new(Duration::hours(2)),
+            TrackConditionDto::new(Duration::zero()),
+            TrackConditionDto::new(Duration::hours(1)),
+        ];
+
+        assert_eq!(
+            Some(&track_conditions[1]),
+            condition_at(&track_conditions, Duration::minutes(59))
+        );
+        assert_eq!(
+            Some(&track_conditions[2]),
+            condition_at(&track_conditions, Duration::hours(1))
+        );
+        assert_eq!(
+            Some(&track_conditions[0]),
+            condition_at(&track_conditions, Duration::hours(5))
+        );
+    }
+
+    #[test]
+    fn condition_at_returns_none_before_the_first_condition() {
+        let track_conditions = vec![TrackConditionDto::new(Duration::hours(1))];
+
+        assert_eq!(None, condition_at(&track_conditions, Duration::minutes(30)));
+        assert_eq!(None, condition_at(&[], Duration::minutes(30)));
+    }
+
+    #[test]
+    fn apply_with_neutral_factors_keeps_the_stint_data() {
+        let condition = TrackConditionDto::new(Duration::zero());
+
+        assert_eq!(stint_data(), condition.apply(&stint_data()));
+    }
+
+    #[test]
+    fn apply_scales_lap_time_and_lap_count_by_the_factors() {
+        let condition = TrackConditionDto {
+            weather: Weather::Wet,
+            lap_time_factor: 1.1,
+            fuel_per_lap_factor: 0.8,
+            ..TrackConditionDto::new(Duration::zero())
+        };
+
+        let result = condition.apply(&stint_data());
+
+        assert_eq!(Duration::seconds(110), result.lap_time);
+        assert_eq!(3.2, result.fuel_per_lap);
+        assert_eq!(31, result.lap_count);
+        assert_eq!(Duration::seconds(3410), result.track_time);
+        assert_eq!(Duration::seconds(3460), result.track_time_with_pit);
+    }
+}
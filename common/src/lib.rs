@@ -1,10 +1,24 @@
+pub mod access_token;
+pub mod api_error;
+pub mod calendar;
+pub mod client;
+pub mod conditions;
 mod duration_serde;
+pub mod plan_export;
+pub mod revision;
+pub mod scenario;
 pub mod schedule;
+pub mod schedule_csv;
+pub mod tires;
+pub mod traffic;
 pub mod uuid_gen;
+pub mod validation;
 
 use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, Utc};
+use conditions::TrackConditionDto;
 use schedule::ScheduleStintDto;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use traffic::TrafficModelDto;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
@@ -16,6 +30,10 @@ pub struct RacePlannerDto {
     pub overall_fuel_stint_config: Option<OverallFuelStintConfigData>,
     pub fuel_stint_average_times: Option<FuelStintAverageTimes>,
     pub time_of_day_lap_factors: Vec<TimeOfDayLapFactor>,
+    #[serde(default)]
+    pub track_conditions: Vec<TrackConditionDto>,
+    #[serde(default)]
+    pub traffic_model: Option<TrafficModelDto>,
     pub per_driver_lap_factors: Vec<DriverLapFactor>,
     pub driver_roster: Vec<Driver>,
     pub schedule_rows: Option<Vec<ScheduleStintDto>>,
@@ -30,6 +48,8 @@ impl RacePlannerDto {
             overall_fuel_stint_config: None,
             fuel_stint_average_times: None,
             time_of_day_lap_factors: vec![],
+            track_conditions: vec![],
+            traffic_model: None,
             per_driver_lap_factors: vec![],
             driver_roster: vec![],
             schedule_rows: None,
@@ -52,6 +72,8 @@ pub struct PatchRacePlannerDto {
     pub overall_fuel_stint_config: Option<OverallFuelStintConfigData>,
     pub fuel_stint_average_times: Option<PatchFuelStintAverageTimes>,
     pub time_of_day_lap_factors: Option<Vec<TimeOfDayLapFactor>>,
+    pub track_conditions: Option<Vec<TrackConditionDto>>,
+    pub traffic_model: Option<TrafficModelDto>,
     pub per_driver_lap_factors: Option<Vec<DriverLapFactor>>,
     pub driver_roster: Option<Vec<Driver>>,
     pub schedule_rows: Option<Vec<ScheduleStintDto>>,
@@ -111,6 +133,10 @@ pub struct OverallFuelStintConfigData {
     #[serde(with = "crate::duration_serde")]
     pub tire_change_time: Duration,
     pub add_tire_time: bool,
+    #[serde(default, with = "crate::duration_serde")]
+    pub tire_degradation_per_lap: Duration,
+    #[serde(default)]
+    pub max_stints_per_tire_set: i32,
 }
 
 impl OverallFuelStintConfigData {
@@ -120,6 +146,8 @@ impl OverallFuelStintConfigData {
             fuel_tank_size: 0,
             tire_change_time: Duration::zero(),
             add_tire_time: false,
+            tire_degradation_per_lap: Duration::zero(),
+            max_stints_per_tire_set: 0,
         }
     }
 }
@@ -202,31 +230,86 @@ pub struct Driver {
     pub stint_preference: i16,
 }
 
+/// The claims of an OpenID Connect ID token. Only `iss`, `sub` and `aud` are required, the rest
+/// depend on the provider and the scopes that were granted.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct GoogleOpenIdClaims {
+pub struct OpenIdClaims {
     pub iss: String,
-    pub azp: Option<String>,
-    pub aud: String,
     pub sub: String,
-    pub email: String,
+    #[serde(deserialize_with = "one_or_many")]
+    pub aud: Vec<String>,
+    pub azp: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
     pub email_verified: bool,
     pub at_hash: Option<String>,
-    pub nonce: String,
-    pub name: String,
-    pub picture: String,
+    pub nonce: Option<String>,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    pub picture: Option<String>,
     pub given_name: Option<String>,
     pub family_name: Option<String>,
     pub locale: Option<String>,
     pub jti: Option<String>,
 }
 
+impl OpenIdClaims {
+    /// The best name the provider gave us, falling back to the subject
+    pub fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.preferred_username.as_deref())
+            .or(self.email.as_deref())
+            .unwrap_or(&self.sub)
+    }
+}
+
+/// `aud` is a single client id or a list of them
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(audience) => vec![audience],
+        OneOrMany::Many(audiences) => audiences,
+    })
+}
+
+/// A user is identified by the issuer of their ID token and the subject the issuer gave them
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     pub id: i32,
     pub name: String,
     pub email: String,
-    pub oauth_id: String,
+    pub issuer: String,
+    pub subject: String,
+    pub iracing_customer_id: Option<i32>,
+}
+
+/// The profile fields a user can change, the rest come from their ID token
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchUserDto {
+    pub name: Option<String>,
+    pub iracing_customer_id: Option<i32>,
+}
+
+/// An OpenID Connect provider the web can sign in with
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityProviderDto {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub scopes: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{schedule::ScheduleStintDto, uuid_gen::uuid_time_nextval, RacePlannerDto};
+
+/// Bumped whenever the shape of an exported plan changes
+pub const PLAN_EXPORT_SCHEMA_VERSION: u32 = 2;
+
+/// The oldest schema version that can still be imported. Version 1 exports don't have
+/// `scenarios`, their plan's schedule becomes the only scenario.
+pub const OLDEST_PLAN_EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// A self-contained copy of a plan that can be imported into any environment
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanExportDto {
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// The plan with the schedule of its active scenario
+    pub plan: RacePlannerDto,
+    /// Every scenario of the plan, including the active one
+    #[serde(default)]
+    pub scenarios: Vec<ScenarioExportDto>,
+}
+
+/// A scenario and its schedule, scenarios get new ids when they're imported
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioExportDto {
+    pub name: String,
+    pub is_active: bool,
+    pub schedule: Vec<ScheduleStintDto>,
+}
+
+/// Only the schema version of an exported plan, read before the rest of the document so a
+/// document from another version is rejected by its version instead of a missing field
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanExportVersionDto {
+    pub schema_version: u32,
+}
+
+impl PlanExportVersionDto {
+    pub fn is_supported(&self) -> bool {
+        (OLDEST_PLAN_EXPORT_SCHEMA_VERSION..=PLAN_EXPORT_SCHEMA_VERSION)
+            .contains(&self.schema_version)
+    }
+}
+
+impl PlanExportDto {
+    pub fn new(plan: RacePlannerDto, scenarios: Vec<ScenarioExportDto>) -> Self {
+        Self {
+            schema_version: PLAN_EXPORT_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            plan,
+            scenarios,
+        }
+    }
+
+    /// Points the stints of the plan and of every scenario at the drivers' new ids, see
+    /// [`RacePlannerDto::remap_schedule`]. The plan's schedule is then the active scenario's
+    /// schedule again, with the same new stint ids.
+    pub fn remap_schedules(&mut self, driver_ids: &HashMap<i32, i32>) {
+        self.plan.remap_schedule(driver_ids);
+        for scenario in self.scenarios.iter_mut() {
+            remap_stints(&mut scenario.schedule, driver_ids);
+        }
+        if let Some(active) = self.scenarios.iter().find(|scenario| scenario.is_active) {
+            self.plan.schedule_rows =
+                (!active.schedule.is_empty()).then(|| active.schedule.clone());
+        }
+    }
+}
+
+impl RacePlannerDto {
+    /// Points the stints at the drivers' new ids, stints for drivers that aren't in `driver_ids`
+    /// become unassigned. Every stint also gets a fresh id so the plan can be imported next to
+    /// the plan it was exported from.
+    pub fn remap_schedule(&mut self, driver_ids: &HashMap<i32, i32>) {
+        if let Some(schedule) = self.schedule_rows.as_mut() {
+            remap_stints(schedule, driver_ids);
+        }
+    }
+}
+
+fn remap_stints(schedule: &mut [ScheduleStintDto], driver_ids: &HashMap<i32, i32>) {
+    for stint in schedule.iter_mut() {
+        stint.id = uuid_time_nextval();
+        stint.driver_id = driver_ids.get(&stint.driver_id).copied().unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{Duration, NaiveDateTime, TimeZone, Utc};
+    use uuid::Uuid;
+
+    use super::{PlanExportDto, PlanExportVersionDto, ScenarioExportDto};
+    use crate::{
+        schedule::{ScheduleStintDto, StintType},
+        RacePlannerDto,
+    };
+
+    fn stint(driver_id: i32) -> ScheduleStintDto {
+        let utc_start = Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap();
+        ScheduleStintDto {
+            id: Uuid::from_u128(driver_id as u128),
+            stint_type: StintType::StandardNoTires,
+            fuel_stint_number: 1,
+            utc_start,
+            utc_end: utc_start + Duration::hours(1),
+            tod_start: NaiveDateTime::default(),
+            tod_end: NaiveDateTime::default(),
+            actual_end: utc_start + Duration::hours(1),
+            duration_delta: Duration::zero(),
+            damage_modifier: Duration::zero(),
+            calculated_laps: 30,
+            actual_laps: 30,
+            driver_id,
+            availability: "".to_string(),
+            stint_number: 1,
+            factor: 1.0,
+            tire_age: 0,
+            tire_set_stint: 1,
+            traffic_loss: Duration::zero(),
+        }
+    }
+
+    #[test]
+    fn remap_schedule_points_stints_at_the_new_drivers() {
+        let mut plan = RacePlannerDto::new();
+        plan.schedule_rows = Some(vec![stint(0), stint(7), stint(8)]);
+        let driver_ids = vec![(7, 107)].into_iter().collect::<HashMap<_, _>>();
+
+        plan.remap_schedule(&driver_ids);
+
+        let schedule = plan.schedule_rows.unwrap();
+        assert_eq!(
+            vec![0, 107, 0],
+            schedule
+                .iter()
+                .map(|stint| stint.driver_id)
+                .collect::<Vec<_>>()
+        );
+        assert!(schedule
+            .iter()
+            .all(|stint| ![0, 7, 8].contains(&stint.id.as_u128())));
+    }
+
+    #[test]
+    fn remap_schedules_remaps_every_scenario() {
+        let mut plan = RacePlannerDto::new();
+        plan.schedule_rows = Some(vec![stint(7)]);
+        let mut export = PlanExportDto::new(
+            plan,
+            vec![
+                ScenarioExportDto {
+                    name: "Main".to_string(),
+                    is_active: true,
+                    schedule: vec![stint(7)],
+                },
+                ScenarioExportDto {
+                    name: "Wet race".to_string(),
+                    is_active: false,
+                    schedule: vec![stint(7), stint(8)],
+                },
+            ],
+        );
+        let driver_ids = vec![(7, 107)].into_iter().collect::<HashMap<_, _>>();
+
+        export.remap_schedules(&driver_ids);
+
+        assert_eq!(
+            Some(&export.scenarios[0].schedule),
+            export.plan.schedule_rows.as_ref()
+        );
+        assert_eq!(
+            vec![107, 0],
+            export.scenarios[1]
+                .schedule
+                .iter()
+                .map(|stint| stint.driver_id)
+                .collect::<Vec<_>>()
+        );
+        assert_ne!(
+            export.scenarios[0].schedule[0].id,
+            export.scenarios[1].schedule[0].id
+        );
+    }
+
+    #[test]
+    fn version_1_exports_are_still_supported() {
+        let version = |schema_version| PlanExportVersionDto { schema_version };
+
+        assert!(version(1).is_supported());
+        assert!(version(2).is_supported());
+        assert!(!version(0).is_supported());
+        assert!(!version(3).is_supported());
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::RacePlannerDto;
+
+/// The part of a plan a revision changed
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanSection {
+    Title,
+    EventConfig,
+    FuelStintConfig,
+    FuelStintAverageTimes,
+    TrackConditions,
+    TrafficModel,
+    Schedule,
+}
+
+/// One entry of a plan's history. `before` and `after` hold the whole section as JSON, `before`
+/// is `None` when the section didn't have a value yet.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanRevisionDto {
+    pub id: i64,
+    pub user_name: String,
+    pub created_date: DateTime<Utc>,
+    pub section: PlanSection,
+    pub before: Option<Value>,
+    pub after: Value,
+}
+
+impl Display for PlanSection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanSection::Title => write!(f, "Title"),
+            PlanSection::EventConfig => write!(f, "Event Config"),
+            PlanSection::FuelStintConfig => write!(f, "Fuel Stint Config"),
+            PlanSection::FuelStintAverageTimes => write!(f, "Fuel Stint Average Times"),
+            PlanSection::TrackConditions => write!(f, "Track Conditions"),
+            PlanSection::TrafficModel => write!(f, "Traffic Model"),
+            PlanSection::Schedule => write!(f, "Schedule"),
+        }
+    }
+}
+
+impl RacePlannerDto {
+    /// The current value of `section` as it is recorded in the plan's history
+    pub fn section_value(&self, section: PlanSection) -> Option<Value> {
+        let value = match section {
+            PlanSection::Title => serde_json::to_value(&self.title),
+            PlanSection::EventConfig => match &self.overall_event_config {
+                Some(event_config) => serde_json::to_value(event_config),
+                None => return None,
+            },
+            PlanSection::FuelStintConfig => match &self.overall_fuel_stint_config {
+                Some(fuel_stint_config) => serde_json::to_value(fuel_stint_config),
+                None => return None,
+            },
+            PlanSection::FuelStintAverageTimes => match &self.fuel_stint_average_times {
+                Some(fuel_stint_times) => serde_json::to_value(fuel_stint_times),
+                None => return None,
+            },
+            PlanSection::TrackConditions => serde_json::to_value(&self.track_conditions),
+            PlanSection::TrafficModel => match &self.traffic_model {
+                Some(traffic_model) => serde_json::to_value(traffic_model),
+                None => return None,
+            },
+            PlanSection::Schedule => match &self.schedule_rows {
+                Some(schedule) => serde_json::to_value(schedule),
+                None => return None,
+            },
+        };
+
+        value.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{EventConfigDto, RacePlannerDto};
+
+    use super::PlanSection;
+
+    #[test]
+    fn section_value_is_none_for_sections_without_a_value() {
+        let mut plan = RacePlannerDto::new();
+        plan.title = "Daytona 24".to_string();
+
+        assert_eq!(
+            Some(json!("Daytona 24")),
+            plan.section_value(PlanSection::Title)
+        );
+        assert_eq!(None, plan.section_value(PlanSection::EventConfig));
+        assert_eq!(None, plan.section_value(PlanSection::Schedule));
+        assert_eq!(
+            Some(json!([])),
+            plan.section_value(PlanSection::TrackConditions)
+        );
+    }
+
+    #[test]
+    fn section_value_round_trips_the_section() {
+        let mut plan = RacePlannerDto::new();
+        let event_config = EventConfigDto::new();
+        plan.overall_event_config = Some(event_config.clone());
+
+        let value = plan.section_value(PlanSection::EventConfig).unwrap();
+
+        assert_eq!(
+            event_config,
+            serde_json::from_value::<EventConfigDto>(value).unwrap()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{schedule::ScheduleStintDto, Driver};
+
+/// A named alternative schedule for a plan, the active scenario is the plan's schedule
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioDto {
+    pub id: Uuid,
+    pub name: String,
+    pub is_active: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewScenarioDto {
+    pub name: String,
+    /// The scenario whose stints the new scenario starts from, the active scenario when `None`
+    pub copy_from: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverSeatTimeDto {
+    pub driver_id: i32,
+    pub name: String,
+    #[serde(with = "crate::duration_serde")]
+    pub seat_time: Duration,
+}
+
+/// The headline numbers of a scenario's schedule used to compare scenarios side by side
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioComparisonDto {
+    pub scenario: ScenarioDto,
+    pub total_laps: i32,
+    pub stop_count: i32,
+    pub finish_time: Option<DateTime<Utc>>,
+    pub driver_seat_times: Vec<DriverSeatTimeDto>,
+}
+
+impl ScenarioComparisonDto {
+    /// Every stint but the last ends with a pit stop. Seat times are listed in roster order and
+    /// don't include unassigned stints.
+    pub fn new(scenario: ScenarioDto, schedule: &[ScheduleStintDto], drivers: &[Driver]) -> Self {
+        let driver_seat_times = drivers
+            .iter()
+            .map(|driver| DriverSeatTimeDto {
+                driver_id: driver.id,
+                name: driver.name.clone(),
+                seat_time: schedule
+                    .iter()
+                    .filter(|stint| stint.driver_id == driver.id)
+                    .fold(Duration::zero(), |seat_time, stint| {
+                        seat_time + (stint.actual_end - stint.utc_start)
+                    }),
+            })
+            .collect();
+
+        Self {
+            scenario,
+            total_laps: schedule.iter().map(|stint| stint.actual_laps).sum(),
+            stop_count: schedule.len().saturating_sub(1) as i32,
+            finish_time: schedule.last().map(|stint| stint.actual_end),
+            driver_seat_times,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDateTime, TimeZone, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        schedule::{ScheduleStintDto, StintType},
+        Driver,
+    };
+
+    use super::{ScenarioComparisonDto, ScenarioDto};
+
+    fn driver(id: i32, name: &str) -> Driver {
+        Driver {
+            id,
+            name: name.to_string(),
+            total_stints: 0,
+            fair_share: false,
+            color: "#000000".to_string(),
+            utc_offset: 0,
+            irating: 0,
+            stint_preference: 0,
+        }
+    }
+
+    fn stint(fuel_stint_number: i32, driver_id: i32, actual_laps: i32) -> ScheduleStintDto {
+        let utc_start = Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap()
+            + Duration::hours((fuel_stint_number - 1) as i64);
+        ScheduleStintDto {
+            id: Uuid::from_u128(fuel_stint_number as u128),
+            stint_type: StintType::StandardNoTires,
+            fuel_stint_number,
+            utc_start,
+            utc_end: utc_start + Duration::hours(1),
+            tod_start: NaiveDateTime::default(),
+            tod_end: NaiveDateTime::default(),
+            actual_end: utc_start + Duration::hours(1),
+            duration_delta: Duration::zero(),
+            damage_modifier: Duration::zero(),
+            calculated_laps: actual_laps,
+            actual_laps,
+            driver_id,
+            availability: "".to_string(),
+            stint_number: 1,
+            factor: 1.0,
+            tire_age: 0,
+            tire_set_stint: 1,
+            traffic_loss: Duration::zero(),
+        }
+    }
+
+    #[test]
+    fn comparison_totals_laps_stops_and_seat_time() {
+        let scenario = ScenarioDto {
+            id: Uuid::nil(),
+            name: "Fuel save overnight".to_string(),
+            is_active: false,
+        };
+        let drivers = vec![driver(1, "Alice"), driver(2, "Bob")];
+        let mut schedule = vec![stint(1, 1, 30), stint(2, 1, 31), stint(3, 0, 29)];
+        schedule[1].actual_end += Duration::minutes(2);
+
+        let comparison = ScenarioComparisonDto::new(scenario, &schedule, &drivers);
+
+        assert_eq!(90, comparison.total_laps);
+        assert_eq!(2, comparison.stop_count);
+        assert_eq!(Some(schedule[2].actual_end), comparison.finish_time);
+        assert_eq!(
+            vec![(1, Duration::minutes(122)), (2, Duration::zero())],
+            comparison
+                .driver_seat_times
+                .iter()
+                .map(|seat_time| (seat_time.driver_id, seat_time.seat_time))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn comparison_of_an_empty_schedule_has_no_finish_time() {
+        let scenario = ScenarioDto {
+            id: Uuid::nil(),
+            name: "Main".to_string(),
+            is_active: true,
+        };
+
+        let comparison = ScenarioComparisonDto::new(scenario, &[], &[driver(1, "Alice")]);
+
+        assert_eq!(0, comparison.stop_count);
+        assert_eq!(None, comparison.finish_time);
+    }
+}
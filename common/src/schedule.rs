@@ -7,7 +7,14 @@ use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{uuid_gen::uuid_time_nextval, EventConfigDto, FuelStintAverageTimes};
+use crate::{
+    conditions::{condition_at, TrackConditionDto},
+    tires::TireModel,
+    traffic::TrafficModelDto,
+    uuid_gen::uuid_time_nextval,
+    EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData, RacePlannerDto,
+    StintDataDto,
+};
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub enum StintType {
@@ -17,6 +24,15 @@ pub enum StintType {
     StandardWithTires,
 }
 
+impl StintType {
+    pub fn takes_tires(&self) -> bool {
+        match self {
+            StintType::FuelSavingWithTires | StintType::StandardWithTires => true,
+            StintType::FuelSavingNoTires | StintType::StandardNoTires => false,
+        }
+    }
+}
+
 impl Display for StintType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -42,6 +58,115 @@ impl FromStr for StintType {
     }
 }
 
+/// The plan level settings used to calculate the timing of every stint in the schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleConfig {
+    pub fuel_stint_times: FuelStintAverageTimes,
+    pub race_start_utc: DateTime<Utc>,
+    pub race_end_utc: DateTime<Utc>,
+    pub tod_offset: Duration,
+    pub tire_change_time: Duration,
+    pub tire_model: TireModel,
+    pub track_conditions: Vec<TrackConditionDto>,
+    pub traffic_model: TrafficModelDto,
+}
+
+impl ScheduleConfig {
+    pub fn new(
+        event_config: &EventConfigDto,
+        fuel_stint_times: &FuelStintAverageTimes,
+        fuel_stint_config: &OverallFuelStintConfigData,
+        track_conditions: &[TrackConditionDto],
+        traffic_model: Option<&TrafficModelDto>,
+    ) -> Self {
+        Self {
+            fuel_stint_times: fuel_stint_times.clone(),
+            race_start_utc: event_config.race_start_utc,
+            race_end_utc: event_config.race_end_utc,
+            tod_offset: event_config.tod_offset,
+            tire_change_time: fuel_stint_config.tire_change_time,
+            tire_model: fuel_stint_config.into(),
+            track_conditions: track_conditions.to_vec(),
+            traffic_model: traffic_model.cloned().unwrap_or_default(),
+        }
+    }
+
+    /// The settings of `plan` its schedule is calculated with, `None` until the plan has an event
+    /// config, fuel stint times and a fuel stint config
+    pub fn from_plan(plan: &RacePlannerDto) -> Option<(&EventConfigDto, Self)> {
+        let event_config = plan.overall_event_config.as_ref()?;
+        let config = Self::new(
+            event_config,
+            plan.fuel_stint_average_times.as_ref()?,
+            plan.overall_fuel_stint_config.as_ref()?,
+            &plan.track_conditions,
+            plan.traffic_model.as_ref(),
+        );
+
+        Some((event_config, config))
+    }
+
+    /// Whether a schedule can be calculated, a race without a duration or stints without a track
+    /// time would never reach the race end. A schedule can have stints of either stint type, so
+    /// both need a track time.
+    pub fn is_schedulable(&self) -> bool {
+        self.race_start_utc < self.race_end_utc
+            && [
+                &self.fuel_stint_times.standard_fuel_stint,
+                &self.fuel_stint_times.fuel_saving_stint,
+            ]
+            .iter()
+            .all(|stint_data| stint_data.track_time_with_pit > Duration::zero())
+    }
+
+    /// The time lost to traffic on every lap of a stint starting at `stint_utc_start`
+    pub fn traffic_loss_per_lap(&self, stint_utc_start: DateTime<Utc>) -> Duration {
+        let time_of_day = (stint_utc_start.naive_utc() + self.tod_offset).time();
+        self.traffic_model.loss_per_lap(time_of_day)
+    }
+
+    /// The stint data for a stint of `stint_type` in the track conditions at `stint_utc_start`
+    fn stint_data(&self, stint_type: &StintType, stint_utc_start: DateTime<Utc>) -> StintDataDto {
+        let stint_data = match stint_type {
+            StintType::FuelSavingNoTires | StintType::FuelSavingWithTires => {
+                &self.fuel_stint_times.fuel_saving_stint
+            }
+            StintType::StandardNoTires | StintType::StandardWithTires => {
+                &self.fuel_stint_times.standard_fuel_stint
+            }
+        };
+
+        match condition_at(
+            &self.track_conditions,
+            stint_utc_start - self.race_start_utc,
+        ) {
+            Some(condition) => condition.apply(stint_data),
+            None => stint_data.clone(),
+        }
+    }
+
+    /// The stint type to use for a new stint following `previous_row`. Without a tire model every
+    /// stop takes tires, otherwise the tire model decides whether the stop takes tires.
+    pub fn recommended_stint_type(&self, previous_row: &ScheduleStintDto) -> StintType {
+        if !self.tire_model.is_enabled() {
+            return StintType::FuelSavingWithTires;
+        }
+
+        let should_change_tires = self.tire_model.should_change_tires(
+            previous_row.tire_age_at_end(),
+            previous_row.tire_set_stint,
+            self.stint_data(&StintType::FuelSavingNoTires, previous_row.utc_end)
+                .lap_count,
+            self.tire_change_time,
+        );
+        if should_change_tires {
+            StintType::FuelSavingWithTires
+        } else {
+            StintType::FuelSavingNoTires
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduleStintDto {
@@ -63,51 +188,75 @@ pub struct ScheduleStintDto {
     pub availability: String,
     pub stint_number: i32,
     pub factor: f32,
+    /// Laps already on the tire set at the start of the stint
+    #[serde(default)]
+    pub tire_age: i32,
+    /// Which stint this is on the current tire set, starting at 1
+    #[serde(default = "first_tire_set_stint")]
+    pub tire_set_stint: i32,
+    /// Time lost to traffic from other classes over the calculated laps
+    #[serde(default, with = "crate::duration_serde")]
+    pub traffic_loss: Duration,
+}
+
+fn first_tire_set_stint() -> i32 {
+    1
 }
 
 impl Eq for ScheduleStintDto {}
 
 impl ScheduleStintDto {
-    pub fn new(config: &EventConfigDto, fuel_stint_times: &FuelStintAverageTimes) -> Self {
-        let stint_duration = fuel_stint_times.fuel_saving_stint.track_time_with_pit;
+    pub fn new(event_config: &EventConfigDto, config: &ScheduleConfig) -> Self {
+        let stint_type = StintType::FuelSavingNoTires;
+        let (stint_duration, calculated_laps) = calculate_stint_duration_and_laps(
+            event_config.race_start_utc,
+            &stint_type,
+            config,
+            Duration::zero(),
+            0,
+        );
+
         Self {
             id: uuid_time_nextval(),
-            stint_type: StintType::FuelSavingNoTires,
+            stint_type,
             fuel_stint_number: 1,
-            utc_start: config.race_start_utc,
-            utc_end: config.race_start_utc + stint_duration,
-            tod_start: config.race_start_tod,
-            tod_end: config.race_start_tod + stint_duration,
-            actual_end: config.race_start_utc + stint_duration,
+            utc_start: event_config.race_start_utc,
+            utc_end: event_config.race_start_utc + stint_duration,
+            tod_start: event_config.race_start_tod,
+            tod_end: event_config.race_start_tod + stint_duration,
+            actual_end: event_config.race_start_utc + stint_duration,
             duration_delta: Duration::zero(),
             damage_modifier: Duration::zero(),
-            calculated_laps: fuel_stint_times.fuel_saving_stint.lap_count,
-            actual_laps: fuel_stint_times.fuel_saving_stint.lap_count,
+            calculated_laps,
+            actual_laps: calculated_laps,
             driver_id: 0,
             availability: "".to_string(),
             stint_number: 1,
             factor: 0.0,
+            tire_age: 0,
+            tire_set_stint: 1,
+            traffic_loss: config.traffic_loss_per_lap(event_config.race_start_utc)
+                * calculated_laps,
         }
     }
 
     pub fn from_previous(
         previous_row: &ScheduleStintDto,
         stint_type: StintType,
-        fuel_stint_times: &FuelStintAverageTimes,
-        race_end_utc: DateTime<Utc>,
-        tire_change_time: Duration,
+        config: &ScheduleConfig,
         damage_modifier: Duration,
     ) -> Self {
-        let utc_start = previous_row.utc_end;
-        let tod_start = previous_row.tod_end;
+        // the stint starts when the previous stint actually ended, which the user may have edited
+        let utc_start = previous_row.actual_end;
+        let tod_start = previous_row.tod_end + (previous_row.actual_end - previous_row.utc_end);
+        let (tire_age, tire_set_stint) = tire_state_after(Some(previous_row), &stint_type);
 
         let (stint_duration, calculated_laps) = calculate_stint_duration_and_laps(
             utc_start,
             &stint_type,
-            fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            config,
             damage_modifier,
+            tire_age,
         );
 
         Self {
@@ -120,13 +269,17 @@ impl ScheduleStintDto {
             tod_end: tod_start + stint_duration,
             actual_end: utc_start + stint_duration,
             duration_delta: Duration::zero(),
-            damage_modifier: previous_row.damage_modifier,
+            // the damage of earlier stints is carried in `damage_modifier`, a new stint adds none
+            damage_modifier: Duration::zero(),
             calculated_laps,
             actual_laps: calculated_laps,
             driver_id: 0,
             availability: "".to_string(),
             stint_number: 1,
             factor: 0.0,
+            tire_age,
+            tire_set_stint,
+            traffic_loss: config.traffic_loss_per_lap(utc_start) * calculated_laps,
         }
     }
 
@@ -134,25 +287,37 @@ impl ScheduleStintDto {
         &mut self,
         utc_start: DateTime<Utc>,
         tod_start: NaiveDateTime,
-        previous_row_driver_id: i32,
-        previous_row_stint_number: i32,
-        fuel_stint_times: &FuelStintAverageTimes,
-        race_end_utc: DateTime<Utc>,
-        tire_change_time: Duration,
+        previous_row: Option<&ScheduleStintDto>,
+        config: &ScheduleConfig,
         damage_modifier: Duration,
     ) {
-        // Only modify rows that don't have user updated stint timing
-        if self.actual_end != self.utc_end {
+        (self.tire_age, self.tire_set_stint) = tire_state_after(previous_row, &self.stint_type);
+
+        let previous_row_driver_id = previous_row.map(|row| row.driver_id).unwrap_or(0);
+        if (self.driver_id != 0 && previous_row_driver_id != 0)
+            && previous_row_driver_id == self.driver_id
+        {
+            self.stint_number = previous_row.map(|row| row.stint_number).unwrap_or(0) + 1;
+        } else {
+            self.stint_number = 1;
+        }
+
+        let (utc_end, keeps_actual_end) = self.shifted_end(utc_start);
+        if keeps_actual_end {
+            self.utc_end = utc_end;
+            self.utc_start = utc_start;
+            self.tod_start = tod_start;
+            self.tod_end = tod_start + (self.utc_end - self.utc_start);
+            self.duration_delta = self.actual_end - self.utc_end;
             return;
         }
 
         let (stint_duration, calculated_laps) = calculate_stint_duration_and_laps(
             utc_start,
             &self.stint_type,
-            fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            config,
             damage_modifier + self.damage_modifier,
+            self.tire_age,
         );
         self.utc_start = utc_start;
         self.utc_end = self.utc_start + stint_duration;
@@ -161,66 +326,346 @@ impl ScheduleStintDto {
         self.tod_end = self.tod_start + stint_duration;
         self.calculated_laps = calculated_laps;
         self.actual_laps = calculated_laps;
+        self.traffic_loss = config.traffic_loss_per_lap(utc_start) * calculated_laps;
         self.duration_delta = self.actual_end - self.utc_end;
+    }
 
-        if (self.driver_id != 0 && previous_row_driver_id != 0)
-            && previous_row_driver_id == self.driver_id
-        {
-            self.stint_number = previous_row_stint_number + 1;
+    /// The calculated end of the stint when it starts at `utc_start` instead, and whether the stint
+    /// keeps its actual end there. Rows with user updated stint timing keep their actual end, the
+    /// calculated timing only follows the previous row. An actual end the stint no longer reaches,
+    /// or that the moved calculated end now matches, is recalculated.
+    fn shifted_end(&self, utc_start: DateTime<Utc>) -> (DateTime<Utc>, bool) {
+        let utc_end = self.utc_end + (utc_start - self.utc_start);
+        let keeps_actual_end = self.actual_end != self.utc_end
+            && self.actual_end > utc_start
+            && self.actual_end != utc_end;
+
+        (utc_end, keeps_actual_end)
+    }
+
+    /// Laps on the tire set once the stint is complete
+    pub fn tire_age_at_end(&self) -> i32 {
+        self.tire_age + self.actual_laps
+    }
+
+    /// Whether the stint ends after it starts. Stints are only added while they make progress, a
+    /// negative damage modifier or a track condition can shrink a stint to nothing and the
+    /// schedule would never reach the race end.
+    fn makes_progress(&self) -> bool {
+        self.actual_end > self.utc_start
+    }
+}
+
+/// Calculates a new schedule with stints from the race start until the race end is covered
+pub fn create_schedule(
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) -> Vec<ScheduleStintDto> {
+    if !config.is_schedulable() {
+        return vec![];
+    }
+
+    let mut schedule = vec![ScheduleStintDto::new(event_config, config)];
+    let mut is_schedule_complete = schedule[0].utc_end >= event_config.race_end_utc;
+    while !is_schedule_complete {
+        let previous_row = schedule.last().unwrap();
+        let stint_data = ScheduleStintDto::from_previous(
+            previous_row,
+            config.recommended_stint_type(previous_row),
+            config,
+            Duration::zero(),
+        );
+        if !stint_data.makes_progress() {
+            break;
+        }
+        is_schedule_complete = stint_data.utc_end >= event_config.race_end_utc;
+        schedule.push(stint_data);
+    }
+
+    schedule
+}
+
+/// Calculates the schedule of `plan` from its settings, re-flowing the plan's schedule rows when it
+/// has any the same way `recalculate_schedule` does. `None` when the plan can't be scheduled.
+pub fn plan_schedule(plan: &RacePlannerDto) -> Option<Vec<ScheduleStintDto>> {
+    let (event_config, config) = ScheduleConfig::from_plan(plan)?;
+    if !config.is_schedulable() {
+        return None;
+    }
+
+    let mut schedule = plan.schedule_rows.clone().unwrap_or_default();
+    recalculate_schedule(&mut schedule, event_config, &config);
+
+    Some(schedule)
+}
+
+/// Re-flows the whole schedule from the race start with the current plan settings. Stints keep
+/// their driver, stint type and damage modifier, stints with a user edited actual end keep their
+/// timing. An empty schedule is created from scratch.
+pub fn recalculate_schedule(
+    schedule: &mut Vec<ScheduleStintDto>,
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) {
+    if !config.is_schedulable() {
+        return;
+    }
+    if schedule.is_empty() {
+        *schedule = create_schedule(event_config, config);
+        return;
+    }
+
+    schedule[0].update(
+        event_config.race_start_utc,
+        event_config.race_start_tod,
+        None,
+        config,
+        Duration::zero(),
+    );
+    update_schedule(schedule, 0, event_config, config);
+}
+
+/// Adds an unassigned stint after the stint at `index`, for example for an unplanned stop, and
+/// re-flows the rest of the schedule. Nothing is added after a stint that already reaches the race
+/// end.
+pub fn insert_stint(
+    schedule: &mut Vec<ScheduleStintDto>,
+    index: usize,
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) {
+    let previous_row = &schedule[index];
+    let stint_data = ScheduleStintDto::from_previous(
+        previous_row,
+        config.recommended_stint_type(previous_row),
+        config,
+        Duration::zero(),
+    );
+    schedule.insert(index + 1, stint_data);
+
+    update_schedule(schedule, index, event_config, config);
+    renumber_stints(schedule);
+}
+
+/// Removes the stint at `index` and re-flows the rest of the schedule from the stint before it
+pub fn delete_stint(
+    schedule: &mut Vec<ScheduleStintDto>,
+    index: usize,
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) {
+    schedule.remove(index);
+
+    if index == 0 {
+        recalculate_schedule(schedule, event_config, config);
+    } else {
+        update_schedule(schedule, index - 1, event_config, config);
+    }
+    renumber_stints(schedule);
+}
+
+/// Ends the stint at `index` halfway through and continues with a new stint of the same type and
+/// driver, the rest of the schedule is re-flowed from the new stint
+pub fn split_stint(
+    schedule: &mut Vec<ScheduleStintDto>,
+    index: usize,
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) {
+    let stint = &mut schedule[index];
+    stint.actual_end = stint.utc_start + (stint.actual_end - stint.utc_start) / 2;
+    stint.duration_delta = stint.actual_end - stint.utc_end;
+    stint.actual_laps /= 2;
+
+    let stint = &schedule[index];
+    let mut stint_data =
+        ScheduleStintDto::from_previous(stint, stint.stint_type.clone(), config, Duration::zero());
+    stint_data.driver_id = stint.driver_id;
+    schedule.insert(index + 1, stint_data);
+
+    update_schedule(schedule, index, event_config, config);
+    renumber_stints(schedule);
+}
+
+/// Numbers the fuel stints from 1 and counts consecutive stints by the same driver
+fn renumber_stints(schedule: &mut [ScheduleStintDto]) {
+    for index in 0..schedule.len() {
+        let (previous_driver_id, previous_stint_number) = match index.checked_sub(1) {
+            Some(previous) => (
+                schedule[previous].driver_id,
+                schedule[previous].stint_number,
+            ),
+            None => (0, 0),
+        };
+
+        let stint = &mut schedule[index];
+        stint.fuel_stint_number = index as i32 + 1;
+        stint.stint_number = if stint.driver_id != 0 && stint.driver_id == previous_driver_id {
+            previous_stint_number + 1
         } else {
-            self.stint_number = 1;
+            1
+        };
+    }
+}
+
+/// Re-flows every stint after `update_row_index` from the stint before it, adding stints until the
+/// race end is covered and removing any stints that start after it. A stint covers the race end
+/// when its actual end, which the user may have edited, is at or after the race end.
+pub fn update_schedule(
+    schedule: &mut Vec<ScheduleStintDto>,
+    update_row_index: usize,
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) {
+    let updated_row = &schedule[update_row_index];
+    let mut is_schedule_complete = updated_row.actual_end >= event_config.race_end_utc;
+    let mut next_row_index = update_row_index;
+    let mut damage_modifier = cumulative_damage(&schedule[..=update_row_index]);
+    while !is_schedule_complete {
+        next_row_index += 1;
+        if next_row_index == schedule.len() {
+            let previous_row = schedule.last().unwrap();
+            let stint_data = ScheduleStintDto::from_previous(
+                previous_row,
+                config.recommended_stint_type(previous_row),
+                config,
+                damage_modifier,
+            );
+            if !stint_data.makes_progress() {
+                next_row_index -= 1;
+                break;
+            }
+
+            is_schedule_complete = stint_data.utc_end >= event_config.race_end_utc;
+            schedule.push(stint_data);
+        } else {
+            let previous_row = schedule[next_row_index - 1].clone();
+            let previous_row_actual_end = previous_row.actual_end;
+            let previous_row_tod_end =
+                previous_row_actual_end.naive_utc() + event_config.tod_offset;
+
+            let next_row = &mut schedule[next_row_index];
+            next_row.update(
+                previous_row_actual_end,
+                previous_row_tod_end,
+                Some(&previous_row),
+                config,
+                damage_modifier,
+            );
+
+            is_schedule_complete = next_row.actual_end >= event_config.race_end_utc;
+            damage_modifier += next_row.damage_modifier;
+        }
+    }
+
+    schedule.truncate(next_row_index + 1);
+}
+
+/// When every stint starts and ends once each stint starts at the actual end of the stint before
+/// it, the way `update_schedule` re-flows the schedule. A stint keeps an actual end the user
+/// edited, otherwise it keeps its calculated duration.
+pub(crate) fn effective_stint_times(
+    schedule: &[ScheduleStintDto],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut times: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::with_capacity(schedule.len());
+    for stint in schedule {
+        let utc_start = times.last().map_or(stint.utc_start, |(_, end)| *end);
+        let (utc_end, keeps_actual_end) = stint.shifted_end(utc_start);
+        if keeps_actual_end {
+            times.push((utc_start, stint.actual_end));
+        } else {
+            times.push((utc_start, utc_end));
         }
     }
+
+    times
+}
+
+/// The damage modifier a stint run after `stints` is slowed by, every stint is slowed by the damage
+/// added in all the stints before it
+pub fn cumulative_damage<'a>(stints: impl IntoIterator<Item = &'a ScheduleStintDto>) -> Duration {
+    stints
+        .into_iter()
+        .fold(Duration::zero(), |damage, row| damage + row.damage_modifier)
+}
+
+/// The tire age and tire set stint of a stint of `stint_type` run after `previous_row`
+fn tire_state_after(previous_row: Option<&ScheduleStintDto>, stint_type: &StintType) -> (i32, i32) {
+    match previous_row {
+        Some(previous_row) if !stint_type.takes_tires() => (
+            previous_row.tire_age_at_end(),
+            previous_row.tire_set_stint + 1,
+        ),
+        _ => (0, 1),
+    }
 }
 
 fn calculate_stint_duration_and_laps(
     stint_utc_start: DateTime<Utc>,
     stint_type: &StintType,
-    fuel_stint_times: &FuelStintAverageTimes,
-    race_end_utc: DateTime<Utc>,
-    tire_change_time: Duration,
+    config: &ScheduleConfig,
     damage_modifier: Duration,
+    tire_age: i32,
 ) -> (Duration, i32) {
-    let fuel_stint_data = match stint_type {
-        StintType::FuelSavingNoTires | StintType::FuelSavingWithTires => {
-            &fuel_stint_times.fuel_saving_stint
-        }
-        StintType::StandardNoTires | StintType::StandardWithTires => {
-            &fuel_stint_times.standard_fuel_stint
-        }
-    };
-    let track_time_with_pit = match stint_type {
-        StintType::FuelSavingWithTires | StintType::StandardWithTires => {
-            fuel_stint_data.track_time_with_pit + tire_change_time
-        }
-        StintType::FuelSavingNoTires | StintType::StandardNoTires => {
-            fuel_stint_data.track_time_with_pit
-        }
+    let fuel_stint_data = config.stint_data(stint_type, stint_utc_start);
+    let traffic_loss_per_lap = config.traffic_loss_per_lap(stint_utc_start);
+    let track_time_with_pit = if stint_type.takes_tires() {
+        fuel_stint_data.track_time_with_pit + config.tire_change_time
+    } else {
+        fuel_stint_data.track_time_with_pit
     };
 
-    let total_stint_time =
-        Duration::seconds((fuel_stint_data.lap_count as i64) * damage_modifier.num_seconds())
-            + track_time_with_pit;
+    let total_stint_time = damage_modifier * fuel_stint_data.lap_count
+        + config
+            .tire_model
+            .degradation(tire_age, fuel_stint_data.lap_count)
+        + traffic_loss_per_lap * fuel_stint_data.lap_count
+        + track_time_with_pit;
 
-    if stint_utc_start + total_stint_time > race_end_utc {
-        let stint_duration = race_end_utc - stint_utc_start;
-        let lap_time = fuel_stint_data.lap_time + damage_modifier;
-        let calculated_laps = (stint_duration.num_milliseconds() as f64
-            / lap_time.num_milliseconds() as f64)
-            .ceil() as i32;
+    if stint_utc_start + total_stint_time > config.race_end_utc {
+        let stint_duration = config.race_end_utc - stint_utc_start;
+        let lap_time = fuel_stint_data.lap_time + damage_modifier + traffic_loss_per_lap;
+        // a lap that takes no time never gets closer to the race end, a negative damage modifier
+        // can cancel out the lap time
+        let calculated_laps = if lap_time <= Duration::zero() {
+            0
+        } else if config.tire_model.degradation_per_lap.is_zero() {
+            (stint_duration.num_milliseconds() as f64 / lap_time.num_milliseconds() as f64).ceil()
+                as i32
+        } else {
+            let mut elapsed = Duration::zero();
+            let mut laps = 0;
+            while elapsed < stint_duration {
+                elapsed = elapsed + lap_time + config.tire_model.lap_time_delta(tire_age + laps);
+                laps += 1;
+            }
+            laps
+        };
         (stint_duration, calculated_laps)
     } else {
         (total_stint_time, fuel_stint_data.lap_count)
     }
 }
 
+#[cfg(test)]
+mod properties;
+
 #[cfg(test)]
 mod tests {
     use chrono::{Duration, TimeZone, Utc};
 
-    use crate::{FuelStintAverageTimes, StintDataDto};
+    use crate::{
+        conditions::TrackConditionDto,
+        tires::TireModel,
+        traffic::{TrafficClassDto, TrafficModelDto},
+        FuelStintAverageTimes, StintDataDto,
+    };
 
-    use super::{calculate_stint_duration_and_laps, StintType};
+    use super::{
+        calculate_stint_duration_and_laps, create_schedule, delete_stint, effective_stint_times,
+        insert_stint, recalculate_schedule, split_stint, ScheduleConfig, ScheduleStintDto,
+        StintType,
+    };
 
     #[test]
     fn calculate_stint_duration_and_laps_uses_stint_type_for_stint_time() {
@@ -249,14 +694,23 @@ mod tests {
         };
         let tire_change_time = Duration::seconds(40);
         let damage_modifier = Duration::zero();
+        let config = ScheduleConfig {
+            fuel_stint_times: fuel_stint_times.clone(),
+            race_start_utc: stint_utc_start,
+            race_end_utc,
+            tod_offset: Duration::zero(),
+            tire_change_time,
+            tire_model: TireModel::default(),
+            track_conditions: vec![],
+            traffic_model: TrafficModelDto::default(),
+        };
 
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
         );
 
         assert_eq!(
@@ -269,10 +723,9 @@ mod tests {
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
         );
 
         assert_eq!(
@@ -309,14 +762,23 @@ mod tests {
         };
         let tire_change_time = Duration::seconds(40);
         let damage_modifier = Duration::zero();
+        let config = ScheduleConfig {
+            fuel_stint_times: fuel_stint_times.clone(),
+            race_start_utc: stint_utc_start,
+            race_end_utc,
+            tod_offset: Duration::zero(),
+            tire_change_time,
+            tire_model: TireModel::default(),
+            track_conditions: vec![],
+            traffic_model: TrafficModelDto::default(),
+        };
 
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
         );
 
         assert_eq!(
@@ -329,10 +791,9 @@ mod tests {
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
         );
 
         assert_eq!(
@@ -369,14 +830,23 @@ mod tests {
         };
         let tire_change_time = Duration::seconds(40);
         let damage_modifier = Duration::seconds(1);
+        let config = ScheduleConfig {
+            fuel_stint_times: fuel_stint_times.clone(),
+            race_start_utc: stint_utc_start,
+            race_end_utc,
+            tod_offset: Duration::zero(),
+            tire_change_time,
+            tire_model: TireModel::default(),
+            track_conditions: vec![],
+            traffic_model: TrafficModelDto::default(),
+        };
 
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
         );
 
         assert_eq!(
@@ -418,14 +888,23 @@ mod tests {
         };
         let tire_change_time = Duration::seconds(40);
         let damage_modifier = Duration::zero();
+        let config = ScheduleConfig {
+            fuel_stint_times: fuel_stint_times.clone(),
+            race_start_utc: stint_utc_start,
+            race_end_utc,
+            tod_offset: Duration::zero(),
+            tire_change_time,
+            tire_model: TireModel::default(),
+            track_conditions: vec![],
+            traffic_model: TrafficModelDto::default(),
+        };
 
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
         );
 
         assert_eq!(Duration::minutes(30), result.0);
@@ -435,13 +914,467 @@ mod tests {
         let result = calculate_stint_duration_and_laps(
             stint_utc_start,
             &stint_type,
-            &fuel_stint_times,
-            race_end_utc,
-            tire_change_time,
+            &config,
             damage_modifier,
+            0,
+        );
+
+        assert_eq!(Duration::minutes(30), result.0);
+        assert_eq!(19, result.1);
+    }
+
+    fn tire_test_config(tire_model: TireModel) -> ScheduleConfig {
+        ScheduleConfig {
+            fuel_stint_times: FuelStintAverageTimes {
+                standard_fuel_stint: StintDataDto {
+                    lap_time: Duration::seconds(90),
+                    fuel_per_lap: 3.25,
+                    lap_count: 22,
+                    lap_time_with_pit: Duration::seconds(120),
+                    track_time: Duration::minutes(55),
+                    track_time_with_pit: Duration::minutes(57),
+                    fuel_per_stint: 100.0,
+                },
+                fuel_saving_stint: StintDataDto {
+                    lap_time: Duration::seconds(85),
+                    fuel_per_lap: 3.35,
+                    lap_count: 20,
+                    lap_time_with_pit: Duration::seconds(115),
+                    track_time: Duration::minutes(53),
+                    track_time_with_pit: Duration::minutes(55),
+                    fuel_per_stint: 100.0,
+                },
+            },
+            race_start_utc: Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap(),
+            race_end_utc: Utc.with_ymd_and_hms(2023, 12, 22, 20, 0, 0).unwrap(),
+            tod_offset: Duration::zero(),
+            tire_change_time: Duration::seconds(40),
+            tire_model,
+            track_conditions: vec![],
+            traffic_model: TrafficModelDto::default(),
+        }
+    }
+
+    #[test]
+    fn calculate_stint_duration_and_laps_adds_tire_degradation_for_tire_age_to_stint_time() {
+        let stint_utc_start = Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap();
+        let config = tire_test_config(TireModel::new(Duration::milliseconds(100), 0));
+
+        let result = calculate_stint_duration_and_laps(
+            stint_utc_start,
+            &StintType::FuelSavingNoTires,
+            &config,
+            Duration::zero(),
+            20,
+        );
+
+        // 20 laps starting with 20 laps on the set: (20 * 20 + 190) * 100ms
+        assert_eq!(
+            config
+                .fuel_stint_times
+                .fuel_saving_stint
+                .track_time_with_pit
+                + Duration::milliseconds(59_000),
+            result.0
+        );
+        assert_eq!(
+            config.fuel_stint_times.fuel_saving_stint.lap_count,
+            result.1
+        );
+    }
+
+    #[test]
+    fn calculate_stint_duration_and_laps_counts_worn_laps_when_full_stint_ends_after_race_end() {
+        let stint_utc_start = Utc.with_ymd_and_hms(2023, 12, 22, 19, 30, 0).unwrap();
+        let config = tire_test_config(TireModel::new(Duration::seconds(1), 0));
+
+        let result = calculate_stint_duration_and_laps(
+            stint_utc_start,
+            &StintType::StandardNoTires,
+            &config,
+            Duration::zero(),
+            0,
         );
 
+        // laps of 90s, 91s, 92s, ... take 19 laps to reach the 30 minute mark
         assert_eq!(Duration::minutes(30), result.0);
         assert_eq!(19, result.1);
     }
+
+    #[test]
+    fn from_previous_carries_tire_age_unless_the_stint_takes_tires() {
+        let config = tire_test_config(TireModel::new(Duration::milliseconds(100), 0));
+        let event_config = crate::EventConfigDto {
+            race_start_utc: Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap(),
+            race_end_utc: config.race_end_utc,
+            ..crate::EventConfigDto::new()
+        };
+        let first_stint = ScheduleStintDto::new(&event_config, &config);
+
+        let second_stint = ScheduleStintDto::from_previous(
+            &first_stint,
+            StintType::FuelSavingNoTires,
+            &config,
+            Duration::zero(),
+        );
+        assert_eq!(first_stint.actual_laps, second_stint.tire_age);
+        assert_eq!(2, second_stint.tire_set_stint);
+
+        let third_stint = ScheduleStintDto::from_previous(
+            &second_stint,
+            StintType::FuelSavingWithTires,
+            &config,
+            Duration::zero(),
+        );
+        assert_eq!(0, third_stint.tire_age);
+        assert_eq!(1, third_stint.tire_set_stint);
+    }
+
+    #[test]
+    fn recommended_stint_type_takes_tires_once_the_set_is_worn_out() {
+        let config = tire_test_config(TireModel::new(Duration::zero(), 2));
+        let event_config = crate::EventConfigDto {
+            race_start_utc: Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap(),
+            race_end_utc: config.race_end_utc,
+            ..crate::EventConfigDto::new()
+        };
+        let first_stint = ScheduleStintDto::new(&event_config, &config);
+        assert_eq!(
+            StintType::FuelSavingNoTires,
+            config.recommended_stint_type(&first_stint)
+        );
+
+        let second_stint = ScheduleStintDto::from_previous(
+            &first_stint,
+            StintType::FuelSavingNoTires,
+            &config,
+            Duration::zero(),
+        );
+        assert_eq!(
+            StintType::FuelSavingWithTires,
+            config.recommended_stint_type(&second_stint)
+        );
+
+        let config = tire_test_config(TireModel::default());
+        assert_eq!(
+            StintType::FuelSavingWithTires,
+            config.recommended_stint_type(&first_stint)
+        );
+    }
+
+    #[test]
+    fn calculate_stint_duration_and_laps_counts_no_laps_without_a_lap_time() {
+        let config = tire_test_config(TireModel::default());
+        let stint_start = config.race_end_utc - Duration::minutes(10);
+
+        let result = calculate_stint_duration_and_laps(
+            stint_start,
+            &StintType::StandardNoTires,
+            &config,
+            Duration::seconds(-90),
+            0,
+        );
+
+        assert_eq!((Duration::minutes(10), 0), result);
+    }
+
+    #[test]
+    fn calculate_stint_duration_and_laps_uses_track_conditions_at_stint_start() {
+        let mut config = tire_test_config(TireModel::default());
+        config.track_conditions = vec![
+            TrackConditionDto::new(Duration::zero()),
+            TrackConditionDto {
+                lap_time_factor: 1.1,
+                fuel_per_lap_factor: 0.9,
+                ..TrackConditionDto::new(Duration::hours(2))
+            },
+        ];
+        let stint_type = StintType::StandardNoTires;
+
+        let before_rain = calculate_stint_duration_and_laps(
+            config.race_start_utc + Duration::minutes(119),
+            &stint_type,
+            &config,
+            Duration::zero(),
+            0,
+        );
+        let after_rain = calculate_stint_duration_and_laps(
+            config.race_start_utc + Duration::hours(2),
+            &stint_type,
+            &config,
+            Duration::zero(),
+            0,
+        );
+
+        // the reference is the configured 22 laps, the rain is 24 laps of 99s with a 2m pit
+        assert_eq!((Duration::minutes(57), 22), before_rain);
+        assert_eq!((Duration::seconds(2496), 24), after_rain);
+    }
+
+    #[test]
+    fn calculate_stint_duration_and_laps_adds_traffic_loss_per_lap_to_stint_time() {
+        let mut config = tire_test_config(TireModel::default());
+        config.traffic_model = TrafficModelDto {
+            time_loss_per_encounter: Duration::seconds(2),
+            classes: vec![TrafficClassDto {
+                name: "GT4".to_string(),
+                relative_pace: 1.25,
+                field_size: 5,
+            }],
+            intensities: vec![],
+        };
+        let stint_utc_start = config.race_start_utc;
+
+        let result = calculate_stint_duration_and_laps(
+            stint_utc_start,
+            &StintType::StandardNoTires,
+            &config,
+            Duration::zero(),
+            0,
+        );
+
+        // one car from the slower class is lapped every lap, costing 2s each
+        assert_eq!(
+            Duration::seconds(2),
+            config.traffic_loss_per_lap(stint_utc_start)
+        );
+        assert_eq!(
+            config
+                .fuel_stint_times
+                .standard_fuel_stint
+                .track_time_with_pit
+                + Duration::seconds(44),
+            result.0
+        );
+    }
+
+    #[test]
+    fn from_previous_records_traffic_loss_over_the_calculated_laps() {
+        let mut config = tire_test_config(TireModel::default());
+        config.traffic_model = TrafficModelDto {
+            time_loss_per_encounter: Duration::seconds(1),
+            classes: vec![TrafficClassDto {
+                name: "LMP2".to_string(),
+                relative_pace: 0.8,
+                field_size: 4,
+            }],
+            intensities: vec![],
+        };
+        let event_config = crate::EventConfigDto {
+            race_start_utc: config.race_start_utc,
+            race_end_utc: config.race_end_utc,
+            ..crate::EventConfigDto::new()
+        };
+        let first_stint = ScheduleStintDto::new(&event_config, &config);
+
+        let second_stint = ScheduleStintDto::from_previous(
+            &first_stint,
+            StintType::FuelSavingNoTires,
+            &config,
+            Duration::zero(),
+        );
+
+        assert_eq!(
+            Duration::seconds(second_stint.calculated_laps as i64),
+            second_stint.traffic_loss
+        );
+    }
+
+    fn event_config_for(config: &ScheduleConfig) -> crate::EventConfigDto {
+        crate::EventConfigDto {
+            race_start_utc: config.race_start_utc,
+            race_end_utc: config.race_end_utc,
+            tod_offset: config.tod_offset,
+            ..crate::EventConfigDto::new()
+        }
+    }
+
+    #[test]
+    fn recalculate_schedule_truncates_stints_after_the_race_end() {
+        let mut config = tire_test_config(TireModel::default());
+        let mut schedule = create_schedule(&event_config_for(&config), &config);
+        let initial_length = schedule.len();
+        let first_stint_id = schedule[0].id;
+
+        config.race_end_utc = config.race_start_utc + Duration::hours(3);
+        let event_config = event_config_for(&config);
+        recalculate_schedule(&mut schedule, &event_config, &config);
+
+        assert!(schedule.len() < initial_length);
+        assert_eq!(first_stint_id, schedule[0].id);
+        assert_eq!(config.race_end_utc, schedule.last().unwrap().utc_end);
+        assert!(schedule[schedule.len() - 2].utc_end < config.race_end_utc);
+    }
+
+    #[test]
+    fn recalculate_schedule_adds_stints_until_the_race_end() {
+        let mut config = tire_test_config(TireModel::default());
+        let mut schedule = create_schedule(&event_config_for(&config), &config);
+        let initial_length = schedule.len();
+
+        config.race_end_utc += Duration::hours(4);
+        let event_config = event_config_for(&config);
+        recalculate_schedule(&mut schedule, &event_config, &config);
+
+        assert!(schedule.len() > initial_length);
+        assert_eq!(config.race_end_utc, schedule.last().unwrap().utc_end);
+        assert!(schedule
+            .windows(2)
+            .all(|rows| rows[0].actual_end == rows[1].utc_start));
+    }
+
+    #[test]
+    fn recalculate_schedule_keeps_user_edited_actual_end() {
+        let mut config = tire_test_config(TireModel::default());
+        let mut schedule = create_schedule(&event_config_for(&config), &config);
+        let edited_actual_end = schedule[1].utc_end + Duration::minutes(5);
+        schedule[1].actual_end = edited_actual_end;
+        schedule[1].duration_delta = Duration::minutes(5);
+
+        config.tire_change_time = Duration::seconds(60);
+        let event_config = event_config_for(&config);
+        recalculate_schedule(&mut schedule, &event_config, &config);
+
+        assert_eq!(edited_actual_end, schedule[1].actual_end);
+        assert_eq!(Duration::minutes(5), schedule[1].duration_delta);
+        assert_eq!(edited_actual_end, schedule[2].utc_start);
+        assert_eq!(schedule[2].utc_end, schedule[2].actual_end);
+        assert_eq!(config.race_end_utc, schedule.last().unwrap().utc_end);
+    }
+
+    #[test]
+    fn effective_stint_times_follow_the_recalculated_schedule() {
+        let config = tire_test_config(TireModel::default());
+        let event_config = event_config_for(&config);
+        let mut schedule = create_schedule(&event_config, &config);
+        schedule[1].actual_end = schedule[1].utc_end + Duration::minutes(5);
+
+        let times = effective_stint_times(&schedule);
+        recalculate_schedule(&mut schedule, &event_config, &config);
+
+        assert_eq!(
+            schedule[..3]
+                .iter()
+                .map(|stint| (stint.utc_start, stint.actual_end))
+                .collect::<Vec<_>>(),
+            times[..3]
+        );
+    }
+
+    #[test]
+    fn create_schedule_is_empty_without_a_race_duration() {
+        let mut config = tire_test_config(TireModel::default());
+        config.race_end_utc = config.race_start_utc;
+
+        assert!(create_schedule(&event_config_for(&config), &config).is_empty());
+    }
+
+    #[test]
+    fn create_schedule_is_empty_without_a_fuel_saving_track_time() {
+        let mut config = tire_test_config(TireModel::default());
+        let fuel_saving_stint = &mut config.fuel_stint_times.fuel_saving_stint;
+        fuel_saving_stint.track_time = Duration::zero();
+        fuel_saving_stint.track_time_with_pit = Duration::zero();
+
+        assert!(!config.is_schedulable());
+        assert!(create_schedule(&event_config_for(&config), &config).is_empty());
+    }
+
+    #[test]
+    fn recalculate_schedule_stops_adding_stints_that_never_reach_the_race_end() {
+        let config = tire_test_config(TireModel::default());
+        let event_config = event_config_for(&config);
+        let mut schedule = create_schedule(&event_config, &config);
+        let initial_length = schedule.len();
+
+        // the damage modifier takes more time off every lap than the lap takes
+        schedule[0].damage_modifier = -Duration::minutes(10);
+        recalculate_schedule(&mut schedule, &event_config, &config);
+
+        assert_eq!(initial_length, schedule.len());
+    }
+
+    fn assert_schedule_is_continuous(schedule: &[ScheduleStintDto], config: &ScheduleConfig) {
+        assert!(schedule
+            .windows(2)
+            .all(|rows| rows[0].actual_end == rows[1].utc_start));
+        assert!(schedule
+            .iter()
+            .enumerate()
+            .all(|(index, stint)| stint.fuel_stint_number == index as i32 + 1));
+        assert_eq!(config.race_end_utc, schedule.last().unwrap().utc_end);
+    }
+
+    #[test]
+    fn insert_stint_adds_an_unassigned_stint_and_renumbers() {
+        let config = tire_test_config(TireModel::default());
+        let event_config = event_config_for(&config);
+        let mut schedule = create_schedule(&event_config, &config);
+        let second_stint_id = schedule[1].id;
+
+        insert_stint(&mut schedule, 0, &event_config, &config);
+
+        assert_eq!(0, schedule[1].driver_id);
+        assert_ne!(second_stint_id, schedule[1].id);
+        assert_eq!(second_stint_id, schedule[2].id);
+        assert_schedule_is_continuous(&schedule, &config);
+    }
+
+    #[test]
+    fn delete_stint_reflows_from_the_previous_stint() {
+        let config = tire_test_config(TireModel::default());
+        let event_config = event_config_for(&config);
+        let mut schedule = create_schedule(&event_config, &config);
+        let initial_length = schedule.len();
+        let third_stint_id = schedule[2].id;
+
+        delete_stint(&mut schedule, 1, &event_config, &config);
+
+        assert_eq!(third_stint_id, schedule[1].id);
+        assert!(schedule.len() <= initial_length);
+        assert_schedule_is_continuous(&schedule, &config);
+
+        delete_stint(&mut schedule, 0, &event_config, &config);
+
+        assert_eq!(config.race_start_utc, schedule[0].utc_start);
+        assert_schedule_is_continuous(&schedule, &config);
+    }
+
+    #[test]
+    fn split_stint_ends_the_stint_halfway_and_continues_with_the_same_driver() {
+        let config = tire_test_config(TireModel::default());
+        let event_config = event_config_for(&config);
+        let mut schedule = create_schedule(&event_config, &config);
+        schedule[1].driver_id = 3;
+        schedule[2].driver_id = 3;
+        let stint_start = schedule[1].utc_start;
+        let stint_duration = schedule[1].utc_end - stint_start;
+
+        split_stint(&mut schedule, 1, &event_config, &config);
+
+        assert_eq!(stint_start + stint_duration / 2, schedule[1].actual_end);
+        assert_eq!(3, schedule[2].driver_id);
+        assert_eq!(schedule[1].stint_type, schedule[2].stint_type);
+        assert_eq!(
+            vec![1, 2, 3],
+            schedule[1..4]
+                .iter()
+                .map(|stint| stint.stint_number)
+                .collect::<Vec<_>>()
+        );
+        assert_schedule_is_continuous(&schedule, &config);
+    }
+
+    #[test]
+    fn split_stint_adds_a_stint_when_splitting_the_last_stint() {
+        let config = tire_test_config(TireModel::default());
+        let event_config = event_config_for(&config);
+        let mut schedule = create_schedule(&event_config, &config);
+        let initial_length = schedule.len();
+
+        split_stint(&mut schedule, initial_length - 1, &event_config, &config);
+
+        assert_eq!(initial_length + 1, schedule.len());
+        assert_schedule_is_continuous(&schedule, &config);
+    }
 }
@@ -0,0 +1,333 @@
+//! Invariants of the schedule engine checked against randomly generated plans and user edits.
+//! Every case is generated from its own seed, a failing case reports its seed and
+//! `SCHEDULE_PROPERTY_SEED=<seed> cargo test -p endurance_racing_planner_common properties`
+//! replays just that case.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use chrono::{Duration, NaiveTime, TimeZone, Utc};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    conditions::TrackConditionDto,
+    traffic::{TrafficClassDto, TrafficIntensityDto, TrafficModelDto},
+    validation::Validate,
+    EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData, RacePlannerDto,
+    StintDataDto,
+};
+
+use super::{
+    delete_stint, insert_stint, plan_schedule, split_stint, ScheduleConfig, ScheduleStintDto,
+    StintType,
+};
+
+const CASES: u64 = 256;
+
+/// Runs `property` once for every case, each with a generator seeded by the case
+fn for_each_case(property: impl Fn(&mut StdRng)) {
+    let seeds = match std::env::var("SCHEDULE_PROPERTY_SEED") {
+        Ok(seed) => vec![seed.parse().expect("SCHEDULE_PROPERTY_SEED to be a number")],
+        Err(_) => (0..CASES).collect(),
+    };
+
+    for seed in seeds {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            property(&mut StdRng::seed_from_u64(seed))
+        }));
+        if result.is_err() {
+            panic!(
+                "the case with seed {} failed, replay it with SCHEDULE_PROPERTY_SEED={}",
+                seed, seed
+            );
+        }
+    }
+}
+
+fn duration_between(rng: &mut StdRng, min: Duration, max: Duration) -> Duration {
+    Duration::milliseconds(rng.gen_range(min.num_milliseconds()..=max.num_milliseconds()))
+}
+
+/// Average stint times as the web calculates them from a lap time, the fuel use and the pit stop
+fn stint_data(rng: &mut StdRng, fuel_tank_size: i32) -> StintDataDto {
+    let lap_time = duration_between(rng, Duration::seconds(60), Duration::seconds(180));
+    let fuel_per_lap = rng.gen_range(1.0..6.0_f32);
+    let lap_count = (fuel_tank_size as f32 / fuel_per_lap).floor() as i32;
+    let pit_duration = duration_between(rng, Duration::seconds(20), Duration::seconds(90));
+    let track_time = lap_time * lap_count;
+
+    StintDataDto {
+        lap_time,
+        fuel_per_lap,
+        lap_count,
+        lap_time_with_pit: lap_time + pit_duration / lap_count,
+        track_time,
+        track_time_with_pit: track_time + pit_duration,
+        fuel_per_stint: fuel_per_lap * lap_count as f32,
+    }
+}
+
+fn event_config(rng: &mut StdRng) -> EventConfigDto {
+    let session_start_utc = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        + duration_between(rng, Duration::zero(), Duration::days(3 * 365));
+    let mut event_config = EventConfigDto {
+        race_duration: duration_between(rng, Duration::minutes(20), Duration::hours(30)),
+        session_start_utc,
+        race_start_tod: session_start_utc.naive_utc()
+            + duration_between(rng, Duration::hours(-12), Duration::hours(12)),
+        green_flag_offset: duration_between(rng, Duration::zero(), Duration::hours(1)),
+        ..EventConfigDto::new()
+    };
+    event_config.update_race_times();
+
+    event_config
+}
+
+fn fuel_stint_config(rng: &mut StdRng) -> OverallFuelStintConfigData {
+    OverallFuelStintConfigData {
+        pit_duration: duration_between(rng, Duration::zero(), Duration::seconds(60)),
+        fuel_tank_size: rng.gen_range(20..=120),
+        tire_change_time: duration_between(rng, Duration::zero(), Duration::seconds(60)),
+        add_tire_time: rng.gen(),
+        tire_degradation_per_lap: if rng.gen_bool(0.5) {
+            Duration::zero()
+        } else {
+            duration_between(rng, Duration::zero(), Duration::milliseconds(300))
+        },
+        max_stints_per_tire_set: rng.gen_range(0..=4),
+    }
+}
+
+fn track_conditions(rng: &mut StdRng, race_duration: Duration) -> Vec<TrackConditionDto> {
+    (0..rng.gen_range(0..=3))
+        .map(|_| TrackConditionDto {
+            lap_time_factor: rng.gen_range(0.85..1.3),
+            fuel_per_lap_factor: rng.gen_range(0.85..1.25),
+            ..TrackConditionDto::new(duration_between(rng, Duration::zero(), race_duration))
+        })
+        .collect()
+}
+
+fn traffic_model(rng: &mut StdRng) -> Option<TrafficModelDto> {
+    if rng.gen_bool(0.5) {
+        return None;
+    }
+
+    Some(TrafficModelDto {
+        time_loss_per_encounter: duration_between(rng, Duration::zero(), Duration::seconds(4)),
+        classes: (0..rng.gen_range(1..=2))
+            .map(|index| TrafficClassDto {
+                name: format!("class {}", index),
+                relative_pace: rng.gen_range(0.8..1.5),
+                field_size: rng.gen_range(0..=30),
+            })
+            .collect(),
+        intensities: (0..rng.gen_range(0..=3))
+            .map(|_| TrafficIntensityDto {
+                tod_start: NaiveTime::from_num_seconds_from_midnight_opt(
+                    rng.gen_range(0..86_400),
+                    0,
+                )
+                .unwrap(),
+                intensity: rng.gen_range(0.0..2.0),
+            })
+            .collect(),
+    })
+}
+
+/// A plan with every setting a schedule is calculated from and no schedule yet
+fn plan(rng: &mut StdRng) -> RacePlannerDto {
+    let event_config = event_config(rng);
+    let fuel_stint_config = fuel_stint_config(rng);
+    let plan = RacePlannerDto {
+        title: "Property".to_string(),
+        track_conditions: track_conditions(rng, event_config.race_duration),
+        traffic_model: traffic_model(rng),
+        fuel_stint_average_times: Some(FuelStintAverageTimes {
+            standard_fuel_stint: stint_data(rng, fuel_stint_config.fuel_tank_size),
+            fuel_saving_stint: stint_data(rng, fuel_stint_config.fuel_tank_size),
+        }),
+        overall_event_config: Some(event_config),
+        overall_fuel_stint_config: Some(fuel_stint_config),
+        ..RacePlannerDto::new()
+    };
+    assert_eq!(Ok(()), plan.validate(), "the generated plan is valid");
+
+    plan
+}
+
+/// Changes a few stints the way a user does on the schedule page, the actual end of a stint is
+/// only ever set to after the stint started
+fn edit_stints(rng: &mut StdRng, schedule: &mut [ScheduleStintDto]) {
+    let stint_types = [
+        StintType::FuelSavingNoTires,
+        StintType::FuelSavingWithTires,
+        StintType::StandardNoTires,
+        StintType::StandardWithTires,
+    ];
+    for _ in 0..rng.gen_range(1..=4) {
+        let stint = schedule.choose_mut(rng).unwrap();
+        match rng.gen_range(0..4) {
+            0 => {
+                let duration = stint.utc_end - stint.utc_start;
+                stint.actual_end = stint.utc_start
+                    + duration * rng.gen_range(10..=150) / 100
+                    + Duration::seconds(1);
+                stint.duration_delta = stint.actual_end - stint.utc_end;
+                stint.actual_laps = rng.gen_range(0..=stint.calculated_laps + 2);
+            }
+            1 => stint.stint_type = stint_types.choose(rng).unwrap().clone(),
+            2 => {
+                stint.damage_modifier =
+                    duration_between(rng, Duration::zero(), Duration::seconds(5))
+            }
+            _ => stint.driver_id = rng.gen_range(0..=3),
+        }
+    }
+}
+
+/// Changes the race the way a user does on the event settings page after the schedule was made
+fn edit_race(rng: &mut StdRng, plan: &mut RacePlannerDto) {
+    let event_config = plan.overall_event_config.as_mut().unwrap();
+    match rng.gen_range(0..3) {
+        0 => {
+            event_config.race_duration =
+                duration_between(rng, Duration::minutes(20), Duration::hours(30))
+        }
+        1 => {
+            event_config.race_start_tod +=
+                duration_between(rng, Duration::hours(-6), Duration::hours(6))
+        }
+        _ => {
+            event_config.session_start_utc +=
+                duration_between(rng, Duration::hours(-2), Duration::hours(2))
+        }
+    }
+    event_config.update_race_times();
+}
+
+/// The invariants every schedule the engine returns holds
+fn assert_schedule_invariants(plan: &RacePlannerDto, schedule: &[ScheduleStintDto]) {
+    let event_config = plan.overall_event_config.as_ref().unwrap();
+    let (first, last) = match (schedule.first(), schedule.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => panic!("a schedulable plan has stints"),
+    };
+
+    assert_eq!(event_config.race_start_utc, first.utc_start);
+    assert_eq!(event_config.race_start_tod, first.tod_start);
+    for stint in schedule.iter() {
+        assert_eq!(
+            stint.tod_end - stint.tod_start,
+            stint.utc_end - stint.utc_start,
+            "a stint lasts as long in the sim as in UTC"
+        );
+        assert_eq!(
+            event_config.tod_offset,
+            stint.tod_start - stint.utc_start.naive_utc(),
+            "a stint starts at the race's time of day"
+        );
+        assert!(stint.utc_start < stint.actual_end, "a stint takes time");
+        assert_eq!(stint.actual_end - stint.utc_end, stint.duration_delta);
+        assert!(stint.calculated_laps >= 0 && stint.actual_laps >= 0);
+        assert!(stint.tire_age >= 0 && stint.tire_set_stint >= 1);
+        assert!(stint.traffic_loss >= Duration::zero());
+        assert_eq!(Ok(()), stint.validate(), "the stint is valid");
+    }
+    for (index, rows) in schedule.windows(2).enumerate() {
+        assert_eq!(
+            rows[0].actual_end,
+            rows[1].utc_start,
+            "stint {} starts when the stint before it ends",
+            index + 2
+        );
+        assert!(
+            rows[0].actual_end < event_config.race_end_utc,
+            "only the last stint reaches the race end"
+        );
+    }
+    assert!(last.actual_end >= event_config.race_end_utc);
+    if last.actual_end == last.utc_end {
+        assert_eq!(
+            event_config.race_end_utc, last.utc_end,
+            "a calculated last stint ends with the race"
+        );
+    }
+}
+
+#[test]
+fn calculated_schedules_cover_the_race() {
+    for_each_case(|rng| {
+        let plan = plan(rng);
+
+        let schedule = plan_schedule(&plan).expect("the plan is schedulable");
+
+        assert_schedule_invariants(&plan, &schedule);
+        assert!(schedule
+            .iter()
+            .all(|stint| stint.actual_end == stint.utc_end));
+    });
+}
+
+#[test]
+fn edited_schedules_are_reflowed() {
+    for_each_case(|rng| {
+        let mut plan = plan(rng);
+        let mut schedule = plan_schedule(&plan).unwrap();
+        edit_stints(rng, &mut schedule);
+        if rng.gen_bool(0.5) {
+            edit_race(rng, &mut plan);
+        }
+        plan.schedule_rows = Some(schedule);
+
+        let schedule = plan_schedule(&plan).unwrap();
+
+        assert_schedule_invariants(&plan, &schedule);
+    });
+}
+
+#[test]
+fn calculating_a_schedule_again_changes_nothing() {
+    for_each_case(|rng| {
+        let mut plan = plan(rng);
+        let mut schedule = plan_schedule(&plan).unwrap();
+        edit_stints(rng, &mut schedule);
+        plan.schedule_rows = Some(schedule);
+        let schedule = plan_schedule(&plan).unwrap();
+        plan.schedule_rows = Some(schedule.clone());
+
+        assert_eq!(schedule, plan_schedule(&plan).unwrap());
+    });
+}
+
+#[test]
+fn inserted_deleted_and_split_stints_are_reflowed() {
+    for_each_case(|rng| {
+        let plan = plan(rng);
+        let (event_config, config) = ScheduleConfig::from_plan(&plan).unwrap();
+        let mut schedule = plan_schedule(&plan).unwrap();
+
+        for _ in 0..rng.gen_range(1..=4) {
+            let index = rng.gen_range(0..schedule.len());
+            match rng.gen_range(0..3) {
+                0 => insert_stint(&mut schedule, index, event_config, &config),
+                1 if schedule.len() > 1 => {
+                    delete_stint(&mut schedule, index, event_config, &config)
+                }
+                _ => split_stint(&mut schedule, index, event_config, &config),
+            }
+
+            assert_schedule_invariants(&plan, &schedule);
+            assert!(schedule
+                .iter()
+                .enumerate()
+                .all(|(index, stint)| stint.fuel_stint_number == index as i32 + 1));
+        }
+
+        // re-flowing from the edited stint gives the same schedule as re-flowing all of it
+        let plan = RacePlannerDto {
+            schedule_rows: Some(schedule.clone()),
+            ..plan
+        };
+        assert_eq!(schedule, plan_schedule(&plan).unwrap());
+    });
+}
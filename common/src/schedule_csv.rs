@@ -0,0 +1,532 @@
+use chrono::{DateTime, Duration, NaiveDateTime, SubsecRound, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    schedule::{cumulative_damage, update_schedule, ScheduleConfig, ScheduleStintDto, StintType},
+    Driver, EventConfigDto,
+};
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// The header takes up the first line so the first stint is on line 2, the same as a spreadsheet
+const FIRST_ROW_NUMBER: usize = 2;
+
+/// A problem with a single row of an imported schedule
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleCsvRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+impl ScheduleCsvRowError {
+    fn new(row: usize, message: String) -> Self {
+        Self { row, message }
+    }
+}
+
+#[derive(Serialize)]
+struct ScheduleCsvExportRecord<'a> {
+    id: Uuid,
+    stint_type: String,
+    fuel_stint_number: i32,
+    utc_start: String,
+    utc_end: String,
+    tod_start: String,
+    tod_end: String,
+    actual_end: String,
+    duration_delta: String,
+    damage_modifier: String,
+    calculated_laps: i32,
+    actual_laps: i32,
+    driver: &'a str,
+    availability: &'a str,
+    stint_number: i32,
+    factor: f32,
+    tire_age: i32,
+    tire_set_stint: i32,
+    traffic_loss: String,
+}
+
+/// The columns read back from an imported schedule, every other column is ignored
+#[derive(Deserialize)]
+struct ScheduleCsvImportRecord {
+    id: String,
+    stint_type: String,
+    driver: String,
+    damage_modifier: String,
+    actual_end: String,
+}
+
+/// The edits from a single imported row, only the values that differ from the exported stint are
+/// set so the re-flow from earlier rows isn't overwritten with the exported values
+struct StintChanges {
+    row: usize,
+    id: Uuid,
+    stint_type: Option<StintType>,
+    driver_id: Option<i32>,
+    damage_modifier: Option<Duration>,
+    actual_end: Option<DateTime<Utc>>,
+}
+
+/// Writes every column of the schedule with the driver names in place of the driver ids
+pub fn schedule_to_csv(schedule: &[ScheduleStintDto], drivers: &[Driver]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+
+    for stint in schedule {
+        let driver = drivers
+            .iter()
+            .find(|driver| driver.id == stint.driver_id)
+            .map_or("", |driver| driver.name.as_str());
+        writer
+            .serialize(ScheduleCsvExportRecord {
+                id: stint.id,
+                stint_type: stint.stint_type.to_string(),
+                fuel_stint_number: stint.fuel_stint_number,
+                utc_start: stint.utc_start.format(DATE_TIME_FORMAT).to_string(),
+                utc_end: stint.utc_end.format(DATE_TIME_FORMAT).to_string(),
+                tod_start: stint.tod_start.format(DATE_TIME_FORMAT).to_string(),
+                tod_end: stint.tod_end.format(DATE_TIME_FORMAT).to_string(),
+                actual_end: stint.actual_end.format(DATE_TIME_FORMAT).to_string(),
+                duration_delta: format_duration(stint.duration_delta),
+                damage_modifier: format_duration(stint.damage_modifier),
+                calculated_laps: stint.calculated_laps,
+                actual_laps: stint.actual_laps,
+                driver,
+                availability: stint.availability.as_str(),
+                stint_number: stint.stint_number,
+                factor: stint.factor,
+                tire_age: stint.tire_age,
+                tire_set_stint: stint.tire_set_stint,
+                traffic_loss: format_duration(stint.traffic_loss),
+            })
+            .expect("a stint to be serializable");
+    }
+
+    String::from_utf8(writer.into_inner().expect("writing to memory to succeed"))
+        .expect("the csv to be utf-8")
+}
+
+/// Applies the stint type, driver, damage modifier and actual end edits from an exported schedule,
+/// re-flowing the stints after every edited row the same way the schedule page does.
+///
+/// Rows are matched to stints by id. Nothing is applied when any row fails validation, but an
+/// edit can still remove a later stint from the schedule, in which case the later row is reported
+/// and `schedule` is left partially updated.
+pub fn import_schedule_csv(
+    csv: &str,
+    schedule: &mut Vec<ScheduleStintDto>,
+    drivers: &[Driver],
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) -> Result<usize, Vec<ScheduleCsvRowError>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let mut changes = vec![];
+    let mut errors = vec![];
+
+    for (index, record) in reader.deserialize::<ScheduleCsvImportRecord>().enumerate() {
+        let row = index + FIRST_ROW_NUMBER;
+        match record
+            .map_err(|e| e.to_string())
+            .and_then(|record| parse_changes(row, &record, schedule, drivers))
+        {
+            Ok(Some(stint_changes)) => changes.push(stint_changes),
+            Ok(None) => (),
+            Err(message) => errors.push(ScheduleCsvRowError::new(row, message)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    for stint_changes in changes.iter() {
+        match schedule
+            .iter()
+            .position(|stint| stint.id == stint_changes.id)
+        {
+            Some(index) => apply_changes(schedule, index, stint_changes, event_config, config),
+            None => errors.push(ScheduleCsvRowError::new(
+                stint_changes.row,
+                "the stint was removed from the schedule by an earlier row".to_string(),
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(changes.len())
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_changes(
+    row: usize,
+    record: &ScheduleCsvImportRecord,
+    schedule: &[ScheduleStintDto],
+    drivers: &[Driver],
+) -> Result<Option<StintChanges>, String> {
+    let id = Uuid::parse_str(record.id.as_str())
+        .map_err(|_| format!("{} is not a valid stint id", record.id))?;
+    let stint = schedule
+        .iter()
+        .find(|stint| stint.id == id)
+        .ok_or_else(|| format!("stint {} is not in the schedule", id))?;
+    let stint_type = record.stint_type.parse::<StintType>()?;
+    let driver_id = if record.driver.is_empty() {
+        0
+    } else {
+        drivers
+            .iter()
+            .find(|driver| driver.name == record.driver)
+            .map(|driver| driver.id)
+            .ok_or_else(|| format!("{} is not on the driver roster", record.driver))?
+    };
+    let damage_modifier = parse_duration(record.damage_modifier.as_str())?;
+    if damage_modifier < Duration::zero() {
+        return Err(format!(
+            "{} is not a valid damage modifier, it can't be negative",
+            record.damage_modifier
+        ));
+    }
+    let actual_end = NaiveDateTime::parse_from_str(record.actual_end.as_str(), DATE_TIME_FORMAT)
+        .map(|actual_end| Utc.from_utc_datetime(&actual_end))
+        .or_else(|_| {
+            DateTime::parse_from_rfc3339(record.actual_end.as_str())
+                .map(|actual_end| actual_end.with_timezone(&Utc))
+        })
+        .map_err(|_| format!("{} is not a valid actual end", record.actual_end))?;
+
+    // compare at the precision of the export so untouched rows aren't treated as edits
+    let exported_damage_modifier = Duration::milliseconds(stint.damage_modifier.num_milliseconds());
+    let exported_actual_end = stint.actual_end.trunc_subsecs(0);
+    let changes = StintChanges {
+        row,
+        id,
+        stint_type: Some(stint_type).filter(|stint_type| *stint_type != stint.stint_type),
+        driver_id: Some(driver_id).filter(|driver_id| *driver_id != stint.driver_id),
+        damage_modifier: Some(damage_modifier)
+            .filter(|damage_modifier| *damage_modifier != exported_damage_modifier),
+        actual_end: Some(actual_end).filter(|actual_end| *actual_end != exported_actual_end),
+    };
+
+    if changes.stint_type.is_none()
+        && changes.driver_id.is_none()
+        && changes.damage_modifier.is_none()
+        && changes.actual_end.is_none()
+    {
+        Ok(None)
+    } else {
+        Ok(Some(changes))
+    }
+}
+
+fn apply_changes(
+    schedule: &mut Vec<ScheduleStintDto>,
+    index: usize,
+    changes: &StintChanges,
+    event_config: &EventConfigDto,
+    config: &ScheduleConfig,
+) {
+    let previous_row = index.checked_sub(1).map(|index| schedule[index].clone());
+    let damage_modifier = cumulative_damage(&schedule[..index]);
+    let stint = &mut schedule[index];
+
+    if let Some(driver_id) = changes.driver_id {
+        stint.driver_id = driver_id;
+        stint.stint_number = match previous_row.as_ref() {
+            Some(previous_row) if driver_id != 0 && previous_row.driver_id == driver_id => {
+                previous_row.stint_number + 1
+            }
+            _ => 1,
+        };
+    }
+
+    if changes.stint_type.is_some() || changes.damage_modifier.is_some() {
+        if let Some(stint_type) = changes.stint_type.as_ref() {
+            stint.stint_type = stint_type.clone();
+        }
+        if let Some(damage_modifier) = changes.damage_modifier {
+            stint.damage_modifier = damage_modifier;
+        }
+        stint.update(
+            stint.utc_start,
+            stint.tod_start,
+            previous_row.as_ref(),
+            config,
+            damage_modifier,
+        );
+    }
+
+    if let Some(actual_end) = changes.actual_end {
+        stint.actual_end = actual_end;
+        stint.duration_delta = stint.actual_end - stint.utc_end;
+    }
+
+    update_schedule(schedule, index, event_config, config);
+}
+
+/// Formats as `[-]HH:MM:SS.mmm`
+fn format_duration(duration: Duration) -> String {
+    let prefix = if duration < Duration::zero() { "-" } else { "" };
+    let milliseconds = duration.num_milliseconds().abs();
+    format!(
+        "{}{:02}:{:02}:{:02}.{:03}",
+        prefix,
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000
+    )
+}
+
+/// Parses `[-][[HH:]MM:]SS[.mmm]`, so spreadsheets that drop the leading fields still import
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let error = || format!("{} is not a valid duration", value);
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (-1, unsigned),
+        None => (1, value),
+    };
+    let parts = unsigned.split(':').collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return Err(error());
+    }
+
+    let (seconds, minutes_and_hours) = parts.split_last().ok_or_else(error)?;
+    let seconds = seconds
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite())
+        .ok_or_else(error)?;
+    let mut milliseconds = (seconds * 1000.0).round() as i64;
+    for (unit, part) in [60_000, 3_600_000]
+        .iter()
+        .zip(minutes_and_hours.iter().rev())
+    {
+        milliseconds += part.parse::<i64>().map_err(|_| error())? * unit;
+    }
+
+    Ok(Duration::milliseconds(sign * milliseconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        schedule::{update_schedule, ScheduleConfig, ScheduleStintDto, StintType},
+        tires::TireModel,
+        traffic::TrafficModelDto,
+        Driver, EventConfigDto, FuelStintAverageTimes, StintDataDto,
+    };
+
+    use super::{
+        format_duration, import_schedule_csv, parse_duration, schedule_to_csv, ScheduleCsvRowError,
+    };
+
+    fn driver(id: i32, name: &str) -> Driver {
+        Driver {
+            id,
+            name: name.to_string(),
+            total_stints: 0,
+            fair_share: false,
+            color: "#000000".to_string(),
+            utc_offset: 0,
+            irating: 0,
+            stint_preference: 0,
+        }
+    }
+
+    fn stint_data(lap_time_seconds: i64) -> StintDataDto {
+        StintDataDto {
+            lap_time: Duration::seconds(lap_time_seconds),
+            fuel_per_lap: 5.0,
+            lap_count: 20,
+            lap_time_with_pit: Duration::seconds(lap_time_seconds + 60),
+            track_time: Duration::seconds(lap_time_seconds * 20),
+            track_time_with_pit: Duration::seconds(lap_time_seconds * 20 + 60),
+            fuel_per_stint: 100.0,
+        }
+    }
+
+    fn event_config_and_schedule() -> (EventConfigDto, ScheduleConfig, Vec<ScheduleStintDto>) {
+        let race_start_utc = Utc.with_ymd_and_hms(2023, 12, 22, 12, 0, 0).unwrap();
+        let race_end_utc = Utc.with_ymd_and_hms(2023, 12, 22, 16, 0, 0).unwrap();
+        let event_config = EventConfigDto {
+            race_duration: race_end_utc - race_start_utc,
+            session_start_utc: race_start_utc,
+            race_start_utc,
+            race_end_utc,
+            race_start_tod: race_start_utc.naive_utc(),
+            race_end_tod: race_end_utc.naive_utc(),
+            green_flag_offset: Duration::zero(),
+            tod_offset: Duration::zero(),
+        };
+        let config = ScheduleConfig {
+            fuel_stint_times: FuelStintAverageTimes {
+                standard_fuel_stint: stint_data(90),
+                fuel_saving_stint: stint_data(92),
+            },
+            race_start_utc,
+            race_end_utc,
+            tod_offset: Duration::zero(),
+            tire_change_time: Duration::seconds(30),
+            tire_model: TireModel::default(),
+            track_conditions: vec![],
+            traffic_model: TrafficModelDto::default(),
+        };
+
+        let mut schedule = vec![ScheduleStintDto::new(&event_config, &config)];
+        while schedule.last().unwrap().utc_end < race_end_utc {
+            let previous_row = schedule.last().unwrap();
+            schedule.push(ScheduleStintDto::from_previous(
+                previous_row,
+                StintType::FuelSavingNoTires,
+                &config,
+                Duration::zero(),
+            ));
+        }
+
+        (event_config, config, schedule)
+    }
+
+    #[test]
+    fn import_schedule_csv_applies_edits_from_an_exported_schedule() {
+        let (event_config, config, mut schedule) = event_config_and_schedule();
+        let drivers = vec![driver(1, "Alice"), driver(2, "Bob")];
+        let original_second_stint_end = schedule[1].utc_end;
+        let csv = schedule_to_csv(&schedule, &drivers);
+        let edited_rows = csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| match index {
+                1 => line.replacen("fs no tires", "std w/ tires", 1),
+                2 => line.replacen(",,,", ",Bob,,", 1),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let result = import_schedule_csv(
+            edited_rows.as_str(),
+            &mut schedule,
+            &drivers,
+            &event_config,
+            &config,
+        );
+
+        assert_eq!(Ok(2), result);
+        assert_eq!(StintType::StandardWithTires, schedule[0].stint_type);
+        assert_eq!(2, schedule[1].driver_id);
+        // the standard stint with tires is 10 seconds shorter than the fuel saving stint
+        assert_eq!(
+            original_second_stint_end - Duration::seconds(10),
+            schedule[1].utc_end
+        );
+        assert_eq!(schedule[1].utc_end, schedule[1].actual_end);
+        assert!(schedule.last().unwrap().utc_end >= config.race_end_utc);
+    }
+
+    #[test]
+    fn import_schedule_csv_slows_an_edited_stint_by_the_damage_of_every_earlier_stint() {
+        let (event_config, config, mut schedule) = event_config_and_schedule();
+        schedule[0].damage_modifier = Duration::seconds(1);
+        schedule[1].damage_modifier = Duration::seconds(2);
+        update_schedule(&mut schedule, 0, &event_config, &config);
+        let csv = schedule_to_csv(&schedule, &[]);
+        let edited_rows = csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| match index {
+                3 => line.replacen("fs no tires", "std w/ tires", 1),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let result = import_schedule_csv(
+            edited_rows.as_str(),
+            &mut schedule,
+            &[],
+            &event_config,
+            &config,
+        );
+
+        assert_eq!(Ok(1), result);
+        assert_eq!(StintType::StandardWithTires, schedule[2].stint_type);
+        // 20 laps of 90 seconds, the pit stop, the tire change and 3 seconds of damage per lap
+        assert_eq!(
+            Duration::seconds(1800 + 60 + 30 + 60),
+            schedule[2].utc_end - schedule[2].utc_start
+        );
+    }
+
+    #[test]
+    fn import_schedule_csv_reports_every_invalid_row_without_applying_any() {
+        let (event_config, config, mut schedule) = event_config_and_schedule();
+        let drivers = vec![driver(1, "Alice")];
+        let original_schedule = schedule.clone();
+        let csv = schedule_to_csv(&schedule, &drivers);
+        let edited_rows = csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| match index {
+                1 => line.replacen("fs no tires", "std w/ tires", 1),
+                2 => line.replacen(",,,", ",Carl,,", 1),
+                3 => line.replacen("fs no tires", "slicks", 1),
+                4 => {
+                    let mut fields = line.split(',').collect::<Vec<_>>();
+                    fields[9] = "-00:00:05.000";
+                    fields.join(",")
+                }
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let result = import_schedule_csv(
+            edited_rows.as_str(),
+            &mut schedule,
+            &drivers,
+            &event_config,
+            &config,
+        );
+
+        assert_eq!(
+            Err(vec![
+                ScheduleCsvRowError {
+                    row: 3,
+                    message: "Carl is not on the driver roster".to_string()
+                },
+                ScheduleCsvRowError {
+                    row: 4,
+                    message: "slicks cannot be mapped to a valid StintType".to_string()
+                },
+                ScheduleCsvRowError {
+                    row: 5,
+                    message: "-00:00:05.000 is not a valid damage modifier, it can't be negative"
+                        .to_string()
+                }
+            ]),
+            result
+        );
+        assert_eq!(original_schedule, schedule);
+    }
+
+    #[test]
+    fn parse_duration_reads_formatted_durations_and_plain_seconds() {
+        let duration = -(Duration::hours(1) + Duration::seconds(2) + Duration::milliseconds(500));
+
+        assert_eq!(
+            Ok(duration),
+            parse_duration(format_duration(duration).as_str())
+        );
+        assert_eq!(Ok(Duration::milliseconds(1500)), parse_duration("1.5"));
+        assert_eq!(Ok(Duration::seconds(62)), parse_duration("01:02"));
+        assert!(parse_duration("fast").is_err());
+        assert!(parse_duration("NaN").is_err());
+        assert!(parse_duration("inf").is_err());
+    }
+}
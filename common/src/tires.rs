@@ -0,0 +1,116 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::OverallFuelStintConfigData;
+
+/// Models how a tire set wears over the course of a stint and when it should be replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TireModel {
+    /// Lap time lost for every lap already run on the set
+    #[serde(with = "crate::duration_serde")]
+    pub degradation_per_lap: Duration,
+    /// The most stints a single set can be run for. Zero means there is no limit
+    pub max_stints_per_set: i32,
+}
+
+impl TireModel {
+    pub fn new(degradation_per_lap: Duration, max_stints_per_set: i32) -> Self {
+        Self {
+            degradation_per_lap,
+            max_stints_per_set,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.degradation_per_lap.is_zero() || self.max_stints_per_set > 0
+    }
+
+    /// The lap time lost on a single lap when the set already has `tire_age` laps on it
+    pub fn lap_time_delta(&self, tire_age: i32) -> Duration {
+        self.degradation_per_lap * tire_age
+    }
+
+    /// The total time lost to wear over `laps` laps on a set that starts with `tire_age` laps on it
+    pub fn degradation(&self, tire_age: i32, laps: i32) -> Duration {
+        if laps <= 0 {
+            return Duration::zero();
+        }
+
+        let laps = laps as i64;
+        let wear_laps = laps * tire_age as i64 + laps * (laps - 1) / 2;
+        Duration::milliseconds(self.degradation_per_lap.num_milliseconds() * wear_laps)
+    }
+
+    /// Whether the next stop should take tires. The set is replaced once it has run the maximum
+    /// number of stints or when the time lost running the next stint on the worn set instead of a
+    /// fresh one is at least the time it takes to change the tires.
+    pub fn should_change_tires(
+        &self,
+        tire_age: i32,
+        stints_on_set: i32,
+        next_stint_laps: i32,
+        tire_change_time: Duration,
+    ) -> bool {
+        if self.max_stints_per_set > 0 && stints_on_set >= self.max_stints_per_set {
+            return true;
+        }
+
+        if self.degradation_per_lap.is_zero() {
+            return false;
+        }
+
+        let worn_set_loss =
+            self.degradation(tire_age, next_stint_laps) - self.degradation(0, next_stint_laps);
+        worn_set_loss >= tire_change_time
+    }
+}
+
+impl Default for TireModel {
+    fn default() -> Self {
+        Self::new(Duration::zero(), 0)
+    }
+}
+
+impl From<&OverallFuelStintConfigData> for TireModel {
+    fn from(config: &OverallFuelStintConfigData) -> Self {
+        Self::new(
+            config.tire_degradation_per_lap,
+            config.max_stints_per_tire_set,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::TireModel;
+
+    #[test]
+    fn degradation_adds_the_wear_of_every_lap_on_the_set() {
+        let tire_model = TireModel::new(Duration::milliseconds(100), 0);
+
+        assert_eq!(Duration::zero(), tire_model.degradation(0, 1));
+        assert_eq!(Duration::milliseconds(300), tire_model.degradation(0, 3));
+        assert_eq!(Duration::milliseconds(3300), tire_model.degradation(10, 3));
+    }
+
+    #[test]
+    fn should_change_tires_when_the_set_reaches_the_maximum_stints() {
+        let tire_model = TireModel::new(Duration::zero(), 2);
+
+        assert!(!tire_model.should_change_tires(20, 1, 20, Duration::seconds(30)));
+        assert!(tire_model.should_change_tires(40, 2, 20, Duration::seconds(30)));
+    }
+
+    #[test]
+    fn should_change_tires_when_wear_costs_more_than_the_tire_change() {
+        let tire_model = TireModel::new(Duration::milliseconds(50), 0);
+
+        // 20 laps on a set with 20 laps already on it loses 20 * 20 * 50ms = 20s
+        assert!(!tire_model.should_change_tires(20, 1, 20, Duration::seconds(30)));
+        // 20 laps on a set with 40 laps already on it loses 20 * 40 * 50ms = 40s
+        assert!(tire_model.should_change_tires(40, 2, 20, Duration::seconds(30)));
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{Duration, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+/// The other classes sharing the track and how much time is lost getting past them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficModelDto {
+    /// Time lost every time a car from another class is passed or passes us
+    #[serde(with = "crate::duration_serde")]
+    pub time_loss_per_encounter: Duration,
+    pub classes: Vec<TrafficClassDto>,
+    pub intensities: Vec<TrafficIntensityDto>,
+}
+
+impl Eq for TrafficModelDto {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficClassDto {
+    pub name: String,
+    /// The class lap time relative to ours, above 1.0 for slower classes and below for faster ones
+    pub relative_pace: f64,
+    pub field_size: i32,
+}
+
+impl Eq for TrafficClassDto {}
+
+/// How busy the track is from `tod_start` until the next intensity starts, 1.0 being normal
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficIntensityDto {
+    pub tod_start: NaiveTime,
+    pub intensity: f64,
+}
+
+impl Eq for TrafficIntensityDto {}
+
+impl TrafficModelDto {
+    pub fn new() -> Self {
+        Self {
+            time_loss_per_encounter: Duration::zero(),
+            classes: vec![],
+            intensities: vec![],
+        }
+    }
+
+    /// How many cars from other classes we expect to meet on every lap
+    pub fn encounters_per_lap(&self) -> f64 {
+        self.classes
+            .iter()
+            .filter(|class| class.relative_pace > 0.0)
+            .map(|class| class.field_size as f64 * (1.0 - 1.0 / class.relative_pace).abs())
+            .sum()
+    }
+
+    /// The intensity at `time_of_day`. Intensities wrap around midnight, so before the first
+    /// intensity of the day the last one is still in effect.
+    pub fn intensity_at(&self, time_of_day: NaiveTime) -> f64 {
+        self.intensities
+            .iter()
+            .filter(|intensity| intensity.tod_start <= time_of_day)
+            .max_by_key(|intensity| intensity.tod_start)
+            .or_else(|| {
+                self.intensities
+                    .iter()
+                    .max_by_key(|intensity| intensity.tod_start)
+            })
+            .map_or(1.0, |intensity| intensity.intensity)
+    }
+
+    /// The time lost to traffic on every lap run at `time_of_day`
+    pub fn loss_per_lap(&self, time_of_day: NaiveTime) -> Duration {
+        let loss_milliseconds = self.time_loss_per_encounter.num_milliseconds() as f64
+            * self.encounters_per_lap()
+            * self.intensity_at(time_of_day);
+        Duration::milliseconds(loss_milliseconds.round() as i64)
+    }
+}
+
+impl Default for TrafficModelDto {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveTime};
+
+    use super::{TrafficClassDto, TrafficIntensityDto, TrafficModelDto};
+
+    fn traffic_model() -> TrafficModelDto {
+        TrafficModelDto {
+            time_loss_per_encounter: Duration::seconds(1),
+            classes: vec![
+                TrafficClassDto {
+                    name: "GT4".to_string(),
+                    relative_pace: 1.25,
+                    field_size: 10,
+                },
+                TrafficClassDto {
+                    name: "LMP2".to_string(),
+                    relative_pace: 0.8,
+                    field_size: 4,
+                },
+            ],
+            intensities: vec![
+                TrafficIntensityDto {
+                    tod_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+                    intensity: 1.0,
+                },
+                TrafficIntensityDto {
+                    tod_start: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+                    intensity: 1.5,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn encounters_per_lap_adds_cars_lapped_and_cars_lapping_us() {
+        // 10 * (1 - 1 / 1.25) + 4 * (1 / 0.8 - 1)
+        assert!((3.0 - traffic_model().encounters_per_lap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn intensity_at_wraps_around_midnight() {
+        let traffic_model = traffic_model();
+
+        assert_eq!(
+            1.0,
+            traffic_model.intensity_at(NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+        );
+        assert_eq!(
+            1.5,
+            traffic_model.intensity_at(NaiveTime::from_hms_opt(21, 0, 0).unwrap())
+        );
+        assert_eq!(
+            1.5,
+            traffic_model.intensity_at(NaiveTime::from_hms_opt(2, 0, 0).unwrap())
+        );
+        assert_eq!(
+            1.0,
+            TrafficModelDto::new().intensity_at(NaiveTime::from_hms_opt(2, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn loss_per_lap_scales_encounters_by_intensity() {
+        let traffic_model = traffic_model();
+
+        assert_eq!(
+            Duration::seconds(3),
+            traffic_model.loss_per_lap(NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+        );
+        assert_eq!(
+            Duration::milliseconds(4500),
+            traffic_model.loss_per_lap(NaiveTime::from_hms_opt(22, 0, 0).unwrap())
+        );
+    }
+}
@@ -0,0 +1,609 @@
+use crate::{
+    access_token::NewAccessTokenDto,
+    api_error::FieldErrorDto,
+    conditions::TrackConditionDto,
+    plan_export::{PlanExportDto, ScenarioExportDto},
+    scenario::NewScenarioDto,
+    schedule::ScheduleStintDto,
+    traffic::{TrafficClassDto, TrafficIntensityDto, TrafficModelDto},
+    Driver, EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData,
+    PatchFuelStintAverageTimes, PatchRacePlannerDto, PatchUserDto, RacePlannerDto, StintDataDto,
+};
+
+/// A dto that can check its own values before it's saved. The web checks a dto before sending it
+/// and the api checks it again when it's received, so both report the same errors.
+pub trait Validate {
+    /// Adds an error to `validator` for each invalid field
+    fn check(&self, validator: &mut Validator);
+
+    fn validate(&self) -> Result<(), Vec<FieldErrorDto>> {
+        let mut validator = Validator::default();
+        self.check(&mut validator);
+        validator.finish()
+    }
+}
+
+/// Collects the errors of a dto, the field of each error is its camelCase path from the root dto,
+/// e.g. `standardFuelStint.lapTime` or `[2].utcEnd`
+#[derive(Default)]
+pub struct Validator {
+    path: Vec<String>,
+    errors: Vec<FieldErrorDto>,
+}
+
+impl Validator {
+    /// Records the outcome of a rule for `field`
+    pub fn field(&mut self, field: &str, rule: Result<(), String>) -> &mut Self {
+        if let Err(message) = rule {
+            let field = self.field_path(field);
+            self.errors.push(FieldErrorDto { field, message });
+        }
+
+        self
+    }
+
+    /// Checks a nested dto, its fields are prefixed with `field`
+    pub fn nested<T: Validate>(&mut self, field: &str, value: &T) -> &mut Self {
+        self.path.push(self.field_path(field));
+        value.check(self);
+        self.path.pop();
+
+        self
+    }
+
+    pub fn optional<T: Validate>(&mut self, field: &str, value: &Option<T>) -> &mut Self {
+        if let Some(value) = value {
+            self.nested(field, value);
+        }
+
+        self
+    }
+
+    /// Checks each dto of a list, the fields are prefixed with `field` and the index
+    pub fn each<T: Validate>(&mut self, field: &str, values: &[T]) -> &mut Self {
+        for (index, value) in values.iter().enumerate() {
+            self.nested(&format!("{}[{}]", field, index), value);
+        }
+
+        self
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldErrorDto>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn field_path(&self, field: &str) -> String {
+        match self.path.last() {
+            Some(parent) if field.starts_with('[') => format!("{}{}", parent, field),
+            Some(parent) => format!("{}.{}", parent, field),
+            None => field.to_string(),
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn check(&self, validator: &mut Validator) {
+        validator.each("", self);
+    }
+}
+
+/// The rules the dtos are checked with, each returns the message to show when the value is invalid
+pub mod rules {
+    use chrono::Duration;
+
+    /// A value that can be compared to zero
+    pub trait Measure: PartialOrd + Copy {
+        fn zero() -> Self;
+    }
+
+    macro_rules! impl_measure {
+        ($($t:ty),*) => {
+            $(impl Measure for $t {
+                fn zero() -> Self {
+                    0 as $t
+                }
+            })*
+        };
+    }
+
+    impl_measure!(i16, i32, i64, f32, f64);
+
+    impl Measure for Duration {
+        fn zero() -> Self {
+            Duration::zero()
+        }
+    }
+
+    pub fn positive<T: Measure>(value: T) -> Result<(), String> {
+        if value > T::zero() {
+            Ok(())
+        } else {
+            Err("must be greater than zero".to_string())
+        }
+    }
+
+    pub fn not_negative<T: Measure>(value: T) -> Result<(), String> {
+        if value >= T::zero() {
+            Ok(())
+        } else {
+            Err("can't be negative".to_string())
+        }
+    }
+
+    pub fn between<T: Measure + std::fmt::Display>(value: T, min: T, max: T) -> Result<(), String> {
+        if value >= min && value <= max {
+            Ok(())
+        } else {
+            Err(format!("must be between {} and {}", min, max))
+        }
+    }
+
+    pub fn not_blank(value: &str) -> Result<(), String> {
+        if value.trim().is_empty() {
+            Err("is required".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// `value` comes at or after `other`, `other_name` is how the other field is shown to the user
+    pub fn not_before<T: PartialOrd>(value: T, other: T, other_name: &str) -> Result<(), String> {
+        if value >= other {
+            Ok(())
+        } else {
+            Err(format!("can't be before the {}", other_name))
+        }
+    }
+
+    /// `value` comes strictly after `other`, `other_name` is how the other field is shown to the user
+    pub fn after<T: PartialOrd>(value: T, other: T, other_name: &str) -> Result<(), String> {
+        if value > other {
+            Ok(())
+        } else {
+            Err(format!("must be after the {}", other_name))
+        }
+    }
+
+    /// A `#RGB` or `#RRGGBB` colour
+    pub fn hex_colour(value: &str) -> Result<(), String> {
+        let digits = value.strip_prefix('#').unwrap_or("");
+        if (digits.len() == 3 || digits.len() == 6) && digits.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            Ok(())
+        } else {
+            Err("must be a hex colour like #1E88E5".to_string())
+        }
+    }
+}
+
+use rules::{after, between, hex_colour, not_before, not_blank, not_negative, positive};
+
+/// iRacing's time zones run from UTC-12 to UTC+14
+const UTC_OFFSET_RANGE: (i16, i16) = (-12, 14);
+
+impl Validate for RacePlannerDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("title", not_blank(&self.title))
+            .optional("overallEventConfig", &self.overall_event_config)
+            .optional("overallFuelStintConfig", &self.overall_fuel_stint_config)
+            .optional("fuelStintAverageTimes", &self.fuel_stint_average_times)
+            .each("trackConditions", &self.track_conditions)
+            .optional("trafficModel", &self.traffic_model)
+            .each("driverRoster", &self.driver_roster)
+            .each(
+                "scheduleRows",
+                self.schedule_rows.as_deref().unwrap_or_default(),
+            );
+    }
+}
+
+impl Validate for PatchRacePlannerDto {
+    fn check(&self, validator: &mut Validator) {
+        if let Some(title) = &self.title {
+            validator.field("title", not_blank(title));
+        }
+        validator
+            .optional("overallEventConfig", &self.overall_event_config)
+            .optional("overallFuelStintConfig", &self.overall_fuel_stint_config)
+            .optional("fuelStintAverageTimes", &self.fuel_stint_average_times)
+            .each(
+                "trackConditions",
+                self.track_conditions.as_deref().unwrap_or_default(),
+            )
+            .optional("trafficModel", &self.traffic_model)
+            .each(
+                "driverRoster",
+                self.driver_roster.as_deref().unwrap_or_default(),
+            )
+            .each(
+                "scheduleRows",
+                self.schedule_rows.as_deref().unwrap_or_default(),
+            );
+    }
+}
+
+impl Validate for EventConfigDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("raceDuration", positive(self.race_duration))
+            .field("greenFlagOffset", not_negative(self.green_flag_offset))
+            .field(
+                "raceStartUtc",
+                not_before(self.race_start_utc, self.session_start_utc, "session start"),
+            )
+            .field(
+                "raceEndUtc",
+                after(self.race_end_utc, self.race_start_utc, "race start"),
+            )
+            .field(
+                "raceEndTod",
+                after(
+                    self.race_end_tod,
+                    self.race_start_tod,
+                    "race start time of day",
+                ),
+            );
+    }
+}
+
+impl Validate for OverallFuelStintConfigData {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("pitDuration", not_negative(self.pit_duration))
+            .field("fuelTankSize", positive(self.fuel_tank_size))
+            .field("tireChangeTime", not_negative(self.tire_change_time))
+            .field(
+                "tireDegradationPerLap",
+                not_negative(self.tire_degradation_per_lap),
+            )
+            .field(
+                "maxStintsPerTireSet",
+                not_negative(self.max_stints_per_tire_set),
+            );
+    }
+}
+
+impl Validate for FuelStintAverageTimes {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .nested("standardFuelStint", &self.standard_fuel_stint)
+            .nested("fuelSavingStint", &self.fuel_saving_stint);
+    }
+}
+
+impl Validate for PatchFuelStintAverageTimes {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .optional("standardFuelStint", &self.standard_fuel_stint)
+            .optional("fuelSavingStint", &self.fuel_saving_stint);
+    }
+}
+
+impl Validate for StintDataDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("lapTime", positive(self.lap_time))
+            .field("fuelPerLap", not_negative(self.fuel_per_lap))
+            .field("lapCount", not_negative(self.lap_count))
+            .field("lapTimeWithPit", not_negative(self.lap_time_with_pit))
+            .field("trackTime", not_negative(self.track_time))
+            .field("trackTimeWithPit", not_negative(self.track_time_with_pit))
+            .field("fuelPerStint", not_negative(self.fuel_per_stint));
+    }
+}
+
+impl Validate for TrackConditionDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("startOffset", not_negative(self.start_offset))
+            .field("lapTimeFactor", positive(self.lap_time_factor))
+            .field("fuelPerLapFactor", positive(self.fuel_per_lap_factor));
+    }
+}
+
+impl Validate for TrafficModelDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field(
+                "timeLossPerEncounter",
+                not_negative(self.time_loss_per_encounter),
+            )
+            .each("classes", &self.classes)
+            .each("intensities", &self.intensities);
+    }
+}
+
+impl Validate for TrafficClassDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("name", not_blank(&self.name))
+            .field("relativePace", positive(self.relative_pace))
+            .field("fieldSize", not_negative(self.field_size));
+    }
+}
+
+impl Validate for TrafficIntensityDto {
+    fn check(&self, validator: &mut Validator) {
+        validator.field("intensity", not_negative(self.intensity));
+    }
+}
+
+impl Validate for Driver {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("name", not_blank(&self.name))
+            .field("color", hex_colour(&self.color))
+            .field(
+                "utcOffset",
+                between(self.utc_offset, UTC_OFFSET_RANGE.0, UTC_OFFSET_RANGE.1),
+            )
+            .field("irating", not_negative(self.irating))
+            .field("stintPreference", not_negative(self.stint_preference));
+    }
+}
+
+impl Validate for ScheduleStintDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field(
+                "utcEnd",
+                not_before(self.utc_end, self.utc_start, "stint start"),
+            )
+            .field(
+                "todEnd",
+                not_before(self.tod_end, self.tod_start, "stint start time of day"),
+            )
+            .field(
+                "actualEnd",
+                not_before(self.actual_end, self.utc_start, "stint start"),
+            )
+            .field("damageModifier", not_negative(self.damage_modifier))
+            .field("calculatedLaps", not_negative(self.calculated_laps))
+            .field("actualLaps", not_negative(self.actual_laps))
+            .field("tireAge", not_negative(self.tire_age));
+    }
+}
+
+impl Validate for NewScenarioDto {
+    fn check(&self, validator: &mut Validator) {
+        validator.field("name", not_blank(&self.name));
+    }
+}
+
+impl Validate for ScenarioExportDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .field("name", not_blank(&self.name))
+            .each("schedule", &self.schedule);
+    }
+}
+
+impl Validate for PlanExportDto {
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .nested("plan", &self.plan)
+            .each("scenarios", &self.scenarios);
+        let active_count = self.scenarios.iter().filter(|s| s.is_active).count();
+        if !self.scenarios.is_empty() && active_count != 1 {
+            validator.field(
+                "scenarios",
+                Err("exactly one scenario must be active".to_string()),
+            );
+        }
+    }
+}
+
+impl Validate for NewAccessTokenDto {
+    fn check(&self, validator: &mut Validator) {
+        validator.field("name", not_blank(&self.name));
+    }
+}
+
+impl Validate for PatchUserDto {
+    fn check(&self, validator: &mut Validator) {
+        if let Some(name) = &self.name {
+            validator.field("name", not_blank(name));
+        }
+        if let Some(iracing_customer_id) = self.iracing_customer_id {
+            validator.field("iracingCustomerId", positive(iracing_customer_id));
+        }
+    }
+}
+
+/// The message of the first error of `field`, used to show the error next to the field's input
+pub fn field_error(errors: &[FieldErrorDto], field: &str) -> Option<String> {
+    errors
+        .iter()
+        .find(|error| error.field == field)
+        .map(|error| error.message.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{field_error, Validate};
+    use crate::{
+        schedule::{ScheduleStintDto, StintType},
+        Driver, EventConfigDto, OverallFuelStintConfigData, PatchFuelStintAverageTimes,
+        PatchRacePlannerDto, PatchUserDto, StintDataDto,
+    };
+
+    fn driver() -> Driver {
+        Driver {
+            id: 1,
+            name: "Max".to_string(),
+            total_stints: 0,
+            fair_share: false,
+            color: "#1E88E5".to_string(),
+            utc_offset: -5,
+            irating: 2500,
+            stint_preference: 2,
+        }
+    }
+
+    #[test]
+    fn valid_driver_passes() {
+        assert_eq!(Ok(()), driver().validate());
+    }
+
+    #[test]
+    fn driver_errors_name_each_field() {
+        let driver = Driver {
+            name: " ".to_string(),
+            color: "blue".to_string(),
+            utc_offset: 20,
+            ..driver()
+        };
+
+        let errors = driver.validate().unwrap_err();
+
+        assert_eq!(
+            vec!["name", "color", "utcOffset"],
+            errors.iter().map(|e| e.field.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some("must be between -12 and 14".to_string()),
+            field_error(&errors, "utcOffset")
+        );
+    }
+
+    #[test]
+    fn race_end_before_race_start_is_rejected() {
+        let mut event_config = EventConfigDto::new();
+        event_config.race_duration = Duration::hours(6);
+        event_config.update_race_times();
+        assert_eq!(Ok(()), event_config.validate());
+
+        event_config.race_end_utc = event_config.race_start_utc - Duration::minutes(1);
+        let errors = event_config.validate().unwrap_err();
+
+        assert_eq!(
+            Some("must be after the race start".to_string()),
+            field_error(&errors, "raceEndUtc")
+        );
+    }
+
+    #[test]
+    fn nested_fields_are_prefixed_with_their_path() {
+        let mut fuel_stint_config = OverallFuelStintConfigData::new();
+        fuel_stint_config.fuel_tank_size = -1;
+        let patch = PatchRacePlannerDto {
+            id: uuid::Uuid::nil(),
+            title: None,
+            overall_event_config: None,
+            overall_fuel_stint_config: Some(fuel_stint_config),
+            fuel_stint_average_times: Some(PatchFuelStintAverageTimes {
+                standard_fuel_stint: Some(StintDataDto {
+                    lap_time: Duration::zero(),
+                    fuel_per_lap: 2.5,
+                    lap_count: 20,
+                    lap_time_with_pit: Duration::zero(),
+                    track_time: Duration::zero(),
+                    track_time_with_pit: Duration::zero(),
+                    fuel_per_stint: 50.0,
+                }),
+                fuel_saving_stint: None,
+            }),
+            time_of_day_lap_factors: None,
+            track_conditions: None,
+            traffic_model: None,
+            per_driver_lap_factors: None,
+            driver_roster: None,
+            schedule_rows: None,
+        };
+
+        let errors = patch.validate().unwrap_err();
+
+        assert_eq!(
+            vec![
+                "overallFuelStintConfig.fuelTankSize",
+                "fuelStintAverageTimes.standardFuelStint.lapTime"
+            ],
+            errors.iter().map(|e| e.field.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    fn stint() -> ScheduleStintDto {
+        let start = Utc.with_ymd_and_hms(2026, 6, 13, 14, 0, 0).unwrap();
+        ScheduleStintDto {
+            id: uuid::Uuid::nil(),
+            stint_type: StintType::StandardNoTires,
+            fuel_stint_number: 1,
+            utc_start: start,
+            utc_end: start + Duration::hours(1),
+            tod_start: start.naive_utc(),
+            tod_end: start.naive_utc() + Duration::hours(1),
+            actual_end: start + Duration::hours(1),
+            duration_delta: Duration::zero(),
+            damage_modifier: Duration::zero(),
+            calculated_laps: 40,
+            actual_laps: 0,
+            driver_id: 0,
+            availability: "".to_string(),
+            stint_number: 1,
+            factor: 1.0,
+            tire_age: 0,
+            tire_set_stint: 1,
+            traffic_loss: Duration::zero(),
+        }
+    }
+
+    #[test]
+    fn list_fields_are_prefixed_with_their_index() {
+        let stint = stint();
+        let mut backwards_stint = stint.clone();
+        backwards_stint.utc_end = stint.utc_start - Duration::hours(1);
+
+        let errors = vec![stint, backwards_stint].validate().unwrap_err();
+
+        assert_eq!(
+            Some("can't be before the stint start".to_string()),
+            field_error(&errors, "[1].utcEnd")
+        );
+    }
+
+    #[test]
+    fn stints_need_an_actual_end_after_their_start_and_no_negative_damage() {
+        let mut stint = stint();
+        stint.actual_end = stint.utc_start - Duration::minutes(1);
+        stint.damage_modifier = Duration::seconds(-2);
+
+        let errors = stint.validate().unwrap_err();
+
+        assert_eq!(
+            Some("can't be before the stint start".to_string()),
+            field_error(&errors, "actualEnd")
+        );
+        assert_eq!(
+            Some("can't be negative".to_string()),
+            field_error(&errors, "damageModifier")
+        );
+    }
+
+    #[test]
+    fn only_the_patched_profile_fields_are_checked() {
+        assert!(PatchUserDto::default().validate().is_ok());
+
+        let errors = PatchUserDto {
+            name: Some(" ".to_string()),
+            iracing_customer_id: Some(0),
+        }
+        .validate()
+        .unwrap_err();
+
+        assert_eq!(
+            Some("is required".to_string()),
+            field_error(&errors, "name")
+        );
+        assert_eq!(
+            Some("must be greater than zero".to_string()),
+            field_error(&errors, "iracingCustomerId")
+        );
+    }
+}
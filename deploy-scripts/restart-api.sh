@@ -1,3 +1,16 @@
 #!/bin/sh
 
-systemctl restart enduranceraceplanner.com.service
\ No newline at end of file
+systemctl restart enduranceraceplanner.com.service
+
+# wait for the api to report it's ready instead of assuming the restart worked
+for attempt in $(seq 1 60); do
+    if curl -fs http://127.0.0.1:3000/readyz > /dev/null; then
+        echo "api is ready"
+        exit 0
+    fi
+    sleep 2
+done
+
+echo "api didn't become ready, last readiness report:"
+curl -s http://127.0.0.1:3000/readyz
+exit 1
@@ -0,0 +1,252 @@
+use crate::http::access_tokens::{create_access_token, get_access_tokens, revoke_access_token};
+use crate::http::plans::get_plans;
+use crate::md_text_field::{MaterialTextField, MaterialTextFieldProps};
+use endurance_racing_planner_common::access_token::{
+    AccessTokenDto, CreatedAccessTokenDto, NewAccessTokenDto,
+};
+use endurance_racing_planner_common::api_error::FieldErrorDto;
+use endurance_racing_planner_common::validation::{field_error, Validate};
+use endurance_racing_planner_common::PlanListDto;
+use uuid::Uuid;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::html::Scope;
+use yew::prelude::*;
+use yew::props;
+use yew_mdc::components::button::{Button, Style};
+
+const TOKEN_TIME_FORMAT: &str = "%b %e %Y %l:%M %p";
+
+pub enum AccessTokensMsg {
+    Load,
+    SetAccessTokens(Vec<AccessTokenDto>),
+    SetPlans(Vec<PlanListDto>),
+    UpdateName(String),
+    UpdatePlan(Option<Uuid>),
+    UpdateReadOnly(bool),
+    CreateAccessToken,
+    SetCreatedAccessToken(CreatedAccessTokenDto),
+    RevokeAccessToken(Uuid),
+}
+
+/// The user's personal access tokens, scripts and bots use them to call the api as the user
+pub struct AccessTokens {
+    access_tokens: Vec<AccessTokenDto>,
+    plans: Vec<PlanListDto>,
+    new_access_token: NewAccessTokenDto,
+    created_token: Option<String>,
+    errors: Vec<FieldErrorDto>,
+    read_only_input_ref: NodeRef,
+    plan_select_ref: NodeRef,
+}
+
+impl Component for AccessTokens {
+    type Message = AccessTokensMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(AccessTokensMsg::Load);
+        get_plans(ctx.link().callback(AccessTokensMsg::SetPlans));
+
+        Self {
+            access_tokens: vec![],
+            plans: vec![],
+            new_access_token: NewAccessTokenDto::default(),
+            created_token: None,
+            errors: vec![],
+            read_only_input_ref: NodeRef::default(),
+            plan_select_ref: NodeRef::default(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            AccessTokensMsg::Load => {
+                get_access_tokens(ctx.link().callback(AccessTokensMsg::SetAccessTokens));
+                false
+            }
+            AccessTokensMsg::SetAccessTokens(access_tokens) => {
+                self.access_tokens = access_tokens;
+                true
+            }
+            AccessTokensMsg::SetPlans(plans) => {
+                self.plans = plans;
+                true
+            }
+            AccessTokensMsg::UpdateName(name) => {
+                self.new_access_token.name = name;
+                false
+            }
+            AccessTokensMsg::UpdatePlan(plan_id) => {
+                self.new_access_token.plan_id = plan_id;
+                false
+            }
+            AccessTokensMsg::UpdateReadOnly(read_only) => {
+                self.new_access_token.read_only = read_only;
+                false
+            }
+            AccessTokensMsg::CreateAccessToken => {
+                self.errors = self.new_access_token.validate().err().unwrap_or_default();
+                if !self.errors.is_empty() {
+                    return true;
+                }
+
+                create_access_token(
+                    self.new_access_token.clone(),
+                    ctx.link().callback(AccessTokensMsg::SetCreatedAccessToken),
+                );
+                false
+            }
+            AccessTokensMsg::SetCreatedAccessToken(created) => {
+                self.created_token = Some(created.token);
+                self.new_access_token = NewAccessTokenDto::default();
+                ctx.link().send_message(AccessTokensMsg::Load);
+                true
+            }
+            AccessTokensMsg::RevokeAccessToken(id) => {
+                revoke_access_token(id, ctx.link().callback(|_| AccessTokensMsg::Load));
+                false
+            }
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let name_props = props! {MaterialTextFieldProps {
+            value: self.new_access_token.name.clone(),
+            label: Some("Token Name".to_string()),
+            id: "new-access-token-name".to_string(),
+            on_change: link.callback(AccessTokensMsg::UpdateName),
+            error: field_error(&self.errors, "name")
+        }};
+        let plan_select_ref = self.plan_select_ref.clone();
+        let plan_callback = link.batch_callback(move |_| {
+            let select = plan_select_ref.cast::<HtmlSelectElement>();
+            select.map(|select| AccessTokensMsg::UpdatePlan(Uuid::parse_str(&select.value()).ok()))
+        });
+        let read_only_input_ref = self.read_only_input_ref.clone();
+        let read_only_callback = link.batch_callback(move |_| {
+            let input = read_only_input_ref.cast::<HtmlInputElement>();
+            input.map(|input| AccessTokensMsg::UpdateReadOnly(input.checked()))
+        });
+
+        html! {
+            <div id="access-tokens" class="mdc-card">
+                <div class="mdc-card-wrapper__text-section">
+                    <div class="card-title">{ "Access Tokens" }</div>
+                </div>
+                {
+                    match &self.created_token {
+                        Some(token) => html! {
+                            <div class="created-access-token">
+                                <p>{ "Copy the token now, it won't be shown again" }</p>
+                                <code>{ token }</code>
+                            </div>
+                        },
+                        None => html! {},
+                    }
+                }
+                <div class="mdc-data-table">
+                  <div class="mdc-data-table__table-container">
+                    <table class="mdc-data-table__table">
+                      <thead>
+                        <tr class="mdc-data-table__header-row">
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Name" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Plan" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Access" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Created (UTC)" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Last Used (UTC)" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col"></th>
+                        </tr>
+                      </thead>
+                      <tbody class="mdc-data-table__content">
+                        {
+                            self.access_tokens
+                                .iter()
+                                .map(|access_token| self.render_access_token(access_token, link))
+                                .collect::<Html>()
+                        }
+                      </tbody>
+                    </table>
+                  </div>
+                </div>
+                <div class="new-access-token">
+                    <MaterialTextField ..name_props />
+                    <select ref={self.plan_select_ref.clone()} onchange={plan_callback}>
+                        <option value="" selected={self.new_access_token.plan_id.is_none()}>{ "All my plans" }</option>
+                        {
+                            self.plans
+                                .iter()
+                                .map(|plan| html! {
+                                    <option value={plan.id.to_string()} selected={self.new_access_token.plan_id == Some(plan.id)}>{ plan.title.clone() }</option>
+                                })
+                                .collect::<Html>()
+                        }
+                    </select>
+                    <div class="mdc-form-field">
+                      <div class="mdc-checkbox">
+                        <input ref={self.read_only_input_ref.clone()}
+                               type="checkbox"
+                               class="mdc-checkbox__native-control"
+                               id="access-token-read-only"
+                               checked={self.new_access_token.read_only}
+                               oninput={read_only_callback}/>
+                        <div class="mdc-checkbox__background">
+                          <svg class="mdc-checkbox__checkmark"
+                               viewBox="0 0 24 24">
+                            <path class="mdc-checkbox__checkmark-path"
+                                  fill="none"
+                                  d="M1.73,12.91 8.1,19.28 22.79,4.59"/>
+                          </svg>
+                          <div class="mdc-checkbox__mixedmark"></div>
+                        </div>
+                        <div class="mdc-checkbox__ripple"></div>
+                      </div>
+                      <label for="access-token-read-only">{ "Read Only" }</label>
+                    </div>
+                    <Button text={"Create Token"} style={Style::Raised} onclick={link.callback(|_| AccessTokensMsg::CreateAccessToken)} />
+                </div>
+            </div>
+        }
+    }
+}
+
+impl AccessTokens {
+    fn render_access_token(&self, access_token: &AccessTokenDto, link: &Scope<Self>) -> Html {
+        let id = access_token.id;
+        let plan = match access_token.plan_id {
+            Some(plan_id) => self
+                .plans
+                .iter()
+                .find(|plan| plan.id == plan_id)
+                .map(|plan| plan.title.clone())
+                .unwrap_or_else(|| plan_id.to_string()),
+            None => "All my plans".to_string(),
+        };
+        let access = if access_token.read_only {
+            "Read only"
+        } else {
+            "Read and write"
+        };
+        let last_used = access_token
+            .last_used_date
+            .map(|last_used| last_used.format(TOKEN_TIME_FORMAT).to_string())
+            .unwrap_or_else(|| "Never".to_string());
+
+        html! {
+            <tr class="mdc-data-table__row">
+              <td class="mdc-data-table__cell">{ access_token.name.clone() }</td>
+              <td class="mdc-data-table__cell">{ plan }</td>
+              <td class="mdc-data-table__cell">{ access }</td>
+              <td class="mdc-data-table__cell">{ access_token.created_date.format(TOKEN_TIME_FORMAT).to_string() }</td>
+              <td class="mdc-data-table__cell">{ last_used }</td>
+              <td class="mdc-data-table__cell">
+                <Button text={"Revoke"} style={Style::Raised} onclick={link.callback(move |_| AccessTokensMsg::RevokeAccessToken(id))} />
+              </td>
+            </tr>
+        }
+    }
+}
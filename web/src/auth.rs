@@ -1,51 +1,58 @@
-﻿use crate::http::CustomError;
-use crate::{http, UserInfo};
-use endurance_racing_planner_common::{GoogleOpenIdClaims, User};
+﻿use crate::http::{self, client};
+use crate::UserInfo;
+use endurance_racing_planner_common::{
+    client::ClientError, IdentityProviderDto, OpenIdClaims, User,
+};
+use gloo_console::error;
 use gloo_storage::{LocalStorage, SessionStorage, Storage};
 use jwt_compact::alg::{Rsa, RsaPublicKey};
 use jwt_compact::jwk::JsonWebKey;
 use jwt_compact::{AlgorithmExt, Claims, ParseError, Token, UntrustedToken, ValidationError};
 use oauth2::basic::BasicClient;
-use oauth2::{
-    url::Url, AuthUrl, ClientId, CsrfToken, RedirectUrl, ResponseType, RevocationUrl, Scope,
-    TokenUrl,
-};
+use oauth2::{url::Url, AuthUrl, ClientId, CsrfToken, RedirectUrl, ResponseType, Scope};
 use serde::Deserialize;
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use wasm_bindgen_futures::spawn_local;
 use web_sys::{window, Location, Window};
+use yew::Callback;
 
 const NONCE_KEY: &str = "nonce";
 const STATE_KEY: &str = "state";
+/// The provider the user signed in with, they're sent back to it when their token expires
+const PROVIDER_KEY: &str = "identity_provider";
 pub const ID_TOKEN_KEY: &str = "id_token";
 
 #[derive(Deserialize)]
-struct GoogleDiscoveryResponse {
+struct DiscoveryDocument {
+    authorization_endpoint: String,
     jwks_uri: String,
 }
 
 #[derive(Deserialize)]
-struct GoogleSigningKey<'a> {
+struct SigningKey<'a> {
     kid: String,
     #[serde(flatten)]
     key: JsonWebKey<'a>,
 }
 
 #[derive(Deserialize)]
-struct GoogleSigningKeysResponse<'a> {
-    keys: Vec<GoogleSigningKey<'a>>,
+struct SigningKeysResponse<'a> {
+    keys: Vec<SigningKey<'a>>,
 }
 
 pub enum AuthError {
     TokenParseError(ParseError),
     TokenValidationError(ValidationError),
+    MismatchedIssuer,
     MismatchedNonce,
     MismatchedState,
     MissingIdTokenInResponse,
     MissingStateInResponse,
     MissingStateInStorage,
     MissingNonceInStorage,
+    MissingProviderInStorage,
     MissingTokenSigningKey,
     Other(String),
 }
@@ -55,12 +62,14 @@ impl Display for AuthError {
         match self {
             AuthError::TokenParseError(e) => write!(f, "{}", e),
             AuthError::TokenValidationError(e) => write!(f, "{}", e),
+            AuthError::MismatchedIssuer => write!(f, "the token wasn't issued by the provider"),
             AuthError::MismatchedNonce => write!(f, "mismatched nonce in the token response"),
             AuthError::MismatchedState => write!(f, "mismatched state in the token response"),
             AuthError::MissingIdTokenInResponse => write!(f, "missing id_token in the response"),
             AuthError::MissingStateInResponse => write!(f, "missing state in the response"),
             AuthError::MissingStateInStorage => write!(f, "missing state in session storage"),
             AuthError::MissingNonceInStorage => write!(f, "missing nonce in session storage"),
+            AuthError::MissingProviderInStorage => write!(f, "missing provider in local storage"),
             AuthError::MissingTokenSigningKey => write!(
                 f,
                 "missing signing key from discovery response used to sign token"
@@ -76,48 +85,71 @@ impl From<&str> for AuthError {
     }
 }
 
-fn create_auth_client() -> BasicClient {
-    let client_id = option_env!("OAUTH_CLIENT_ID")
-        .unwrap_or_else(|| dotenv!("OAUTH_CLIENT_ID"))
-        .to_string();
-    let google_client_id = ClientId::new(client_id);
-    let auth_url = AuthUrl::new("https://accounts.google.com/o/oauth2/v2/auth".to_string())
-        .expect("Invalid authorization endpoint URL");
-    let token_url = TokenUrl::new("https://www.googleapis.com/oauth2/v3/token".to_string())
-        .expect("Invalid token endpoint URL");
+fn create_auth_client(
+    provider: &IdentityProviderDto,
+    discovery_document: &DiscoveryDocument,
+) -> Result<BasicClient, AuthError> {
+    let client_id = ClientId::new(provider.client_id.clone());
+    let auth_url = AuthUrl::new(discovery_document.authorization_endpoint.clone())
+        .map_err(|_| AuthError::Other("invalid authorization endpoint url".into()))?;
 
     let redirect_url = window()
         .expect("window to be present")
         .location()
         .origin()
         .expect("location origin to be present");
-    // Set up the config for the Google OAuth2 process.
-    BasicClient::new(google_client_id, None, auth_url, Some(token_url))
-        .set_redirect_uri(RedirectUrl::new(redirect_url).expect("Invalid redirect URL"))
-        // Google supports OAuth 2.0 Token Revocation (RFC-7009)
-        .set_revocation_uri(
-            RevocationUrl::new("https://oauth2.googleapis.com/revoke".to_string())
-                .expect("Invalid revocation endpoint URL"),
-        )
+    Ok(BasicClient::new(client_id, None, auth_url, None)
+        .set_redirect_uri(RedirectUrl::new(redirect_url).expect("Invalid redirect URL")))
 }
 
+/// Signs in again with the provider the user last signed in with, or sends them to the landing
+/// page to pick one
 pub fn login() {
-    LocalStorage::clear();
+    match LocalStorage::get(PROVIDER_KEY) {
+        Ok(provider) => login_with(provider),
+        Err(_) => {
+            LocalStorage::clear();
+            let window: Window = window().expect("no global `window` object exists");
+            window
+                .location()
+                .set_href("/")
+                .expect("location couldn't be changed");
+        }
+    }
+}
+
+/// Redirects to the provider's authorization endpoint, which is found through its discovery
+/// document
+pub fn login_with(provider: IdentityProviderDto) {
+    spawn_local(async move {
+        let result = async {
+            let discovery_document = get_discovery_document(&provider.issuer)
+                .await
+                .map_err(|_| AuthError::Other("error getting the discovery document".into()))?;
+            redirect_to_provider(provider, &discovery_document)
+        };
+
+        if let Err(e) = result.await {
+            error!(format!("failed to sign in: {}", e).as_str());
+        }
+    })
+}
+
+fn redirect_to_provider(
+    provider: IdentityProviderDto,
+    discovery_document: &DiscoveryDocument,
+) -> Result<(), AuthError> {
     let nonce = CsrfToken::new_random();
     // Generate the authorization URL to which we'll redirect the user.
-    let (authorize_url, csrf_state) = create_auth_client()
+    let (authorize_url, csrf_state) = create_auth_client(&provider, discovery_document)?
         .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new(
-            "https://www.googleapis.com/auth/userinfo.email".to_string(),
-        ))
-        .add_scope(Scope::new(
-            "https://www.googleapis.com/auth/userinfo.profile".to_string(),
-        ))
-        .add_scope(Scope::new("openid".to_string()))
+        .add_scopes(provider.scopes.iter().cloned().map(Scope::new))
         .add_extra_param(NONCE_KEY, nonce.secret())
         .set_response_type(&ResponseType::new("token id_token".to_string()))
         .url();
 
+    LocalStorage::clear();
+    LocalStorage::set(PROVIDER_KEY, provider).expect("can't set local storage");
     SessionStorage::set(NONCE_KEY, nonce.secret().to_owned()).expect("can't set session storage");
     SessionStorage::set(STATE_KEY, csrf_state.secret().to_owned())
         .expect("can't set session storage");
@@ -127,6 +159,7 @@ pub fn login() {
     location
         .set_href(authorize_url.as_str())
         .expect("location couldn't be changed");
+    Ok(())
 }
 
 pub async fn handle_auth_code_redirect() -> Result<Option<UserInfo>, AuthError> {
@@ -147,31 +180,17 @@ pub async fn handle_auth_code_redirect() -> Result<Option<UserInfo>, AuthError>
                 .collect::<Vec<_>>();
 
             validate_state_parameter(&fragments)?;
-            let token = validate_id_token(&fragments).await?;
+            let provider: IdentityProviderDto =
+                LocalStorage::get(PROVIDER_KEY).map_err(|_| AuthError::MissingProviderInStorage)?;
+            let token = validate_id_token(&fragments, &provider).await?;
             validate_nonce(token.claims())?;
             let me = match get_me().await {
                 Ok(user) => Ok(user),
-                Err(e) => match e {
-                    CustomError::FailedRequest => match create_user(&token.claims().custom).await {
-                        Ok(created_user) => Ok(created_user),
-                        Err(_) => Err(AuthError::Other("failed to create a user".into())),
-                    },
-                    CustomError::TokenNotFound(e) => Err(AuthError::Other(format!(
-                        "failed to get me. token not found. {}",
-                        e
-                    ))),
-                    CustomError::BadToken(e) => Err(AuthError::Other(format!(
-                        "failed to get me. bad token. {}",
-                        e
-                    ))),
-                    CustomError::TokenExpired => Err(AuthError::Other(
-                        "failed to get me. token expired.".to_string(),
-                    )),
-                    CustomError::BadUrl(e) => Err(AuthError::Other(format!(
-                        "failed to get me. bad url. {}",
-                        e
-                    ))),
+                Err(e) if e.is_unauthorized() => match register_user().await {
+                    Ok(created_user) => Ok(created_user),
+                    Err(e) => Err(AuthError::Other(format!("failed to create a user. {}", e))),
                 },
+                Err(e) => Err(AuthError::Other(format!("failed to get me. {}", e))),
             }?;
 
             Ok(Some(UserInfo {
@@ -208,70 +227,89 @@ fn validate_state_parameter(fragments: &[(&str, &str)]) -> Result<(), AuthError>
 
 async fn validate_id_token(
     fragments: &[(&str, &str)],
-) -> Result<Token<GoogleOpenIdClaims>, AuthError> {
+    provider: &IdentityProviderDto,
+) -> Result<Token<OpenIdClaims>, AuthError> {
     let id_token = fragments.iter().find(|(key, _)| *key == "id_token");
     match id_token {
         Some((_, value)) => {
-            let signing_keys = get_google_signing_keys().await;
+            let signing_keys = get_signing_keys(&provider.issuer).await;
             signing_keys
                 .map_err(|_| AuthError::Other("error getting signing keys".into()))
                 .and_then(|signing_keys| {
                     LocalStorage::set(ID_TOKEN_KEY, value)
                         .expect("failed to set id token in local storage");
                     let token = UntrustedToken::new(value);
-                    token.map_err(AuthError::TokenParseError).and_then(|token| {
-                        let mut signing_key = &signing_keys.keys[0].key;
-                        let token_key_id = &token.header().key_id;
-                        if let Some(key_id) = token_key_id {
-                            signing_key = signing_keys
+                    token
+                        .map_err(AuthError::TokenParseError)
+                        .and_then(|token| {
+                            let key_id = token
+                                .header()
+                                .key_id
+                                .as_ref()
+                                .ok_or(AuthError::MissingTokenSigningKey)?;
+                            let signing_key = signing_keys
                                 .keys
                                 .iter()
                                 .find(|key| &key.kid == key_id)
                                 .map(|k| &k.key)
                                 .ok_or(AuthError::MissingTokenSigningKey)?;
-                        }
-                        let rsa_public_key = RsaPublicKey::try_from(signing_key).unwrap();
-                        let token_message = Rsa::rs256()
-                            .validate_integrity::<GoogleOpenIdClaims>(&token, &rsa_public_key);
-                        token_message.map_err(AuthError::TokenValidationError)
-                    })
+                            let rsa_public_key = RsaPublicKey::try_from(signing_key)
+                                .map_err(|_| AuthError::MissingTokenSigningKey)?;
+                            let token_message = Rsa::rs256()
+                                .validate_integrity::<OpenIdClaims>(&token, &rsa_public_key);
+                            token_message.map_err(AuthError::TokenValidationError)
+                        })
+                        .and_then(|token| {
+                            if token.claims().custom.iss == provider.issuer {
+                                Ok(token)
+                            } else {
+                                Err(AuthError::MismatchedIssuer)
+                            }
+                        })
                 })
         }
         None => Err(AuthError::MissingIdTokenInResponse),
     }
 }
 
-async fn get_google_signing_keys() -> Result<GoogleSigningKeysResponse<'static>, Box<dyn Error>> {
-    const GOOGLE_DISCOVERY_URL: &str =
-        "https://accounts.google.com/.well-known/openid-configuration";
+async fn get_discovery_document(issuer: &str) -> Result<DiscoveryDocument, Box<dyn Error>> {
+    let discovery_url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    );
 
-    let client = reqwest::Client::new();
-    let discovery_info = client
-        .get(GOOGLE_DISCOVERY_URL)
+    let discovery_document = reqwest::Client::new()
+        .get(discovery_url)
         .header("Accept", "application/json")
         .send()
         .await?
-        .json::<GoogleDiscoveryResponse>()
+        .json::<DiscoveryDocument>()
         .await?;
 
-    let signing_keys = client
-        .get(&discovery_info.jwks_uri)
+    Ok(discovery_document)
+}
+
+async fn get_signing_keys(issuer: &str) -> Result<SigningKeysResponse<'static>, Box<dyn Error>> {
+    let discovery_document = get_discovery_document(issuer).await?;
+
+    let signing_keys = reqwest::Client::new()
+        .get(&discovery_document.jwks_uri)
         .header("Accept", "application/json")
         .send()
         .await?
-        .json::<GoogleSigningKeysResponse>()
+        .json::<SigningKeysResponse>()
         .await?;
 
     Ok(signing_keys)
 }
 
-fn validate_nonce(data: &Claims<GoogleOpenIdClaims>) -> Result<(), AuthError> {
+fn validate_nonce(data: &Claims<OpenIdClaims>) -> Result<(), AuthError> {
     let nonce = SessionStorage::get(NONCE_KEY);
     nonce
         .map_err(|_| AuthError::MissingNonceInStorage)
         .and_then(|nonce: String| {
             SessionStorage::delete(NONCE_KEY);
-            if data.custom.nonce != nonce {
+            if data.custom.nonce.as_deref() != Some(nonce.as_str()) {
                 Err(AuthError::MismatchedNonce)
             } else {
                 Ok(())
@@ -279,19 +317,19 @@ fn validate_nonce(data: &Claims<GoogleOpenIdClaims>) -> Result<(), AuthError> {
         })
 }
 
-async fn create_user(claims: &GoogleOpenIdClaims) -> Result<User, Box<dyn Error>> {
-    let user = User {
-        id: 0,
-        name: claims.name.clone(),
-        email: claims.email.clone(),
-        oauth_id: claims.sub.clone(),
-    };
-
-    let new_user = http::post_async("users".to_string(), user).await;
+/// The api registers the user from their ID token, registering again returns the existing user
+async fn register_user() -> Result<User, ClientError> {
+    client().register_user().await
+}
 
-    Ok(new_user)
+pub async fn get_me() -> Result<User, ClientError> {
+    client().get_me().await
 }
 
-pub async fn get_me() -> Result<User, CustomError> {
-    http::get_async::<User>("users/me".to_string()).await
+/// The providers the user can sign in with
+pub fn get_providers(callback: Callback<Vec<IdentityProviderDto>>) {
+    http::spawn(
+        async move { client().get_providers().await },
+        Some(callback),
+    )
 }
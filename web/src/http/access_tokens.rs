@@ -0,0 +1,32 @@
+use endurance_racing_planner_common::access_token::{
+    AccessTokenDto, CreatedAccessTokenDto, NewAccessTokenDto,
+};
+use uuid::Uuid;
+use yew::Callback;
+
+use super::{client, spawn};
+
+pub fn get_access_tokens(callback: Callback<Vec<AccessTokenDto>>) {
+    spawn(
+        async move { client().get_access_tokens().await },
+        Some(callback),
+    )
+}
+
+/// The callback receives the token, it can't be fetched again
+pub fn create_access_token(
+    access_token: NewAccessTokenDto,
+    callback: Callback<CreatedAccessTokenDto>,
+) {
+    spawn(
+        async move { client().create_access_token(&access_token).await },
+        Some(callback),
+    )
+}
+
+pub fn revoke_access_token(id: Uuid, callback: Callback<String>) {
+    spawn(
+        async move { client().revoke_access_token(id).await },
+        Some(callback),
+    )
+}
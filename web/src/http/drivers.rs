@@ -1,17 +1,30 @@
-use endurance_racing_planner_common::Driver;
+use endurance_racing_planner_common::{client::ClientError, Driver};
 use uuid::Uuid;
 use yew::Callback;
 
-use super::{get_async, post, put, CustomError};
+use super::{client, spawn, BASE_PATH};
 
 pub fn create_plan_driver(plan_id: Uuid, driver: Driver, callback: Callback<Driver>) {
-    post(format!("plans/{}/drivers", plan_id), driver, Some(callback))
+    spawn(
+        async move { client().create_plan_driver(plan_id, &driver).await },
+        Some(callback),
+    )
 }
 
-pub async fn get_plan_drivers_async(plan_id: Uuid) -> Result<Vec<Driver>, CustomError> {
-    get_async(format!("plans/{}/drivers", plan_id)).await
+pub async fn get_plan_drivers_async(plan_id: Uuid) -> Result<Vec<Driver>, ClientError> {
+    client().get_plan_drivers(plan_id).await
 }
 
 pub fn update_driver(driver: Driver) {
-    put(format!("drivers/{}", driver.id), driver)
+    spawn(async move { client().update_driver(&driver).await }, None)
+}
+
+/// The iCalendar feed is fetched by calendar apps without a bearer token, so this is a plain url
+pub fn driver_calendar_url(plan_id: Uuid, driver_id: i32) -> String {
+    format!(
+        "{}/plans/{}/drivers/{}/calendar.ics",
+        BASE_PATH.trim_end_matches('/'),
+        plan_id,
+        driver_id
+    )
 }
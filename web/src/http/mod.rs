@@ -1,16 +1,24 @@
+pub mod access_tokens;
 pub mod drivers;
 pub mod plans;
+pub mod revisions;
+pub mod scenarios;
 pub mod schedules;
 
-use std::fmt::Debug;
+use std::{cell::RefCell, future::Future, rc::Rc, time::Duration};
 
-use endurance_racing_planner_common::GoogleOpenIdClaims;
+use endurance_racing_planner_common::{
+    client::{
+        ApiClient, ClientError, Credentials, HttpMethod, HttpRequest, HttpResponse, LocalFuture,
+        Transport,
+    },
+    OpenIdClaims,
+};
 use gloo_console::info;
-use gloo_storage::{errors::StorageError, LocalStorage, Storage};
-use jwt_compact::{ParseError, TimeOptions, UntrustedToken};
-use reqwest::{header::CONTENT_TYPE, Method, RequestBuilder, Url};
-use serde::{de::DeserializeOwned, Serialize};
-use wasm_bindgen_futures::spawn_local;
+use gloo_storage::{LocalStorage, Storage};
+use jwt_compact::{TimeOptions, UntrustedToken};
+use reqwest::{header::CONTENT_TYPE, Method, Url};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 use yew::Callback;
 
 use crate::auth::{login, ID_TOKEN_KEY};
@@ -20,173 +28,135 @@ const BASE_PATH: &str = match option_env!("API_BASE_PATH") {
     None => dotenv!("API_BASE_PATH"),
 };
 
-pub enum CustomError {
-    TokenNotFound(StorageError),
-    BadToken(ParseError),
-    TokenExpired,
-    BadUrl(oauth2::url::ParseError),
-    FailedRequest,
-}
-
-impl Debug for CustomError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::TokenNotFound(e) => write!(f, "TokenNotFound: {:?}", e),
-            Self::BadToken(e) => write!(f, "BadToken: {:?}", e),
-            Self::TokenExpired => write!(f, "TokenExpired"),
-            Self::BadUrl(e) => write!(f, "BadUrl: {:?}", e),
-            Self::FailedRequest => write!(f, "FailedRequest"),
+/// The signed in user's ID token, an expired or malformed token is removed from storage
+fn get_auth_token() -> Option<String> {
+    let token_string: String = LocalStorage::get(ID_TOKEN_KEY).ok()?;
+    let token = match UntrustedToken::new(token_string.as_str()) {
+        Ok(token) => token,
+        Err(_) => {
+            LocalStorage::delete(ID_TOKEN_KEY);
+            return None;
         }
+    };
+    let claims = token.deserialize_claims_unchecked::<OpenIdClaims>().ok()?;
+    if claims.validate_expiration(&TimeOptions::default()).is_err() {
+        LocalStorage::delete(ID_TOKEN_KEY);
+        return None;
     }
-}
 
-fn get_auth_token() -> Result<String, CustomError> {
-    LocalStorage::get(ID_TOKEN_KEY)
-        .map_err(CustomError::TokenNotFound)
-        .and_then(
-            move |token_string: String| match UntrustedToken::new(token_string.as_str()) {
-                Ok(token) => token
-                    .deserialize_claims_unchecked::<GoogleOpenIdClaims>()
-                    .map_err(|_| CustomError::TokenExpired)
-                    .and_then(
-                        |claims| match claims.validate_expiration(&TimeOptions::default()) {
-                            Ok(_) => Ok(()),
-                            Err(_) => {
-                                LocalStorage::delete(ID_TOKEN_KEY);
-                                Err(CustomError::TokenExpired)
-                            }
-                        },
-                    )
-                    .map(|_| token_string),
-                Err(e) => {
-                    LocalStorage::delete(ID_TOKEN_KEY);
-                    Err(CustomError::BadToken(e))
-                }
-            },
-        )
+    Some(token_string)
 }
 
-fn get_request_builder(method: Method, route: &str) -> Result<RequestBuilder, CustomError> {
-    let base_url = Url::parse(BASE_PATH).map_err(CustomError::BadUrl)?;
-    let client = reqwest::Client::new();
-    Ok(client
-        .request(method, base_url.join(route).map_err(CustomError::BadUrl)?)
-        .bearer_auth(get_auth_token()?))
-}
+/// The ID token in local storage. The implicit flow doesn't hand out refresh tokens, so a
+/// rejected token is only replaced when the user signed in again in another tab.
+struct StoredCredentials;
 
-fn handle_error(e: CustomError) {
-    match e {
-        CustomError::TokenNotFound(_) | CustomError::BadToken(_) | CustomError::TokenExpired => {
-            login()
-        }
-        CustomError::BadUrl(e) => info!(format!("{:?}", e)),
-        CustomError::FailedRequest => info!("request failed"),
+impl Credentials for StoredCredentials {
+    fn token(&self) -> Option<String> {
+        get_auth_token()
+    }
+
+    fn refresh(&self, rejected: &str) -> LocalFuture<Option<String>> {
+        let token = get_auth_token().filter(|token| token != rejected);
+        Box::pin(async move { token })
     }
 }
 
-pub fn post<T, U>(route: String, body: T, callback: Option<Callback<U>>)
-where
-    T: Serialize + DeserializeOwned + 'static,
-    U: Serialize + DeserializeOwned + 'static,
-{
-    spawn_local(async move {
-        match get_request_builder(Method::POST, &route) {
-            Ok(builder) => {
-                let response = builder
-                    .header(CONTENT_TYPE, "application/json")
-                    .body(serde_json::to_string(&body).unwrap())
-                    .send()
-                    .await
-                    .unwrap();
+struct ReqwestTransport;
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> LocalFuture<Result<HttpResponse, ClientError>> {
+        Box::pin(async move {
+            let url = Url::parse(BASE_PATH)
+                .and_then(|base_url| base_url.join(&request.route))
+                .map_err(|e| ClientError::Request(e.to_string()))?;
+            let method = match request.method {
+                HttpMethod::Get => Method::GET,
+                HttpMethod::Post => Method::POST,
+                HttpMethod::Put => Method::PUT,
+                HttpMethod::Patch => Method::PATCH,
+                HttpMethod::Delete => Method::DELETE,
+            };
+            let mut builder = reqwest::Client::new().request(method, url);
+            if let Some(token) = request.token {
+                builder = builder.bearer_auth(token);
+            }
+            if let Some(body) = request.body {
+                builder = builder.header(CONTENT_TYPE, "application/json").body(body);
+            }
 
-                if let Some(callback) = callback {
-                    let response = response.json::<U>().await.unwrap();
+            let response = builder
+                .send()
+                .await
+                .map_err(|e| ClientError::Offline(e.to_string()))?;
+            let status = response.status().as_u16();
+            let body = response
+                .text()
+                .await
+                .map_err(|e| ClientError::Offline(e.to_string()))?;
+
+            Ok(HttpResponse { status, body })
+        })
+    }
 
-                    callback.emit(response)
-                }
+    fn sleep(&self, duration: Duration) -> LocalFuture<()> {
+        let timeout = js_sys::Promise::new(&mut |resolve, _| {
+            if let Some(window) = web_sys::window() {
+                let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve,
+                    duration.as_millis() as i32,
+                );
             }
-            Err(e) => handle_error(e),
-        }
-    })
+        });
+        Box::pin(async move {
+            let _ = JsFuture::from(timeout).await;
+        })
+    }
 }
 
-pub async fn post_async<T>(route: String, body: T) -> T
-where
-    T: Serialize + DeserializeOwned + 'static,
-{
-    get_request_builder(Method::POST, &route)
-        .unwrap()
-        .header(CONTENT_TYPE, "application/json")
-        .body(serde_json::to_string(&body).unwrap())
-        .send()
-        .await
-        .unwrap()
-        .json::<T>()
-        .await
-        .unwrap()
+thread_local! {
+    static CLIENT: RefCell<ApiClient> =
+        RefCell::new(ApiClient::new(Rc::new(ReqwestTransport), Rc::new(StoredCredentials)));
 }
 
-pub fn get<T>(route: String, callback: Callback<T>)
-where
-    T: DeserializeOwned + 'static,
-{
-    spawn_local(async move {
-        match get_request_builder(Method::GET, &route) {
-            Ok(builder) => {
-                let response = builder.send().await.unwrap().json::<T>().await.unwrap();
-
-                callback.emit(response)
-            }
-            Err(e) => handle_error(e),
-        }
-    })
+/// The client every request to the api goes through
+pub fn client() -> ApiClient {
+    CLIENT.with(|client| client.borrow().clone())
 }
 
-pub async fn get_async<T>(route: String) -> Result<T, CustomError>
-where
-    T: DeserializeOwned + 'static,
-{
-    get_request_builder(Method::GET, &route)?
-        .send()
-        .await
-        .unwrap()
-        .json::<T>()
-        .await
-        .map_err(|_| CustomError::FailedRequest)
+/// Sends every following request through `client`, the tests give it a fake transport so
+/// components can be mounted without a server
+#[cfg(all(test, target_arch = "wasm32"))]
+pub fn set_client(client: ApiClient) {
+    CLIENT.with(|current| *current.borrow_mut() = client);
 }
 
-pub fn patch<T>(route: String, body: T)
-where
-    T: Serialize + DeserializeOwned + 'static,
-{
-    spawn_local(async move {
-        match get_request_builder(Method::PATCH, &route) {
-            Ok(builder) => {
-                builder
-                    .header(CONTENT_TYPE, "application/json")
-                    .body(serde_json::to_string(&body).unwrap())
-                    .send()
-                    .await
-                    .unwrap();
-            }
-            Err(e) => handle_error(e),
+/// Sends the user to sign in again when their token is missing or rejected, otherwise shows the
+/// error's message
+pub fn handle_error(e: ClientError) {
+    if e.is_unauthorized() {
+        login()
+    } else {
+        info!(format!("{:?}", e));
+        if let Some(window) = web_sys::window() {
+            let _ = window.alert_with_message(&e.to_string());
         }
-    })
+    }
 }
 
-pub fn put<T>(route: String, body: T)
+/// Sends the request in the background, the callback receives the response and an error goes to
+/// `handle_error`
+pub fn spawn<T, F>(request: F, callback: Option<Callback<T>>)
 where
-    T: Serialize + DeserializeOwned + 'static,
+    T: 'static,
+    F: Future<Output = Result<T, ClientError>> + 'static,
 {
     spawn_local(async move {
-        match get_request_builder(Method::PUT, &route) {
-            Ok(builder) => {
-                builder
-                    .header(CONTENT_TYPE, "application/json")
-                    .body(serde_json::to_string(&body).unwrap())
-                    .send()
-                    .await
-                    .unwrap();
+        match request.await {
+            Ok(response) => {
+                if let Some(callback) = callback {
+                    callback.emit(response)
+                }
             }
             Err(e) => handle_error(e),
         }
@@ -1,31 +1,37 @@
-use endurance_racing_planner_common::{PatchRacePlannerDto, PlanListDto, RacePlannerDto, User};
+use endurance_racing_planner_common::{
+    client::ClientError, PatchRacePlannerDto, PlanListDto, RacePlannerDto, User,
+};
 use uuid::Uuid;
 use yew::Callback;
 
-use super::{get, get_async, patch, post, CustomError};
-
-static PLANS_BASE_ROUTE: &str = "plans";
+use super::{client, spawn};
 
 pub fn create_plan(plan: RacePlannerDto, callback: Callback<RacePlannerDto>) {
-    post(PLANS_BASE_ROUTE.into(), plan, Some(callback))
+    spawn(
+        async move { client().create_plan(&plan).await },
+        Some(callback),
+    )
 }
 
 pub fn get_plans(callback: Callback<Vec<PlanListDto>>) {
-    get(PLANS_BASE_ROUTE.into(), callback)
+    spawn(async move { client().get_plans().await }, Some(callback))
 }
 
-pub async fn get_plan_async(id: Uuid) -> Result<RacePlannerDto, CustomError> {
-    get_async(format!("{}/{}", PLANS_BASE_ROUTE, id)).await
+pub async fn get_plan_async(id: Uuid) -> Result<RacePlannerDto, ClientError> {
+    client().get_plan(id).await
 }
 
 pub fn patch_plan(id: Uuid, plan: PatchRacePlannerDto) {
-    patch(format!("{}/{}", PLANS_BASE_ROUTE, id), plan)
+    spawn(async move { client().patch_plan(id, &plan).await }, None)
 }
 
 pub fn share_plan(id: Uuid, emails: Vec<String>) {
-    post::<Vec<String>, ()>(format!("{}/{}/share", PLANS_BASE_ROUTE, id), emails, None)
+    spawn(async move { client().share_plan(id, &emails).await }, None)
 }
 
 pub fn get_shared_users_for_plan(id: Uuid, callback: Callback<Vec<User>>) {
-    get(format!("{}/{}/share", PLANS_BASE_ROUTE, id), callback)
+    spawn(
+        async move { client().get_shared_users(id).await },
+        Some(callback),
+    )
 }
@@ -0,0 +1,29 @@
+use endurance_racing_planner_common::revision::PlanRevisionDto;
+use endurance_racing_planner_common::RacePlannerDto;
+use uuid::Uuid;
+use yew::Callback;
+
+use super::{client, spawn};
+
+pub fn get_plan_revisions(plan_id: Uuid, callback: Callback<Vec<PlanRevisionDto>>) {
+    spawn(
+        async move { client().get_plan_revisions(plan_id).await },
+        Some(callback),
+    )
+}
+
+/// The callback receives the whole plan after the revision was restored
+pub fn restore_revision(plan_id: Uuid, revision_id: i64, callback: Callback<RacePlannerDto>) {
+    spawn(
+        async move { client().restore_revision(plan_id, revision_id).await },
+        Some(callback),
+    )
+}
+
+/// The callback receives the whole plan after the revision was undone
+pub fn undo_revision(plan_id: Uuid, revision_id: i64, callback: Callback<RacePlannerDto>) {
+    spawn(
+        async move { client().undo_revision(plan_id, revision_id).await },
+        Some(callback),
+    )
+}
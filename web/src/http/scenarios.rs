@@ -0,0 +1,41 @@
+use endurance_racing_planner_common::scenario::{
+    NewScenarioDto, ScenarioComparisonDto, ScenarioDto,
+};
+use endurance_racing_planner_common::schedule::ScheduleStintDto;
+use uuid::Uuid;
+use yew::Callback;
+
+use super::{client, spawn};
+
+pub fn get_scenario_comparison(plan_id: Uuid, callback: Callback<Vec<ScenarioComparisonDto>>) {
+    spawn(
+        async move { client().get_scenario_comparison(plan_id).await },
+        Some(callback),
+    )
+}
+
+pub fn create_scenario(plan_id: Uuid, scenario: NewScenarioDto, callback: Callback<ScenarioDto>) {
+    spawn(
+        async move { client().create_scenario(plan_id, &scenario).await },
+        Some(callback),
+    )
+}
+
+/// The callback receives the schedule of the newly active scenario
+pub fn activate_scenario(
+    plan_id: Uuid,
+    scenario_id: Uuid,
+    callback: Callback<Vec<ScheduleStintDto>>,
+) {
+    spawn(
+        async move { client().activate_scenario(plan_id, scenario_id).await },
+        Some(callback),
+    )
+}
+
+pub fn delete_scenario(plan_id: Uuid, scenario_id: Uuid, callback: Callback<String>) {
+    spawn(
+        async move { client().delete_scenario(plan_id, scenario_id).await },
+        Some(callback),
+    )
+}
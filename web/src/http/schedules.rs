@@ -1,16 +1,82 @@
-use endurance_racing_planner_common::schedule::ScheduleStintDto;
+use endurance_racing_planner_common::{client::ClientError, schedule::ScheduleStintDto};
+use gloo_console::error;
 use uuid::Uuid;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+use yew::Callback;
 
-use super::{get_async, post, put, CustomError};
+use super::{client, spawn};
 
 pub fn create_schedule(plan_id: Uuid, schedule: Vec<ScheduleStintDto>) {
-    post::<Vec<ScheduleStintDto>, ()>(format!("plans/{}/schedule", plan_id), schedule, None)
+    spawn(
+        async move { client().create_schedule(plan_id, &schedule).await },
+        None,
+    )
 }
 
-pub async fn get_schedule_async(plan_id: Uuid) -> Result<Vec<ScheduleStintDto>, CustomError> {
-    get_async(format!("plans/{}/schedule", plan_id)).await
+pub async fn get_schedule_async(plan_id: Uuid) -> Result<Vec<ScheduleStintDto>, ClientError> {
+    client().get_schedule(plan_id).await
 }
 
 pub fn update_schedule(plan_id: Uuid, schedule: Vec<ScheduleStintDto>) {
-    put::<Vec<ScheduleStintDto>>(format!("plans/{}/schedule", plan_id), schedule)
+    spawn(
+        async move { client().update_schedule(plan_id, &schedule).await },
+        None,
+    )
+}
+
+pub fn insert_stint(plan_id: Uuid, stint_id: Uuid, callback: Callback<Vec<ScheduleStintDto>>) {
+    spawn(
+        async move { client().insert_stint(plan_id, stint_id).await },
+        Some(callback),
+    )
+}
+
+pub fn split_stint(plan_id: Uuid, stint_id: Uuid, callback: Callback<Vec<ScheduleStintDto>>) {
+    spawn(
+        async move { client().split_stint(plan_id, stint_id).await },
+        Some(callback),
+    )
+}
+
+pub fn delete_stint(plan_id: Uuid, stint_id: Uuid, callback: Callback<Vec<ScheduleStintDto>>) {
+    spawn(
+        async move { client().delete_stint(plan_id, stint_id).await },
+        Some(callback),
+    )
+}
+
+/// Downloads the schedule as CSV. The export needs the user's token, so it is fetched through the
+/// client and saved from a blob instead of linking to the api.
+pub fn download_schedule_csv(plan_id: Uuid) {
+    spawn(
+        async move { client().get_schedule_csv(plan_id).await },
+        Some(Callback::from(|csv: String| {
+            if let Err(e) = save_file("schedule.csv", "text/csv", &csv) {
+                error!(e);
+            }
+        })),
+    )
+}
+
+fn save_file(file_name: &str, content_type: &str, contents: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("there is no document to save the file from"))?;
+    let mut options = BlobPropertyBag::new();
+    options.type_(content_type);
+    let blob = Blob::new_with_str_sequence_and_options(
+        &js_sys::Array::of1(&JsValue::from_str(contents)),
+        &options,
+    )?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    Url::revoke_object_url(&url)
 }
@@ -1,20 +1,25 @@
-﻿use crate::auth::login;
+﻿use crate::auth::{get_providers, login_with};
 use crate::http::plans::get_plans;
 use crate::planner::PlannerRoutes;
 use crate::{AppState, AppStateAction, AppStateContext, UserInfo};
-use endurance_racing_planner_common::PlanListDto;
+use endurance_racing_planner_common::{IdentityProviderDto, PlanListDto};
 use uuid::Uuid;
 use yew::context::ContextHandle;
 use yew::prelude::*;
 use yew::{Component, Html};
 use yew_mdc::components::{
+    button::{Button, Style},
     list::item::{LeadingType, TrailingType},
     Card, List, ListItem, PrimaryAction,
 };
 use yew_router::prelude::*;
 
+/// Google's sign in button follows their branding guidelines, every other provider gets a plain one
+const GOOGLE_ISSUER: &str = "https://accounts.google.com";
+
 pub struct Landing {
     google_login_image: String,
+    providers: Vec<IdentityProviderDto>,
     user: Option<UserInfo>,
     _app_state_context_handle: ContextHandle<AppStateContext>,
     app_state_context: UseReducerHandle<AppState>,
@@ -31,7 +36,8 @@ pub enum MouseEventType {
 
 pub enum LandingMsg {
     OnMouseEvent(MouseEventType),
-    OnLoginClick,
+    OnLoginClick(usize),
+    UpdateProviders(Vec<IdentityProviderDto>),
     OnAppStateContextUpdate(AppStateContext),
     UpdatePlans(Vec<PlanListDto>),
     OpenPlan(Uuid, String),
@@ -68,9 +74,12 @@ impl Component for Landing {
 
         if app_state_context.user_info.is_some() {
             get_plans(ctx.link().callback(LandingMsg::UpdatePlans));
+        } else {
+            get_providers(ctx.link().callback(LandingMsg::UpdateProviders));
         }
         Self {
             google_login_image: "btn_google_signin_light_normal_web.png".to_string(),
+            providers: vec![],
             user: app_state_context.user_info.clone(),
             _app_state_context_handle: context_listener,
             app_state_context,
@@ -90,10 +99,16 @@ impl Component for Landing {
                 };
                 true
             }
-            LandingMsg::OnLoginClick => {
-                login();
+            LandingMsg::OnLoginClick(index) => {
+                if let Some(provider) = self.providers.get(index) {
+                    login_with(provider.clone());
+                }
                 false
             }
+            LandingMsg::UpdateProviders(providers) => {
+                self.providers = providers;
+                true
+            }
             LandingMsg::OnAppStateContextUpdate(app_state_context) => {
                 if self.user != app_state_context.user_info {
                     self.user = app_state_context.user_info.clone();
@@ -132,7 +147,6 @@ impl Component for Landing {
         let on_mouse_out = mouse_events(MouseEventType::Out);
         let on_mouse_down = mouse_events(MouseEventType::Down);
         let on_mouse_up = mouse_events(MouseEventType::Up);
-        let on_login_click = link.callback(|_| LandingMsg::OnLoginClick);
 
         match &self.user {
             Some(_) => {
@@ -184,12 +198,29 @@ impl Component for Landing {
                             <div class="mdc-card-wrapper__text-section">
                                 <div class="card-title">{ "Login" }</div>
                             </div>
-                            <img src={format!("images/{}", self.google_login_image)} alt="Sign in with Google" width="191" height="46"
-                                onmouseover={on_mouse_over}
-                                onmouseout={on_mouse_out}
-                                onmousedown={on_mouse_down}
-                                onmouseup={on_mouse_up}
-                                onclick={on_login_click} />
+                            {
+                                self.providers
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(index, provider)| {
+                                        let on_login_click = link.callback(move |_: MouseEvent| LandingMsg::OnLoginClick(index));
+                                        if provider.issuer == GOOGLE_ISSUER {
+                                            html! {
+                                                <img src={format!("images/{}", self.google_login_image)} alt="Sign in with Google" width="191" height="46"
+                                                    onmouseover={on_mouse_over.clone()}
+                                                    onmouseout={on_mouse_out.clone()}
+                                                    onmousedown={on_mouse_down.clone()}
+                                                    onmouseup={on_mouse_up.clone()}
+                                                    onclick={on_login_click} />
+                                            }
+                                        } else {
+                                            html! {
+                                                <Button text={format!("Sign in with {}", provider.name)} style={Style::Raised} onclick={on_login_click} />
+                                            }
+                                        }
+                                    })
+                                    .collect::<Html>()
+                            }
                         </div>
                     </div>
                 }
@@ -1,10 +1,12 @@
+use crate::access_tokens::AccessTokens;
 use crate::auth::{get_me, handle_auth_code_redirect, login, ID_TOKEN_KEY};
 use crate::event_bus::{EventBus, EventBusInput};
+use crate::http::handle_error;
 use crate::landing::Landing;
 use crate::loading::Loading;
 use crate::planner::{Planner, RacePlannerProvider};
 use crate::share_plan::SharePlan;
-use endurance_racing_planner_common::GoogleOpenIdClaims;
+use endurance_racing_planner_common::OpenIdClaims;
 use gloo_console::error;
 use gloo_storage::{LocalStorage, Storage};
 use jwt_compact::UntrustedToken;
@@ -26,19 +28,28 @@ use yew_router::prelude::*;
 #[macro_use]
 extern crate dotenv_codegen;
 
+mod access_tokens;
 mod auth;
 mod bindings;
 mod duration_serde;
 mod event_bus;
 mod http;
 mod landing;
+mod load_error;
 mod loading;
 mod md_text_field;
 mod overview;
+mod plan_history;
 mod planner;
 mod roster;
+mod scenarios;
 mod schedule;
 mod share_plan;
+#[cfg(all(test, target_arch = "wasm32"))]
+mod test_support;
+
+#[cfg(all(test, target_arch = "wasm32"))]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
 #[derive(Routable, Clone, Eq, PartialEq, Copy)]
 enum AppRoutes {
@@ -46,6 +57,8 @@ enum AppRoutes {
     Landing,
     #[at("/planner/*")]
     Planner,
+    #[at("/tokens")]
+    AccessTokens,
 }
 
 impl Display for AppRoutes {
@@ -53,6 +66,7 @@ impl Display for AppRoutes {
         match self {
             AppRoutes::Landing => f.write_str("My Plans"),
             AppRoutes::Planner => f.write_str("Planner"),
+            AppRoutes::AccessTokens => f.write_str("Access Tokens"),
         }
     }
 }
@@ -75,13 +89,20 @@ fn switch(switch: &AppRoutes) -> Html {
                 <Landing />
             }
         }
+        AppRoutes::AccessTokens =>
+        {
+            #[allow(clippy::needless_return, clippy::let_unit_value)]
+            return html! {
+                <AccessTokens />
+            }
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct UserInfo {
     pub email: String,
-    pub picture: String,
+    pub picture: Option<String>,
     pub name: String,
 }
 
@@ -148,6 +169,16 @@ pub fn app() -> Html {
             app_state_context.dispatch(AppStateAction::SetSidebarOpen(false));
         })
     };
+    let access_tokens_onclick = {
+        let app_state_context = app_state_context.clone();
+        Callback::from(move |_| {
+            app_state_context.dispatch(AppStateAction::SetPageTitle(
+                AppRoutes::AccessTokens.to_string(),
+            ));
+            app_state_context.dispatch(AppStateAction::SetPlanId(None));
+            app_state_context.dispatch(AppStateAction::SetSidebarOpen(false));
+        })
+    };
 
     #[allow(clippy::let_unit_value)]
     let _handle_auth_redirect = {
@@ -165,7 +196,7 @@ pub fn app() -> Html {
                                 Ok(user) => {
                                     let parsed_token = UntrustedToken::new(&id_token).unwrap();
                                     let claims = parsed_token
-                                        .deserialize_claims_unchecked::<GoogleOpenIdClaims>()
+                                        .deserialize_claims_unchecked::<OpenIdClaims>()
                                         .unwrap();
                                     is_loading.set(false);
                                     app_state_context.dispatch(AppStateAction::SetUser(Some(
@@ -176,7 +207,11 @@ pub fn app() -> Html {
                                         },
                                     )))
                                 }
-                                Err(_) => login(),
+                                Err(e) if e.is_unauthorized() => login(),
+                                Err(e) => {
+                                    is_loading.set(false);
+                                    handle_error(e)
+                                }
                             }
                         } else {
                             is_loading.set(true);
@@ -222,6 +257,13 @@ pub fn app() -> Html {
                                     <span class="mdc-deprecated-list-item__text">{ "My Plans" }</span>
                                 </Link<AppRoutes>>
                             </div>
+                            <div onclick={ access_tokens_onclick }>
+                                <Link<AppRoutes> to={AppRoutes::AccessTokens} classes="mdc-deprecated-list-item">
+                                    <span class="mdc-deprecated-list-item__ripple"></span>
+                                    <i class="material-icons mdc-deprecated-list-item__graphic" aria-hidden="true">{ "vpn_key" }</i>
+                                    <span class="mdc-deprecated-list-item__text">{ "Access Tokens" }</span>
+                                </Link<AppRoutes>>
+                            </div>
                         </nav>
                     </DrawerContent>
                 </Drawer>
@@ -298,7 +340,16 @@ pub fn header() -> Html {
         let profile_picture_section = if let Some(user) = &app_state_context.user_info {
             html! {
                 <div class="mdc-menu-surface--anchor">
-                    <img id="profile-picture" class="mdc-top-app-bar__action-item" src={user.picture.clone()} onclick={profile_picture_onclick} />
+                    {
+                        match &user.picture {
+                            Some(picture) => html! {
+                                <img id="profile-picture" class="mdc-top-app-bar__action-item" src={picture.clone()} onclick={profile_picture_onclick} />
+                            },
+                            None => html! {
+                                <i id="profile-picture" class="material-icons mdc-top-app-bar__action-item" onclick={profile_picture_onclick}>{ "account_circle" }</i>
+                            },
+                        }
+                    }
                     <Menu open={state.profile_menu_open} onclose={profile_menu_onclose} corner={Corner::BottomLeft} fixed_position={true}>
                         <MenuItem text={user.name.clone()} />
                         <MenuItem text={user.email.clone()} />
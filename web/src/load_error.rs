@@ -0,0 +1,41 @@
+use endurance_racing_planner_common::client::ClientError;
+use yew::prelude::*;
+use yew_mdc::components::button::{Button, Style};
+
+#[derive(Properties, PartialEq)]
+pub struct LoadErrorProps {
+    /// What failed to load, e.g. "the plan"
+    pub subject: String,
+    pub error: ClientError,
+    pub onretry: Callback<MouseEvent>,
+}
+
+/// Shown in place of a page whose data couldn't be loaded, with a retry instead of an empty page
+#[function_component(LoadError)]
+pub fn load_error(props: &LoadErrorProps) -> Html {
+    let (icon, title, message) = if props.error.is_offline() {
+        (
+            "cloud_off",
+            "You're offline".to_string(),
+            format!(
+                "The planner couldn't reach the server to load {}. Check your connection and try again.",
+                props.subject
+            ),
+        )
+    } else {
+        (
+            "error_outline",
+            format!("Couldn't load {}", props.subject),
+            props.error.to_string(),
+        )
+    };
+
+    html! {
+        <div class="load-error">
+            <i class="material-icons" aria-hidden="true">{ icon }</i>
+            <h2>{ title }</h2>
+            <p>{ message }</p>
+            <Button text={"Retry"} style={Style::Raised} onclick={props.onretry.clone()} />
+        </div>
+    }
+}
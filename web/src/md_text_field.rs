@@ -59,6 +59,10 @@ pub struct MaterialTextFieldProps {
     // Defaults to None
     #[prop_or_default]
     pub icon: Option<MaterialTextFieldIcon>,
+    // The validation message to show under the text field, the field is styled as invalid while set
+    // Defaults to None
+    #[prop_or_default]
+    pub error: Option<String>,
 }
 
 pub struct MaterialTextField;
@@ -85,7 +89,7 @@ impl Component for MaterialTextField {
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let Self::Properties { id, value, label, disabled, end_aligned, icon, .. } = ctx.props().clone();
+        let Self::Properties { id, value, label, disabled, end_aligned, icon, error, .. } = ctx.props().clone();
         let onchange = ctx.link().batch_callback(|event: Event| {
             let target: Option<EventTarget> = event.target();
             let input = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
@@ -97,16 +101,20 @@ impl Component for MaterialTextField {
             disabled.as_some("mdc-text-field--disabled"), 
             label.is_none().as_some("mdc-text-field--no-label"),
             end_aligned.as_some("mdc-text-field--end-aligned"),
-            icon.clone().map(|value| format!("mdc-text-field--with-{}-icon", value.style))
+            icon.clone().map(|value| format!("mdc-text-field--with-{}-icon", value.style)),
+            error.is_some().as_some("mdc-text-field--invalid")
         );
         html! {
-            <label class={classes}>
-                <span class="mdc-text-field__ripple"></span>
-                { render_label(label, id.clone()) }
-                { render_icon(icon) }
-                <input class="mdc-text-field__input" type="text" disabled={disabled} value={value} onchange={onchange} aria-labelledby={id.clone()} />
-                <span class="mdc-line-ripple"></span>
-            </label>
+            <>
+                <label class={classes}>
+                    <span class="mdc-text-field__ripple"></span>
+                    { render_label(label, id.clone()) }
+                    { render_icon(icon) }
+                    <input class="mdc-text-field__input" type="text" disabled={disabled} value={value} onchange={onchange} aria-labelledby={id.clone()} aria-invalid={error.is_some().to_string()} />
+                    <span class="mdc-line-ripple"></span>
+                </label>
+                { render_error(error) }
+            </>
         }
     }
 }
@@ -120,6 +128,17 @@ fn render_label(label: Option<String>, id: String) -> Html {
     }
 }
 
+fn render_error(error: Option<String>) -> Html {
+    match error {
+        None => html! {},
+        Some(message) => html! {
+            <div class="mdc-text-field-helper-line">
+                <div class="mdc-text-field-helper-text mdc-text-field-helper-text--persistent mdc-text-field-helper-text--validation-msg" role="alert">{ message }</div>
+            </div>
+        }
+    }
+}
+
 fn render_icon(icon: Option<MaterialTextFieldIcon>) -> Html {
     match icon {
         None => html! {},
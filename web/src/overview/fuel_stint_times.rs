@@ -392,6 +392,8 @@ fn send_patch_request(plan_id: Uuid, data: StintData, stint_type: &StintType) {
                     },
                 }),
                 time_of_day_lap_factors: None,
+                track_conditions: None,
+                traffic_model: None,
                 per_driver_lap_factors: None,
                 driver_roster: None,
                 schedule_rows: None,
@@ -7,13 +7,15 @@ use self::fuel_stint_times::{FuelStintTimes, FuelStintTimesProps};
 use self::overall_event_config::EventConfig;
 use self::overall_fuel_stint_config::OverallFuelStintConfig;
 use self::per_driver_lap_factors::PerDriverLapFactors;
-use self::time_of_day_lap_factors::TimeOfDayLapFactors;
+use self::track_conditions::TrackConditions;
+use self::traffic_model::TrafficModel;
 
 pub(crate) mod fuel_stint_times;
 pub(crate) mod overall_event_config;
 pub(crate) mod overall_fuel_stint_config;
 pub(crate) mod per_driver_lap_factors;
-pub(crate) mod time_of_day_lap_factors;
+pub(crate) mod track_conditions;
+pub(crate) mod traffic_model;
 
 pub struct Overview {
     _context_listener: ContextHandle<RacePlannerContext>,
@@ -87,7 +89,12 @@ impl Component for Overview {
                         <FuelStintTimes ..fuel_stint_times_props />
                     </div>
                     <div class="flex-container flex-row">
-                        <TimeOfDayLapFactors lap_time={standard_lap_time} />
+                        <TrackConditions lap_time={standard_lap_time} />
+                    </div>
+                    <div class="flex-container flex-row">
+                        <TrafficModel />
+                    </div>
+                    <div class="flex-container flex-row">
                         <PerDriverLapFactors lap_time={standard_lap_time} />
                     </div>
                     <div class="flex-container flex-row">
@@ -8,6 +8,8 @@ use crate::{
     },
 };
 use chrono::{NaiveDateTime, TimeZone, Utc};
+use endurance_racing_planner_common::api_error::FieldErrorDto;
+use endurance_racing_planner_common::validation::{field_error, Validate};
 use endurance_racing_planner_common::{EventConfigDto, PatchRacePlannerDto};
 use gloo_console::error;
 use uuid::Uuid;
@@ -22,9 +24,12 @@ pub enum EventConfigMsg {
     OnCreate(Uuid, EventConfigDto),
 }
 
+const DURATION_ERROR: &str = "must be a duration like HH:MM:SS";
+
 pub struct EventConfig {
     data: EventConfigDto,
     plan_id: Uuid,
+    errors: Vec<FieldErrorDto>,
     _planner_context_listener: ContextHandle<RacePlannerContext>,
 }
 
@@ -55,6 +60,7 @@ impl Component for EventConfig {
                 .clone()
                 .unwrap_or_else(EventConfigDto::new),
             plan_id: planner_context.data.id,
+            errors: vec![],
             _planner_context_listener: planner_context_listener,
         }
     }
@@ -67,25 +73,13 @@ impl Component for EventConfig {
                     Ok(duration) => {
                         self.data.green_flag_offset = duration;
                         self.data.update_race_times();
-
-                        patch_plan(
-                            self.plan_id,
-                            PatchRacePlannerDto {
-                                id: self.plan_id,
-                                title: None,
-                                overall_event_config: Some(self.data.clone()),
-                                overall_fuel_stint_config: None,
-                                fuel_stint_average_times: None,
-                                time_of_day_lap_factors: None,
-                                per_driver_lap_factors: None,
-                                schedule_rows: None,
-                                driver_roster: None,
-                            },
-                        );
+                        self.patch_event_config();
                         should_update = true;
                     }
                     Err(e) => {
                         error!(format!("green flag offset parse failure: {:?}", e).as_str());
+                        self.set_error("greenFlagOffset", Some(DURATION_ERROR));
+                        return true;
                     }
                 }
             }
@@ -122,10 +116,13 @@ impl Component for EventConfig {
                     Ok(duration) => {
                         self.data.race_duration = duration;
                         self.data.update_race_times();
+                        self.errors = self.data.validate().err().unwrap_or_default();
                         should_update = true;
                     }
                     Err(e) => {
                         error!(format!("race duration parse failure: {:?}", e).as_str());
+                        self.set_error("raceDuration", Some(DURATION_ERROR));
+                        return true;
                     }
                 }
             }
@@ -153,7 +150,8 @@ impl Component for EventConfig {
             label: Some("Race Duration (HH:MM:SS)".to_string()),
             id: "race_duration".to_string(),
             disabled: false,
-            on_change: link.callback(EventConfigMsg::ChangeRaceDuration)
+            on_change: link.callback(EventConfigMsg::ChangeRaceDuration),
+            error: field_error(&self.errors, "raceDuration")
         }};
         let session_start_utc_text_field_props = props! {MaterialTextFieldProps {
             value: format_date_time(self.data.session_start_utc.naive_utc()),
@@ -192,7 +190,8 @@ impl Component for EventConfig {
             label: Some("Green Flag Offset (HH:MM:SS)".to_string()),
             id: "green-flag-offset".to_string(),
             disabled: false,
-            on_change: link.callback(EventConfigMsg::ChangeGreenFlagOffset)
+            on_change: link.callback(EventConfigMsg::ChangeGreenFlagOffset),
+            error: field_error(&self.errors, "greenFlagOffset")
         }};
         let tod_offset_text_field_props = props! {MaterialTextFieldProps {
             value: format_duration(self.data.tod_offset, DurationFormat::HourMinSec),
@@ -226,6 +225,39 @@ impl Component for EventConfig {
 }
 
 impl EventConfig {
+    fn set_error(&mut self, field: &str, message: Option<&str>) {
+        self.errors.retain(|error| error.field != field);
+        if let Some(message) = message {
+            self.errors.push(FieldErrorDto::new(field, message));
+        }
+    }
+
+    /// Saves the config when it passes the same rules the api uses, otherwise the errors are shown
+    /// next to the fields until they're fixed
+    fn patch_event_config(&mut self) {
+        self.errors = self.data.validate().err().unwrap_or_default();
+        if !self.errors.is_empty() {
+            return;
+        }
+
+        patch_plan(
+            self.plan_id,
+            PatchRacePlannerDto {
+                id: self.plan_id,
+                title: None,
+                overall_event_config: Some(self.data.clone()),
+                overall_fuel_stint_config: None,
+                fuel_stint_average_times: None,
+                time_of_day_lap_factors: None,
+                track_conditions: None,
+                traffic_model: None,
+                per_driver_lap_factors: None,
+                schedule_rows: None,
+                driver_roster: None,
+            },
+        );
+    }
+
     fn update_planner_context(&self, ctx: &Context<Self>) {
         let (planner_context, _) = ctx
             .link()
@@ -234,3 +266,109 @@ impl EventConfig {
         planner_context.dispatch(RacePlannerAction::SetOverallEventConfig(self.data.clone()));
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::{EventConfig, DURATION_ERROR};
+    use crate::planner::{format_date_time, PlannerRoutes};
+    use crate::test_support::{event_config, mount, settle, FakeTransport, Mounted};
+    use chrono::Duration;
+    use endurance_racing_planner_common::{
+        client::HttpMethod, PatchRacePlannerDto, RacePlannerDto,
+    };
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use yew::html;
+
+    const RACE_DURATION: &str = "input[aria-labelledby='race_duration']";
+    const GREEN_FLAG_OFFSET: &str = "input[aria-labelledby='green-flag-offset']";
+
+    fn mount_event_config(plan: &RacePlannerDto) -> Mounted {
+        mount(
+            plan.clone(),
+            PlannerRoutes::Overview { id: plan.id },
+            html! { <EventConfig /> },
+        )
+    }
+
+    fn plan() -> RacePlannerDto {
+        RacePlannerDto {
+            overall_event_config: Some(event_config()),
+            ..RacePlannerDto::new()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn the_plans_event_config_is_shown() {
+        FakeTransport::install();
+        let plan = plan();
+        let event_config = event_config();
+        let mounted = mount_event_config(&plan);
+
+        assert_eq!("06:00:00", mounted.value(RACE_DURATION));
+        assert_eq!("00:40:00", mounted.value(GREEN_FLAG_OFFSET));
+        assert_eq!(
+            format_date_time(event_config.race_end_utc.naive_utc()),
+            mounted.value("input[aria-labelledby='race-end-utc']")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn changing_the_race_duration_moves_the_race_end() {
+        FakeTransport::install();
+        let plan = plan();
+        let mounted = mount_event_config(&plan);
+
+        mounted.change_text_field(RACE_DURATION, "12:00:00");
+        settle().await;
+
+        let race_end_utc = event_config().race_start_utc + Duration::hours(12);
+        assert_eq!(
+            format_date_time(race_end_utc.naive_utc()),
+            mounted.value("input[aria-labelledby='race-end-utc']")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn changing_the_green_flag_offset_saves_the_event_config() {
+        let http = FakeTransport::install();
+        let plan = plan();
+        let mounted = mount_event_config(&plan);
+
+        mounted.change_text_field(GREEN_FLAG_OFFSET, "00:30:00");
+        settle().await;
+
+        let patch: PatchRacePlannerDto =
+            http.body(HttpMethod::Patch, &format!("plans/{}", plan.id));
+        let saved = patch
+            .overall_event_config
+            .expect("the event config to be saved");
+        assert_eq!(Duration::minutes(30), saved.green_flag_offset);
+        assert_eq!(
+            event_config().session_start_utc + Duration::minutes(30),
+            saved.race_start_utc
+        );
+        assert_eq!(
+            format_date_time(saved.race_start_utc.naive_utc()),
+            mounted.value("input[aria-labelledby='race-start-utc']")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn an_offset_that_is_not_a_duration_is_shown_and_not_saved() {
+        let http = FakeTransport::install();
+        let plan = plan();
+        let mounted = mount_event_config(&plan);
+
+        mounted.change_text_field(GREEN_FLAG_OFFSET, "soon");
+        settle().await;
+
+        assert_eq!(
+            DURATION_ERROR,
+            mounted
+                .query(".mdc-text-field-helper-text")
+                .text_content()
+                .unwrap_or_default()
+        );
+        assert!(http.requests().is_empty());
+    }
+}
@@ -3,8 +3,9 @@ use crate::md_text_field::{MaterialTextField, MaterialTextFieldProps};
 use crate::planner::{
     format_duration, parse_duration_from_str, DurationFormat, RacePlannerAction, RacePlannerContext,
 };
+use endurance_racing_planner_common::api_error::FieldErrorDto;
+use endurance_racing_planner_common::validation::{field_error, Validate};
 use endurance_racing_planner_common::{OverallFuelStintConfigData, PatchRacePlannerDto};
-use gloo_console::error;
 use web_sys::HtmlInputElement;
 use yew::context::ContextHandle;
 use yew::prelude::*;
@@ -16,15 +17,57 @@ pub enum OverallFuelStintMessage {
     UpdateFuelTankSize(String),
     UpdateTireChangeTime(String),
     UpdateAddTireTire(bool),
+    UpdateTireDegradationPerLap(String),
+    UpdateMaxStintsPerTireSet(String),
     OnCreate(OverallFuelStintConfigData),
 }
 
 pub struct OverallFuelStintConfig {
     data: OverallFuelStintConfigData,
+    errors: Vec<FieldErrorDto>,
     add_tire_time_input_ref: NodeRef,
     _planner_context_listener: ContextHandle<RacePlannerContext>,
 }
 
+const DURATION_ERROR: &str = "must be a duration like MM:SS.mmm";
+const WHOLE_NUMBER_ERROR: &str = "must be a whole number";
+
+impl OverallFuelStintConfig {
+    /// Replaces the error shown for `field`, `None` clears it
+    fn set_error(&mut self, field: &str, message: Option<&str>) {
+        self.errors.retain(|error| error.field != field);
+        if let Some(message) = message {
+            self.errors.push(FieldErrorDto::new(field, message));
+        }
+    }
+
+    /// Saves the config when it passes the same rules the api uses, otherwise the errors are shown
+    /// next to the fields until they're fixed
+    fn patch_fuel_stint_config(&mut self, planner_context: &RacePlannerContext) {
+        self.errors = self.data.validate().err().unwrap_or_default();
+        if !self.errors.is_empty() {
+            return;
+        }
+
+        patch_plan(
+            planner_context.data.id,
+            PatchRacePlannerDto {
+                id: planner_context.data.id,
+                title: None,
+                overall_event_config: None,
+                overall_fuel_stint_config: Some(self.data.clone()),
+                fuel_stint_average_times: None,
+                time_of_day_lap_factors: None,
+                track_conditions: None,
+                traffic_model: None,
+                per_driver_lap_factors: None,
+                driver_roster: None,
+                schedule_rows: None,
+            },
+        );
+    }
+}
+
 impl Component for OverallFuelStintConfig {
     type Message = OverallFuelStintMessage;
     type Properties = ();
@@ -52,6 +95,7 @@ impl Component for OverallFuelStintConfig {
                 .as_ref()
                 .cloned()
                 .unwrap_or_else(OverallFuelStintConfigData::new),
+            errors: vec![],
             add_tire_time_input_ref: NodeRef::default(),
             _planner_context_listener: planner_context_listener,
         }
@@ -69,26 +113,28 @@ impl Component for OverallFuelStintConfig {
                 match pit_duration {
                     Ok(duration) => {
                         self.data.pit_duration = duration;
+                        self.set_error("pitDuration", None);
                         planner_context
                             .dispatch(RacePlannerAction::SetFuelStintConfig(self.data.clone()));
                         true
                     }
-                    Err(message) => {
-                        error!(format!("pit duration parse failed: {}", message).as_str());
-                        false
+                    Err(_) => {
+                        self.set_error("pitDuration", Some(DURATION_ERROR));
+                        true
                     }
                 }
             }
             OverallFuelStintMessage::UpdateFuelTankSize(value) => match value.parse::<i32>() {
                 Ok(tank_size) => {
                     self.data.fuel_tank_size = tank_size;
+                    self.set_error("fuelTankSize", None);
                     planner_context
                         .dispatch(RacePlannerAction::SetFuelStintConfig(self.data.clone()));
                     true
                 }
-                Err(e) => {
-                    error!(format!("fuel tank size parse failed: {:?}", e).as_str());
-                    false
+                Err(_) => {
+                    self.set_error("fuelTankSize", Some(WHOLE_NUMBER_ERROR));
+                    true
                 }
             },
             OverallFuelStintMessage::UpdateTireChangeTime(value) => {
@@ -97,35 +143,57 @@ impl Component for OverallFuelStintConfig {
                 match tire_change_time {
                     Ok(duration) => {
                         self.data.tire_change_time = duration;
+                        self.set_error("tireChangeTime", None);
                         planner_context
                             .dispatch(RacePlannerAction::SetFuelStintConfig(self.data.clone()));
                         true
                     }
-                    Err(message) => {
-                        error!(format!("tire change time parse failed: {}", message).as_str());
-                        false
+                    Err(_) => {
+                        self.set_error("tireChangeTime", Some(DURATION_ERROR));
+                        true
                     }
                 }
             }
             OverallFuelStintMessage::UpdateAddTireTire(value) => {
                 self.data.add_tire_time = value;
                 planner_context.dispatch(RacePlannerAction::SetFuelStintConfig(self.data.clone()));
-                patch_plan(
-                    planner_context.data.id,
-                    PatchRacePlannerDto {
-                        id: planner_context.data.id,
-                        title: None,
-                        overall_event_config: None,
-                        overall_fuel_stint_config: Some(self.data.clone()),
-                        fuel_stint_average_times: None,
-                        time_of_day_lap_factors: None,
-                        per_driver_lap_factors: None,
-                        driver_roster: None,
-                        schedule_rows: None,
-                    },
-                );
+                self.patch_fuel_stint_config(&planner_context);
                 false
             }
+            OverallFuelStintMessage::UpdateTireDegradationPerLap(value) => {
+                let tire_degradation_per_lap =
+                    parse_duration_from_str(value.as_str(), DurationFormat::MinSecMilli);
+                match tire_degradation_per_lap {
+                    Ok(duration) => {
+                        self.data.tire_degradation_per_lap = duration;
+                        self.set_error("tireDegradationPerLap", None);
+                        planner_context
+                            .dispatch(RacePlannerAction::SetFuelStintConfig(self.data.clone()));
+                        self.patch_fuel_stint_config(&planner_context);
+                        true
+                    }
+                    Err(_) => {
+                        self.set_error("tireDegradationPerLap", Some(DURATION_ERROR));
+                        true
+                    }
+                }
+            }
+            OverallFuelStintMessage::UpdateMaxStintsPerTireSet(value) => {
+                match value.parse::<i32>() {
+                    Ok(max_stints) => {
+                        self.data.max_stints_per_tire_set = max_stints;
+                        self.set_error("maxStintsPerTireSet", None);
+                        planner_context
+                            .dispatch(RacePlannerAction::SetFuelStintConfig(self.data.clone()));
+                        self.patch_fuel_stint_config(&planner_context);
+                        true
+                    }
+                    Err(_) => {
+                        self.set_error("maxStintsPerTireSet", Some(WHOLE_NUMBER_ERROR));
+                        true
+                    }
+                }
+            }
             OverallFuelStintMessage::OnCreate(data) => {
                 self.data = data;
                 true
@@ -143,20 +211,37 @@ impl Component for OverallFuelStintConfig {
             value: format_duration(self.data.pit_duration, DurationFormat::MinSecMilli),
             label: Some("Pit Duration (MM:SS.mmm)".to_string()),
             id: "pit-duration".to_string(),
+            error: field_error(&self.errors, "pitDuration"),
             on_change: link.callback(OverallFuelStintMessage::UpdatePitDuration)
         }};
         let fuel_tank_size_props = props! {MaterialTextFieldProps {
             value: self.data.fuel_tank_size.to_string(),
             label: Some("Fuel Tank Size".to_string()),
             id: "fuel-tank-size".to_string(),
+            error: field_error(&self.errors, "fuelTankSize"),
             on_change: link.callback(OverallFuelStintMessage::UpdateFuelTankSize)
         }};
         let tire_change_time_props = props! {MaterialTextFieldProps {
             value: format_duration(self.data.tire_change_time, DurationFormat::MinSecMilli),
             label: Some("Tire Change Time (MM:SS.mmm)".to_string()),
             id: "tire-change-time".to_string(),
+            error: field_error(&self.errors, "tireChangeTime"),
             on_change: link.callback(OverallFuelStintMessage::UpdateTireChangeTime)
         }};
+        let tire_degradation_per_lap_props = props! {MaterialTextFieldProps {
+            value: format_duration(self.data.tire_degradation_per_lap, DurationFormat::MinSecMilli),
+            label: Some("Tire Degradation Per Lap (MM:SS.mmm)".to_string()),
+            id: "tire-degradation-per-lap".to_string(),
+            error: field_error(&self.errors, "tireDegradationPerLap"),
+            on_change: link.callback(OverallFuelStintMessage::UpdateTireDegradationPerLap)
+        }};
+        let max_stints_per_tire_set_props = props! {MaterialTextFieldProps {
+            value: self.data.max_stints_per_tire_set.to_string(),
+            label: Some("Max Stints Per Tire Set (0 = no limit)".to_string()),
+            id: "max-stints-per-tire-set".to_string(),
+            error: field_error(&self.errors, "maxStintsPerTireSet"),
+            on_change: link.callback(OverallFuelStintMessage::UpdateMaxStintsPerTireSet)
+        }};
 
         let add_tire_time_input_ref = self.add_tire_time_input_ref.clone();
         let add_tire_time_callback = link.batch_callback(move |_| {
@@ -171,6 +256,8 @@ impl Component for OverallFuelStintConfig {
                 <MaterialTextField ..pit_duration_props />
                 <MaterialTextField ..fuel_tank_size_props />
                 <MaterialTextField ..tire_change_time_props />
+                <MaterialTextField ..tire_degradation_per_lap_props />
+                <MaterialTextField ..max_stints_per_tire_set_props />
                 <div class="mdc-form-field">
                   <div class="mdc-checkbox">
                     <input ref={self.add_tire_time_input_ref.clone()}
@@ -196,3 +283,84 @@ impl Component for OverallFuelStintConfig {
         }
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::{OverallFuelStintConfig, WHOLE_NUMBER_ERROR};
+    use crate::planner::PlannerRoutes;
+    use crate::test_support::{mount, schedulable_plan, settle, FakeTransport, Mounted};
+    use chrono::Duration;
+    use endurance_racing_planner_common::{
+        client::HttpMethod, PatchRacePlannerDto, RacePlannerDto,
+    };
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use yew::html;
+
+    fn mount_fuel_stint_config(plan: &RacePlannerDto) -> Mounted {
+        mount(
+            plan.clone(),
+            PlannerRoutes::Overview { id: plan.id },
+            html! { <OverallFuelStintConfig /> },
+        )
+    }
+
+    #[wasm_bindgen_test]
+    async fn changing_the_tire_degradation_saves_the_config() {
+        let http = FakeTransport::install();
+        let plan = schedulable_plan();
+        let mounted = mount_fuel_stint_config(&plan);
+
+        mounted.change_text_field("input[aria-labelledby='tire-degradation-per-lap']", "0.150");
+        settle().await;
+
+        let patch: PatchRacePlannerDto =
+            http.body(HttpMethod::Patch, &format!("plans/{}", plan.id));
+        let saved = patch
+            .overall_fuel_stint_config
+            .expect("the fuel stint config to be saved");
+        assert_eq!(Duration::milliseconds(150), saved.tire_degradation_per_lap);
+        assert_eq!(100, saved.fuel_tank_size);
+        assert_eq!(
+            "00:00.150",
+            mounted.value("input[aria-labelledby='tire-degradation-per-lap']")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn changing_the_max_stints_per_tire_set_saves_the_config() {
+        let http = FakeTransport::install();
+        let plan = schedulable_plan();
+        let mounted = mount_fuel_stint_config(&plan);
+
+        mounted.change_text_field("input[aria-labelledby='max-stints-per-tire-set']", "3");
+        settle().await;
+
+        let patch: PatchRacePlannerDto =
+            http.body(HttpMethod::Patch, &format!("plans/{}", plan.id));
+        assert_eq!(
+            3,
+            patch
+                .overall_fuel_stint_config
+                .expect("the fuel stint config to be saved")
+                .max_stints_per_tire_set
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_tank_size_that_is_not_a_whole_number_is_shown_and_not_saved() {
+        let http = FakeTransport::install();
+        let plan = schedulable_plan();
+        let mounted = mount_fuel_stint_config(&plan);
+
+        mounted.change_text_field("input[aria-labelledby='fuel-tank-size']", "12.5");
+        settle().await;
+
+        let errors = mounted.query_all(".mdc-text-field-helper-text");
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            Some(WHOLE_NUMBER_ERROR.to_string()),
+            errors[0].text_content()
+        );
+        assert!(http.requests().is_empty());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::http::plans::patch_plan;
+use crate::md_text_field::{MaterialTextField, MaterialTextFieldProps};
+use crate::planner::{
+    format_duration, parse_duration_from_str, DurationFormat, RacePlannerAction, RacePlannerContext,
+};
+use chrono::Duration;
+use endurance_racing_planner_common::conditions::{TrackConditionDto, Weather};
+use endurance_racing_planner_common::PatchRacePlannerDto;
+use gloo_console::error;
+use std::str::FromStr;
+use yew::context::ContextHandle;
+use yew::html::Scope;
+use yew::prelude::*;
+use yew::{props, Properties};
+use yew_mdc::components::{
+    button::{Button, Style},
+    select::SelectChangeEventData,
+    IconButton, Select, SelectItem,
+};
+
+const TIME_OF_DAY_TIME_FORMAT: &str = "%I:%M %p";
+
+pub enum TrackConditionsMsg {
+    OnCreate(Vec<TrackConditionDto>),
+    AddCondition,
+    RemoveCondition(usize),
+    UpdateStartOffset(String, usize),
+    UpdateWeather(Weather, usize),
+    UpdateAirTemperature(String, usize),
+    UpdateTrackTemperature(String, usize),
+    UpdateLapTime(String, usize),
+    UpdateFuelPerLapFactor(String, usize),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TrackConditionsProps {
+    #[prop_or(Duration::zero())]
+    pub lap_time: Duration,
+}
+
+pub struct TrackConditions {
+    conditions: Vec<TrackConditionDto>,
+    _planner_context_listener: ContextHandle<RacePlannerContext>,
+}
+
+impl TrackConditions {
+    fn save(&mut self, ctx: &Context<Self>) {
+        let (planner_context, _) = ctx
+            .link()
+            .context::<RacePlannerContext>(Callback::noop())
+            .expect("planner context");
+
+        self.conditions
+            .sort_by_key(|condition| condition.start_offset);
+        planner_context.dispatch(RacePlannerAction::SetTrackConditions(
+            self.conditions.clone(),
+        ));
+        patch_plan(
+            planner_context.data.id,
+            PatchRacePlannerDto {
+                id: planner_context.data.id,
+                title: None,
+                overall_event_config: None,
+                overall_fuel_stint_config: None,
+                fuel_stint_average_times: None,
+                time_of_day_lap_factors: None,
+                track_conditions: Some(self.conditions.clone()),
+                traffic_model: None,
+                per_driver_lap_factors: None,
+                driver_roster: None,
+                schedule_rows: None,
+            },
+        );
+    }
+}
+
+impl Component for TrackConditions {
+    type Message = TrackConditionsMsg;
+    type Properties = TrackConditionsProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (planner_context, planner_context_listener) = ctx
+            .link()
+            .context::<RacePlannerContext>(ctx.link().callback(|context: RacePlannerContext| {
+                TrackConditionsMsg::OnCreate(context.data.track_conditions.clone())
+            }))
+            .expect("No Planner Context Provided");
+
+        Self {
+            conditions: planner_context.data.track_conditions.clone(),
+            _planner_context_listener: planner_context_listener,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let reference_lap_time = ctx.props().lap_time;
+        match msg {
+            TrackConditionsMsg::OnCreate(conditions) => {
+                self.conditions = conditions;
+                return true;
+            }
+            TrackConditionsMsg::AddCondition => {
+                let start_offset = self
+                    .conditions
+                    .last()
+                    .map_or_else(Duration::zero, |condition| {
+                        condition.start_offset + Duration::hours(1)
+                    });
+                self.conditions.push(TrackConditionDto::new(start_offset));
+            }
+            TrackConditionsMsg::RemoveCondition(index) => {
+                self.conditions.remove(index);
+            }
+            TrackConditionsMsg::UpdateStartOffset(value, index) => {
+                match parse_duration_from_str(value.as_str(), DurationFormat::HourMinSec) {
+                    Ok(start_offset) => self.conditions[index].start_offset = start_offset,
+                    Err(message) => {
+                        error!(format!("condition race time parse failed: {}", message).as_str());
+                        return false;
+                    }
+                }
+            }
+            TrackConditionsMsg::UpdateWeather(weather, index) => {
+                self.conditions[index].weather = weather;
+            }
+            TrackConditionsMsg::UpdateAirTemperature(value, index) => match value.parse::<f32>() {
+                Ok(temperature) => self.conditions[index].air_temperature = temperature,
+                Err(e) => {
+                    error!(format!("air temperature parse failed: {:?}", e).as_str());
+                    return false;
+                }
+            },
+            TrackConditionsMsg::UpdateTrackTemperature(value, index) => {
+                match value.parse::<f32>() {
+                    Ok(temperature) => self.conditions[index].track_temperature = temperature,
+                    Err(e) => {
+                        error!(format!("track temperature parse failed: {:?}", e).as_str());
+                        return false;
+                    }
+                }
+            }
+            TrackConditionsMsg::UpdateLapTime(value, index) => {
+                if reference_lap_time.is_zero() {
+                    error!("a standard lap time is needed before setting condition lap times");
+                    return false;
+                }
+
+                match parse_duration_from_str(value.as_str(), DurationFormat::MinSecMilli) {
+                    Ok(lap_time) => {
+                        self.conditions[index].lap_time_factor = lap_time.num_milliseconds() as f64
+                            / reference_lap_time.num_milliseconds() as f64;
+                    }
+                    Err(message) => {
+                        error!(format!("condition lap time parse failed: {}", message).as_str());
+                        return false;
+                    }
+                }
+            }
+            TrackConditionsMsg::UpdateFuelPerLapFactor(value, index) => {
+                match value.parse::<f64>() {
+                    Ok(factor) => self.conditions[index].fuel_per_lap_factor = factor,
+                    Err(e) => {
+                        error!(format!("fuel per lap factor parse failed: {:?}", e).as_str());
+                        return false;
+                    }
+                }
+            }
+        }
+
+        self.save(ctx);
+        true
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let (planner_context, _) = link
+            .context::<RacePlannerContext>(Callback::noop())
+            .expect("planner context");
+        let race_start_tod = planner_context
+            .data
+            .overall_event_config
+            .as_ref()
+            .map(|config| config.race_start_tod);
+        let reference_lap_time = ctx.props().lap_time;
+
+        html! {
+            <div id="track-conditions" class="mdc-card">
+                <div class="mdc-card-wrapper__text-section">
+                    <div class="card-title">{ "Track Conditions" }</div>
+                </div>
+                <div class="mdc-data-table">
+                  <div class="mdc-data-table__table-container">
+                    <table class="mdc-data-table__table">
+                      <thead>
+                        <tr class="mdc-data-table__header-row">
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Race Time (HH:MM:SS)" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "ToD Start" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Weather" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Air Temp" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Track Temp" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Laptime  (MM:SS.mmm)" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Fuel Factor" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col"></th>
+                        </tr>
+                      </thead>
+                      <tbody class="mdc-data-table__content">
+                        {
+                            self.conditions
+                                .iter()
+                                .enumerate()
+                                .map(|(index, condition)| render_track_condition(
+                                    condition,
+                                    link,
+                                    index,
+                                    race_start_tod.map(|tod| tod + condition.start_offset),
+                                    reference_lap_time,
+                                ))
+                                .collect::<Html>()
+                        }
+                      </tbody>
+                    </table>
+                  </div>
+                </div>
+                <Button text={"Add Condition"} style={Style::Raised} onclick={link.callback(|_| TrackConditionsMsg::AddCondition)} />
+            </div>
+        }
+    }
+}
+
+fn render_track_condition(
+    condition: &TrackConditionDto,
+    link: &Scope<TrackConditions>,
+    index: usize,
+    tod_start: Option<chrono::NaiveDateTime>,
+    reference_lap_time: Duration,
+) -> Html {
+    let start_offset_props = props! {MaterialTextFieldProps {
+        value: format_duration(condition.start_offset, DurationFormat::HourMinSec),
+        on_change: link.callback(move |value| TrackConditionsMsg::UpdateStartOffset(value, index)),
+        end_aligned: true
+    }};
+    let air_temperature_props = props! {MaterialTextFieldProps {
+        value: condition.air_temperature.to_string(),
+        on_change: link.callback(move |value| TrackConditionsMsg::UpdateAirTemperature(value, index)),
+        end_aligned: true
+    }};
+    let track_temperature_props = props! {MaterialTextFieldProps {
+        value: condition.track_temperature.to_string(),
+        on_change: link.callback(move |value| TrackConditionsMsg::UpdateTrackTemperature(value, index)),
+        end_aligned: true
+    }};
+    let lap_time = Duration::milliseconds(
+        (reference_lap_time.num_milliseconds() as f64 * condition.lap_time_factor).round() as i64,
+    );
+    let lap_time_props = props! {MaterialTextFieldProps {
+        value: format_duration(lap_time, DurationFormat::MinSecMilli),
+        on_change: link.callback(move |value| TrackConditionsMsg::UpdateLapTime(value, index)),
+        end_aligned: true
+    }};
+    let fuel_per_lap_factor_props = props! {MaterialTextFieldProps {
+        value: format!("{:.2}", condition.fuel_per_lap_factor),
+        on_change: link.callback(move |value| TrackConditionsMsg::UpdateFuelPerLapFactor(value, index)),
+        end_aligned: true
+    }};
+    let weather_onchange = link.batch_callback(move |data: SelectChangeEventData| {
+        match Weather::from_str(data.value.as_str()) {
+            Ok(weather) => Some(TrackConditionsMsg::UpdateWeather(weather, index)),
+            Err(s) => {
+                error!(s.as_str());
+                None
+            }
+        }
+    });
+
+    html! {
+        <tr class="mdc-data-table__row">
+          <td class="mdc-data-table__cell">
+            <MaterialTextField ..start_offset_props />
+          </td>
+          <td class="mdc-data-table__cell">
+            { tod_start.map_or_else(String::new, |tod| tod.format(TIME_OF_DAY_TIME_FORMAT).to_string()) }
+          </td>
+          <td class="mdc-data-table__cell">
+            <Select id={format!("condition-weather-{}", index)}
+                select_width_class="select-width"
+                fixed_position={true}
+                selected_value={Some(condition.weather.to_string())}
+                onchange={weather_onchange}>
+                <SelectItem text={Weather::Dry.to_string()} value={Weather::Dry.to_string()} />
+                <SelectItem text={Weather::Damp.to_string()} value={Weather::Damp.to_string()} />
+                <SelectItem text={Weather::Wet.to_string()} value={Weather::Wet.to_string()} />
+            </Select>
+          </td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            <MaterialTextField ..air_temperature_props />
+          </td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            <MaterialTextField ..track_temperature_props />
+          </td>
+          <td class="mdc-data-table__cell">
+            <MaterialTextField ..lap_time_props />
+          </td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            <MaterialTextField ..fuel_per_lap_factor_props />
+          </td>
+          <td class="mdc-data-table__cell">
+            <IconButton classes="material-icons" onclick={link.callback(move |_| TrackConditionsMsg::RemoveCondition(index))}>{ "delete" }</IconButton>
+          </td>
+        </tr>
+    }
+}
@@ -0,0 +1,305 @@
+use crate::http::plans::patch_plan;
+use crate::md_text_field::{MaterialTextField, MaterialTextFieldProps};
+use crate::planner::{
+    format_duration, parse_duration_from_str, DurationFormat, RacePlannerAction, RacePlannerContext,
+};
+use chrono::NaiveTime;
+use endurance_racing_planner_common::traffic::{
+    TrafficClassDto, TrafficIntensityDto, TrafficModelDto,
+};
+use endurance_racing_planner_common::PatchRacePlannerDto;
+use gloo_console::error;
+use yew::context::ContextHandle;
+use yew::html::Scope;
+use yew::prelude::*;
+use yew::props;
+use yew_mdc::components::{
+    button::{Button, Style},
+    IconButton,
+};
+
+const TIME_OF_DAY_TIME_FORMAT: &str = "%I:%M %p";
+
+pub enum TrafficModelMsg {
+    OnCreate(TrafficModelDto),
+    UpdateTimeLossPerEncounter(String),
+    AddClass,
+    RemoveClass(usize),
+    UpdateClassName(String, usize),
+    UpdateClassRelativePace(String, usize),
+    UpdateClassFieldSize(String, usize),
+    AddIntensity,
+    RemoveIntensity(usize),
+    UpdateIntensityStart(String, usize),
+    UpdateIntensity(String, usize),
+}
+
+pub struct TrafficModel {
+    data: TrafficModelDto,
+    _planner_context_listener: ContextHandle<RacePlannerContext>,
+}
+
+impl TrafficModel {
+    fn save(&self, ctx: &Context<Self>) {
+        let (planner_context, _) = ctx
+            .link()
+            .context::<RacePlannerContext>(Callback::noop())
+            .expect("planner context");
+
+        planner_context.dispatch(RacePlannerAction::SetTrafficModel(self.data.clone()));
+        patch_plan(
+            planner_context.data.id,
+            PatchRacePlannerDto {
+                id: planner_context.data.id,
+                title: None,
+                overall_event_config: None,
+                overall_fuel_stint_config: None,
+                fuel_stint_average_times: None,
+                time_of_day_lap_factors: None,
+                track_conditions: None,
+                traffic_model: Some(self.data.clone()),
+                per_driver_lap_factors: None,
+                driver_roster: None,
+                schedule_rows: None,
+            },
+        );
+    }
+}
+
+impl Component for TrafficModel {
+    type Message = TrafficModelMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (planner_context, planner_context_listener) = ctx
+            .link()
+            .context::<RacePlannerContext>(ctx.link().batch_callback(
+                |context: RacePlannerContext| -> Option<TrafficModelMsg> {
+                    context
+                        .data
+                        .traffic_model
+                        .as_ref()
+                        .map(|traffic_model| TrafficModelMsg::OnCreate(traffic_model.clone()))
+                },
+            ))
+            .expect("No Planner Context Provided");
+
+        Self {
+            data: planner_context
+                .data
+                .traffic_model
+                .as_ref()
+                .cloned()
+                .unwrap_or_else(TrafficModelDto::new),
+            _planner_context_listener: planner_context_listener,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            TrafficModelMsg::OnCreate(data) => {
+                self.data = data;
+                return true;
+            }
+            TrafficModelMsg::UpdateTimeLossPerEncounter(value) => {
+                match parse_duration_from_str(value.as_str(), DurationFormat::MinSecMilli) {
+                    Ok(duration) => self.data.time_loss_per_encounter = duration,
+                    Err(message) => {
+                        error!(
+                            format!("time loss per encounter parse failed: {}", message).as_str()
+                        );
+                        return false;
+                    }
+                }
+            }
+            TrafficModelMsg::AddClass => self.data.classes.push(TrafficClassDto {
+                name: "".to_string(),
+                relative_pace: 1.0,
+                field_size: 0,
+            }),
+            TrafficModelMsg::RemoveClass(index) => {
+                self.data.classes.remove(index);
+            }
+            TrafficModelMsg::UpdateClassName(value, index) => {
+                self.data.classes[index].name = value;
+            }
+            TrafficModelMsg::UpdateClassRelativePace(value, index) => match value.parse::<f64>() {
+                Ok(relative_pace) => self.data.classes[index].relative_pace = relative_pace,
+                Err(e) => {
+                    error!(format!("class relative pace parse failed: {:?}", e).as_str());
+                    return false;
+                }
+            },
+            TrafficModelMsg::UpdateClassFieldSize(value, index) => match value.parse::<i32>() {
+                Ok(field_size) => self.data.classes[index].field_size = field_size,
+                Err(e) => {
+                    error!(format!("class field size parse failed: {:?}", e).as_str());
+                    return false;
+                }
+            },
+            TrafficModelMsg::AddIntensity => self.data.intensities.push(TrafficIntensityDto {
+                tod_start: NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+                intensity: 1.0,
+            }),
+            TrafficModelMsg::RemoveIntensity(index) => {
+                self.data.intensities.remove(index);
+            }
+            TrafficModelMsg::UpdateIntensityStart(value, index) => {
+                match NaiveTime::parse_from_str(value.as_str(), TIME_OF_DAY_TIME_FORMAT) {
+                    Ok(tod_start) => self.data.intensities[index].tod_start = tod_start,
+                    Err(e) => {
+                        error!(format!("intensity start time parse failed: {:?}", e).as_str());
+                        return false;
+                    }
+                }
+                self.data
+                    .intensities
+                    .sort_by_key(|intensity| intensity.tod_start);
+            }
+            TrafficModelMsg::UpdateIntensity(value, index) => match value.parse::<f64>() {
+                Ok(intensity) => self.data.intensities[index].intensity = intensity,
+                Err(e) => {
+                    error!(format!("traffic intensity parse failed: {:?}", e).as_str());
+                    return false;
+                }
+            },
+        }
+
+        self.save(ctx);
+        true
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let time_loss_per_encounter_props = props! {MaterialTextFieldProps {
+            value: format_duration(self.data.time_loss_per_encounter, DurationFormat::MinSecMilli),
+            label: Some("Time Loss Per Encounter (MM:SS.mmm)".to_string()),
+            id: "time-loss-per-encounter".to_string(),
+            on_change: link.callback(TrafficModelMsg::UpdateTimeLossPerEncounter)
+        }};
+
+        html! {
+            <div id="traffic-model" class="mdc-card">
+                <div class="mdc-card-wrapper__text-section">
+                    <div class="card-title">{ "Multi-class Traffic" }</div>
+                </div>
+                <MaterialTextField ..time_loss_per_encounter_props />
+                <p>{ format!("{:.2} encounters per lap", self.data.encounters_per_lap()) }</p>
+                <div class="mdc-data-table">
+                  <div class="mdc-data-table__table-container">
+                    <table class="mdc-data-table__table">
+                      <thead>
+                        <tr class="mdc-data-table__header-row">
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Class" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Relative Pace" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Field Size" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col"></th>
+                        </tr>
+                      </thead>
+                      <tbody class="mdc-data-table__content">
+                        {
+                            self.data.classes
+                                .iter()
+                                .enumerate()
+                                .map(|(index, class)| render_traffic_class(class, link, index))
+                                .collect::<Html>()
+                        }
+                      </tbody>
+                    </table>
+                  </div>
+                </div>
+                <Button text={"Add Class"} style={Style::Raised} onclick={link.callback(|_| TrafficModelMsg::AddClass)} />
+                <div class="mdc-data-table">
+                  <div class="mdc-data-table__table-container">
+                    <table class="mdc-data-table__table">
+                      <thead>
+                        <tr class="mdc-data-table__header-row">
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "ToD Start" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Intensity" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col"></th>
+                        </tr>
+                      </thead>
+                      <tbody class="mdc-data-table__content">
+                        {
+                            self.data.intensities
+                                .iter()
+                                .enumerate()
+                                .map(|(index, intensity)| render_traffic_intensity(intensity, link, index))
+                                .collect::<Html>()
+                        }
+                      </tbody>
+                    </table>
+                  </div>
+                </div>
+                <Button text={"Add Intensity"} style={Style::Raised} onclick={link.callback(|_| TrafficModelMsg::AddIntensity)} />
+            </div>
+        }
+    }
+}
+
+fn render_traffic_class(class: &TrafficClassDto, link: &Scope<TrafficModel>, index: usize) -> Html {
+    let name_props = props! {MaterialTextFieldProps {
+        value: class.name.clone(),
+        on_change: link.callback(move |value| TrafficModelMsg::UpdateClassName(value, index)),
+    }};
+    let relative_pace_props = props! {MaterialTextFieldProps {
+        value: format!("{:.3}", class.relative_pace),
+        on_change: link.callback(move |value| TrafficModelMsg::UpdateClassRelativePace(value, index)),
+        end_aligned: true
+    }};
+    let field_size_props = props! {MaterialTextFieldProps {
+        value: class.field_size.to_string(),
+        on_change: link.callback(move |value| TrafficModelMsg::UpdateClassFieldSize(value, index)),
+        end_aligned: true
+    }};
+    html! {
+        <tr class="mdc-data-table__row">
+          <td class="mdc-data-table__cell">
+            <MaterialTextField ..name_props />
+          </td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            <MaterialTextField ..relative_pace_props />
+          </td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            <MaterialTextField ..field_size_props />
+          </td>
+          <td class="mdc-data-table__cell">
+            <IconButton classes="material-icons" onclick={link.callback(move |_| TrafficModelMsg::RemoveClass(index))}>{ "delete" }</IconButton>
+          </td>
+        </tr>
+    }
+}
+
+fn render_traffic_intensity(
+    intensity: &TrafficIntensityDto,
+    link: &Scope<TrafficModel>,
+    index: usize,
+) -> Html {
+    let tod_start_props = props! {MaterialTextFieldProps {
+        value: intensity.tod_start.format(TIME_OF_DAY_TIME_FORMAT).to_string(),
+        on_change: link.callback(move |value| TrafficModelMsg::UpdateIntensityStart(value, index)),
+        end_aligned: true
+    }};
+    let intensity_props = props! {MaterialTextFieldProps {
+        value: format!("{:.2}", intensity.intensity),
+        on_change: link.callback(move |value| TrafficModelMsg::UpdateIntensity(value, index)),
+        end_aligned: true
+    }};
+    html! {
+        <tr class="mdc-data-table__row">
+          <td class="mdc-data-table__cell">
+            <MaterialTextField ..tod_start_props />
+          </td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            <MaterialTextField ..intensity_props />
+          </td>
+          <td class="mdc-data-table__cell">
+            <IconButton classes="material-icons" onclick={link.callback(move |_| TrafficModelMsg::RemoveIntensity(index))}>{ "delete" }</IconButton>
+          </td>
+        </tr>
+    }
+}
@@ -0,0 +1,169 @@
+use crate::http::revisions::{get_plan_revisions, restore_revision, undo_revision};
+use crate::planner::{RacePlannerAction, RacePlannerContext};
+use endurance_racing_planner_common::revision::PlanRevisionDto;
+use endurance_racing_planner_common::RacePlannerDto;
+use serde_json::Value;
+use uuid::Uuid;
+use yew::html::Scope;
+use yew::prelude::*;
+use yew_mdc::components::button::{Button, Style};
+
+const REVISION_TIME_FORMAT: &str = "%b %e %l:%M:%S %p";
+
+pub enum PlanHistoryMsg {
+    Load,
+    SetRevisions(Vec<PlanRevisionDto>),
+    Restore(i64),
+    Undo(i64),
+    SetPlan(RacePlannerDto),
+}
+
+pub struct PlanHistory {
+    plan_id: Uuid,
+    revisions: Vec<PlanRevisionDto>,
+}
+
+impl Component for PlanHistory {
+    type Message = PlanHistoryMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (planner_context, _) = ctx
+            .link()
+            .context::<RacePlannerContext>(Callback::noop())
+            .expect("No Planner Context Provided");
+        ctx.link().send_message(PlanHistoryMsg::Load);
+
+        Self {
+            plan_id: planner_context.data.id,
+            revisions: vec![],
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            PlanHistoryMsg::Load => {
+                get_plan_revisions(
+                    self.plan_id,
+                    ctx.link().callback(PlanHistoryMsg::SetRevisions),
+                );
+                false
+            }
+            PlanHistoryMsg::SetRevisions(revisions) => {
+                self.revisions = revisions;
+                true
+            }
+            PlanHistoryMsg::Restore(revision_id) => {
+                restore_revision(
+                    self.plan_id,
+                    revision_id,
+                    ctx.link().callback(PlanHistoryMsg::SetPlan),
+                );
+                false
+            }
+            PlanHistoryMsg::Undo(revision_id) => {
+                undo_revision(
+                    self.plan_id,
+                    revision_id,
+                    ctx.link().callback(PlanHistoryMsg::SetPlan),
+                );
+                false
+            }
+            PlanHistoryMsg::SetPlan(plan) => {
+                let (planner_context, _) = ctx
+                    .link()
+                    .context::<RacePlannerContext>(Callback::noop())
+                    .expect("planner context");
+                planner_context.dispatch(RacePlannerAction::SetPlan(plan));
+                ctx.link().send_message(PlanHistoryMsg::Load);
+                false
+            }
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+
+        html! {
+            <div id="plan-history" class="mdc-card">
+                <div class="mdc-card-wrapper__text-section">
+                    <div class="card-title">{ "History" }</div>
+                </div>
+                <div class="mdc-data-table">
+                  <div class="mdc-data-table__table-container">
+                    <table class="mdc-data-table__table">
+                      <thead>
+                        <tr class="mdc-data-table__header-row">
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Time (UTC)" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "User" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Section" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Before" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "After" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col"></th>
+                        </tr>
+                      </thead>
+                      <tbody class="mdc-data-table__content">
+                        {
+                            self.revisions
+                                .iter()
+                                .map(|revision| render_revision(revision, link))
+                                .collect::<Html>()
+                        }
+                      </tbody>
+                    </table>
+                  </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+fn render_revision(revision: &PlanRevisionDto, link: &Scope<PlanHistory>) -> Html {
+    let revision_id = revision.id;
+
+    html! {
+        <tr class="mdc-data-table__row">
+          <td class="mdc-data-table__cell">{ revision.created_date.format(REVISION_TIME_FORMAT).to_string() }</td>
+          <td class="mdc-data-table__cell">{ revision.user_name.clone() }</td>
+          <td class="mdc-data-table__cell">{ revision.section.to_string() }</td>
+          <td class="mdc-data-table__cell">{ render_value(revision.before.as_ref()) }</td>
+          <td class="mdc-data-table__cell">{ render_value(Some(&revision.after)) }</td>
+          <td class="mdc-data-table__cell revision-actions">
+            {
+                if revision.before.is_some() {
+                    html! {
+                        <Button text={"Undo"} style={Style::Raised} onclick={link.callback(move |_| PlanHistoryMsg::Undo(revision_id))} />
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <Button text={"Restore"} style={Style::Raised} onclick={link.callback(move |_| PlanHistoryMsg::Restore(revision_id))} />
+          </td>
+        </tr>
+    }
+}
+
+/// Strings are shown as they are, anything else collapses to a summary that expands to its JSON
+fn render_value(value: Option<&Value>) -> Html {
+    match value {
+        None => html! { { "-" } },
+        Some(Value::String(value)) => html! { { value.clone() } },
+        Some(value) => {
+            let summary = match value {
+                Value::Array(values) => format!("{} items", values.len()),
+                _ => "Show".to_string(),
+            };
+            html! {
+                <details>
+                    <summary>{ summary }</summary>
+                    <pre>{ serde_json::to_string_pretty(value).unwrap_or_default() }</pre>
+                </details>
+            }
+        }
+    }
+}
@@ -1,15 +1,22 @@
 ﻿use crate::bindings::enable_tab_bar;
 use crate::event_bus::{EventBus, EventBusOutput};
 use crate::http::drivers::get_plan_drivers_async;
+use crate::http::handle_error;
 use crate::http::plans::{create_plan, get_plan_async, patch_plan};
 use crate::http::schedules::get_schedule_async;
+use crate::load_error::LoadError;
 use crate::overview::Overview;
+use crate::plan_history::PlanHistory;
 use crate::roster::DriverRoster;
+use crate::scenarios::Scenarios;
 use crate::schedule::Schedule;
 use crate::{AppStateAction, AppStateContext, Loading};
 use boolinator::Boolinator;
 use chrono::{Duration, NaiveDateTime};
+use endurance_racing_planner_common::client::ClientError;
+use endurance_racing_planner_common::conditions::TrackConditionDto;
 use endurance_racing_planner_common::schedule::ScheduleStintDto;
+use endurance_racing_planner_common::traffic::TrafficModelDto;
 use endurance_racing_planner_common::{
     Driver, EventConfigDto, OverallFuelStintConfigData, PatchRacePlannerDto, RacePlannerDto,
 };
@@ -35,6 +42,10 @@ pub enum PlannerRoutes {
     Roster { id: Uuid },
     #[at("/planner/:id/overview")]
     Overview { id: Uuid },
+    #[at("/planner/:id/scenarios")]
+    Scenarios { id: Uuid },
+    #[at("/planner/:id/history")]
+    History { id: Uuid },
 }
 
 fn render_tab(
@@ -46,6 +57,8 @@ fn render_tab(
         PlannerRoutes::Schedule { id: _ } => "schedule",
         PlannerRoutes::Roster { id: _ } => "list",
         PlannerRoutes::Overview { id: _ } => "home",
+        PlannerRoutes::Scenarios { id: _ } => "compare_arrows",
+        PlannerRoutes::History { id: _ } => "history",
     };
 
     let is_active = *current_route == tab_route;
@@ -73,6 +86,8 @@ impl Display for PlannerRoutes {
             PlannerRoutes::Schedule { id: _ } => write!(f, "Schedule"),
             PlannerRoutes::Roster { id: _ } => write!(f, "Roster"),
             PlannerRoutes::Overview { id: _ } => write!(f, "Overview"),
+            PlannerRoutes::Scenarios { id: _ } => write!(f, "Scenarios"),
+            PlannerRoutes::History { id: _ } => write!(f, "History"),
         }
     }
 }
@@ -98,6 +113,8 @@ pub enum RacePlannerAction {
     SetTitle(String),
     SetPlan(RacePlannerDto),
     SetDriverRoster(Vec<Driver>),
+    SetTrackConditions(Vec<TrackConditionDto>),
+    SetTrafficModel(TrafficModelDto),
 }
 
 impl Reducible for RacePlanner {
@@ -150,6 +167,18 @@ impl Reducible for RacePlanner {
                     ..current_plan
                 },
             },
+            RacePlannerAction::SetTrackConditions(track_conditions) => RacePlanner {
+                data: RacePlannerDto {
+                    track_conditions,
+                    ..current_plan
+                },
+            },
+            RacePlannerAction::SetTrafficModel(traffic_model) => RacePlanner {
+                data: RacePlannerDto {
+                    traffic_model: Some(traffic_model),
+                    ..current_plan
+                },
+            },
         }
         .into()
     }
@@ -163,10 +192,18 @@ pub struct RacePlannerProviderProps {
     pub children: Children,
 }
 
+/// Where loading the plan from the api is at
+#[derive(PartialEq)]
+enum PlanLoad {
+    Loading,
+    Loaded,
+    Failed(ClientError),
+}
+
 #[function_component(RacePlannerProvider)]
 pub fn race_planner_provider(props: &RacePlannerProviderProps) -> Html {
     let race_planner = use_reducer(RacePlanner::new);
-    let is_loading = use_state(|| false);
+    let plan_load = use_state(|| PlanLoad::Loading);
 
     let current_route = use_route::<PlannerRoutes>();
     let app_context = use_context::<AppStateContext>().unwrap();
@@ -175,20 +212,40 @@ pub fn race_planner_provider(props: &RacePlannerProviderProps) -> Html {
         return html! {};
     }
 
-    let current_route = current_route.unwrap();
+    let plan_id = match current_route.unwrap() {
+        PlannerRoutes::Schedule { id }
+        | PlannerRoutes::Roster { id }
+        | PlannerRoutes::Overview { id }
+        | PlannerRoutes::Scenarios { id }
+        | PlannerRoutes::History { id } => id,
+    };
     let history = use_history().unwrap();
+    let load = {
+        let race_planner = race_planner.clone();
+        let plan_load = plan_load.clone();
+        Callback::from(move |_| {
+            plan_load.set(PlanLoad::Loading);
+            let plan_load = plan_load.clone();
+            load_plan(
+                plan_id,
+                race_planner.clone(),
+                app_context.clone(),
+                Callback::from(move |result: Result<(), ClientError>| match result {
+                    Ok(()) => plan_load.set(PlanLoad::Loaded),
+                    Err(e) if e.is_unauthorized() => handle_error(e),
+                    Err(e) => plan_load.set(PlanLoad::Failed(e)),
+                }),
+            )
+        })
+    };
     let load_planner = {
         let race_planner = race_planner.clone();
-        let is_loading = is_loading.clone();
+        let plan_load = plan_load.clone();
+        let load = load.clone();
         move |_: &Option<bool>| {
-            let id = match current_route {
-                PlannerRoutes::Schedule { id }
-                | PlannerRoutes::Roster { id }
-                | PlannerRoutes::Overview { id } => id,
-            };
-
             let default_plan = &race_planner.data;
-            if Uuid::is_nil(&id) {
+            if Uuid::is_nil(&plan_id) {
+                plan_load.set(PlanLoad::Loaded);
                 history.replace(PlannerRoutes::Overview {
                     id: default_plan.id,
                 });
@@ -199,14 +256,7 @@ pub fn race_planner_provider(props: &RacePlannerProviderProps) -> Html {
                     }),
                 );
             } else {
-                is_loading.set(true);
-                let is_loading = is_loading.clone();
-                load_plan(
-                    id,
-                    race_planner,
-                    app_context,
-                    Callback::from(move |_| is_loading.set(false)),
-                )
+                load.emit(())
             }
 
             || ()
@@ -214,46 +264,43 @@ pub fn race_planner_provider(props: &RacePlannerProviderProps) -> Html {
     };
     use_effect_with_deps(load_planner, None);
 
-    if *is_loading {
-        html! { <Loading /> }
-    } else {
-        html! {
+    match &*plan_load {
+        PlanLoad::Loading => html! { <Loading /> },
+        PlanLoad::Failed(error) => html! {
+            <LoadError subject={"the plan"} error={error.clone()} onretry={load.reform(|_| ())} />
+        },
+        PlanLoad::Loaded => html! {
             <ContextProvider<RacePlannerContext> context={race_planner}>
                 {props.children.clone()}
             </ContextProvider<RacePlannerContext>>
-        }
+        },
     }
 }
 
+/// Loads the plan with its schedule and roster. A plan is only shown once all three loaded, the
+/// planner would save over a schedule or roster it didn't load.
 fn load_plan(
     plan_id: Uuid,
     race_planner_context: RacePlannerContext,
     app_state_context: AppStateContext,
-    done_callback: Callback<()>,
+    done_callback: Callback<Result<(), ClientError>>,
 ) {
     spawn_local(async move {
         let get_plan = get_plan_async(plan_id);
         let get_schedule = get_schedule_async(plan_id);
         let get_driver_roster = get_plan_drivers_async(plan_id);
 
-        let (plan_result, schedule_result, driver_roster_result) =
-            join!(get_plan, get_schedule, get_driver_roster);
-
-        match plan_result {
-            Ok(plan) => {
-                app_state_context.dispatch(AppStateAction::SetPageTitle(plan.title.clone()));
-                race_planner_context.dispatch(RacePlannerAction::SetPlan(plan))
-            }
-            Err(e) => panic!("failed to load the plan: {:?}", e),
-        }
-        if let Ok(stints) = schedule_result {
-            race_planner_context.dispatch(RacePlannerAction::SetStints(stints))
-        };
-        if let Ok(drivers) = driver_roster_result {
-            race_planner_context.dispatch(RacePlannerAction::SetDriverRoster(drivers))
+        let loaded = match join!(get_plan, get_schedule, get_driver_roster) {
+            (Ok(plan), Ok(stints), Ok(drivers)) => Ok((plan, stints, drivers)),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => Err(e),
         };
 
-        done_callback.emit(())
+        done_callback.emit(loaded.map(|(plan, stints, drivers)| {
+            app_state_context.dispatch(AppStateAction::SetPageTitle(plan.title.clone()));
+            race_planner_context.dispatch(RacePlannerAction::SetPlan(plan));
+            race_planner_context.dispatch(RacePlannerAction::SetStints(stints));
+            race_planner_context.dispatch(RacePlannerAction::SetDriverRoster(drivers));
+        }))
     });
 }
 
@@ -290,6 +337,8 @@ impl Component for Planner {
             PlannerRoutes::Schedule { id } => id,
             PlannerRoutes::Roster { id } => id,
             PlannerRoutes::Overview { id } => id,
+            PlannerRoutes::Scenarios { id } => id,
+            PlannerRoutes::History { id } => id,
         });
 
         let (app_state_context, _) = link.context::<AppStateContext>(Callback::noop()).unwrap();
@@ -331,6 +380,8 @@ impl Component for Planner {
                         overall_fuel_stint_config: None,
                         fuel_stint_average_times: None,
                         time_of_day_lap_factors: None,
+                        track_conditions: None,
+                        traffic_model: None,
                         per_driver_lap_factors: None,
                         driver_roster: None,
                         schedule_rows: None,
@@ -367,6 +418,8 @@ impl Component for Planner {
                                 { render_tab(PlannerRoutes::Overview { id: plan_id }, &current_route, link) }
                                 { render_tab(PlannerRoutes::Schedule { id: plan_id }, &current_route, link) }
                                 { render_tab(PlannerRoutes::Roster { id: plan_id }, &current_route, link) }
+                                { render_tab(PlannerRoutes::Scenarios { id: plan_id }, &current_route, link) }
+                                { render_tab(PlannerRoutes::History { id: plan_id }, &current_route, link) }
                               </div>
                             </div>
                         </div>
@@ -409,6 +462,24 @@ impl Planner {
                     <Schedule />
                 }
             }
+            PlannerRoutes::Scenarios { id: _ } =>
+            {
+                #[allow(clippy::needless_return, clippy::let_unit_value)]
+                return html! {
+                    <div class="mdc-typography flex-container flex-row">
+                        <Scenarios />
+                    </div>
+                }
+            }
+            PlannerRoutes::History { id: _ } =>
+            {
+                #[allow(clippy::needless_return, clippy::let_unit_value)]
+                return html! {
+                    <div class="mdc-typography flex-container flex-row">
+                        <PlanHistory />
+                    </div>
+                }
+            }
         }
     }
 }
@@ -469,8 +540,9 @@ pub fn parse_duration_from_str(str: &str, format: DurationFormat) -> Result<Dura
         DurationFormat::HourMinSec => {
             let duration_split = str
                 .split(':')
-                .map(|part| part.parse::<i64>().unwrap())
-                .collect::<Vec<_>>();
+                .map(|part| part.trim().parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_default();
 
             let duration_seconds = match duration_split.len() {
                 3 => Some((duration_split[0] * 60 + duration_split[1]) * 60 + duration_split[2]),
@@ -555,6 +627,12 @@ mod parse_hour_min_sec_duration {
         assert!(parsed.is_ok());
         assert_eq!(Duration::seconds(10), parsed.unwrap());
     }
+
+    #[test]
+    fn should_not_parse_text_that_is_not_a_number() {
+        assert!(parse_duration_from_str("soon", DurationFormat::HourMinSec).is_err());
+        assert!(parse_duration_from_str("1:xx:00", DurationFormat::HourMinSec).is_err());
+    }
 }
 
 #[cfg(test)]
@@ -605,3 +683,137 @@ mod parse_min_sec_milli_duration {
         assert_eq!(0, parsed.unwrap().num_milliseconds() % 1000);
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod race_planner_provider {
+    use super::{PlannerRoutes, RacePlannerContext, RacePlannerProvider};
+    use crate::test_support::{mount, settle, FakeTransport, Mounted};
+    use endurance_racing_planner_common::api_error::{ApiErrorCode, ApiErrorDto};
+    use endurance_racing_planner_common::client::{ClientError, HttpMethod};
+    use endurance_racing_planner_common::schedule::ScheduleStintDto;
+    use endurance_racing_planner_common::{Driver, RacePlannerDto};
+    use std::rc::Rc;
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use yew::prelude::*;
+
+    #[function_component(PlanTitle)]
+    fn plan_title() -> Html {
+        let race_planner = use_context::<RacePlannerContext>().unwrap();
+
+        html! { <h1 class="plan-title">{ race_planner.data.title.clone() }</h1> }
+    }
+
+    fn plan() -> RacePlannerDto {
+        RacePlannerDto {
+            title: "Daytona 24".to_string(),
+            ..RacePlannerDto::new()
+        }
+    }
+
+    /// Answers the schedule and roster requests, the tests decide how the plan request goes
+    fn install_transport(plan: &RacePlannerDto) -> Rc<FakeTransport> {
+        let http = FakeTransport::install();
+        http.respond_with(
+            HttpMethod::Get,
+            &format!("plans/{}/schedule", plan.id),
+            &Vec::<ScheduleStintDto>::new(),
+        );
+        http.respond_with(
+            HttpMethod::Get,
+            &format!("plans/{}/drivers", plan.id),
+            &Vec::<Driver>::new(),
+        );
+
+        http
+    }
+
+    fn mount_provider(plan: &RacePlannerDto) -> Mounted {
+        mount(
+            RacePlannerDto::new(),
+            PlannerRoutes::Overview { id: plan.id },
+            html! {
+                <RacePlannerProvider>
+                    <PlanTitle />
+                </RacePlannerProvider>
+            },
+        )
+    }
+
+    #[wasm_bindgen_test]
+    async fn should_show_the_loaded_plan() {
+        let plan = plan();
+        let http = install_transport(&plan);
+        http.respond_with(HttpMethod::Get, &format!("plans/{}", plan.id), &plan);
+
+        let mounted = mount_provider(&plan);
+        settle().await;
+
+        assert_eq!(
+            "Daytona 24",
+            mounted.query(".plan-title").text_content().unwrap()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn should_show_the_offline_state_when_the_api_can_not_be_reached() {
+        let plan = plan();
+        let http = install_transport(&plan);
+        http.fail_with(
+            HttpMethod::Get,
+            &format!("plans/{}", plan.id),
+            ClientError::Offline("network error".to_string()),
+        );
+
+        let mounted = mount_provider(&plan);
+        settle().await;
+
+        assert!(mounted.text().contains("You're offline"));
+        assert!(mounted.query_all(".plan-title").is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn should_show_the_plan_when_a_retry_succeeds() {
+        let plan = plan();
+        let http = install_transport(&plan);
+        let route = format!("plans/{}", plan.id);
+        http.fail_with(
+            HttpMethod::Get,
+            &route,
+            ClientError::Offline("network error".to_string()),
+        );
+        let mounted = mount_provider(&plan);
+        settle().await;
+
+        http.respond_with(HttpMethod::Get, &route, &plan);
+        mounted.click(".load-error button");
+        settle().await;
+
+        assert_eq!(
+            "Daytona 24",
+            mounted.query(".plan-title").text_content().unwrap()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn should_show_the_apis_error_instead_of_an_empty_plan() {
+        let plan = plan();
+        let http = install_transport(&plan);
+        http.fail_with(
+            HttpMethod::Get,
+            &format!("plans/{}/drivers", plan.id),
+            ClientError::Forbidden(ApiErrorDto {
+                code: ApiErrorCode::Forbidden,
+                message: "the plan isn't shared with you".to_string(),
+                details: vec![],
+            }),
+        );
+        http.respond_with(HttpMethod::Get, &format!("plans/{}", plan.id), &plan);
+
+        let mounted = mount_provider(&plan);
+        settle().await;
+
+        assert!(mounted.text().contains("Couldn't load the plan"));
+        assert!(mounted.text().contains("the plan isn't shared with you"));
+        assert!(mounted.query_all(".plan-title").is_empty());
+    }
+}
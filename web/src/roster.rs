@@ -1,10 +1,13 @@
 ﻿use crate::bindings;
-use crate::http::drivers::{create_plan_driver, update_driver};
+use crate::http::drivers::{create_plan_driver, driver_calendar_url, update_driver};
 use crate::md_text_field::{
     MaterialTextField, MaterialTextFieldIcon, MaterialTextFieldIconStyle, MaterialTextFieldProps,
 };
 use crate::planner::{RacePlannerAction, RacePlannerContext};
+use endurance_racing_planner_common::api_error::FieldErrorDto;
+use endurance_racing_planner_common::validation::{field_error, Validate};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use yew::context::ContextHandle;
 use yew::prelude::*;
 use yew::{html::Scope, props, Component, Context, Html};
@@ -19,6 +22,8 @@ pub struct Driver {
     pub utc_offset: i16,
     pub irating: i16,
     pub stint_preference: i16,
+    #[serde(skip)]
+    pub errors: Vec<FieldErrorDto>,
 }
 
 impl From<&endurance_racing_planner_common::Driver> for Driver {
@@ -32,6 +37,7 @@ impl From<&endurance_racing_planner_common::Driver> for Driver {
             utc_offset: driver.utc_offset,
             irating: driver.irating,
             stint_preference: driver.stint_preference,
+            errors: vec![],
         }
     }
 }
@@ -62,15 +68,40 @@ impl Driver {
             utc_offset: 0,
             irating: 0,
             stint_preference: 0,
+            errors: vec![],
         }
     }
 
-    fn get_view(&self, link: &Scope<DriverRoster>, index: usize) -> Html {
+    /// Replaces the error shown for `field`, `None` clears it
+    fn set_error(&mut self, field: &str, message: Option<String>) {
+        self.errors.retain(|error| error.field != field);
+        if let Some(message) = message {
+            self.errors.push(FieldErrorDto::new(field, message));
+        }
+    }
+
+    /// Checks the driver with the same rules the api uses, returns whether it can be saved
+    fn check(&mut self) -> bool {
+        let driver: endurance_racing_planner_common::Driver = self.clone().into();
+        self.errors = driver.validate().err().unwrap_or_default();
+        self.errors.is_empty()
+    }
+
+    /// Once the driver has failed a check its errors are refreshed on every change, so fixing a
+    /// field clears its error
+    fn recheck(&mut self) {
+        if !self.errors.is_empty() {
+            self.check();
+        }
+    }
+
+    fn get_view(&self, link: &Scope<DriverRoster>, index: usize, plan_id: Uuid) -> Html {
         let name_props = props! {MaterialTextFieldProps {
             value: self.name.clone(),
             on_change: link.callback(move |value| {
                 DriverRosterMsg::UpdateDriverName(value, index)
-            })
+            }),
+            error: field_error(&self.errors, "name"),
         }};
         let color_props = props! {MaterialTextFieldProps {
             value: self.color.clone(),
@@ -82,31 +113,32 @@ impl Driver {
                 icon: "a".to_string(),
                 on_click: None,
                 background_color: Some(self.color.clone()),
-            }
+            },
+            error: field_error(&self.errors, "color"),
         }};
         let utc_offset_props = props! {MaterialTextFieldProps {
             value: self.utc_offset.to_string(),
             end_aligned: true,
             on_change: link.callback(move |value: String| {
-                let value = value.parse::<i16>().unwrap();
                 DriverRosterMsg::UpdateDriverUtcOffset(value, index)
             }),
+            error: field_error(&self.errors, "utcOffset"),
         }};
         let irating_props = props! {MaterialTextFieldProps {
             value: self.irating.to_string(),
             end_aligned: true,
             on_change: link.callback(move |value: String| {
-                let value = value.parse::<i16>().unwrap();
                 DriverRosterMsg::UpdateDriverIrating(value, index)
             }),
+            error: field_error(&self.errors, "irating"),
         }};
         let stint_preference_props = props! {MaterialTextFieldProps {
             value: self.stint_preference.to_string(),
             end_aligned: true,
             on_change: link.callback(move |value: String| {
-                let value = value.parse::<i16>().unwrap();
                 DriverRosterMsg::UpdateDriverStintPreference(value, index)
             }),
+            error: field_error(&self.errors, "stintPreference"),
         }};
 
         html! {
@@ -132,6 +164,19 @@ impl Driver {
               <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
                 <MaterialTextField ..stint_preference_props />
               </td>
+              <td class="mdc-data-table__cell">
+                {
+                    if self.id == 0 {
+                        html! {}
+                    } else {
+                        html! {
+                            <a class="material-icons"
+                                href={driver_calendar_url(plan_id, self.id)}
+                                title="Subscribe to this driver's stints">{ "event" }</a>
+                        }
+                    }
+                }
+              </td>
             </tr>
         }
     }
@@ -148,17 +193,27 @@ impl Clone for Driver {
             utc_offset: self.utc_offset,
             irating: self.irating,
             stint_preference: self.stint_preference,
+            errors: self.errors.clone(),
         }
     }
 }
 
+/// The roster's numeric fields are whole numbers, a value that doesn't parse is shown as an error
+/// instead of being saved
+fn parse_whole_number(value: &str) -> Result<i16, String> {
+    value
+        .trim()
+        .parse::<i16>()
+        .map_err(|_| "must be a whole number".to_string())
+}
+
 pub enum DriverRosterMsg {
     AddDriver,
     UpdateDriverName(String, usize),
     UpdateDriverColor(String, usize),
-    UpdateDriverUtcOffset(i16, usize),
-    UpdateDriverIrating(i16, usize),
-    UpdateDriverStintPreference(i16, usize),
+    UpdateDriverUtcOffset(String, usize),
+    UpdateDriverIrating(String, usize),
+    UpdateDriverStintPreference(String, usize),
     UpdateDriverId(i32, usize),
 }
 
@@ -198,40 +253,63 @@ impl Component for DriverRoster {
             DriverRosterMsg::UpdateDriverName(name, index) => {
                 let driver_to_update = &mut self.drivers[index];
                 driver_to_update.name = name;
-                false
+                driver_to_update.recheck();
+                true
             }
             DriverRosterMsg::UpdateDriverColor(color, index) => {
                 let driver_to_update = &mut self.drivers[index];
                 driver_to_update.color = color;
+                driver_to_update.recheck();
                 true
             }
-            DriverRosterMsg::UpdateDriverUtcOffset(offset, index) => {
+            DriverRosterMsg::UpdateDriverUtcOffset(value, index) => {
                 let driver_to_update = &mut self.drivers[index];
-                driver_to_update.utc_offset = offset;
-                false
+                match parse_whole_number(&value) {
+                    Ok(offset) => {
+                        driver_to_update.utc_offset = offset;
+                        driver_to_update.recheck();
+                    }
+                    Err(message) => driver_to_update.set_error("utcOffset", Some(message)),
+                }
+                true
             }
-            DriverRosterMsg::UpdateDriverIrating(irating, index) => {
+            DriverRosterMsg::UpdateDriverIrating(value, index) => {
                 let driver_to_update = &mut self.drivers[index];
-                driver_to_update.irating = irating;
-                false
+                match parse_whole_number(&value) {
+                    Ok(irating) => {
+                        driver_to_update.irating = irating;
+                        driver_to_update.recheck();
+                    }
+                    Err(message) => driver_to_update.set_error("irating", Some(message)),
+                }
+                true
             }
-            DriverRosterMsg::UpdateDriverStintPreference(stint_preference, index) => {
+            DriverRosterMsg::UpdateDriverStintPreference(value, index) => {
                 let driver_to_update = &mut self.drivers[index];
-                driver_to_update.stint_preference = stint_preference;
-                if driver_to_update.id == 0 {
-                    create_plan_driver(
-                        self.planner_context.data.id,
-                        driver_to_update.clone().into(),
-                        ctx.link().callback(
-                            move |driver: endurance_racing_planner_common::Driver| {
-                                DriverRosterMsg::UpdateDriverId(driver.id, index)
-                            },
-                        ),
-                    );
-                } else {
-                    update_driver(driver_to_update.clone().into());
+                match parse_whole_number(&value) {
+                    Ok(stint_preference) => driver_to_update.stint_preference = stint_preference,
+                    Err(message) => {
+                        driver_to_update.set_error("stintPreference", Some(message));
+                        return true;
+                    }
                 }
-                false
+                // nothing is saved until the errors shown next to the fields are fixed
+                if driver_to_update.check() {
+                    if driver_to_update.id == 0 {
+                        create_plan_driver(
+                            self.planner_context.data.id,
+                            driver_to_update.clone().into(),
+                            ctx.link().callback(
+                                move |driver: endurance_racing_planner_common::Driver| {
+                                    DriverRosterMsg::UpdateDriverId(driver.id, index)
+                                },
+                            ),
+                        );
+                    } else {
+                        update_driver(driver_to_update.clone().into());
+                    }
+                }
+                true
             }
             DriverRosterMsg::UpdateDriverId(id, index) => {
                 let driver_to_update = &mut self.drivers[index];
@@ -269,6 +347,7 @@ impl Component for DriverRoster {
                           <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "UTC Offset" }</th>
                           <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "iRating" }</th>
                           <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Stint Preference" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Calendar" }</th>
                         </tr>
                       </thead>
                       <tbody class="mdc-data-table__content">
@@ -276,7 +355,7 @@ impl Component for DriverRoster {
                             self.drivers
                                 .iter()
                                 .enumerate()
-                                .map(|(index, driver)| driver.get_view(ctx.link(), index))
+                                .map(|(index, driver)| driver.get_view(ctx.link(), index, self.planner_context.data.id))
                                 .collect::<Vec<_>>()
                         }
                       </tbody>
@@ -304,3 +383,134 @@ impl Component for DriverRoster {
 
     fn destroy(&mut self, _ctx: &Context<Self>) {}
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::DriverRoster;
+    use crate::http::drivers::driver_calendar_url;
+    use crate::planner::PlannerRoutes;
+    use crate::test_support::{mount, settle, FakeTransport, Mounted};
+    use endurance_racing_planner_common::{client::HttpMethod, Driver, RacePlannerDto};
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use yew::html;
+
+    const NAME: &str = "tbody tr:nth-child(1) td:nth-child(1) input";
+    const STINT_PREFERENCE: &str = "tbody tr:nth-child(1) td:nth-child(7) input";
+
+    fn driver(id: i32, name: &str) -> Driver {
+        Driver {
+            id,
+            name: name.to_string(),
+            total_stints: 0,
+            fair_share: false,
+            color: "#FF0000".to_string(),
+            utc_offset: -5,
+            irating: 2500,
+            stint_preference: 0,
+        }
+    }
+
+    fn mount_roster(plan: &RacePlannerDto) -> Mounted {
+        mount(
+            plan.clone(),
+            PlannerRoutes::Roster { id: plan.id },
+            html! { <DriverRoster /> },
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn a_row_is_shown_for_each_driver() {
+        FakeTransport::install();
+        let plan = RacePlannerDto {
+            driver_roster: vec![driver(1, "Max"), driver(2, "Charles")],
+            ..RacePlannerDto::new()
+        };
+        let mounted = mount_roster(&plan);
+
+        assert_eq!(2, mounted.query_all("tbody tr").len());
+        assert_eq!("Max", mounted.value(NAME));
+        assert_eq!(
+            "Charles",
+            mounted.value("tbody tr:nth-child(2) td:nth-child(1) input")
+        );
+        assert_eq!(
+            Some(driver_calendar_url(plan.id, 1)),
+            mounted
+                .query("tbody tr:nth-child(1) a")
+                .get_attribute("href")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_new_driver_is_created_then_updated() {
+        let http = FakeTransport::install();
+        let plan = RacePlannerDto::new();
+        let created_route = format!("plans/{}/drivers", plan.id);
+        http.respond_with(HttpMethod::Post, &created_route, &driver(7, "Max"));
+        let mounted = mount_roster(&plan);
+
+        mounted.click("button[title='New Driver']");
+        assert_eq!(1, mounted.query_all("tbody tr").len());
+
+        mounted.change_text_field(NAME, "Max");
+        settle().await;
+        assert!(http.requests().is_empty());
+
+        mounted.change_text_field(STINT_PREFERENCE, "2");
+        settle().await;
+        let created: Driver = http.body(HttpMethod::Post, &created_route);
+        assert_eq!(0, created.id);
+        assert_eq!("Max", created.name);
+        assert_eq!(2, created.stint_preference);
+
+        // the id the api gave the driver is used from then on
+        mounted.change_text_field(STINT_PREFERENCE, "3");
+        settle().await;
+        let updated: Driver = http.body(HttpMethod::Put, "drivers/7");
+        assert_eq!(7, updated.id);
+        assert_eq!(3, updated.stint_preference);
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_driver_that_fails_validation_is_shown_and_not_saved() {
+        let http = FakeTransport::install();
+        let plan = RacePlannerDto {
+            driver_roster: vec![driver(1, "Max")],
+            ..RacePlannerDto::new()
+        };
+        let mounted = mount_roster(&plan);
+
+        mounted.change_text_field(NAME, "");
+        mounted.change_text_field(STINT_PREFERENCE, "2");
+        settle().await;
+
+        assert_eq!(
+            1,
+            mounted
+                .query_all("tbody tr:nth-child(1) td:nth-child(1) .mdc-text-field-helper-text")
+                .len()
+        );
+        assert!(http.requests().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn an_offset_that_is_not_a_whole_number_is_shown() {
+        let http = FakeTransport::install();
+        let plan = RacePlannerDto {
+            driver_roster: vec![driver(1, "Max")],
+            ..RacePlannerDto::new()
+        };
+        let mounted = mount_roster(&plan);
+
+        mounted.change_text_field("tbody tr:nth-child(1) td:nth-child(5) input", "east");
+        settle().await;
+
+        assert_eq!(
+            Some("must be a whole number".to_string()),
+            mounted
+                .query("tbody tr:nth-child(1) td:nth-child(5) .mdc-text-field-helper-text")
+                .text_content()
+        );
+        assert!(http.requests().is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::http::scenarios::{
+    activate_scenario, create_scenario, delete_scenario, get_scenario_comparison,
+};
+use crate::md_text_field::{MaterialTextField, MaterialTextFieldProps};
+use crate::planner::{format_duration, DurationFormat, RacePlannerAction, RacePlannerContext};
+use endurance_racing_planner_common::scenario::{NewScenarioDto, ScenarioComparisonDto};
+use endurance_racing_planner_common::schedule::ScheduleStintDto;
+use uuid::Uuid;
+use yew::html::Scope;
+use yew::prelude::*;
+use yew::props;
+use yew_mdc::components::{
+    button::{Button, Style},
+    IconButton,
+};
+
+const FINISH_TIME_FORMAT: &str = "%a %l:%M %p";
+
+pub enum ScenariosMsg {
+    Load,
+    SetComparisons(Vec<ScenarioComparisonDto>),
+    UpdateNewScenarioName(String),
+    CreateScenario,
+    ActivateScenario(Uuid),
+    SetActiveSchedule(Vec<ScheduleStintDto>),
+    DeleteScenario(Uuid),
+}
+
+pub struct Scenarios {
+    plan_id: Uuid,
+    comparisons: Vec<ScenarioComparisonDto>,
+    new_scenario_name: String,
+}
+
+impl Component for Scenarios {
+    type Message = ScenariosMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (planner_context, _) = ctx
+            .link()
+            .context::<RacePlannerContext>(Callback::noop())
+            .expect("No Planner Context Provided");
+        ctx.link().send_message(ScenariosMsg::Load);
+
+        Self {
+            plan_id: planner_context.data.id,
+            comparisons: vec![],
+            new_scenario_name: "".to_string(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ScenariosMsg::Load => {
+                get_scenario_comparison(
+                    self.plan_id,
+                    ctx.link().callback(ScenariosMsg::SetComparisons),
+                );
+                false
+            }
+            ScenariosMsg::SetComparisons(comparisons) => {
+                self.comparisons = comparisons;
+                true
+            }
+            ScenariosMsg::UpdateNewScenarioName(name) => {
+                self.new_scenario_name = name;
+                false
+            }
+            ScenariosMsg::CreateScenario => {
+                if self.new_scenario_name.trim().is_empty() {
+                    return false;
+                }
+
+                create_scenario(
+                    self.plan_id,
+                    NewScenarioDto {
+                        name: self.new_scenario_name.clone(),
+                        copy_from: None,
+                    },
+                    ctx.link().callback(|_| ScenariosMsg::Load),
+                );
+                self.new_scenario_name = "".to_string();
+                true
+            }
+            ScenariosMsg::ActivateScenario(scenario_id) => {
+                activate_scenario(
+                    self.plan_id,
+                    scenario_id,
+                    ctx.link().callback(ScenariosMsg::SetActiveSchedule),
+                );
+                false
+            }
+            ScenariosMsg::SetActiveSchedule(stints) => {
+                let (planner_context, _) = ctx
+                    .link()
+                    .context::<RacePlannerContext>(Callback::noop())
+                    .expect("planner context");
+                planner_context.dispatch(RacePlannerAction::SetStints(stints));
+                ctx.link().send_message(ScenariosMsg::Load);
+                false
+            }
+            ScenariosMsg::DeleteScenario(scenario_id) => {
+                delete_scenario(
+                    self.plan_id,
+                    scenario_id,
+                    ctx.link().callback(|_| ScenariosMsg::Load),
+                );
+                false
+            }
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let new_scenario_name_props = props! {MaterialTextFieldProps {
+            value: self.new_scenario_name.clone(),
+            label: Some("Scenario Name".to_string()),
+            id: "new-scenario-name".to_string(),
+            on_change: link.callback(ScenariosMsg::UpdateNewScenarioName)
+        }};
+        let driver_names = self
+            .comparisons
+            .first()
+            .map(|comparison| {
+                comparison
+                    .driver_seat_times
+                    .iter()
+                    .map(|seat_time| seat_time.name.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        html! {
+            <div id="scenarios" class="mdc-card">
+                <div class="mdc-card-wrapper__text-section">
+                    <div class="card-title">{ "Scenarios" }</div>
+                </div>
+                <div class="mdc-data-table">
+                  <div class="mdc-data-table__table-container">
+                    <table class="mdc-data-table__table">
+                      <thead>
+                        <tr class="mdc-data-table__header-row">
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Active" }</th>
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Scenario" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Total Laps" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Stops" }</th>
+                          <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Finish (UTC)" }</th>
+                          {
+                              driver_names
+                                  .iter()
+                                  .map(|name| html! {
+                                      <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ name }</th>
+                                  })
+                                  .collect::<Html>()
+                          }
+                          <th class="mdc-data-table__header-cell" role="columnheader" scope="col"></th>
+                        </tr>
+                      </thead>
+                      <tbody class="mdc-data-table__content">
+                        {
+                            self.comparisons
+                                .iter()
+                                .map(|comparison| render_comparison(comparison, link))
+                                .collect::<Html>()
+                        }
+                      </tbody>
+                    </table>
+                  </div>
+                </div>
+                <div class="new-scenario">
+                    <MaterialTextField ..new_scenario_name_props />
+                    <Button text={"Copy Active Scenario"} style={Style::Raised} onclick={link.callback(|_| ScenariosMsg::CreateScenario)} />
+                </div>
+            </div>
+        }
+    }
+}
+
+fn render_comparison(comparison: &ScenarioComparisonDto, link: &Scope<Scenarios>) -> Html {
+    let scenario_id = comparison.scenario.id;
+    let is_active = comparison.scenario.is_active;
+
+    html! {
+        <tr class="mdc-data-table__row">
+          <td class="mdc-data-table__cell">
+            <IconButton classes="material-icons" onclick={link.batch_callback(move |_| (!is_active).then(|| ScenariosMsg::ActivateScenario(scenario_id)))}>
+              { if is_active { "radio_button_checked" } else { "radio_button_unchecked" } }
+            </IconButton>
+          </td>
+          <td class="mdc-data-table__cell">{ comparison.scenario.name.clone() }</td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">{ comparison.total_laps }</td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">{ comparison.stop_count }</td>
+          <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
+            { comparison.finish_time.map_or_else(String::new, |finish_time| finish_time.format(FINISH_TIME_FORMAT).to_string()) }
+          </td>
+          {
+              comparison.driver_seat_times
+                  .iter()
+                  .map(|seat_time| html! {
+                      <td class="mdc-data-table__cell mdc-data-table__cell--numeric">{ format_duration(seat_time.seat_time, DurationFormat::HourMinSec) }</td>
+                  })
+                  .collect::<Html>()
+          }
+          <td class="mdc-data-table__cell">
+            {
+                if is_active {
+                    html! {}
+                } else {
+                    html! {
+                        <IconButton classes="material-icons" onclick={link.callback(move |_| ScenariosMsg::DeleteScenario(scenario_id))}>{ "delete" }</IconButton>
+                    }
+                }
+            }
+          </td>
+        </tr>
+    }
+}
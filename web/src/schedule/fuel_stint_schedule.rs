@@ -5,7 +5,11 @@ use crate::planner::{
     RacePlannerContext,
 };
 use chrono::{Duration, NaiveTime, Timelike};
-use endurance_racing_planner_common::schedule::{ScheduleStintDto, StintType};
+use endurance_racing_planner_common::conditions::TrackConditionDto;
+use endurance_racing_planner_common::schedule::{
+    self, cumulative_damage, ScheduleConfig, ScheduleStintDto, StintType,
+};
+use endurance_racing_planner_common::traffic::TrafficModelDto;
 use endurance_racing_planner_common::{
     Driver, EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData,
 };
@@ -17,7 +21,11 @@ use web_sys::Element;
 use yew::prelude::*;
 use yew::{html::Scope, props, Component, Context, Html};
 use yew_mdc::{
-    components::{select::SelectChangeEventData, Select, SelectItem},
+    components::{
+        button::{Button, Style},
+        select::SelectChangeEventData,
+        Select, SelectItem,
+    },
     mdc_sys::MDCDataTable,
 };
 use yew_router::prelude::*;
@@ -33,6 +41,7 @@ impl ScheduleRow {
         link: &Scope<FuelStintSchedule>,
         index: usize,
         drivers: Option<&Vec<Driver>>,
+        is_selected: bool,
     ) -> Html {
         let time_format = "%l:%M %p"; // (H)H:MM AM|PM
         let actual_end_on_change = link.batch_callback(move |value: String| {
@@ -106,12 +115,19 @@ impl ScheduleRow {
         };
 
         let row_id = format!("row-{}", index);
+        let row_classes = if is_selected {
+            "mdc-data-table__row mdc-data-table__row--selected"
+        } else {
+            "mdc-data-table__row"
+        };
         html! {
-            <tr data-row-id={row_id.clone()} class="mdc-data-table__row">
+            <tr data-row-id={row_id.clone()} class={row_classes} aria-selected={is_selected.to_string()}>
                 <td class="mdc-data-table__cell mdc-data-table__cell--checkbox">
                     <div class="mdc-touch-target-wrapper">
                         <div class="mdc-checkbox mdc-checkbox--touch mdc-data-table__row-checkbox">
-                            <input type="checkbox" class="mdc-checkbox__native-control" aria-labelledby={row_id.clone()} />
+                            <input type="checkbox" class="mdc-checkbox__native-control" aria-labelledby={row_id.clone()}
+                                checked={is_selected}
+                                onclick={link.callback(move |_| FuelStintScheduleMsg::SelectRow(index))} />
                             <div class="mdc-checkbox__background">
                                 <svg class="mdc-checkbox__checkmark" viewBox="0 0 24 24">
                                     <path class="mdc-checkbox__checkmark-path" fill="none" d="M1.73,12.91 8.1,19.28 22.79,4.59"/>
@@ -154,6 +170,8 @@ impl ScheduleRow {
                 <td class="mdc-data-table__cell mdc-data-table__cell--numeric">
                     <MaterialTextField ..actual_laps_props />
                 </td>
+                <td class="mdc-data-table__cell mdc-data-table__cell--numeric">{ self.stint_data.tire_age }</td>
+                <td class="mdc-data-table__cell mdc-data-table__cell--numeric">{ format_duration(self.stint_data.traffic_loss, DurationFormat::HourMinSec) }</td>
                 <td class="mdc-data-table__cell">
                     <Select id={format!("driver-name-{}", index)}
                         select_width_class="select-width"
@@ -195,14 +213,23 @@ pub enum FuelStintScheduleMsg {
     UpdateActualEndTime(NaiveTime, usize),
     UpdateDriver(SelectChangeEventData, usize),
     UpdateDamageModifier(Duration, usize),
+    SelectRow(usize),
+    InsertStint,
+    SplitStint,
+    DeleteStint,
+    DownloadCsv,
+    SetSchedule(Vec<ScheduleStintDto>),
 }
 
 pub struct FuelStintSchedule {
     plan_id: Uuid,
     schedule_rows: Vec<ScheduleRow>,
+    selected_row: Option<usize>,
     overall_event_config: Option<EventConfigDto>,
     fuel_stint_times: Option<FuelStintAverageTimes>,
     overall_fuel_stint_config: Option<OverallFuelStintConfigData>,
+    track_conditions: Vec<TrackConditionDto>,
+    traffic_model: Option<TrafficModelDto>,
     drivers: Option<Vec<Driver>>,
     mdc_data_table_node_ref: NodeRef,
     data_table: Option<MDCDataTable>,
@@ -210,18 +237,26 @@ pub struct FuelStintSchedule {
 }
 
 impl FuelStintSchedule {
+    fn schedule_config(&self) -> ScheduleConfig {
+        ScheduleConfig::new(
+            self.overall_event_config.as_ref().unwrap(),
+            self.fuel_stint_times.as_ref().unwrap(),
+            self.overall_fuel_stint_config.as_ref().unwrap(),
+            &self.track_conditions,
+            self.traffic_model.as_ref(),
+        )
+    }
+
     fn update_schedule(&mut self, update_row_index: usize) {
+        let config = self.schedule_config();
         let event_config = self.overall_event_config.as_ref().unwrap();
-        let fuel_stint_times = self.fuel_stint_times.as_ref().unwrap();
-        let fuel_stint_config = self.overall_fuel_stint_config.as_ref().unwrap();
 
         let initial_schedule_length = self.schedule_rows.len();
         update_schedule(
             &mut self.schedule_rows,
             update_row_index,
             event_config,
-            fuel_stint_times,
-            fuel_stint_config,
+            &config,
         );
 
         if initial_schedule_length != self.schedule_rows.len() {
@@ -237,38 +272,27 @@ fn create_schedule(
     overall_event_config: Option<EventConfigDto>,
     fuel_stint_times: Option<FuelStintAverageTimes>,
     overall_fuel_stint_config: Option<OverallFuelStintConfigData>,
+    track_conditions: &[TrackConditionDto],
+    traffic_model: Option<&TrafficModelDto>,
 ) -> Vec<ScheduleRow> {
     if overall_event_config.is_some()
         && fuel_stint_times.is_some()
         && overall_fuel_stint_config.is_some()
     {
         let event_config = overall_event_config.as_ref().unwrap();
-        let fuel_stint_times = fuel_stint_times.as_ref().unwrap();
-        let fuel_stint_config = overall_fuel_stint_config.as_ref().unwrap();
-
-        if event_config.race_start_utc == event_config.race_end_utc
-            || fuel_stint_times.standard_fuel_stint.track_time == Duration::zero()
-        {
-            return vec![];
-        }
-
-        let mut schedule_rows = vec![];
-        let stint_data = ScheduleStintDto::new(event_config, fuel_stint_times);
-        let mut is_schedule_complete = stint_data.utc_end >= event_config.race_end_utc;
-        schedule_rows.push(ScheduleRow { stint_data });
-
-        while !is_schedule_complete {
-            let previous_row = schedule_rows.last().unwrap();
-            let stint_data = ScheduleStintDto::from_previous(
-                &previous_row.stint_data,
-                StintType::FuelSavingWithTires,
-                fuel_stint_times,
-                event_config.race_end_utc,
-                fuel_stint_config.tire_change_time,
-                Duration::zero(),
-            );
-            is_schedule_complete = stint_data.utc_end >= event_config.race_end_utc;
-            schedule_rows.push(ScheduleRow { stint_data });
+        let config = ScheduleConfig::new(
+            event_config,
+            fuel_stint_times.as_ref().unwrap(),
+            overall_fuel_stint_config.as_ref().unwrap(),
+            track_conditions,
+            traffic_model,
+        );
+        let schedule_rows = schedule::create_schedule(event_config, &config)
+            .into_iter()
+            .map(|stint_data| ScheduleRow { stint_data })
+            .collect::<Vec<_>>();
+        if schedule_rows.is_empty() {
+            return schedule_rows;
         }
 
         http::schedules::create_schedule(
@@ -288,56 +312,17 @@ fn update_schedule(
     schedule_rows: &mut Vec<ScheduleRow>,
     update_row_index: usize,
     event_config: &EventConfigDto,
-    fuel_stint_times: &FuelStintAverageTimes,
-    fuel_stint_config: &OverallFuelStintConfigData,
+    config: &ScheduleConfig,
 ) {
-    let updated_row = &schedule_rows[update_row_index];
-    let mut is_schedule_complete = updated_row.stint_data.utc_end >= event_config.race_end_utc;
-    let mut next_row_index = update_row_index;
-    let mut damage_modifier = updated_row.stint_data.damage_modifier;
-    while !is_schedule_complete {
-        next_row_index += 1;
-        if next_row_index == schedule_rows.len() {
-            let previous_row = schedule_rows.last().unwrap();
-            let stint_data = ScheduleStintDto::from_previous(
-                &previous_row.stint_data,
-                StintType::FuelSavingWithTires,
-                fuel_stint_times,
-                event_config.race_end_utc,
-                fuel_stint_config.tire_change_time,
-                damage_modifier,
-            );
-
-            is_schedule_complete = stint_data.utc_end >= event_config.race_end_utc;
-            schedule_rows.push(ScheduleRow { stint_data });
-        } else {
-            let previous_row = &schedule_rows[next_row_index - 1];
-            let previous_row_actual_end = previous_row.stint_data.actual_end;
-            let previous_row_driver_id = previous_row.stint_data.driver_id;
-            let previous_row_stint_number = previous_row.stint_data.stint_number;
-            let previous_row_tod_end =
-                previous_row_actual_end.naive_utc() + event_config.tod_offset;
-
-            let next_row = &mut schedule_rows[next_row_index];
-            next_row.stint_data.update(
-                previous_row_actual_end,
-                previous_row_tod_end,
-                previous_row_driver_id,
-                previous_row_stint_number,
-                fuel_stint_times,
-                event_config.race_end_utc,
-                fuel_stint_config.tire_change_time,
-                damage_modifier,
-            );
-
-            is_schedule_complete = next_row.stint_data.utc_end >= event_config.race_end_utc;
-            damage_modifier = damage_modifier + next_row.stint_data.damage_modifier;
-        }
-    }
-
-    while schedule_rows.len() > next_row_index + 1 {
-        schedule_rows.pop();
-    }
+    let mut stints = schedule_rows
+        .iter()
+        .map(|row| row.stint_data.clone())
+        .collect::<Vec<_>>();
+    schedule::update_schedule(&mut stints, update_row_index, event_config, config);
+    *schedule_rows = stints
+        .into_iter()
+        .map(|stint_data| ScheduleRow { stint_data })
+        .collect();
 }
 
 impl Component for FuelStintSchedule {
@@ -361,6 +346,8 @@ impl Component for FuelStintSchedule {
         let overall_event_config = planner_context.data.overall_event_config.clone();
         let fuel_stint_times = planner_context.data.fuel_stint_average_times.clone();
         let overall_fuel_stint_config = planner_context.data.overall_fuel_stint_config.clone();
+        let track_conditions = &planner_context.data.track_conditions;
+        let traffic_model = planner_context.data.traffic_model.as_ref();
         let schedule_rows = planner_context.data.schedule_rows.as_ref().map(|stints| {
             stints
                 .iter()
@@ -371,38 +358,32 @@ impl Component for FuelStintSchedule {
         });
 
         let schedule_rows = match schedule_rows {
-            Some(mut rows) => {
+            Some(rows) => {
                 if overall_event_config.is_some()
                     && fuel_stint_times.is_some()
                     && overall_fuel_stint_config.is_some()
                 {
                     let event_config = overall_event_config.as_ref().unwrap();
-                    let fuel_stint_times = fuel_stint_times.as_ref().unwrap();
-                    let fuel_stint_config = overall_fuel_stint_config.as_ref().unwrap();
-
-                    if event_config.race_start_utc == event_config.race_end_utc
-                        || fuel_stint_times.standard_fuel_stint.track_time == Duration::zero()
-                    {
-                        vec![]
+                    let config = ScheduleConfig::new(
+                        event_config,
+                        fuel_stint_times.as_ref().unwrap(),
+                        overall_fuel_stint_config.as_ref().unwrap(),
+                        track_conditions,
+                        traffic_model,
+                    );
+
+                    if config.is_schedulable() {
+                        let mut stints = rows
+                            .into_iter()
+                            .map(|row| row.stint_data)
+                            .collect::<Vec<_>>();
+                        schedule::recalculate_schedule(&mut stints, event_config, &config);
+                        stints
+                            .into_iter()
+                            .map(|stint_data| ScheduleRow { stint_data })
+                            .collect()
                     } else {
-                        rows[0].stint_data.update(
-                            event_config.race_start_utc,
-                            event_config.race_start_tod,
-                            0,
-                            1,
-                            fuel_stint_times,
-                            event_config.race_end_utc,
-                            fuel_stint_config.tire_change_time,
-                            Duration::zero(),
-                        );
-                        update_schedule(
-                            &mut rows,
-                            0,
-                            event_config,
-                            fuel_stint_times,
-                            fuel_stint_config,
-                        );
-                        rows
+                        vec![]
                     }
                 } else {
                     vec![]
@@ -413,6 +394,8 @@ impl Component for FuelStintSchedule {
                 overall_event_config,
                 fuel_stint_times,
                 overall_fuel_stint_config,
+                track_conditions,
+                traffic_model,
             ),
         };
 
@@ -425,9 +408,12 @@ impl Component for FuelStintSchedule {
         Self {
             plan_id,
             schedule_rows,
+            selected_row: None,
             overall_event_config: planner_context.data.overall_event_config.clone(),
             fuel_stint_times: planner_context.data.fuel_stint_average_times.clone(),
             overall_fuel_stint_config: planner_context.data.overall_fuel_stint_config.clone(),
+            track_conditions: planner_context.data.track_conditions.clone(),
+            traffic_model: planner_context.data.traffic_model.clone(),
             drivers,
             mdc_data_table_node_ref: NodeRef::default(),
             data_table: None,
@@ -435,7 +421,7 @@ impl Component for FuelStintSchedule {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             FuelStintScheduleMsg::UpdateFuelStintType(stint_type, index) => {
                 let previous_row_stint_data = if index > 0 {
@@ -443,29 +429,21 @@ impl Component for FuelStintSchedule {
                 } else {
                     None
                 };
+                let damage_modifier = cumulative_damage(
+                    self.schedule_rows[..index]
+                        .iter()
+                        .map(|row| &row.stint_data),
+                );
+                let config = self.schedule_config();
 
                 let stint_data = &mut self.schedule_rows[index].stint_data;
                 stint_data.stint_type = stint_type;
                 stint_data.update(
                     stint_data.utc_start,
                     stint_data.tod_start,
-                    previous_row_stint_data
-                        .as_ref()
-                        .map(|row| row.driver_id)
-                        .unwrap_or(0),
-                    previous_row_stint_data
-                        .as_ref()
-                        .map(|row| row.stint_number)
-                        .unwrap_or(0),
-                    self.fuel_stint_times.as_ref().unwrap(),
-                    self.overall_event_config.as_ref().unwrap().race_end_utc,
-                    self.overall_fuel_stint_config
-                        .as_ref()
-                        .unwrap()
-                        .tire_change_time,
-                    previous_row_stint_data
-                        .map(|row| row.damage_modifier)
-                        .unwrap_or_else(Duration::zero),
+                    previous_row_stint_data.as_ref(),
+                    &config,
+                    damage_modifier,
                 );
                 self.update_schedule(index);
             }
@@ -516,32 +494,69 @@ impl Component for FuelStintSchedule {
                 } else {
                     None
                 };
+                let damage_modifier = cumulative_damage(
+                    self.schedule_rows[..index]
+                        .iter()
+                        .map(|row| &row.stint_data),
+                );
+                let config = self.schedule_config();
 
                 let stint_data = &mut self.schedule_rows[index].stint_data;
                 stint_data.damage_modifier = value;
                 stint_data.update(
                     stint_data.utc_start,
                     stint_data.tod_start,
-                    previous_row_stint_data
-                        .as_ref()
-                        .map(|row| row.driver_id)
-                        .unwrap_or(0),
-                    previous_row_stint_data
-                        .as_ref()
-                        .map(|row| row.stint_number)
-                        .unwrap_or(0),
-                    self.fuel_stint_times.as_ref().unwrap(),
-                    self.overall_event_config.as_ref().unwrap().race_end_utc,
-                    self.overall_fuel_stint_config
-                        .as_ref()
-                        .unwrap()
-                        .tire_change_time,
-                    previous_row_stint_data
-                        .map(|row| row.damage_modifier)
-                        .unwrap_or_else(Duration::zero),
+                    previous_row_stint_data.as_ref(),
+                    &config,
+                    damage_modifier,
                 );
                 self.update_schedule(index);
             }
+            FuelStintScheduleMsg::SelectRow(index) => {
+                self.selected_row = if self.selected_row == Some(index) {
+                    None
+                } else {
+                    Some(index)
+                };
+                return true;
+            }
+            FuelStintScheduleMsg::InsertStint
+            | FuelStintScheduleMsg::SplitStint
+            | FuelStintScheduleMsg::DeleteStint => {
+                let stint_id = match self
+                    .selected_row
+                    .and_then(|index| self.schedule_rows.get(index))
+                {
+                    Some(row) => row.stint_data.id,
+                    None => return false,
+                };
+                let callback = ctx.link().callback(FuelStintScheduleMsg::SetSchedule);
+                match msg {
+                    FuelStintScheduleMsg::InsertStint => {
+                        http::schedules::insert_stint(self.plan_id, stint_id, callback)
+                    }
+                    FuelStintScheduleMsg::SplitStint => {
+                        http::schedules::split_stint(self.plan_id, stint_id, callback)
+                    }
+                    _ => http::schedules::delete_stint(self.plan_id, stint_id, callback),
+                }
+                return false;
+            }
+            FuelStintScheduleMsg::DownloadCsv => {
+                http::schedules::download_schedule_csv(self.plan_id);
+                return false;
+            }
+            FuelStintScheduleMsg::SetSchedule(stints) => {
+                self.selected_row = None;
+                self.schedule_rows = stints
+                    .into_iter()
+                    .map(|stint_data| ScheduleRow { stint_data })
+                    .collect();
+                if let Some(data_table) = self.data_table.take() {
+                    data_table.destroy();
+                }
+                return true;
+            }
         }
         http::schedules::update_schedule(
             self.plan_id,
@@ -566,6 +581,20 @@ impl Component for FuelStintSchedule {
             <div id="fuel-stint-schedule" class="mdc-card">
                 <div class="mdc-card-wrapper__text-section">
                     <div class="card-title">{ "Fuel Stint Schedule" }</div>
+                    <Button text={"Download CSV"} style={Style::Raised} onclick={ctx.link().callback(|_| FuelStintScheduleMsg::DownloadCsv)} />
+                    {
+                        if self.selected_row.is_some() {
+                            html! {
+                                <div class="stint-actions">
+                                    <Button text={"Insert Stint After"} style={Style::Raised} onclick={ctx.link().callback(|_| FuelStintScheduleMsg::InsertStint)} />
+                                    <Button text={"Split Stint"} style={Style::Raised} onclick={ctx.link().callback(|_| FuelStintScheduleMsg::SplitStint)} />
+                                    <Button text={"Delete Stint"} style={Style::Raised} onclick={ctx.link().callback(|_| FuelStintScheduleMsg::DeleteStint)} />
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
                 </div>
                 {
                     if self.schedule_rows.is_empty() {
@@ -607,6 +636,8 @@ impl Component for FuelStintSchedule {
                                       <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Damage Modifier" }</th>
                                       <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Calc" }<br/>{ "Laps" }</th>
                                       <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Actual Laps" }</th>
+                                      <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Tire" }<br/>{ "Age" }</th>
+                                      <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Traffic" }<br/>{ "Loss" }</th>
                                       <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Driver" }</th>
                                       <th class="mdc-data-table__header-cell" role="columnheader" scope="col">{ "Availability" }</th>
                                       <th class="mdc-data-table__header-cell mdc-data-table__header-cell--numeric" role="columnheader" scope="col">{ "Stint" }<br/>{ "Num" }</th>
@@ -621,7 +652,7 @@ impl Component for FuelStintSchedule {
                                         self.schedule_rows
                                             .iter()
                                             .enumerate()
-                                            .map(|(index, row)| row.get_view(ctx.link(), index, self.drivers.as_ref()))
+                                            .map(|(index, row)| row.get_view(ctx.link(), index, self.drivers.as_ref(), self.selected_row == Some(index)))
                                             .collect::<Vec<_>>()
                                     }
                                   </tbody>
@@ -659,3 +690,152 @@ impl Component for FuelStintSchedule {
         }
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::FuelStintSchedule;
+    use crate::planner::PlannerRoutes;
+    use crate::test_support::{click, mount, schedulable_plan, settle, FakeTransport, Mounted};
+    use chrono::Duration;
+    use endurance_racing_planner_common::client::HttpMethod;
+    use endurance_racing_planner_common::schedule::{self, ScheduleConfig, ScheduleStintDto};
+    use endurance_racing_planner_common::RacePlannerDto;
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use yew::html;
+
+    const TIME_FORMAT: &str = "%l:%M %p";
+
+    fn mount_schedule(plan: &RacePlannerDto) -> Mounted {
+        mount(
+            plan.clone(),
+            PlannerRoutes::Schedule { id: plan.id },
+            html! { <FuelStintSchedule /> },
+        )
+    }
+
+    /// A plan with its calculated schedule
+    fn scheduled_plan() -> (RacePlannerDto, Vec<ScheduleStintDto>) {
+        let plan = schedulable_plan();
+        let stints = schedule::plan_schedule(&plan).expect("the plan to be schedulable");
+
+        (
+            RacePlannerDto {
+                schedule_rows: Some(stints.clone()),
+                ..plan
+            },
+            stints,
+        )
+    }
+
+    /// The text of the cell in `column` of the schedule's `row`, both counted from one
+    fn cell_text(mounted: &Mounted, row: usize, column: usize) -> String {
+        mounted
+            .query(&format!(
+                "tbody tr:nth-child({}) td:nth-child({})",
+                row, column
+            ))
+            .text_content()
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_plan_without_a_schedule_gets_one_calculated_and_saved() {
+        let http = FakeTransport::install();
+        let plan = schedulable_plan();
+        let expected = schedule::plan_schedule(&plan).expect("the plan to be schedulable");
+        let mounted = mount_schedule(&plan);
+        settle().await;
+
+        assert_eq!(expected.len(), mounted.query_all("tbody tr").len());
+        assert_eq!(
+            expected[0].utc_start.format(TIME_FORMAT).to_string(),
+            cell_text(&mounted, 1, 4)
+        );
+
+        let saved: Vec<ScheduleStintDto> =
+            http.body(HttpMethod::Post, &format!("plans/{}/schedule", plan.id));
+        assert_eq!(expected.len(), saved.len());
+        assert_eq!(expected[0].utc_start, saved[0].utc_start);
+        assert_eq!(
+            expected.last().unwrap().utc_end,
+            saved.last().unwrap().utc_end
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_plan_without_its_settings_links_to_the_overview() {
+        let http = FakeTransport::install();
+        let plan = RacePlannerDto::new();
+        let mounted = mount_schedule(&plan);
+        settle().await;
+
+        assert!(mounted.query_all("tbody tr").is_empty());
+        assert!(mounted.text().contains(
+            "Complete the event config, fuel stint average times, and fuel stint config"
+        ));
+        assert!(http.requests().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_later_actual_end_moves_the_next_stint_and_saves_the_schedule() {
+        let http = FakeTransport::install();
+        let (plan, stints) = scheduled_plan();
+        let mounted = mount_schedule(&plan);
+        settle().await;
+        assert!(http.requests().is_empty());
+
+        let actual_end = stints[0].utc_end + Duration::minutes(5);
+        mounted.change_text_field(
+            "tbody tr:nth-child(1) td:nth-child(8) input",
+            &actual_end.format("%H:%M:%S").to_string(),
+        );
+        settle().await;
+
+        let saved: Vec<ScheduleStintDto> =
+            http.body(HttpMethod::Put, &format!("plans/{}/schedule", plan.id));
+        assert_eq!(actual_end, saved[0].actual_end);
+        assert_eq!(Duration::minutes(5), saved[0].duration_delta);
+        assert_eq!(actual_end, saved[1].utc_start);
+        assert_eq!(
+            actual_end.format(TIME_FORMAT).to_string(),
+            cell_text(&mounted, 2, 4)
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn deleting_the_selected_stint_shows_the_schedule_the_api_returns() {
+        let http = FakeTransport::install();
+        let (plan, stints) = scheduled_plan();
+        let mut expected = stints.clone();
+        let (event_config, config) =
+            ScheduleConfig::from_plan(&plan).expect("the plan to be schedulable");
+        schedule::delete_stint(&mut expected, 0, event_config, &config);
+        http.respond_with(
+            HttpMethod::Delete,
+            &format!("plans/{}/schedule/stints/{}", plan.id, stints[0].id),
+            &expected,
+        );
+        let mounted = mount_schedule(&plan);
+        settle().await;
+
+        mounted.click("tbody tr:nth-child(1) input[type='checkbox']");
+        let delete_button = mounted
+            .query_all(".stint-actions button")
+            .into_iter()
+            .find(|button| {
+                button
+                    .text_content()
+                    .map_or(false, |text| text.contains("Delete Stint"))
+            })
+            .expect("the stint actions to be shown for the selected stint");
+        click(&delete_button);
+        settle().await;
+
+        assert_eq!(1, http.requests().len());
+        assert_eq!(expected.len(), mounted.query_all("tbody tr").len());
+        assert_eq!(
+            expected[0].utc_end.format(TIME_FORMAT).to_string(),
+            cell_text(&mounted, 1, 5)
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Mounts components in a browser for the `wasm_bindgen_test` suites, `wasm-pack test --headless
+//! --firefox` runs them. Requests go to a `FakeTransport` instead of the api, and the Material
+//! components are stubbed because the test page doesn't load their script.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use chrono::{Duration, TimeZone, Utc};
+use endurance_racing_planner_common::client::{
+    ApiClient, BearerToken, ClientError, HttpMethod, HttpRequest, HttpResponse, LocalFuture,
+    RetryPolicy, Transport,
+};
+use endurance_racing_planner_common::{
+    EventConfigDto, FuelStintAverageTimes, OverallFuelStintConfigData, RacePlannerDto, StintDataDto,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Element, Event, EventInit, HtmlElement, HtmlInputElement};
+use yew::prelude::*;
+use yew::AppHandle;
+use yew_router::history::{BrowserHistory, History};
+use yew_router::prelude::*;
+
+use crate::http;
+use crate::planner::{PlannerRoutes, RacePlanner, RacePlannerContext};
+use crate::{AppState, AppStateContext};
+
+#[wasm_bindgen(inline_js = r#"
+export function stubMaterialComponents() {
+    if (window.mdc) {
+        return;
+    }
+
+    // every component accepts any method call and keeps the properties set on it
+    class Component {
+        constructor(root) {
+            this.root = root;
+            return new Proxy(this, {
+                get: (target, property) => property in target ? target[property] : () => {},
+            });
+        }
+
+        static attachTo(root) {
+            return new Component(root);
+        }
+    }
+    const namespace = new Proxy({}, { get: () => Component });
+    window.mdc = new Proxy({}, { get: () => namespace });
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(js_name = "stubMaterialComponents")]
+    fn stub_material_components();
+}
+
+/// Records the requests the components send and answers them with the latest response it was
+/// given for the route, any other request gets an empty `200 OK`
+#[derive(Default)]
+pub struct FakeTransport {
+    requests: RefCell<Vec<HttpRequest>>,
+    responses: RefCell<Vec<(HttpMethod, String, Result<HttpResponse, ClientError>)>>,
+}
+
+impl FakeTransport {
+    /// Sends the http layer's requests to a new fake, failed requests aren't retried
+    pub fn install() -> Rc<Self> {
+        let transport = Rc::new(Self::default());
+        http::set_client(
+            ApiClient::new(
+                transport.clone(),
+                Rc::new(BearerToken("test-token".to_string())),
+            )
+            .with_retry(RetryPolicy::none()),
+        );
+
+        transport
+    }
+
+    pub fn respond_with(&self, method: HttpMethod, route: &str, body: &impl Serialize) {
+        self.responses.borrow_mut().push((
+            method,
+            route.to_string(),
+            Ok(HttpResponse {
+                status: 200,
+                body: serde_json::to_string(body).expect("the response to be serializable"),
+            }),
+        ));
+    }
+
+    pub fn fail_with(&self, method: HttpMethod, route: &str, error: ClientError) {
+        self.responses
+            .borrow_mut()
+            .push((method, route.to_string(), Err(error)));
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.borrow().clone()
+    }
+
+    /// The JSON body of the only request sent to `route`
+    pub fn body<T: DeserializeOwned>(&self, method: HttpMethod, route: &str) -> T {
+        let requests = self
+            .requests()
+            .into_iter()
+            .filter(|request| request.method == method && request.route == route)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            1,
+            requests.len(),
+            "{} {} was sent {} times in {:?}",
+            method,
+            route,
+            requests.len(),
+            self.requests()
+        );
+
+        let body = requests[0]
+            .body
+            .as_ref()
+            .expect("the request to have a body");
+        serde_json::from_str(body).expect("the body to be the expected json")
+    }
+}
+
+impl Transport for FakeTransport {
+    fn send(&self, request: HttpRequest) -> LocalFuture<Result<HttpResponse, ClientError>> {
+        let response = self
+            .responses
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(method, route, _)| *method == request.method && *route == request.route)
+            .map(|(_, _, response)| response.clone())
+            .unwrap_or_else(|| {
+                Ok(HttpResponse {
+                    status: 200,
+                    body: "null".to_string(),
+                })
+            });
+        self.requests.borrow_mut().push(request);
+
+        Box::pin(async move { response })
+    }
+
+    fn sleep(&self, _duration: std::time::Duration) -> LocalFuture<()> {
+        Box::pin(async {})
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TestPlannerProps {
+    pub plan: RacePlannerDto,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Provides the app's state and the planner context like `RacePlannerProvider` does, without
+/// loading the plan from the api
+#[function_component(TestPlanner)]
+pub fn test_planner(props: &TestPlannerProps) -> Html {
+    let plan = props.plan.clone();
+    let race_planner = use_reducer(move || RacePlanner { data: plan });
+    let app_state = use_reducer(|| AppState {
+        user_info: None,
+        nav_sidebar_open: false,
+        page_title: None,
+        plan_id: None,
+    });
+
+    html! {
+        <BrowserRouter>
+            <ContextProvider<AppStateContext> context={app_state}>
+                <ContextProvider<RacePlannerContext> context={race_planner}>
+                    { for props.children.iter() }
+                </ContextProvider<RacePlannerContext>>
+            </ContextProvider<AppStateContext>>
+        </BrowserRouter>
+    }
+}
+
+/// A component mounted in an element of its own, both are removed when it's dropped
+pub struct Mounted {
+    host: Element,
+    app: Option<AppHandle<TestPlanner>>,
+}
+
+/// Mounts `content` at `route` with `plan` as the planner context
+pub fn mount(plan: RacePlannerDto, route: PlannerRoutes, content: Html) -> Mounted {
+    stub_material_components();
+    BrowserHistory::new().push(route);
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .expect("the test to run in a browser");
+    let host = document.create_element("div").unwrap();
+    document
+        .body()
+        .expect("the test page to have a body")
+        .append_child(&host)
+        .unwrap();
+    let app = yew::start_app_with_props_in_element::<TestPlanner>(
+        host.clone(),
+        TestPlannerProps {
+            plan,
+            children: Children::new(vec![content]),
+        },
+    );
+
+    Mounted {
+        host,
+        app: Some(app),
+    }
+}
+
+impl Mounted {
+    pub fn query(&self, selector: &str) -> Element {
+        self.host
+            .query_selector(selector)
+            .unwrap()
+            .unwrap_or_else(|| panic!("nothing matches {}", selector))
+    }
+
+    pub fn query_all(&self, selector: &str) -> Vec<Element> {
+        let nodes = self.host.query_selector_all(selector).unwrap();
+        (0..nodes.length())
+            .filter_map(|index| nodes.get(index))
+            .filter_map(|node| node.dyn_into::<Element>().ok())
+            .collect()
+    }
+
+    /// The value of the input `selector` matches
+    pub fn value(&self, selector: &str) -> String {
+        self.query(selector)
+            .dyn_into::<HtmlInputElement>()
+            .unwrap_or_else(|_| panic!("{} isn't an input", selector))
+            .value()
+    }
+
+    pub fn text(&self) -> String {
+        self.host.text_content().unwrap_or_default()
+    }
+
+    /// Types `value` into the text field's input and leaves it, which is when the components
+    /// read it
+    pub fn change_text_field(&self, selector: &str, value: &str) {
+        let input = self
+            .query(selector)
+            .dyn_into::<HtmlInputElement>()
+            .unwrap_or_else(|_| panic!("{} isn't an input", selector));
+        input.set_value(value);
+
+        let mut init = EventInit::new();
+        init.bubbles(true);
+        let event = Event::new_with_event_init_dict("change", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    }
+
+    pub fn click(&self, selector: &str) {
+        click(&self.query(selector));
+    }
+}
+
+pub fn click(element: &Element) {
+    element
+        .dyn_ref::<HtmlElement>()
+        .expect("the element to be clickable")
+        .click();
+}
+
+impl Drop for Mounted {
+    fn drop(&mut self) {
+        if let Some(app) = self.app.take() {
+            app.destroy();
+        }
+        self.host.remove();
+    }
+}
+
+/// Waits for the requests the components spawned, and their callbacks, to finish
+pub async fn settle() {
+    let timeout = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback(&resolve)
+            .unwrap();
+    });
+    JsFuture::from(timeout).await.unwrap();
+}
+
+/// A six hour race starting at 18:40 UTC, the sim's clock starts five hours earlier
+pub fn event_config() -> EventConfigDto {
+    let session_start_utc = Utc.with_ymd_and_hms(2027, 1, 23, 18, 0, 0).unwrap();
+    let mut event_config = EventConfigDto {
+        race_duration: Duration::hours(6),
+        session_start_utc,
+        race_start_tod: session_start_utc.naive_utc() - Duration::hours(5),
+        green_flag_offset: Duration::minutes(40),
+        ..EventConfigDto::new()
+    };
+    event_config.update_race_times();
+
+    event_config
+}
+
+pub fn stint_data() -> StintDataDto {
+    StintDataDto {
+        lap_time: Duration::seconds(90),
+        fuel_per_lap: 3.25,
+        lap_count: 22,
+        lap_time_with_pit: Duration::seconds(120),
+        track_time: Duration::minutes(55),
+        track_time_with_pit: Duration::minutes(57),
+        fuel_per_stint: 100.0,
+    }
+}
+
+/// A plan with every setting a schedule is calculated from and no schedule yet
+pub fn schedulable_plan() -> RacePlannerDto {
+    RacePlannerDto {
+        overall_event_config: Some(event_config()),
+        overall_fuel_stint_config: Some(OverallFuelStintConfigData {
+            fuel_tank_size: 100,
+            ..OverallFuelStintConfigData::new()
+        }),
+        fuel_stint_average_times: Some(FuelStintAverageTimes {
+            standard_fuel_stint: stint_data(),
+            fuel_saving_stint: stint_data(),
+        }),
+        ..RacePlannerDto::new()
+    }
+}